@@ -0,0 +1,10 @@
+use thiserror::Error;
+
+#[derive(Error, Debug)]
+pub enum Sg2dPdfError {
+    #[error("SceneGraph error: {0}")]
+    SceneGraphError(#[from] sg2d::error::SceneGraphError),
+
+    #[error("A PDF document needs at least one scene graph")]
+    NoPages,
+}
@@ -0,0 +1,228 @@
+use crate::compress;
+use pdf_writer::types::{CidFontType, FontFlags, SystemInfo, UnicodeCmap};
+use pdf_writer::{Filter, Finish, Name, Pdf, Rect, Ref, Str};
+use sg2d_text::cosmic_text::fontdb;
+use sg2d_text::cosmic_text::rustybuzz::ttf_parser::GlyphId;
+use sg2d_text::cosmic_text::{Font, FontSystem};
+use std::collections::hash_map::DefaultHasher;
+use std::collections::{BTreeMap, HashMap};
+use std::hash::{Hash, Hasher};
+use std::sync::Arc;
+use subsetter::Profile;
+
+/// Glyph ids are used as character ids, so every font shares the identity ordering
+const SYSTEM_INFO: SystemInfo = SystemInfo {
+    registry: Str(b"Adobe"),
+    ordering: Str(b"Identity"),
+    supplement: 0,
+};
+
+/// Fonts used by the text marks of a document, with the glyphs each one has to keep
+#[derive(Default)]
+pub(crate) struct FontSet {
+    fonts: Vec<UsedFont>,
+    /// Index of each font in `fonts`, or None for fonts that can't be embedded
+    indices: HashMap<fontdb::ID, Option<usize>>,
+}
+
+struct UsedFont {
+    font: Arc<Font>,
+    face_index: u32,
+    post_script_name: String,
+    weight: u16,
+    /// Text of the first cluster each glyph was shaped from, for the ToUnicode map
+    glyphs: BTreeMap<u16, String>,
+}
+
+impl FontSet {
+    /// Index of the font with the given id, loading it from the font system on first use.
+    ///
+    /// Fonts without TrueType or CFF outlines, like color emoji fonts with only CBDT bitmap
+    /// glyphs, can't be shown as PDF text, so they aren't registered and their glyphs are
+    /// left out.
+    pub(crate) fn register(
+        &mut self,
+        font_system: &mut FontSystem,
+        id: fontdb::ID,
+    ) -> Option<usize> {
+        if let Some(index) = self.indices.get(&id) {
+            return *index;
+        }
+        let font = font_system.get_font(id)?;
+        let tables = font.rustybuzz().tables();
+        if tables.glyf.is_none() && tables.cff.is_none() {
+            self.indices.insert(id, None);
+            return None;
+        }
+        let face = font_system.db().face(id)?;
+        let used = UsedFont {
+            face_index: face.index,
+            post_script_name: face.post_script_name.replace(' ', ""),
+            weight: face.weight.0,
+            font,
+            glyphs: BTreeMap::new(),
+        };
+        self.fonts.push(used);
+        self.indices.insert(id, Some(self.fonts.len() - 1));
+        Some(self.fonts.len() - 1)
+    }
+
+    pub(crate) fn add_glyph(&mut self, index: usize, glyph_id: u16, text: &str) {
+        self.fonts[index]
+            .glyphs
+            .entry(glyph_id)
+            .or_insert_with(|| text.to_string());
+    }
+
+    pub(crate) fn resource_name(index: usize) -> String {
+        format!("F{index}")
+    }
+
+    /// Write every font as a subset Type0 font, returning their references in index order
+    pub(crate) fn write(&self, pdf: &mut Pdf, next_ref: &mut Ref) -> Vec<Ref> {
+        self.fonts
+            .iter()
+            .map(|font| font.write(pdf, next_ref))
+            .collect()
+    }
+}
+
+impl UsedFont {
+    fn write(&self, pdf: &mut Pdf, next_ref: &mut Ref) -> Ref {
+        let type0_id = next_ref.bump();
+        let cid_id = next_ref.bump();
+        let descriptor_id = next_ref.bump();
+        let file_id = next_ref.bump();
+        let cmap_id = next_ref.bump();
+
+        let face = self.font.rustybuzz();
+        let cff = face.tables().cff.is_some();
+        let glyph_ids = self.glyphs.keys().copied().collect::<Vec<_>>();
+        let base_font = format!("{}+{}", self.subset_tag(), self.post_script_name);
+        let base_font = Name(base_font.as_bytes());
+
+        pdf.type0_font(type0_id)
+            .base_font(base_font)
+            .encoding_predefined(Name(b"Identity-H"))
+            .descendant_font(cid_id)
+            .to_unicode(cmap_id);
+
+        // Widths are in thousandths of the font size
+        let units_per_em = face.units_per_em() as f32;
+        let to_pdf_units = |units: f32| units * 1000.0 / units_per_em;
+        let mut cid = pdf.cid_font(cid_id);
+        cid.subtype(if cff {
+            CidFontType::Type0
+        } else {
+            CidFontType::Type2
+        })
+        .base_font(base_font)
+        .system_info(SYSTEM_INFO)
+        .font_descriptor(descriptor_id);
+        if !cff {
+            cid.cid_to_gid_map_predefined(Name(b"Identity"));
+        }
+        let mut widths = cid.widths();
+        for (first, run) in consecutive_runs(&glyph_ids) {
+            widths.consecutive(
+                first,
+                run.iter().map(|glyph_id| {
+                    let advance = face.glyph_hor_advance(GlyphId(*glyph_id)).unwrap_or(0);
+                    to_pdf_units(advance as f32)
+                }),
+            );
+        }
+        widths.finish();
+        cid.finish();
+
+        let bbox = face.global_bounding_box();
+        let italic_angle = face.italic_angle().unwrap_or(0.0);
+        let mut flags = FontFlags::SYMBOLIC;
+        if italic_angle != 0.0 {
+            flags |= FontFlags::ITALIC;
+        }
+        if face.is_monospaced() {
+            flags |= FontFlags::FIXED_PITCH;
+        }
+        let ascent = to_pdf_units(face.ascender() as f32);
+        let mut descriptor = pdf.font_descriptor(descriptor_id);
+        descriptor
+            .name(base_font)
+            .flags(flags)
+            .bbox(Rect::new(
+                to_pdf_units(bbox.x_min as f32),
+                to_pdf_units(bbox.y_min as f32),
+                to_pdf_units(bbox.x_max as f32),
+                to_pdf_units(bbox.y_max as f32),
+            ))
+            .italic_angle(italic_angle)
+            .ascent(ascent)
+            .descent(to_pdf_units(face.descender() as f32))
+            .cap_height(
+                face.capital_height()
+                    .map_or(ascent, |h| to_pdf_units(h as f32)),
+            )
+            // Estimate of the stem width from the weight, which PDF readers don't rely on
+            .stem_v(10.0 + 0.244 * (self.weight as f32 - 50.0));
+        if cff {
+            descriptor.font_file3(file_id);
+        } else {
+            descriptor.font_file2(file_id);
+        }
+        descriptor.finish();
+
+        // Subsetting removes the outlines of unused glyphs but keeps glyph ids, so the
+        // identity mapping still holds. Fonts the subsetter can't handle are embedded whole.
+        let data = self.font.data();
+        let subset = subsetter::subset(data, self.face_index, Profile::pdf(&glyph_ids))
+            .unwrap_or_else(|_| data.to_vec());
+        let subset = compress(&subset);
+        let mut stream = pdf.stream(file_id, &subset);
+        stream.filter(Filter::FlateDecode);
+        if cff {
+            stream.pair(Name(b"Subtype"), Name(b"OpenType"));
+        }
+        stream.finish();
+
+        let mut cmap = UnicodeCmap::new(Name(b"Custom"), SYSTEM_INFO);
+        for (glyph_id, text) in &self.glyphs {
+            if !text.is_empty() {
+                cmap.pair_with_multiple(*glyph_id, text.chars());
+            }
+        }
+        let cmap = compress(&cmap.finish());
+        pdf.cmap(cmap_id, &cmap).filter(Filter::FlateDecode);
+
+        type0_id
+    }
+
+    /// Six letter prefix that distinguishes different subsets of the same font
+    fn subset_tag(&self) -> String {
+        let mut hasher = DefaultHasher::new();
+        self.post_script_name.hash(&mut hasher);
+        self.glyphs
+            .keys()
+            .for_each(|glyph_id| glyph_id.hash(&mut hasher));
+        let mut hash = Hasher::finish(&hasher);
+        (0..6)
+            .map(|_| {
+                let letter = (b'A' + (hash % 26) as u8) as char;
+                hash /= 26;
+                letter
+            })
+            .collect()
+    }
+}
+
+/// Split sorted glyph ids into runs of consecutive ids
+fn consecutive_runs(glyph_ids: &[u16]) -> Vec<(u16, &[u16])> {
+    let mut runs = Vec::new();
+    let mut start = 0;
+    for i in 1..=glyph_ids.len() {
+        if i == glyph_ids.len() || glyph_ids[i] != glyph_ids[i - 1] + 1 {
+            runs.push((glyph_ids[start], &glyph_ids[start..i]));
+            start = i;
+        }
+    }
+    runs
+}
@@ -0,0 +1,513 @@
+//! Export scene graphs as PDF documents.
+//!
+//! Each scene graph becomes a page the size of the scene, in points. Marks are written as
+//! vector paths with the same geometry as the other renderers, translucent colors use the
+//! alpha of an ExtGState, and text is drawn glyph by glyph from the `sg2d-text` layout, with
+//! subsets of the fonts that shaped it embedded in the document. Glyphs from fonts without
+//! outlines, like the bitmap glyphs of color emoji fonts, are left out.
+pub mod error;
+mod font;
+
+use crate::error::Sg2dPdfError;
+use crate::font::FontSet;
+use lyon_path::geom::{point, Angle};
+use lyon_path::math::{vector, Transform};
+use lyon_path::{Event, Path};
+use pdf_writer::types::{LineCapStyle, TextRenderingMode};
+use pdf_writer::{Content, Filter, Finish, Name, Pdf, Rect, Ref, Str};
+use sg2d::marks::group::SceneGroup;
+use sg2d::marks::mark::SceneMark;
+use sg2d::marks::rect::RectMark;
+use sg2d::marks::rule::RuleMark;
+use sg2d::marks::symbol::{SymbolMark, SymbolShape};
+use sg2d::marks::text::TextMark;
+use sg2d::scene_graph::SceneGraph;
+use sg2d::value::{Color, StrokeCap};
+use sg2d_text::outline::map_text_items;
+use std::collections::HashMap;
+
+/// Write a scene graph as a single page PDF document
+pub fn to_pdf(scene_graph: &SceneGraph) -> Result<Vec<u8>, Sg2dPdfError> {
+    to_pdf_pages(std::slice::from_ref(scene_graph))
+}
+
+/// Write a PDF document with one page per scene graph
+pub fn to_pdf_pages(scene_graphs: &[SceneGraph]) -> Result<Vec<u8>, Sg2dPdfError> {
+    if scene_graphs.is_empty() {
+        return Err(Sg2dPdfError::NoPages);
+    }
+    let mut pdf = Pdf::new();
+    let mut next_ref = Ref::new(1);
+    let catalog_id = next_ref.bump();
+    let page_tree_id = next_ref.bump();
+
+    let mut resources = Resources::default();
+    let mut page_ids = Vec::with_capacity(scene_graphs.len());
+    for scene_graph in scene_graphs {
+        scene_graph.validate()?;
+        let page_id = next_ref.bump();
+        let content_id = next_ref.bump();
+
+        let mut writer = ContentWriter::new(&mut resources);
+        // Scene graphs are y-down with the origin at the top left of the page
+        writer
+            .content
+            .transform([1.0, 0.0, 0.0, -1.0, 0.0, scene_graph.height])
+            .set_miter_limit(10.0);
+        // Pages without a background are left transparent
+        if let Some(background) = &scene_graph.background {
+            if writer.set_fill(background) {
+                writer
+                    .content
+                    .rect(0.0, 0.0, scene_graph.width, scene_graph.height);
+                writer.paint(true, false);
+            }
+        }
+        for group in &scene_graph.groups {
+            writer.write_group(group);
+        }
+        let content = compress(&writer.content.finish());
+        pdf.stream(content_id, &content).filter(Filter::FlateDecode);
+
+        pdf.page(page_id)
+            .media_box(Rect::new(0.0, 0.0, scene_graph.width, scene_graph.height))
+            .parent(page_tree_id)
+            .contents(content_id);
+        page_ids.push(page_id);
+    }
+
+    // Fonts and alpha states are shared by every page, which inherit them from the page tree
+    let alpha_ids = resources
+        .alphas
+        .iter()
+        .map(|(alpha, stroke)| {
+            let id = next_ref.bump();
+            let mut state = pdf.ext_graphics(id);
+            if *stroke {
+                state.stroking_alpha(*alpha as f32 / 255.0);
+            } else {
+                state.non_stroking_alpha(*alpha as f32 / 255.0);
+            }
+            id
+        })
+        .collect::<Vec<_>>();
+    let font_ids = resources.fonts.write(&mut pdf, &mut next_ref);
+
+    let alpha_names = (0..alpha_ids.len()).map(alpha_name).collect::<Vec<_>>();
+    let font_names = (0..font_ids.len())
+        .map(FontSet::resource_name)
+        .collect::<Vec<_>>();
+    let mut pages = pdf.pages(page_tree_id);
+    pages
+        .kids(page_ids.iter().copied())
+        .count(page_ids.len() as i32);
+    let mut page_resources = pages.resources();
+    page_resources.fonts().pairs(
+        font_names
+            .iter()
+            .map(|name| Name(name.as_bytes()))
+            .zip(font_ids),
+    );
+    page_resources.ext_g_states().pairs(
+        alpha_names
+            .iter()
+            .map(|name| Name(name.as_bytes()))
+            .zip(alpha_ids),
+    );
+    page_resources.finish();
+    pages.finish();
+
+    pdf.catalog(catalog_id).pages(page_tree_id);
+    Ok(pdf.finish())
+}
+
+/// Deflate a stream for the FlateDecode filter
+pub(crate) fn compress(data: &[u8]) -> Vec<u8> {
+    miniz_oxide::deflate::compress_to_vec_zlib(data, 6)
+}
+
+fn alpha_name(index: usize) -> String {
+    format!("Gs{index}")
+}
+
+/// Resources referenced by the content of the pages
+#[derive(Default)]
+struct Resources {
+    /// Alpha of each ExtGState in units of 1/255, and whether it applies to strokes
+    alphas: Vec<(u8, bool)>,
+    alpha_indices: HashMap<(u8, bool), usize>,
+    fonts: FontSet,
+}
+
+impl Resources {
+    fn alpha_state(&mut self, alpha: u8, stroke: bool) -> usize {
+        *self
+            .alpha_indices
+            .entry((alpha, stroke))
+            .or_insert_with(|| {
+                self.alphas.push((alpha, stroke));
+                self.alphas.len() - 1
+            })
+    }
+}
+
+/// The parts of the PDF graphics state that marks set, tracked to skip redundant operators
+#[derive(Clone, Copy)]
+struct GraphicsState {
+    fill: [f32; 3],
+    fill_alpha: u8,
+    stroke: [f32; 3],
+    stroke_alpha: u8,
+    line_width: f32,
+    line_cap: StrokeCap,
+}
+
+impl Default for GraphicsState {
+    fn default() -> Self {
+        Self {
+            fill: [0.0, 0.0, 0.0],
+            fill_alpha: 255,
+            stroke: [0.0, 0.0, 0.0],
+            stroke_alpha: 255,
+            line_width: 1.0,
+            line_cap: StrokeCap::Butt,
+        }
+    }
+}
+
+/// Glyph of shaped text with its text matrix, in group coordinates
+struct TextGlyph {
+    font: usize,
+    font_size: f32,
+    glyph_id: u16,
+    matrix: [f32; 6],
+}
+
+struct ContentWriter<'a> {
+    content: Content,
+    resources: &'a mut Resources,
+    state: GraphicsState,
+    saved_states: Vec<GraphicsState>,
+}
+
+impl<'a> ContentWriter<'a> {
+    fn new(resources: &'a mut Resources) -> Self {
+        Self {
+            content: Content::new(),
+            resources,
+            state: GraphicsState::default(),
+            saved_states: Vec::new(),
+        }
+    }
+
+    fn save(&mut self) {
+        self.content.save_state();
+        self.saved_states.push(self.state);
+    }
+
+    fn restore(&mut self) {
+        self.content.restore_state();
+        self.state = self.saved_states.pop().unwrap_or_default();
+    }
+
+    /// Set the fill color, returning false if the color is fully transparent
+    fn set_fill(&mut self, color: &Color) -> bool {
+        if color.a <= 0.0 {
+            return false;
+        }
+        let rgb = [color.r, color.g, color.b];
+        if rgb != self.state.fill {
+            self.content.set_fill_rgb(rgb[0], rgb[1], rgb[2]);
+            self.state.fill = rgb;
+        }
+        let alpha = (color.a.min(1.0) * 255.0).round() as u8;
+        if alpha != self.state.fill_alpha {
+            let index = self.resources.alpha_state(alpha, false);
+            self.content
+                .set_parameters(Name(alpha_name(index).as_bytes()));
+            self.state.fill_alpha = alpha;
+        }
+        true
+    }
+
+    /// Set the stroke color and width, returning false if nothing would be stroked
+    fn set_stroke(&mut self, color: &Color, width: f32) -> bool {
+        if color.a <= 0.0 || width <= 0.0 {
+            return false;
+        }
+        let rgb = [color.r, color.g, color.b];
+        if rgb != self.state.stroke {
+            self.content.set_stroke_rgb(rgb[0], rgb[1], rgb[2]);
+            self.state.stroke = rgb;
+        }
+        let alpha = (color.a.min(1.0) * 255.0).round() as u8;
+        if alpha != self.state.stroke_alpha {
+            let index = self.resources.alpha_state(alpha, true);
+            self.content
+                .set_parameters(Name(alpha_name(index).as_bytes()));
+            self.state.stroke_alpha = alpha;
+        }
+        if width != self.state.line_width {
+            self.content.set_line_width(width);
+            self.state.line_width = width;
+        }
+        true
+    }
+
+    fn set_line_cap(&mut self, cap: StrokeCap) {
+        if cap != self.state.line_cap {
+            self.content.set_line_cap(match cap {
+                StrokeCap::Butt => LineCapStyle::ButtCap,
+                StrokeCap::Round => LineCapStyle::RoundCap,
+                StrokeCap::Square => LineCapStyle::ProjectingSquareCap,
+            });
+            self.state.line_cap = cap;
+        }
+    }
+
+    /// Fill and/or stroke the current path
+    fn paint(&mut self, fill: bool, stroke: bool) {
+        match (fill, stroke) {
+            (true, true) => self.content.fill_nonzero_and_stroke(),
+            (true, false) => self.content.fill_nonzero(),
+            (false, true) => self.content.stroke(),
+            (false, false) => self.content.end_path(),
+        };
+    }
+
+    fn write_group(&mut self, group: &SceneGroup) {
+        let bounds = &group.bounds;
+        self.save();
+        if bounds.x != 0.0 || bounds.y != 0.0 {
+            self.content
+                .transform([1.0, 0.0, 0.0, 1.0, bounds.x, bounds.y]);
+        }
+
+        for mark in &group.marks {
+            // The clip region is offset with the group, but isn't moved by its transform
+            let clip = match (bounds.width, bounds.height) {
+                (Some(width), Some(height)) if mark.clip() => Some((width, height)),
+                _ => None,
+            };
+            let isolate = clip.is_some() || !group.transform.is_identity();
+            if isolate {
+                self.save();
+                if let Some((width, height)) = clip {
+                    self.content
+                        .rect(0.0, 0.0, width, height)
+                        .clip_nonzero()
+                        .end_path();
+                }
+                if !group.transform.is_identity() {
+                    self.content.transform(group.transform.0);
+                }
+            }
+            match mark {
+                SceneMark::Symbol(mark) => self.write_symbol_mark(mark),
+                SceneMark::Rect(mark) => self.write_rect_mark(mark),
+                SceneMark::Rule(mark) => self.write_rule_mark(mark),
+                SceneMark::Text(mark) => self.write_text_mark(mark),
+                SceneMark::Group(group) => self.write_group(group),
+            }
+            if isolate {
+                self.restore();
+            }
+        }
+        self.restore();
+    }
+
+    fn write_symbol_mark(&mut self, mark: &SymbolMark) {
+        let stroke_width = mark.stroke_width.unwrap_or(0.0);
+        for i in 0..mark.len as usize {
+            let [x, y] = [*mark.x.item(i), *mark.y.item(i)];
+            let scale = mark.size.item(i).sqrt();
+            let stroke_color = mark.stroke.item(i);
+            match &mark.shape {
+                // Fill only inside translucent strokes, like the wgpu circle shader
+                SymbolShape::Circle
+                    if stroke_width > 0.0 && stroke_color.a > 0.0 && stroke_color.a < 1.0 =>
+                {
+                    let radius = scale / 2.0;
+                    let inner_radius = radius - stroke_width / 2.0;
+                    if inner_radius > 0.0 && self.set_fill(mark.fill.item(i)) {
+                        circle(&mut self.content, x, y, inner_radius);
+                        self.paint(true, false);
+                    }
+                    if self.set_stroke(stroke_color, stroke_width) {
+                        circle(&mut self.content, x, y, radius);
+                        self.paint(false, true);
+                    }
+                }
+                SymbolShape::Circle => {
+                    let fill = self.set_fill(mark.fill.item(i));
+                    let stroke = self.set_stroke(stroke_color, stroke_width);
+                    if fill || stroke {
+                        circle(&mut self.content, x, y, scale / 2.0);
+                        self.paint(fill, stroke);
+                    }
+                }
+                SymbolShape::Path(_) if scale == 0.0 => {}
+                SymbolShape::Path(path) => {
+                    let fill = self.set_fill(mark.fill.item(i));
+                    let stroke = self.set_stroke(stroke_color, stroke_width);
+                    if fill || stroke {
+                        // Points are transformed here so that strokes keep a fixed width
+                        let transform = Transform::scale(scale, scale)
+                            .then_rotate(Angle::degrees(*mark.angle.item(i)))
+                            .then_translate(vector(x, y));
+                        path_ops(&mut self.content, path, &transform);
+                        self.paint(fill, stroke);
+                    }
+                }
+            }
+        }
+    }
+
+    fn write_rect_mark(&mut self, mark: &RectMark) {
+        for i in 0..mark.len as usize {
+            if self.set_fill(mark.fill.item(i)) {
+                self.content.rect(
+                    *mark.x.item(i),
+                    *mark.y.item(i),
+                    *mark.width.item(i),
+                    *mark.height.item(i),
+                );
+                self.paint(true, false);
+            }
+        }
+    }
+
+    fn write_rule_mark(&mut self, mark: &RuleMark) {
+        for i in 0..mark.len as usize {
+            if !self.set_stroke(mark.stroke.item(i), *mark.stroke_width.item(i)) {
+                continue;
+            }
+            self.set_line_cap(*mark.stroke_cap.item(i));
+            self.content
+                .move_to(*mark.x0.item(i), *mark.y0.item(i))
+                .line_to(*mark.x1.item(i), *mark.y1.item(i));
+            self.paint(false, true);
+        }
+    }
+
+    fn write_text_mark(&mut self, mark: &TextMark) {
+        let fonts = &mut self.resources.fonts;
+        let items = map_text_items(mark, |font_system, buffer, transform| {
+            let mut glyphs = Vec::new();
+            for run in buffer.layout_runs() {
+                for glyph in run.glyphs {
+                    // Lines start with a directional mark, which has no ink
+                    let text = &run.text[glyph.start..glyph.end];
+                    if text.trim_matches(['\u{200E}', '\u{200F}']).is_empty() {
+                        continue;
+                    }
+                    let Some(font) = fonts.register(font_system, glyph.font_id) else {
+                        continue;
+                    };
+                    fonts.add_glyph(font, glyph.glyph_id, text);
+
+                    // Glyphs are y-up, so the text matrix flips them back in the y-down
+                    // page coordinates
+                    let origin = transform.transform_point(point(
+                        glyph.x + glyph.font_size * glyph.x_offset,
+                        run.line_y + glyph.y - glyph.font_size * glyph.y_offset,
+                    ));
+                    glyphs.push(TextGlyph {
+                        font,
+                        font_size: glyph.font_size,
+                        glyph_id: glyph.glyph_id,
+                        matrix: [
+                            transform.m11,
+                            transform.m12,
+                            -transform.m21,
+                            -transform.m22,
+                            origin.x,
+                            origin.y,
+                        ],
+                    });
+                }
+            }
+            glyphs
+        });
+
+        for (i, glyphs) in items.iter().enumerate() {
+            if glyphs.is_empty() {
+                continue;
+            }
+            // The halo is stroked beneath the fill
+            let stroke = mark.stroke.item(i);
+            let stroke =
+                stroke.with_alpha(stroke.a * mark.stroke_opacity.item(i) * mark.opacity.item(i));
+            if self.set_stroke(&stroke, *mark.stroke_width.item(i)) {
+                self.show_glyphs(glyphs, TextRenderingMode::Stroke);
+            }
+            let color = mark.color.item(i);
+            if self.set_fill(&color.with_alpha(color.a * mark.opacity.item(i))) {
+                self.show_glyphs(glyphs, TextRenderingMode::Fill);
+            }
+        }
+    }
+
+    fn show_glyphs(&mut self, glyphs: &[TextGlyph], mode: TextRenderingMode) {
+        self.content.begin_text().set_text_rendering_mode(mode);
+        let mut current_font = None;
+        for glyph in glyphs {
+            if current_font != Some((glyph.font, glyph.font_size)) {
+                self.content.set_font(
+                    Name(FontSet::resource_name(glyph.font).as_bytes()),
+                    glyph.font_size,
+                );
+                current_font = Some((glyph.font, glyph.font_size));
+            }
+            // Fonts use the Identity-H encoding, so strings are big endian glyph ids
+            self.content
+                .set_text_matrix(glyph.matrix)
+                .show(Str(&glyph.glyph_id.to_be_bytes()));
+        }
+        self.content.end_text();
+    }
+}
+
+/// Add a circle to the current path as four cubic Béziers
+fn circle(content: &mut Content, x: f32, y: f32, radius: f32) {
+    let k = radius * 0.552_284_8;
+    content
+        .move_to(x + radius, y)
+        .cubic_to(x + radius, y + k, x + k, y + radius, x, y + radius)
+        .cubic_to(x - k, y + radius, x - radius, y + k, x - radius, y)
+        .cubic_to(x - radius, y - k, x - k, y - radius, x, y - radius)
+        .cubic_to(x + k, y - radius, x + radius, y - k, x + radius, y)
+        .close_path();
+}
+
+/// Add a transformed path to the current path, with quadratic curves raised to cubics
+fn path_ops(content: &mut Content, path: &Path, transform: &Transform) {
+    for event in path.iter() {
+        match event {
+            Event::Begin { at } => {
+                let at = transform.transform_point(at);
+                content.move_to(at.x, at.y);
+            }
+            Event::Line { to, .. } => {
+                let to = transform.transform_point(to);
+                content.line_to(to.x, to.y);
+            }
+            Event::Quadratic { from, ctrl, to } => {
+                let [from, ctrl, to] = [from, ctrl, to].map(|p| transform.transform_point(p));
+                let ctrl1 = from + (ctrl - from) * (2.0 / 3.0);
+                let ctrl2 = to + (ctrl - to) * (2.0 / 3.0);
+                content.cubic_to(ctrl1.x, ctrl1.y, ctrl2.x, ctrl2.y, to.x, to.y);
+            }
+            Event::Cubic {
+                ctrl1, ctrl2, to, ..
+            } => {
+                let [ctrl1, ctrl2, to] = [ctrl1, ctrl2, to].map(|p| transform.transform_point(p));
+                content.cubic_to(ctrl1.x, ctrl1.y, ctrl2.x, ctrl2.y, to.x, to.y);
+            }
+            Event::End { close: true, .. } => {
+                content.close_path();
+            }
+            Event::End { close: false, .. } => {}
+        }
+    }
+}
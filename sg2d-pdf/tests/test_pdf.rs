@@ -0,0 +1,213 @@
+#[cfg(test)]
+mod test_pdf {
+    use lopdf::{Dictionary, Document, Object};
+    use sg2d::marks::group::SceneGroup;
+    use sg2d::marks::rect::RectMark;
+    use sg2d::marks::symbol::SymbolMark;
+    use sg2d::marks::text::TextMark;
+    use sg2d::scene_graph::SceneGraph;
+    use sg2d::value::Color;
+    use sg2d_pdf::error::Sg2dPdfError;
+    use sg2d_pdf::{to_pdf, to_pdf_pages};
+
+    fn shapes(width: f32, height: f32) -> SceneGraph {
+        SceneGraph::builder(width, height)
+            .group(
+                SceneGroup::builder()
+                    .origin(10.0, 10.0)
+                    .size(50.0, 50.0)
+                    .mark(
+                        RectMark::builder("bars")
+                            .x(vec![0.0, 20.0])
+                            .y_scalar(0.0)
+                            .width_scalar(10.0)
+                            .height_scalar(40.0)
+                            .fill_scalar([0.0, 0.0, 1.0, 0.5])
+                            .clip(true),
+                    )
+                    .mark(
+                        SymbolMark::builder("points")
+                            .x(vec![10.0, 20.0, 30.0])
+                            .y(vec![15.0, 25.0, 35.0])
+                            .fill_scalar([1.0, 0.0, 0.0, 0.5])
+                            .stroke_scalar([0.0, 0.0, 0.0, 0.25])
+                            .stroke_width(2.0),
+                    ),
+            )
+            .build()
+            .unwrap()
+    }
+
+    fn labels(text: &[&str]) -> SceneGraph {
+        SceneGraph::builder(100.0, 40.0)
+            .group(
+                SceneGroup::builder().mark(
+                    TextMark::builder("labels")
+                        .text(text.iter().map(|text| text.to_string()).collect())
+                        .x_scalar(10.0)
+                        .y_scalar(20.0),
+                ),
+            )
+            .build()
+            .unwrap()
+    }
+
+    /// Dictionaries of the objects in the document, including those of streams
+    fn dicts(doc: &Document) -> Vec<&Dictionary> {
+        doc.objects
+            .values()
+            .filter_map(|object| match object {
+                Object::Dictionary(dict) => Some(dict),
+                Object::Stream(stream) => Some(&stream.dict),
+                _ => None,
+            })
+            .collect()
+    }
+
+    fn count_type(doc: &Document, type_name: &[u8], subtype: Option<&[u8]>) -> usize {
+        dicts(doc)
+            .into_iter()
+            .filter(|dict| {
+                dict.get(b"Type").and_then(Object::as_name).ok() == Some(type_name)
+                    && match subtype {
+                        Some(subtype) => {
+                            dict.get(b"Subtype").and_then(Object::as_name).ok() == Some(subtype)
+                        }
+                        None => true,
+                    }
+            })
+            .count()
+    }
+
+    fn media_box(doc: &Document, page: u32) -> Vec<f32> {
+        let page_id = doc.get_pages()[&page];
+        doc.get_dictionary(page_id)
+            .unwrap()
+            .get(b"MediaBox")
+            .unwrap()
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|value| value.as_float().unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn test_pages() {
+        let bytes = to_pdf_pages(&[shapes(200.0, 100.0), shapes(80.0, 120.0)]).unwrap();
+        let doc = Document::load_mem(&bytes).unwrap();
+        assert_eq!(doc.get_pages().len(), 2);
+        assert_eq!(media_box(&doc, 1), vec![0.0, 0.0, 200.0, 100.0]);
+        assert_eq!(media_box(&doc, 2), vec![0.0, 0.0, 80.0, 120.0]);
+
+        // The page content flips to y-down, and clips the bars to the group
+        let content =
+            String::from_utf8(doc.get_page_content(doc.get_pages()[&1]).unwrap()).unwrap();
+        assert!(content.starts_with("1 0 0 -1 0 100 cm"));
+        assert!(content.contains("0 0 50 50 re\nW\nn"));
+    }
+
+    #[test]
+    fn test_alpha() {
+        // Alpha states are shared by the pages, with one state per alpha and paint
+        let bytes = to_pdf_pages(&[shapes(200.0, 100.0), shapes(200.0, 100.0)]).unwrap();
+        let doc = Document::load_mem(&bytes).unwrap();
+        let states = dicts(&doc)
+            .into_iter()
+            .filter(|dict| dict.get(b"Type").and_then(Object::as_name).ok() == Some(b"ExtGState"))
+            .collect::<Vec<_>>();
+        assert_eq!(states.len(), 2);
+        assert!(states.iter().any(|state| state.has(b"ca")));
+        assert!(states.iter().any(|state| state.has(b"CA")));
+    }
+
+    #[test]
+    fn test_fonts() {
+        let bytes = to_pdf_pages(&[labels(&["Hello", "World"]), labels(&["Hello"])]).unwrap();
+        let doc = Document::load_mem(&bytes).unwrap();
+        assert_eq!(doc.get_pages().len(), 2);
+
+        // Both pages use a single embedded subset of the same font
+        assert_eq!(count_type(&doc, b"Font", Some(b"Type0")), 1);
+        assert_eq!(count_type(&doc, b"Font", Some(b"CIDFontType2")), 1);
+        assert_eq!(count_type(&doc, b"FontDescriptor", None), 1);
+        let font = dicts(&doc)
+            .into_iter()
+            .find(|dict| dict.get(b"Subtype").and_then(Object::as_name).ok() == Some(b"Type0"))
+            .unwrap();
+        let base_font = font.get(b"BaseFont").and_then(Object::as_name_str).unwrap();
+        assert_eq!(base_font.find('+'), Some(6));
+
+        let descriptor = dicts(&doc)
+            .into_iter()
+            .find(|dict| {
+                dict.get(b"Type").and_then(Object::as_name).ok() == Some(b"FontDescriptor")
+            })
+            .unwrap();
+        let file_id = descriptor
+            .get(b"FontFile2")
+            .and_then(Object::as_reference)
+            .unwrap();
+        let file = doc.get_object(file_id).and_then(Object::as_stream).unwrap();
+        assert!(!file.decompressed_content().unwrap().is_empty());
+
+        // Glyphs map back to the characters of "HeloWrd" for text extraction
+        let cmap_id = font
+            .get(b"ToUnicode")
+            .and_then(Object::as_reference)
+            .unwrap();
+        let cmap = doc.get_object(cmap_id).and_then(Object::as_stream).unwrap();
+        let cmap = String::from_utf8(cmap.decompressed_content().unwrap()).unwrap();
+        assert!(cmap.contains("7 beginbfchar"));
+        assert!(cmap.contains("<0048>"));
+    }
+
+    #[test]
+    #[cfg(feature = "bundled-emoji-font")]
+    fn test_bitmap_font_glyphs_left_out() {
+        // Noto Color Emoji only has bitmap glyphs, so only Noto Sans is embedded
+        let bytes = to_pdf(&labels(&["ok 😀"])).unwrap();
+        let doc = Document::load_mem(&bytes).unwrap();
+        let base_fonts = dicts(&doc)
+            .into_iter()
+            .filter(|dict| dict.get(b"Subtype").and_then(Object::as_name).ok() == Some(b"Type0"))
+            .map(|dict| dict.get(b"BaseFont").and_then(Object::as_name_str).unwrap())
+            .collect::<Vec<_>>();
+        assert_eq!(base_fonts.len(), 1);
+        assert!(base_fonts[0].ends_with("+NotoSans"));
+
+        // Only the glyphs of "ok " are shown
+        let content = doc.get_page_content(doc.get_pages()[&1]).unwrap();
+        let content = lopdf::content::Content::decode(&content).unwrap();
+        let shown = content
+            .operations
+            .iter()
+            .filter(|operation| operation.operator == "Tj")
+            .count();
+        assert_eq!(shown, 3);
+    }
+
+    #[test]
+    fn test_errors() {
+        assert!(matches!(to_pdf_pages(&[]), Err(Sg2dPdfError::NoPages)));
+        let mut invalid = shapes(10.0, 10.0);
+        invalid.width = f32::NAN;
+        assert!(matches!(
+            to_pdf(&invalid),
+            Err(Sg2dPdfError::SceneGraphError(_))
+        ));
+    }
+
+    #[test]
+    fn test_background() {
+        let page_content = |scene_graph: &SceneGraph| {
+            let doc = Document::load_mem(&to_pdf(scene_graph).unwrap()).unwrap();
+            String::from_utf8(doc.get_page_content(doc.get_pages()[&1]).unwrap()).unwrap()
+        };
+        assert!(!page_content(&labels(&[])).contains("re\nf"));
+
+        let mut scene_graph = labels(&[]);
+        scene_graph.background = Some(Color::rgb(1.0, 1.0, 1.0));
+        assert!(page_content(&scene_graph).contains("1 1 1 rg\n0 0 100 40 re\nf"));
+    }
+}
@@ -0,0 +1,28 @@
+use thiserror::Error;
+
+#[derive(Error, Debug)]
+pub enum Sg2dRenderError {
+    #[error("IO error: {0}")]
+    IoError(#[from] std::io::Error),
+
+    #[error("Invalid JSON: {0}")]
+    JsonError(#[from] serde_json::Error),
+
+    #[error("SceneGraph error: {0}")]
+    SceneGraphError(#[from] sg2d::error::SceneGraphError),
+
+    #[error("Vega scene graph error: {0}")]
+    VegaSceneGraphError(#[from] sg2d_vega::error::VegaSceneGraphError),
+
+    #[error("WGPU error: {0}")]
+    WgpuError(#[from] sg2d_wgpu::error::Sg2dWgpuError),
+
+    #[error("Failed to encode image: {0}")]
+    ImageError(#[from] image::ImageError),
+
+    #[error("The scene graph has no dims file and draws nothing, so its size is unknown")]
+    UnknownSize,
+
+    #[error("Can't render an image of {width}x{height} pixels")]
+    InvalidImageSize { width: f32, height: f32 },
+}
@@ -0,0 +1,236 @@
+//! Render scene graph JSON files headlessly, as used by the `sg2d-render` command.
+//!
+//! Inputs are either Vega scene graphs, as written by `vega.scenegraph().toJSON()`, or scene
+//! graphs in sg2d's own JSON format. Vega scene graphs take their size, origin and background
+//! from a dims file like those written by the gen-test-data crate. Without one, the scene is
+//! sized to fit everything it draws.
+pub mod error;
+
+use crate::error::Sg2dRenderError;
+use clap::ValueEnum;
+use sg2d::scene_graph::SceneGraph;
+use sg2d::value::Color;
+use sg2d_text::bounds::SceneBounds;
+use sg2d_vega::dims::VegaSceneGraphDims;
+use sg2d_vega::scene_graph::VegaSceneGraph;
+use sg2d_wgpu::canvas::{Canvas, PngCanvas};
+use sg2d_wgpu::config::CanvasConfig;
+use sg2d_wgpu::context::RenderContext;
+use sg2d_wgpu::tiled::TiledRenderer;
+use std::io::Cursor;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum OutputFormat {
+    Png,
+    Svg,
+}
+
+impl OutputFormat {
+    pub fn extension(&self) -> &'static str {
+        match self {
+            OutputFormat::Png => "png",
+            OutputFormat::Svg => "svg",
+        }
+    }
+}
+
+/// Name of a scene graph file without its `.sg.json` or `.json` extension
+pub fn scene_name(path: &Path) -> String {
+    let file_name = path
+        .file_name()
+        .map(|name| name.to_string_lossy().to_string())
+        .unwrap_or_default();
+    file_name
+        .strip_suffix(".sg.json")
+        .or_else(|| file_name.strip_suffix(".json"))
+        .unwrap_or(&file_name)
+        .to_string()
+}
+
+/// Dims file next to a scene graph file, `name.dims.json` for `name.sg.json`
+pub fn default_dims_path(path: &Path) -> PathBuf {
+    path.with_file_name(format!("{}.dims.json", scene_name(path)))
+}
+
+/// File that a scene graph is rendered to, in `output_dir` or next to the input
+pub fn output_path(path: &Path, output_dir: Option<&Path>, format: OutputFormat) -> PathBuf {
+    let file_name = format!("{}.{}", scene_name(path), format.extension());
+    match output_dir {
+        Some(output_dir) => output_dir.join(file_name),
+        None => path.with_file_name(file_name),
+    }
+}
+
+/// Read a scene graph file in either format.
+///
+/// The dims file only applies to Vega scene graphs. When it's `None`, `name.dims.json` next to
+/// the input is used if it exists.
+pub fn read_scene_graph(path: &Path, dims: Option<&Path>) -> Result<SceneGraph, Sg2dRenderError> {
+    let value: serde_json::Value = serde_json::from_str(&std::fs::read_to_string(path)?)?;
+
+    // Vega scene graphs are a root group mark, which has no `groups`
+    if value.get("groups").is_some() {
+        let scene_graph: SceneGraph = serde_json::from_value(value)?;
+        scene_graph.validate()?;
+        return Ok(scene_graph);
+    }
+    let vega_scene_graph: VegaSceneGraph = serde_json::from_value(value)?;
+
+    let dims_path = dims
+        .map(Path::to_path_buf)
+        .unwrap_or_else(|| default_dims_path(path));
+    if dims.is_some() || dims_path.exists() {
+        let dims: VegaSceneGraphDims = serde_json::from_str(&std::fs::read_to_string(dims_path)?)?;
+        Ok(vega_scene_graph.to_scene_graph_with_dims(&dims)?)
+    } else {
+        fit_to_bounds(&vega_scene_graph)
+    }
+}
+
+/// Convert a Vega scene graph with an origin and size that fit everything it draws
+fn fit_to_bounds(vega_scene_graph: &VegaSceneGraph) -> Result<SceneGraph, Sg2dRenderError> {
+    let bounds = vega_scene_graph
+        .to_scene_graph([0.0, 0.0], 0.0, 0.0)?
+        .bounds()
+        .ok_or(Sg2dRenderError::UnknownSize)?;
+    let origin = [-bounds.x0.floor(), -bounds.y0.floor()];
+    let width = (bounds.x1 + origin[0]).ceil();
+    let height = (bounds.y1 + origin[1]).ceil();
+    Ok(vega_scene_graph.to_scene_graph(origin, width, height)?)
+}
+
+/// Tile size for images too large for a single texture, small enough for WebGL2 limits
+pub const DEFAULT_TILE_SIZE: u32 = 2048;
+
+/// Renders scene graphs one after another, reusing a single PNG canvas for every scene
+pub struct BatchRenderer {
+    format: OutputFormat,
+    scale: f32,
+    background: Option<Color>,
+    config: CanvasConfig,
+    tile_size: u32,
+    context: Option<Arc<RenderContext>>,
+    canvas: Option<PngCanvas>,
+    tiled: Option<TiledRenderer>,
+}
+
+impl BatchRenderer {
+    pub fn new(format: OutputFormat, scale: f32) -> Self {
+        Self {
+            format,
+            scale,
+            background: None,
+            config: Default::default(),
+            tile_size: DEFAULT_TILE_SIZE,
+            context: None,
+            canvas: None,
+            tiled: None,
+        }
+    }
+
+    /// Override the background of every scene
+    pub fn background(mut self, background: Option<Color>) -> Self {
+        self.background = background;
+        self
+    }
+
+    /// Config used to create the wgpu device when the first PNG is rendered
+    pub fn config(mut self, config: CanvasConfig) -> Self {
+        self.config = config;
+        self
+    }
+
+    /// PNGs wider or taller than this many pixels, or than the device's maximum texture size,
+    /// are rendered in tiles of this size
+    pub fn tile_size(mut self, tile_size: u32) -> Self {
+        self.tile_size = tile_size;
+        self
+    }
+
+    pub fn format(&self) -> OutputFormat {
+        self.format
+    }
+
+    /// Render a scene graph to the contents of a PNG or SVG file
+    pub fn render(&mut self, scene_graph: &SceneGraph) -> Result<Vec<u8>, Sg2dRenderError> {
+        let mut scene_graph = scene_graph.clone();
+        if self.background.is_some() {
+            scene_graph.background = self.background;
+        }
+        match self.format {
+            OutputFormat::Png => self.render_png(&scene_graph),
+            OutputFormat::Svg => Ok(sg2d_svg::to_svg(&scene_graph).into_bytes()),
+        }
+    }
+
+    /// Device and pipelines shared by every PNG. They're only created once a PNG is needed, so
+    /// SVG batches don't need a GPU.
+    fn context(&mut self) -> Result<Arc<RenderContext>, Sg2dRenderError> {
+        if let Some(context) = &self.context {
+            return Ok(context.clone());
+        }
+        let context = Arc::new(pollster::block_on(RenderContext::new(&self.config))?);
+        Ok(self.context.insert(context).clone())
+    }
+
+    fn render_png(&mut self, scene_graph: &SceneGraph) -> Result<Vec<u8>, Sg2dRenderError> {
+        let (width, height, scale) = (scene_graph.width, scene_graph.height, self.scale);
+        // wgpu panics instead of returning an error for empty textures
+        if !(width * scale >= 1.0 && height * scale >= 1.0) {
+            return Err(Sg2dRenderError::InvalidImageSize {
+                width: width * scale,
+                height: height * scale,
+            });
+        }
+        let context = self.context()?;
+
+        let max_size = context.device().limits().max_texture_dimension_2d;
+        let max_size = self.tile_size.min(max_size) as f32;
+        if width * scale > max_size || height * scale > max_size {
+            let tiled = match &mut self.tiled {
+                Some(tiled) => tiled,
+                None => self
+                    .tiled
+                    .insert(TiledRenderer::new(context, scale, self.tile_size)?),
+            };
+            let mut png = Vec::new();
+            pollster::block_on(tiled.render_png(scene_graph, &mut png))?;
+            return Ok(png);
+        }
+
+        let canvas = match &mut self.canvas {
+            Some(canvas) => {
+                if (canvas.width, canvas.height, canvas.scale) != (width, height, scale) {
+                    canvas.resize(width, height, scale)?;
+                }
+                canvas
+            }
+            None => {
+                let canvas = PngCanvas::with_context(context, width, height, scale)?;
+                self.canvas.insert(canvas)
+            }
+        };
+        canvas.set_scene(scene_graph)?;
+        let img = pollster::block_on(canvas.render())?;
+
+        let mut png = Vec::new();
+        img.write_to(&mut Cursor::new(&mut png), image::ImageOutputFormat::Png)?;
+        Ok(png)
+    }
+
+    /// Render a scene graph file, returning the path of the file that was written
+    pub fn render_file(
+        &mut self,
+        path: &Path,
+        dims: Option<&Path>,
+        output_dir: Option<&Path>,
+    ) -> Result<PathBuf, Sg2dRenderError> {
+        let scene_graph = read_scene_graph(path, dims)?;
+        let contents = self.render(&scene_graph)?;
+        let output = output_path(path, output_dir, self.format);
+        std::fs::write(&output, contents)?;
+        Ok(output)
+    }
+}
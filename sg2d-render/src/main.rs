@@ -0,0 +1,73 @@
+use clap::Parser;
+use sg2d::value::Color;
+use sg2d_render::{BatchRenderer, OutputFormat};
+use std::path::PathBuf;
+use std::process::ExitCode;
+
+/// Render scene graph JSON files to PNG or SVG without a window
+#[derive(Parser, Debug)]
+#[command(name = "sg2d-render")]
+struct Args {
+    /// Scene graph files, as Vega scene graphs or in sg2d's JSON format
+    #[arg(required = true)]
+    inputs: Vec<PathBuf>,
+
+    /// Size, origin and background of a Vega scene graph. Only allowed with a single input.
+    /// By default `name.dims.json` next to `name.sg.json` is used when it exists.
+    #[arg(long)]
+    dims: Option<PathBuf>,
+
+    /// Pixels per scene unit of PNG output
+    #[arg(long, default_value_t = 1.0)]
+    scale: f32,
+
+    /// CSS color that replaces the background of every scene
+    #[arg(long, value_parser = parse_color)]
+    background: Option<Color>,
+
+    /// Format of the output files
+    #[arg(long, value_enum, default_value_t = OutputFormat::Png)]
+    format: OutputFormat,
+
+    /// Directory to write output files to, instead of next to each input
+    #[arg(long, short)]
+    output_dir: Option<PathBuf>,
+}
+
+fn parse_color(css: &str) -> Result<Color, String> {
+    Color::parse(css).map_err(|err| err.to_string())
+}
+
+fn main() -> ExitCode {
+    let args = Args::parse();
+    if args.dims.is_some() && args.inputs.len() > 1 {
+        eprintln!("error: --dims can only be used with a single input");
+        return ExitCode::FAILURE;
+    }
+    if let Some(output_dir) = &args.output_dir {
+        if let Err(err) = std::fs::create_dir_all(output_dir) {
+            eprintln!("error: {}: {err}", output_dir.display());
+            return ExitCode::FAILURE;
+        }
+    }
+
+    // Each file is rendered independently, so one failure doesn't stop the batch
+    let mut renderer = BatchRenderer::new(args.format, args.scale).background(args.background);
+    let mut failures = 0;
+    for input in &args.inputs {
+        match renderer.render_file(input, args.dims.as_deref(), args.output_dir.as_deref()) {
+            Ok(output) => println!("{} -> {}", input.display(), output.display()),
+            Err(err) => {
+                eprintln!("error: {}: {err}", input.display());
+                failures += 1;
+            }
+        }
+    }
+
+    if failures > 0 {
+        eprintln!("{failures} of {} files failed to render", args.inputs.len());
+        ExitCode::FAILURE
+    } else {
+        ExitCode::SUCCESS
+    }
+}
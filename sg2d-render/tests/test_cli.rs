@@ -0,0 +1,85 @@
+#[cfg(test)]
+mod test_cli {
+    use std::fs;
+    use std::path::{Path, PathBuf};
+    use std::process::Command;
+
+    fn test_data(name: &str) -> PathBuf {
+        Path::new(env!("CARGO_MANIFEST_DIR"))
+            .join("../sg2d-vega-test-data/vega-scenegraphs/rect")
+            .join(name)
+    }
+
+    fn output_dir(test_name: &str) -> PathBuf {
+        let dir = Path::new(env!("CARGO_TARGET_TMPDIR")).join(test_name);
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    fn sg2d_render() -> Command {
+        Command::new(env!("CARGO_BIN_EXE_sg2d-render"))
+    }
+
+    #[test]
+    fn test_batch_continues_after_errors() {
+        let dir = output_dir("test_batch_continues_after_errors");
+        let broken = dir.join("broken.sg.json");
+        fs::write(&broken, "not json").unwrap();
+
+        let output = sg2d_render()
+            .arg(&broken)
+            .arg(test_data("heatmap.sg.json"))
+            .arg(dir.join("missing.sg.json"))
+            .arg(test_data("stacked_bar.sg.json"))
+            .args(["--format", "svg", "--output-dir"])
+            .arg(&dir)
+            .output()
+            .unwrap();
+
+        assert!(!output.status.success());
+        assert!(dir.join("heatmap.svg").exists());
+        assert!(dir.join("stacked_bar.svg").exists());
+        let stderr = String::from_utf8(output.stderr).unwrap();
+        assert!(stderr.contains("broken.sg.json"), "{stderr}");
+        assert!(stderr.contains("missing.sg.json"), "{stderr}");
+        assert!(stderr.contains("2 of 4 files failed to render"), "{stderr}");
+    }
+
+    #[test]
+    fn test_png() {
+        let dir = output_dir("test_png");
+        let output = sg2d_render()
+            .arg(test_data("stacked_bar.sg.json"))
+            .args(["--scale", "2", "--background", "transparent", "-o"])
+            .arg(&dir)
+            .output()
+            .unwrap();
+
+        assert!(output.status.success());
+        let img = image::open(dir.join("stacked_bar.png")).unwrap().to_rgba8();
+        let dims: serde_json::Value =
+            serde_json::from_str(&fs::read_to_string(test_data("stacked_bar.dims.json")).unwrap())
+                .unwrap();
+        assert_eq!(
+            img.dimensions(),
+            (
+                dims["width"].as_u64().unwrap() as u32 * 2,
+                dims["height"].as_u64().unwrap() as u32 * 2
+            )
+        );
+        assert_eq!(img.get_pixel(0, 0).0[3], 0);
+    }
+
+    #[test]
+    fn test_dims_requires_single_input() {
+        let output = sg2d_render()
+            .arg(test_data("heatmap.sg.json"))
+            .arg(test_data("stacked_bar.sg.json"))
+            .arg("--dims")
+            .arg(test_data("heatmap.dims.json"))
+            .output()
+            .unwrap();
+        assert!(!output.status.success());
+    }
+}
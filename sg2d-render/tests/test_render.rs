@@ -0,0 +1,155 @@
+#[cfg(test)]
+mod test_render {
+    use sg2d::marks::group::SceneGroup;
+    use sg2d::marks::rect::RectMark;
+    use sg2d::scene_graph::SceneGraph;
+    use sg2d::value::Color;
+    use sg2d_render::error::Sg2dRenderError;
+    use sg2d_render::{output_path, read_scene_graph, BatchRenderer, OutputFormat};
+    use sg2d_text::bounds::SceneBounds;
+    use std::fs;
+    use std::path::{Path, PathBuf};
+
+    fn test_data(name: &str) -> PathBuf {
+        Path::new(env!("CARGO_MANIFEST_DIR"))
+            .join("../sg2d-vega-test-data/vega-scenegraphs/rect")
+            .join(name)
+    }
+
+    /// Empty directory for the files written by a test
+    fn output_dir(test_name: &str) -> PathBuf {
+        let dir = Path::new(env!("CARGO_TARGET_TMPDIR")).join(test_name);
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    /// Red rect over the left half of the scene
+    fn scene_graph() -> SceneGraph {
+        SceneGraph::builder(20.0, 10.0)
+            .group(
+                SceneGroup::builder().mark(
+                    RectMark::builder("bars")
+                        .x_scalar(0.0)
+                        .y_scalar(0.0)
+                        .width_scalar(10.0)
+                        .height_scalar(10.0)
+                        .fill_scalar([1.0, 0.0, 0.0, 1.0]),
+                ),
+            )
+            .build()
+            .unwrap()
+    }
+
+    #[test]
+    fn test_output_path() {
+        let input = Path::new("charts/heatmap.sg.json");
+        assert_eq!(
+            output_path(input, None, OutputFormat::Png),
+            Path::new("charts/heatmap.png")
+        );
+        assert_eq!(
+            output_path(input, Some(Path::new("out")), OutputFormat::Svg),
+            Path::new("out/heatmap.svg")
+        );
+        assert_eq!(
+            output_path(Path::new("chart.json"), None, OutputFormat::Png),
+            Path::new("chart.png")
+        );
+    }
+
+    #[test]
+    fn test_vega_with_dims() {
+        let scene_graph = read_scene_graph(&test_data("heatmap.sg.json"), None).unwrap();
+        assert_eq!((scene_graph.width, scene_graph.height), (319.0, 211.0));
+        assert_eq!(scene_graph.background, Some(Color::rgb(1.0, 1.0, 1.0)));
+    }
+
+    #[test]
+    fn test_vega_without_dims() {
+        let dir = output_dir("test_vega_without_dims");
+        let input = dir.join("heatmap.sg.json");
+        fs::copy(test_data("heatmap.sg.json"), &input).unwrap();
+
+        let scene_graph = read_scene_graph(&input, None).unwrap();
+        let bounds = scene_graph.bounds().unwrap();
+        assert!(bounds.x0 >= 0.0 && bounds.y0 >= 0.0);
+        assert!(bounds.x1 <= scene_graph.width && bounds.y1 <= scene_graph.height);
+        assert!(scene_graph.width - bounds.width() < 2.0);
+        assert!(scene_graph.height - bounds.height() < 2.0);
+        assert_eq!(scene_graph.background, None);
+    }
+
+    #[test]
+    fn test_native_format() {
+        let dir = output_dir("test_native_format");
+        let input = dir.join("bars.sg.json");
+        fs::write(&input, serde_json::to_string(&scene_graph()).unwrap()).unwrap();
+
+        let scene_graph = read_scene_graph(&input, None).unwrap();
+        assert_eq!((scene_graph.width, scene_graph.height), (20.0, 10.0));
+        assert_eq!(scene_graph.groups.len(), 1);
+    }
+
+    #[test]
+    fn test_invalid_input() {
+        let dir = output_dir("test_invalid_input");
+        let input = dir.join("broken.sg.json");
+        fs::write(&input, "{\"marktype\": ").unwrap();
+        assert!(matches!(
+            read_scene_graph(&input, None),
+            Err(Sg2dRenderError::JsonError(_))
+        ));
+        assert!(matches!(
+            read_scene_graph(&dir.join("missing.sg.json"), None),
+            Err(Sg2dRenderError::IoError(_))
+        ));
+    }
+
+    #[test]
+    fn test_render_png() {
+        let mut renderer =
+            BatchRenderer::new(OutputFormat::Png, 2.0).background(Some(Color::rgb(0.0, 0.0, 1.0)));
+        let png = renderer.render(&scene_graph()).unwrap();
+        let img = image::load_from_memory(&png).unwrap().to_rgba8();
+        assert_eq!(img.dimensions(), (40, 20));
+        assert_eq!(img.get_pixel(5, 10).0, [255, 0, 0, 255]);
+        assert_eq!(img.get_pixel(35, 10).0, [0, 0, 255, 255]);
+
+        // The canvas is resized for scenes of other sizes
+        let mut larger = scene_graph();
+        larger.width = 30.0;
+        let png = renderer.render(&larger).unwrap();
+        let img = image::load_from_memory(&png).unwrap().to_rgba8();
+        assert_eq!(img.dimensions(), (60, 20));
+    }
+
+    #[test]
+    fn test_render_png_tiled() {
+        // Each renderer creates its own device, so the first is dropped before the second is
+        // created, which the GL backend requires
+        let expected = BatchRenderer::new(OutputFormat::Png, 2.0)
+            .render(&scene_graph())
+            .unwrap();
+
+        let mut tiled_renderer = BatchRenderer::new(OutputFormat::Png, 2.0).tile_size(16);
+        let png = tiled_renderer.render(&scene_graph()).unwrap();
+        assert_eq!(
+            image::load_from_memory(&png).unwrap().to_rgba8(),
+            image::load_from_memory(&expected).unwrap().to_rgba8()
+        );
+    }
+
+    #[test]
+    fn test_render_file_svg() {
+        let dir = output_dir("test_render_file_svg");
+        let mut renderer = BatchRenderer::new(OutputFormat::Svg, 1.0);
+        let output = renderer
+            .render_file(&test_data("heatmap.sg.json"), None, Some(&dir))
+            .unwrap();
+        assert_eq!(output, dir.join("heatmap.svg"));
+        let svg = fs::read_to_string(output).unwrap();
+        assert!(svg.starts_with("<svg"));
+        assert!(svg.contains("width=\"319\""));
+    }
+}
@@ -0,0 +1,403 @@
+use crate::error::Sg2dSkiaError;
+use image::RgbaImage;
+use lyon_path::Event;
+use sg2d::marks::group::SceneGroup;
+use sg2d::marks::mark::SceneMark;
+use sg2d::marks::rect::RectMark;
+use sg2d::marks::rule::RuleMark;
+use sg2d::marks::symbol::{SymbolMark, SymbolShape};
+use sg2d::marks::text::TextMark;
+use sg2d::scene_graph::SceneGraph;
+use sg2d::transform::AffineTransform;
+use sg2d::value::{Color, StrokeCap};
+use sg2d_text::outline::text_mark_outlines;
+use tiny_skia::{
+    FillRule, LineCap, Mask, Paint, Path, PathBuilder, Pixmap, Rect, Stroke, Transform,
+};
+
+/// Vega draws with the canvas miter limit, which is larger than the tiny-skia default
+const MITER_LIMIT: f32 = 10.0;
+
+/// How a draw command paints its path
+#[derive(Debug, Clone)]
+enum DrawPaint {
+    Fill(Color),
+    Stroke(Color, Stroke),
+}
+
+/// Path to paint, with the transform from path coordinates to scene coordinates
+#[derive(Debug, Clone)]
+struct DrawCommand {
+    /// Index into the canvas paths, so that symbols share a single path
+    path: usize,
+    paint: DrawPaint,
+    transform: Transform,
+    /// Index into the canvas clip regions
+    clip: Option<usize>,
+}
+
+/// Canvas that rasterizes scene graphs on the CPU with tiny-skia.
+///
+/// It has the scene-setting API of the wgpu `PngCanvas`, so it can render the same scene graphs
+/// on machines without a GPU adapter.
+pub struct SkiaCanvas {
+    width: f32,
+    height: f32,
+    scale: f32,
+    paths: Vec<Path>,
+    /// Clip rectangles, with the transform from group coordinates to scene coordinates
+    clips: Vec<(Rect, Transform)>,
+    commands: Vec<DrawCommand>,
+    background: Option<Color>,
+    scene_background: Option<Color>,
+}
+
+impl SkiaCanvas {
+    pub fn new(width: f32, height: f32, scale: f32) -> Result<Self, Sg2dSkiaError> {
+        let canvas = Self {
+            width,
+            height,
+            scale,
+            paths: Vec::new(),
+            clips: Vec::new(),
+            commands: Vec::new(),
+            background: None,
+            scene_background: None,
+        };
+        canvas.pixmap_size()?;
+        Ok(canvas)
+    }
+
+    pub fn width(&self) -> f32 {
+        self.width
+    }
+
+    pub fn height(&self) -> f32 {
+        self.height
+    }
+
+    pub fn scale(&self) -> f32 {
+        self.scale
+    }
+
+    /// Override the background of every scene, or use the scene's background again with `None`
+    pub fn set_background(&mut self, background: Option<Color>) {
+        self.background = background;
+    }
+
+    /// Color the image is cleared to: the canvas override, then the scene background, then
+    /// opaque white
+    pub fn background(&self) -> Color {
+        self.background
+            .or(self.scene_background)
+            .unwrap_or(Color::WHITE)
+    }
+
+    fn pixmap_size(&self) -> Result<(u32, u32), Sg2dSkiaError> {
+        let width = (self.width * self.scale).ceil();
+        let height = (self.height * self.scale).ceil();
+        if width >= 1.0 && height >= 1.0 && width <= u32::MAX as f32 && height <= u32::MAX as f32 {
+            Ok((width as u32, height as u32))
+        } else {
+            Err(Sg2dSkiaError::InvalidSize {
+                width: self.width,
+                height: self.height,
+                scale: self.scale,
+            })
+        }
+    }
+
+    pub fn set_scene(&mut self, scene_graph: &SceneGraph) -> Result<(), Sg2dSkiaError> {
+        scene_graph.validate()?;
+        self.paths.clear();
+        self.clips.clear();
+        self.commands.clear();
+        self.scene_background = scene_graph.background;
+        for group in &scene_graph.groups {
+            self.add_group(group, &AffineTransform::IDENTITY);
+        }
+        Ok(())
+    }
+
+    /// Rasterize the current scene over its background, with straight (not premultiplied) alpha
+    pub fn render(&self) -> Result<RgbaImage, Sg2dSkiaError> {
+        let (width, height) = self.pixmap_size()?;
+        let mut pixmap = Pixmap::new(width, height).ok_or(Sg2dSkiaError::InvalidSize {
+            width: self.width,
+            height: self.height,
+            scale: self.scale,
+        })?;
+        let background = self.background();
+        pixmap.fill(tiny_skia::Color::from_rgba8(
+            (background.r * 255.0).round() as u8,
+            (background.g * 255.0).round() as u8,
+            (background.b * 255.0).round() as u8,
+            (background.a * 255.0).round() as u8,
+        ));
+
+        let pixel_transform = Transform::from_scale(self.scale, self.scale);
+        let masks = self
+            .clips
+            .iter()
+            .map(|(rect, transform)| {
+                let mut mask = Mask::new(width, height)?;
+                mask.fill_path(
+                    &PathBuilder::from_rect(*rect),
+                    FillRule::Winding,
+                    true,
+                    transform.post_concat(pixel_transform),
+                );
+                Some(mask)
+            })
+            .collect::<Vec<_>>();
+
+        for command in &self.commands {
+            let path = &self.paths[command.path];
+            let transform = command.transform.post_concat(pixel_transform);
+            let mask = command.clip.and_then(|clip| masks[clip].as_ref());
+            match &command.paint {
+                DrawPaint::Fill(color) => {
+                    pixmap.fill_path(path, &paint(color), FillRule::Winding, transform, mask)
+                }
+                DrawPaint::Stroke(color, stroke) => {
+                    pixmap.stroke_path(path, &paint(color), stroke, transform, mask)
+                }
+            }
+        }
+
+        let data = pixmap
+            .pixels()
+            .iter()
+            .flat_map(|pixel| {
+                let color = pixel.demultiply();
+                [color.red(), color.green(), color.blue(), color.alpha()]
+            })
+            .collect::<Vec<_>>();
+        Ok(RgbaImage::from_raw(width, height, data).expect("Pixmap size matches image size"))
+    }
+
+    /// Add the marks of a group, where `parent` is the transform from the coordinates of the
+    /// group's parent to scene coordinates
+    fn add_group(&mut self, group: &SceneGroup, parent: &AffineTransform) {
+        let transform = group.local_transform().then(parent);
+
+        // The clip region is offset with the group, but isn't moved by its transform
+        let clip = match (group.bounds.width, group.bounds.height) {
+            (Some(width), Some(height)) if group.marks.iter().any(SceneMark::clip) => {
+                Rect::from_xywh(0.0, 0.0, width, height).map(|rect| {
+                    let offset = AffineTransform::translate(group.bounds.x, group.bounds.y);
+                    self.clips.push((rect, to_skia(&offset.then(parent))));
+                    self.clips.len() - 1
+                })
+            }
+            _ => None,
+        };
+
+        for mark in &group.marks {
+            let mut builder = MarkBuilder {
+                canvas: self,
+                transform: to_skia(&transform),
+                clip: clip.filter(|_| mark.clip()),
+            };
+            match mark {
+                SceneMark::Symbol(mark) => builder.add_symbol_mark(mark),
+                SceneMark::Rect(mark) => builder.add_rect_mark(mark),
+                SceneMark::Rule(mark) => builder.add_rule_mark(mark),
+                SceneMark::Text(mark) => builder.add_text_mark(mark),
+                SceneMark::Group(group) => self.add_group(group, &transform),
+            }
+        }
+    }
+}
+
+/// Adds the draw commands of a single mark
+struct MarkBuilder<'a> {
+    canvas: &'a mut SkiaCanvas,
+    /// Transform from group coordinates to scene coordinates
+    transform: Transform,
+    clip: Option<usize>,
+}
+
+impl MarkBuilder<'_> {
+    fn add_path(&mut self, path: Path) -> usize {
+        self.canvas.paths.push(path);
+        self.canvas.paths.len() - 1
+    }
+
+    fn draw(&mut self, path: usize, paint: DrawPaint, transform: Transform) {
+        let visible = match &paint {
+            DrawPaint::Fill(color) => color.a > 0.0,
+            DrawPaint::Stroke(color, stroke) => color.a > 0.0 && stroke.width > 0.0,
+        };
+        if visible {
+            self.canvas.commands.push(DrawCommand {
+                path,
+                paint,
+                transform,
+                clip: self.clip,
+            });
+        }
+    }
+
+    fn add_symbol_mark(&mut self, mark: &SymbolMark) {
+        // Symbols of a mark share a unit path that is scaled by the square root of their size
+        let path = match &mark.shape {
+            SymbolShape::Circle => PathBuilder::from_circle(0.0, 0.0, 0.5),
+            SymbolShape::Path(path) => to_skia_path(path),
+        };
+        let Some(path) = path else {
+            return;
+        };
+        let is_circle = matches!(mark.shape, SymbolShape::Circle);
+        let path = self.add_path(path);
+        let stroke_width = mark.stroke_width.unwrap_or(0.0);
+
+        for i in 0..mark.len as usize {
+            let scale = mark.size.item(i).sqrt();
+            if scale == 0.0 {
+                continue;
+            }
+            let transform = self
+                .transform
+                .pre_translate(*mark.x.item(i), *mark.y.item(i))
+                .pre_rotate(*mark.angle.item(i))
+                .pre_scale(scale, scale);
+            let fill = *mark.fill.item(i);
+            let stroke = *mark.stroke.item(i);
+            // Strokes have a fixed width, in group coordinates
+            let symbol_stroke = Stroke {
+                width: stroke_width / scale,
+                miter_limit: MITER_LIMIT,
+                ..Default::default()
+            };
+
+            if is_circle && stroke_width > 0.0 && stroke.a < 1.0 {
+                // Fill only inside translucent strokes, like the wgpu circle shader
+                let inner = 1.0 - stroke_width / scale;
+                if inner > 0.0 {
+                    self.draw(
+                        path,
+                        DrawPaint::Fill(fill),
+                        transform.pre_scale(inner, inner),
+                    );
+                }
+            } else {
+                self.draw(path, DrawPaint::Fill(fill), transform);
+            }
+            if stroke_width > 0.0 {
+                self.draw(path, DrawPaint::Stroke(stroke, symbol_stroke), transform);
+            }
+        }
+    }
+
+    fn add_rect_mark(&mut self, mark: &RectMark) {
+        // Rects share a unit square that is scaled to their size
+        let square = Rect::from_xywh(0.0, 0.0, 1.0, 1.0).expect("Unit square is valid");
+        let path = self.add_path(PathBuilder::from_rect(square));
+        for i in 0..mark.len as usize {
+            let [width, height] = [*mark.width.item(i), *mark.height.item(i)];
+            if width == 0.0 || height == 0.0 {
+                continue;
+            }
+            let transform = self
+                .transform
+                .pre_translate(*mark.x.item(i), *mark.y.item(i))
+                .pre_scale(width, height);
+            self.draw(path, DrawPaint::Fill(*mark.fill.item(i)), transform);
+        }
+    }
+
+    fn add_rule_mark(&mut self, mark: &RuleMark) {
+        for i in 0..mark.len as usize {
+            let mut builder = PathBuilder::new();
+            builder.move_to(*mark.x0.item(i), *mark.y0.item(i));
+            builder.line_to(*mark.x1.item(i), *mark.y1.item(i));
+            let Some(path) = builder.finish() else {
+                continue;
+            };
+            let path = self.add_path(path);
+            let stroke = Stroke {
+                width: *mark.stroke_width.item(i),
+                line_cap: match mark.stroke_cap.item(i) {
+                    StrokeCap::Butt => LineCap::Butt,
+                    StrokeCap::Round => LineCap::Round,
+                    StrokeCap::Square => LineCap::Square,
+                },
+                miter_limit: MITER_LIMIT,
+                ..Default::default()
+            };
+            self.draw(
+                path,
+                DrawPaint::Stroke(*mark.stroke.item(i), stroke),
+                self.transform,
+            );
+        }
+    }
+
+    fn add_text_mark(&mut self, mark: &TextMark) {
+        // Glyph outlines are laid out like the other renderers lay out text
+        for (i, outline) in text_mark_outlines(mark).iter().enumerate() {
+            let Some(path) = to_skia_path(outline) else {
+                continue;
+            };
+            let path = self.add_path(path);
+            let color = mark.color.item(i);
+            let stroke = mark.stroke.item(i);
+            // Halos are drawn beneath the fill
+            let halo = Stroke {
+                width: *mark.stroke_width.item(i),
+                miter_limit: MITER_LIMIT,
+                ..Default::default()
+            };
+            self.draw(
+                path,
+                DrawPaint::Stroke(
+                    stroke
+                        .with_alpha(stroke.a * mark.stroke_opacity.item(i) * mark.opacity.item(i)),
+                    halo,
+                ),
+                self.transform,
+            );
+            self.draw(
+                path,
+                DrawPaint::Fill(color.with_alpha(color.a * mark.opacity.item(i))),
+                self.transform,
+            );
+        }
+    }
+}
+
+fn paint(color: &Color) -> Paint<'static> {
+    let mut paint = Paint::default();
+    paint.set_color_rgba8(
+        (color.r * 255.0).round() as u8,
+        (color.g * 255.0).round() as u8,
+        (color.b * 255.0).round() as u8,
+        (color.a * 255.0).round() as u8,
+    );
+    paint.anti_alias = true;
+    paint
+}
+
+fn to_skia(transform: &AffineTransform) -> Transform {
+    let [a, b, c, d, e, f] = transform.0;
+    Transform::from_row(a, b, c, d, e, f)
+}
+
+/// Convert a lyon path, or return None if it's empty
+fn to_skia_path(path: &lyon_path::Path) -> Option<Path> {
+    let mut builder = PathBuilder::new();
+    for event in path.iter() {
+        match event {
+            Event::Begin { at } => builder.move_to(at.x, at.y),
+            Event::Line { to, .. } => builder.line_to(to.x, to.y),
+            Event::Quadratic { ctrl, to, .. } => builder.quad_to(ctrl.x, ctrl.y, to.x, to.y),
+            Event::Cubic {
+                ctrl1, ctrl2, to, ..
+            } => builder.cubic_to(ctrl1.x, ctrl1.y, ctrl2.x, ctrl2.y, to.x, to.y),
+            Event::End { close: true, .. } => builder.close(),
+            Event::End { close: false, .. } => {}
+        }
+    }
+    builder.finish()
+}
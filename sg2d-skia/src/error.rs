@@ -0,0 +1,10 @@
+use thiserror::Error;
+
+#[derive(Error, Debug)]
+pub enum Sg2dSkiaError {
+    #[error("SceneGraph error: {0}")]
+    SceneGraphError(#[from] sg2d::error::SceneGraphError),
+
+    #[error("Invalid canvas size: {width}x{height} at scale {scale}")]
+    InvalidSize { width: f32, height: f32, scale: f32 },
+}
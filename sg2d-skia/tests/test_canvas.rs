@@ -0,0 +1,85 @@
+#[cfg(test)]
+mod test_canvas {
+    use sg2d::marks::group::SceneGroup;
+    use sg2d::marks::rect::RectMark;
+    use sg2d::scene_graph::SceneGraph;
+    use sg2d::transform::AffineTransform;
+    use sg2d::value::Color;
+    use sg2d_skia::canvas::SkiaCanvas;
+    use sg2d_skia::error::Sg2dSkiaError;
+
+    fn scene_graph(clip: bool) -> SceneGraph {
+        SceneGraph::builder(40.0, 20.0)
+            .group(
+                SceneGroup::builder()
+                    .origin(10.0, 0.0)
+                    .size(10.0, 20.0)
+                    .transform(AffineTransform::scale(2.0, 1.0))
+                    .mark(
+                        RectMark::builder("bars")
+                            .x_scalar(0.0)
+                            .y_scalar(0.0)
+                            .width_scalar(10.0)
+                            .height_scalar(20.0)
+                            .fill_scalar(Color::rgb(1.0, 0.0, 0.0))
+                            .clip(clip),
+                    ),
+            )
+            .build()
+            .unwrap()
+    }
+
+    #[test]
+    fn test_render() {
+        let mut canvas = SkiaCanvas::new(40.0, 20.0, 2.0).unwrap();
+        canvas.set_scene(&scene_graph(false)).unwrap();
+        let img = canvas.render().unwrap();
+        assert_eq!(img.dimensions(), (80, 40));
+        // The scaled rect covers x from 10 to 30
+        assert_eq!(img.get_pixel(10, 20).0, [255, 255, 255, 255]);
+        assert_eq!(img.get_pixel(30, 20).0, [255, 0, 0, 255]);
+        assert_eq!(img.get_pixel(50, 20).0, [255, 0, 0, 255]);
+        assert_eq!(img.get_pixel(70, 20).0, [255, 255, 255, 255]);
+    }
+
+    #[test]
+    fn test_clip() {
+        let mut canvas = SkiaCanvas::new(40.0, 20.0, 2.0).unwrap();
+        canvas.set_scene(&scene_graph(true)).unwrap();
+        let img = canvas.render().unwrap();
+        // The clip region is 10 wide and isn't scaled by the group transform
+        assert_eq!(img.get_pixel(30, 20).0, [255, 0, 0, 255]);
+        assert_eq!(img.get_pixel(50, 20).0, [255, 255, 255, 255]);
+    }
+
+    #[test]
+    fn test_errors() {
+        assert!(matches!(
+            SkiaCanvas::new(0.0, 10.0, 1.0),
+            Err(Sg2dSkiaError::InvalidSize { .. })
+        ));
+        let mut canvas = SkiaCanvas::new(10.0, 10.0, 1.0).unwrap();
+        let mut invalid = scene_graph(false);
+        invalid.width = f32::NAN;
+        assert!(matches!(
+            canvas.set_scene(&invalid),
+            Err(Sg2dSkiaError::SceneGraphError(_))
+        ));
+    }
+
+    #[test]
+    fn test_background() {
+        let mut scene_graph = scene_graph(false);
+        scene_graph.background = Some(Color::TRANSPARENT);
+        let mut canvas = SkiaCanvas::new(40.0, 20.0, 2.0).unwrap();
+        canvas.set_scene(&scene_graph).unwrap();
+        let img = canvas.render().unwrap();
+        assert_eq!(img.get_pixel(10, 20).0, [0, 0, 0, 0]);
+        assert_eq!(img.get_pixel(30, 20).0, [255, 0, 0, 255]);
+
+        // The canvas background overrides the scene's
+        canvas.set_background(Some(Color::rgb(0.0, 0.0, 1.0)));
+        let img = canvas.render().unwrap();
+        assert_eq!(img.get_pixel(10, 20).0, [0, 0, 255, 255]);
+    }
+}
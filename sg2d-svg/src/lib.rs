@@ -0,0 +1,403 @@
+//! Export scene graphs as SVG documents.
+//!
+//! Marks are written with the same geometry as the GPU renderers: symbols are circles or
+//! paths scaled by the square root of their size and rotated by their angle, rules keep
+//! their caps, and text is laid out with `sg2d-text` so that each line is anchored where
+//! the rasterized glyphs would be. Text stays text, using the mark's font attributes.
+use lyon_path::{Event, Path};
+use sg2d::marks::group::SceneGroup;
+use sg2d::marks::mark::SceneMark;
+use sg2d::marks::rect::RectMark;
+use sg2d::marks::rule::RuleMark;
+use sg2d::marks::symbol::{SymbolMark, SymbolShape};
+use sg2d::marks::text::{
+    FontStyleSpec, FontWeightNameSpec, FontWeightSpec, TextAlignSpec, TextDirectionSpec, TextMark,
+};
+use sg2d::scene_graph::SceneGraph;
+use sg2d::transform::AffineTransform;
+use sg2d::value::{Color, StrokeCap};
+use sg2d_text::font::metric_aliases;
+use sg2d_text::outline::map_text_items;
+use std::fmt::{self, Write};
+
+/// Serialize a scene graph as a standalone SVG document
+pub fn to_svg(scene_graph: &SceneGraph) -> String {
+    let mut svg = String::new();
+    write_svg(scene_graph, &mut svg).expect("Writing to a String can't fail");
+    svg
+}
+
+/// Write a scene graph as a standalone SVG document
+pub fn write_svg<W: Write>(scene_graph: &SceneGraph, out: &mut W) -> fmt::Result {
+    let mut writer = SvgWriter { out, next_clip: 0 };
+    writeln!(
+        writer.out,
+        r#"<svg xmlns="http://www.w3.org/2000/svg" width="{w}" height="{h}" viewBox="0 0 {w} {h}">"#,
+        w = scene_graph.width,
+        h = scene_graph.height,
+    )?;
+    // Without a background the document stays transparent
+    if let Some(background) = scene_graph.background.filter(|color| color.a > 0.0) {
+        writeln!(
+            writer.out,
+            r#"<rect width="100%" height="100%"{}/>"#,
+            paint("fill", &background)
+        )?;
+    }
+    for group in &scene_graph.groups {
+        writer.write_group(group)?;
+    }
+    writeln!(writer.out, "</svg>")
+}
+
+/// Line of shaped text, in the coordinates of its text block
+struct TextLine {
+    text: String,
+    left: f32,
+    width: f32,
+    baseline: f32,
+}
+
+struct SvgWriter<'a, W: Write> {
+    out: &'a mut W,
+    /// Number used for the id of the next clip path
+    next_clip: usize,
+}
+
+impl<W: Write> SvgWriter<'_, W> {
+    fn write_group(&mut self, group: &SceneGroup) -> fmt::Result {
+        let bounds = &group.bounds;
+        if bounds.x == 0.0 && bounds.y == 0.0 {
+            writeln!(self.out, "<g>")?;
+        } else {
+            writeln!(
+                self.out,
+                r#"<g transform="translate({} {})">"#,
+                bounds.x, bounds.y
+            )?;
+        }
+
+        // The clip region is offset with the group, but isn't moved by its transform
+        let clip_id = match (bounds.width, bounds.height) {
+            (Some(width), Some(height)) if group.marks.iter().any(SceneMark::clip) => {
+                let id = format!("clip{}", self.next_clip);
+                self.next_clip += 1;
+                writeln!(
+                    self.out,
+                    r#"<clipPath id="{id}"><rect width="{width}" height="{height}"/></clipPath>"#
+                )?;
+                Some(id)
+            }
+            _ => None,
+        };
+
+        for mark in &group.marks {
+            let clip = clip_id.as_ref().filter(|_| mark.clip());
+            if let Some(id) = clip {
+                writeln!(self.out, r#"<g clip-path="url(#{id})">"#)?;
+            }
+            if !group.transform.is_identity() {
+                writeln!(self.out, "<g {}>", transform_attr(&group.transform))?;
+            }
+            match mark {
+                SceneMark::Symbol(mark) => self.write_symbol_mark(mark)?,
+                SceneMark::Rect(mark) => self.write_rect_mark(mark)?,
+                SceneMark::Rule(mark) => self.write_rule_mark(mark)?,
+                SceneMark::Text(mark) => self.write_text_mark(mark)?,
+                SceneMark::Group(group) => self.write_group(group)?,
+            }
+            if !group.transform.is_identity() {
+                writeln!(self.out, "</g>")?;
+            }
+            if clip.is_some() {
+                writeln!(self.out, "</g>")?;
+            }
+        }
+        writeln!(self.out, "</g>")
+    }
+
+    fn write_symbol_mark(&mut self, mark: &SymbolMark) -> fmt::Result {
+        let path_data = match &mark.shape {
+            SymbolShape::Circle => None,
+            SymbolShape::Path(path) => Some(path_data(path)),
+        };
+        for i in 0..mark.len as usize {
+            let [x, y] = [*mark.x.item(i), *mark.y.item(i)];
+            let scale = mark.size.item(i).sqrt();
+            let fill = paint("fill", mark.fill.item(i));
+            // Paths are in units of the symbol size, but strokes have a fixed width
+            let stroke_scale = if path_data.is_some() { scale } else { 1.0 };
+            let stroke = match mark.stroke_width {
+                Some(stroke_width) => stroke_paint(mark.stroke.item(i), stroke_width, stroke_scale),
+                None => String::new(),
+            };
+            match &path_data {
+                // Fill only inside translucent strokes, like the wgpu circle shader
+                None if !stroke.is_empty() && mark.stroke.item(i).a < 1.0 => {
+                    let radius = scale / 2.0;
+                    let inner_radius = radius - mark.stroke_width.unwrap_or(0.0) / 2.0;
+                    if inner_radius > 0.0 {
+                        writeln!(
+                            self.out,
+                            r#"<circle cx="{x}" cy="{y}" r="{inner_radius}"{fill}/>"#
+                        )?;
+                    }
+                    writeln!(
+                        self.out,
+                        r#"<circle cx="{x}" cy="{y}" r="{radius}" fill="none"{stroke}/>"#
+                    )?
+                }
+                None => writeln!(
+                    self.out,
+                    r#"<circle cx="{x}" cy="{y}" r="{}"{fill}{stroke}/>"#,
+                    scale / 2.0
+                )?,
+                Some(_) if scale == 0.0 => {}
+                Some(d) => writeln!(
+                    self.out,
+                    r#"<path transform="translate({x} {y}) rotate({}) scale({scale})" d="{d}"{fill}{stroke}/>"#,
+                    mark.angle.item(i)
+                )?,
+            }
+        }
+        Ok(())
+    }
+
+    fn write_rect_mark(&mut self, mark: &RectMark) -> fmt::Result {
+        for i in 0..mark.len as usize {
+            writeln!(
+                self.out,
+                r#"<rect x="{}" y="{}" width="{}" height="{}"{}/>"#,
+                mark.x.item(i),
+                mark.y.item(i),
+                mark.width.item(i),
+                mark.height.item(i),
+                paint("fill", mark.fill.item(i)),
+            )?;
+        }
+        Ok(())
+    }
+
+    fn write_rule_mark(&mut self, mark: &RuleMark) -> fmt::Result {
+        for i in 0..mark.len as usize {
+            let cap = match mark.stroke_cap.item(i) {
+                StrokeCap::Butt => "butt",
+                StrokeCap::Round => "round",
+                StrokeCap::Square => "square",
+            };
+            writeln!(
+                self.out,
+                r#"<line x1="{}" y1="{}" x2="{}" y2="{}"{} stroke-linecap="{cap}"/>"#,
+                mark.x0.item(i),
+                mark.y0.item(i),
+                mark.x1.item(i),
+                mark.y1.item(i),
+                stroke_paint(mark.stroke.item(i), *mark.stroke_width.item(i), 1.0),
+            )?;
+        }
+        Ok(())
+    }
+
+    fn write_text_mark(&mut self, mark: &TextMark) -> fmt::Result {
+        // Lines are positioned from the same layout the GPU renderers use, and anchored at
+        // their aligned edge so that small differences in font metrics don't shift them
+        let items = map_text_items(mark, |_, buffer, transform| {
+            let lines = buffer
+                .layout_runs()
+                .map(|run| {
+                    let left = run
+                        .glyphs
+                        .iter()
+                        .map(|glyph| glyph.x)
+                        .fold(f32::INFINITY, f32::min);
+                    // Lines start with a directional mark, which the direction attribute
+                    // replaces
+                    TextLine {
+                        text: run
+                            .text
+                            .trim_start_matches(['\u{200E}', '\u{200F}'])
+                            .to_string(),
+                        left: if left.is_finite() { left } else { 0.0 },
+                        width: run.line_w,
+                        baseline: run.line_y,
+                    }
+                })
+                .filter(|line| !line.text.is_empty())
+                .collect::<Vec<_>>();
+            (*transform, lines)
+        });
+
+        for (i, (transform, lines)) in items.into_iter().enumerate() {
+            if lines.is_empty() {
+                continue;
+            }
+            let align = *mark.align.item(i);
+            let rtl = *mark.dir.item(i) == TextDirectionSpec::Rtl;
+            let anchor = match (align, rtl) {
+                (TextAlignSpec::Center, _) => "middle",
+                (TextAlignSpec::Left, false) | (TextAlignSpec::Right, true) => "start",
+                _ => "end",
+            };
+            let color = mark.color.item(i);
+            let stroke = mark.stroke.item(i);
+            let halo = stroke_paint(
+                &stroke.with_alpha(stroke.a * mark.stroke_opacity.item(i) * mark.opacity.item(i)),
+                *mark.stroke_width.item(i),
+                1.0,
+            );
+            write!(
+                self.out,
+                r#"<text transform="matrix({} {} {} {} {} {})" font-family="{}" font-size="{}" font-weight="{}" font-style="{}" text-anchor="{anchor}"{}{}{halo} paint-order="stroke" xml:space="preserve">"#,
+                transform.m11,
+                transform.m12,
+                transform.m21,
+                transform.m22,
+                transform.m31,
+                transform.m32,
+                escape(&font_family(mark.font.item(i))),
+                mark.font_size.item(i),
+                font_weight(mark.font_weight.item(i)),
+                font_style(mark.font_style.item(i)),
+                if rtl { r#" direction="rtl""# } else { "" },
+                paint("fill", &color.with_alpha(color.a * mark.opacity.item(i))),
+            )?;
+            for line in &lines {
+                let x = match align {
+                    TextAlignSpec::Left => line.left,
+                    TextAlignSpec::Center => line.left + line.width / 2.0,
+                    TextAlignSpec::Right => line.left + line.width,
+                };
+                write!(
+                    self.out,
+                    r#"<tspan x="{x}" y="{}">{}</tspan>"#,
+                    line.baseline,
+                    escape(&line.text)
+                )?;
+            }
+            writeln!(self.out, "</text>")?;
+        }
+        Ok(())
+    }
+}
+
+fn transform_attr(transform: &AffineTransform) -> String {
+    let [a, b, c, d, e, f] = transform.0;
+    format!(r#"transform="matrix({a} {b} {c} {d} {e} {f})""#)
+}
+
+/// Fill or stroke attributes of a color, with transparent colors left unpainted
+fn paint(name: &str, color: &Color) -> String {
+    if color.a <= 0.0 {
+        return format!(r#" {name}="none""#);
+    }
+    let [r, g, b] = [color.r, color.g, color.b].map(|c| (c * 255.0).round() as u8);
+    let mut attrs = format!(r##" {name}="#{r:02x}{g:02x}{b:02x}""##);
+    if color.a < 1.0 {
+        write!(attrs, r#" {name}-opacity="{}""#, color.a).unwrap();
+    }
+    attrs
+}
+
+/// Stroke attributes, with the width divided by the scale of the element's transform
+fn stroke_paint(color: &Color, width: f32, scale: f32) -> String {
+    if width <= 0.0 || color.a <= 0.0 {
+        return String::new();
+    }
+    // Vega draws with the canvas miter limit, which is larger than the SVG default
+    format!(
+        r#"{} stroke-width="{}" stroke-miterlimit="10""#,
+        paint("stroke", color),
+        width / scale
+    )
+}
+
+fn path_data(path: &Path) -> String {
+    let mut d = String::new();
+    for event in path.iter() {
+        match event {
+            Event::Begin { at } => write!(d, "M{} {}", at.x, at.y),
+            Event::Line { to, .. } => write!(d, "L{} {}", to.x, to.y),
+            Event::Quadratic { ctrl, to, .. } => {
+                write!(d, "Q{} {} {} {}", ctrl.x, ctrl.y, to.x, to.y)
+            }
+            Event::Cubic {
+                ctrl1, ctrl2, to, ..
+            } => write!(
+                d,
+                "C{} {} {} {} {} {}",
+                ctrl1.x, ctrl1.y, ctrl2.x, ctrl2.y, to.x, to.y
+            ),
+            Event::End { close: true, .. } => write!(d, "Z"),
+            Event::End { close: false, .. } => Ok(()),
+        }
+        .unwrap();
+    }
+    d
+}
+
+/// CSS font family list, with the generic family names Vega uses mapped to their CSS
+/// keywords. Named families are followed by fonts with the same metrics, which text is shaped
+/// with when the family isn't installed, and a sans serif fallback is added, since text is
+/// shaped with the default font when none of the families are available.
+fn font_family(font: &str) -> String {
+    let mut families = Vec::<String>::new();
+    let named = font
+        .split(',')
+        .map(|family| family.trim().trim_matches(['"', '\'']))
+        .filter(|family| !family.is_empty());
+    for family in named {
+        let generic = match family.to_lowercase().as_str() {
+            "sans serif" | "sans-serif" => Some("sans-serif".to_string()),
+            generic @ ("serif" | "cursive" | "fantasy" | "monospace") => Some(generic.to_string()),
+            _ => None,
+        };
+        if let Some(generic) = generic {
+            families.push(generic);
+            continue;
+        }
+        let aliases = metric_aliases(family)
+            .iter()
+            .filter(|alias| !alias.eq_ignore_ascii_case(family));
+        for name in std::iter::once(&family).chain(aliases) {
+            let quoted = format!("'{name}'");
+            if !families.contains(&quoted) {
+                families.push(quoted);
+            }
+        }
+    }
+    if !families.iter().any(|family| !family.starts_with('\'')) {
+        families.push("sans-serif".to_string());
+    }
+    families.join(", ")
+}
+
+fn font_weight(weight: &FontWeightSpec) -> String {
+    match weight {
+        FontWeightSpec::Name(FontWeightNameSpec::Normal) => "normal".to_string(),
+        FontWeightSpec::Name(FontWeightNameSpec::Bold) => "bold".to_string(),
+        FontWeightSpec::Number(weight) => weight.to_string(),
+    }
+}
+
+fn font_style(style: &FontStyleSpec) -> &'static str {
+    match style {
+        FontStyleSpec::Normal => "normal",
+        FontStyleSpec::Italic => "italic",
+    }
+}
+
+/// Escape text for use in XML content and attribute values
+fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&apos;"),
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
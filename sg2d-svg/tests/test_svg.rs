@@ -0,0 +1,114 @@
+#[cfg(test)]
+mod test_svg {
+    use sg2d::marks::group::SceneGroup;
+    use sg2d::marks::rect::RectMark;
+    use sg2d::marks::rule::RuleMark;
+    use sg2d::marks::text::TextMark;
+    use sg2d::scene_graph::SceneGraph;
+    use sg2d::transform::AffineTransform;
+    use sg2d::value::{Color, StrokeCap};
+    use sg2d_svg::to_svg;
+
+    #[test]
+    fn test_clip_and_transform() {
+        let scene_graph = SceneGraph::builder(100.0, 50.0)
+            .group(
+                SceneGroup::builder()
+                    .origin(10.0, 5.0)
+                    .size(80.0, 40.0)
+                    .transform(AffineTransform::scale(2.0, 2.0))
+                    .mark(
+                        RectMark::builder("bars")
+                            .x(vec![0.0, 20.0])
+                            .width_scalar(10.0)
+                            .height_scalar(5.0)
+                            .fill_scalar(Color::rgba(1.0, 0.0, 0.0, 0.5))
+                            .clip(true),
+                    )
+                    .mark(
+                        RuleMark::builder("rules")
+                            .x1_scalar(10.0)
+                            .stroke_width_scalar(2.0)
+                            .stroke_cap_scalar(StrokeCap::Round)
+                            .clip(false),
+                    ),
+            )
+            .build()
+            .unwrap();
+        let svg = to_svg(&scene_graph);
+        assert!(
+            svg.starts_with(r#"<svg xmlns="http://www.w3.org/2000/svg" width="100" height="50""#)
+        );
+        assert!(svg.contains(r#"<g transform="translate(10 5)">"#));
+        assert!(svg.contains(r#"<clipPath id="clip0"><rect width="80" height="40"/></clipPath>"#));
+        // Only the clipped mark refers to the clip path
+        assert_eq!(svg.matches(r#"clip-path="url(#clip0)""#).count(), 1);
+        assert_eq!(
+            svg.matches(r#"<g transform="matrix(2 0 0 2 0 0)">"#)
+                .count(),
+            2
+        );
+        assert!(svg.contains(r##"fill="#ff0000" fill-opacity="0.5""##));
+        assert!(svg.contains(r#"stroke-linecap="round""#));
+        assert!(svg.trim_end().ends_with("</svg>"));
+    }
+
+    #[test]
+    fn test_text() {
+        let scene_graph = SceneGraph::builder(100.0, 50.0)
+            .group(
+                SceneGroup::builder().mark(
+                    TextMark::builder("labels")
+                        .text(vec!["a < b & c".to_string(), "".to_string()])
+                        .font_scalar("Helvetica Neue, serif".to_string())
+                        .font_size_scalar(12.0),
+                ),
+            )
+            .build()
+            .unwrap();
+        let svg = to_svg(&scene_graph);
+        assert!(svg.contains(">a &lt; b &amp; c</tspan>"));
+        assert!(svg.contains(r#"font-family="&apos;Helvetica Neue&apos;, serif""#));
+        assert!(svg.contains(r#"font-size="12""#));
+        // Empty text has no lines to write
+        assert_eq!(svg.matches("<text ").count(), 1);
+    }
+
+    #[test]
+    fn test_font_metric_aliases() {
+        let scene_graph = SceneGraph::builder(100.0, 50.0)
+            .group(
+                SceneGroup::builder().mark(
+                    TextMark::builder("labels")
+                        .text_scalar("Label")
+                        .font_scalar("Arial, Helvetica".to_string()),
+                ),
+            )
+            .build()
+            .unwrap();
+        let svg = to_svg(&scene_graph);
+        assert!(svg.contains(
+            "font-family=\"&apos;Arial&apos;, &apos;Helvetica&apos;, &apos;Liberation Sans&apos;, \
+            &apos;Arimo&apos;, &apos;Nimbus Sans&apos;, &apos;TeX Gyre Heros&apos;, sans-serif\""
+        ));
+    }
+
+    #[test]
+    fn test_background() {
+        let scene_graph = SceneGraph::builder(20.0, 10.0)
+            .group(SceneGroup::builder())
+            .build()
+            .unwrap();
+        assert!(!to_svg(&scene_graph).contains("<rect"));
+
+        let scene_graph = SceneGraph::builder(20.0, 10.0)
+            .background(Color::rgba(0.0, 0.0, 0.0, 0.25))
+            .group(SceneGroup::builder())
+            .build()
+            .unwrap();
+        let svg = to_svg(&scene_graph);
+        assert!(svg.contains(
+            r##"<rect width="100%" height="100%" fill="#000000" fill-opacity="0.25"/>"##
+        ));
+    }
+}
@@ -0,0 +1,117 @@
+#[cfg(test)]
+mod test_svg_baselines {
+    use dssim::Dssim;
+    use image::RgbaImage;
+    use resvg::tiny_skia::{Color, Pixmap, Transform};
+    use resvg::usvg::{fontdb, Options, PostProcessingSteps, Tree, TreeParsing, TreePostProc};
+    use rstest::rstest;
+    use sg2d::scene_graph::SceneGraph;
+    use sg2d_svg::to_svg;
+    use sg2d_vega::dims::VegaSceneGraphDims;
+    use sg2d_vega::scene_graph::VegaSceneGraph;
+    use std::fs;
+    use std::path::Path;
+
+    /// Render an SVG document with resvg over a white background, using the fonts bundled
+    /// with sg2d-text so that text matches the layout the SVG was written with
+    fn render_svg(svg: &str, scale: f32) -> RgbaImage {
+        // System fonts provide metric compatible substitutes for fonts like Helvetica
+        let mut fontdb = fontdb::Database::new();
+        fontdb.load_system_fonts();
+        let fonts_dir = format!("{}/../sg2d-text/fonts", env!("CARGO_MANIFEST_DIR"));
+        for entry in fs::read_dir(fonts_dir).unwrap() {
+            let path = entry.unwrap().path();
+            if path.extension().is_some_and(|ext| ext == "ttf") {
+                fontdb.load_font_data(fs::read(path).unwrap());
+            }
+        }
+        fontdb.set_sans_serif_family("Noto Sans");
+
+        let mut tree = Tree::from_str(svg, &Options::default()).unwrap();
+        tree.postprocess(
+            PostProcessingSteps {
+                convert_text_into_paths: true,
+            },
+            &fontdb,
+        );
+        let size = tree.size.to_int_size().scale_by(scale).unwrap();
+        let mut pixmap = Pixmap::new(size.width(), size.height()).unwrap();
+        pixmap.fill(Color::WHITE);
+        resvg::render(
+            &tree,
+            Transform::from_scale(scale, scale),
+            &mut pixmap.as_mut(),
+        );
+        RgbaImage::from_raw(size.width(), size.height(), pixmap.take()).unwrap()
+    }
+
+    #[rstest(
+        category,
+        spec_name,
+        tolerance,
+        case("rect", "stacked_bar", 0.001),
+        case("rect", "heatmap", 0.006),
+        case("symbol", "binned_scatter_diamonds", 0.001),
+        case("symbol", "binned_scatter_square", 0.001),
+        case("symbol", "binned_scatter_triangle-down", 0.001),
+        case("symbol", "binned_scatter_triangle-up", 0.001),
+        case("symbol", "binned_scatter_triangle-left", 0.001),
+        case("symbol", "binned_scatter_triangle-right", 0.001),
+        case("symbol", "binned_scatter_triangle", 0.001),
+        case("symbol", "binned_scatter_wedge", 0.001),
+        case("symbol", "binned_scatter_arrow", 0.001),
+        case("symbol", "binned_scatter_cross", 0.001),
+        case("symbol", "binned_scatter_circle", 0.001),
+        case("symbol", "binned_scatter_path", 0.001),
+        case("symbol", "binned_scatter_path_star", 0.001),
+        case("symbol", "binned_scatter_cross_stroke", 0.001),
+        case("symbol", "binned_scatter_circle_stroke", 0.001),
+        case("symbol", "binned_scatter_circle_stroke_no_fill", 0.001),
+        case("symbol", "binned_scatter_path_star_stroke_no_fill", 0.001),
+        case("symbol", "scatter_transparent_stroke", 0.001),
+        case("symbol", "scatter_transparent_stroke_star", 0.006),
+        case("symbol", "wind_vector", 0.0015),
+        case("symbol", "wedge_angle", 0.001),
+        case("symbol", "wedge_stroke_angle", 0.001),
+        case("rule", "wide_rule_axes", 0.0001),
+        case("text", "bar_axis_labels", 0.025)
+    )]
+    fn test_svg_baseline(category: &str, spec_name: &str, tolerance: f64) {
+        let specs_dir = format!(
+            "{}/../sg2d-vega-test-data/vega-scenegraphs/{category}",
+            env!("CARGO_MANIFEST_DIR")
+        );
+        let output_dir = format!("{}/tests/output", env!("CARGO_MANIFEST_DIR"));
+        fs::create_dir_all(Path::new(&output_dir)).unwrap();
+
+        let scene_spec: VegaSceneGraph = serde_json::from_str(
+            &fs::read_to_string(format!("{specs_dir}/{spec_name}.sg.json")).unwrap(),
+        )
+        .unwrap();
+        let scene_dims: VegaSceneGraphDims = serde_json::from_str(
+            &fs::read_to_string(format!("{specs_dir}/{spec_name}.dims.json")).unwrap(),
+        )
+        .unwrap();
+        let scene_graph: SceneGraph = scene_spec
+            .to_scene_graph_with_dims(&scene_dims)
+            .expect("Failed to parse scene graph");
+
+        let svg = to_svg(&scene_graph);
+        fs::write(format!("{output_dir}/{category}-{spec_name}.svg"), &svg).unwrap();
+        let img = render_svg(&svg, 2.0);
+        let result_path = format!("{output_dir}/{category}-{spec_name}.png");
+        img.save(&result_path).unwrap();
+
+        // Compare images
+        let attr = Dssim::new();
+        let expected =
+            dssim::load_image(&attr, Path::new(&format!("{specs_dir}/{spec_name}.png"))).unwrap();
+        let result = dssim::load_image(&attr, result_path).unwrap();
+        let (diff, _) = attr.compare(&expected, result);
+        println!("{diff}");
+        assert!(diff < tolerance);
+    }
+
+    #[test]
+    fn test_marker() {} // Help IDE detect test module
+}
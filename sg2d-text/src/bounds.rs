@@ -0,0 +1,210 @@
+//! Bounding boxes of scene graph marks.
+//!
+//! Text extents depend on shaping, so bounds are computed here rather than in `sg2d`, which
+//! has no access to fonts. Bounds are geometric: they cover every item of a mark, including
+//! items whose colors are fully transparent.
+use crate::metrics::TextMetrics;
+use crate::outline::map_text_items;
+use itertools::izip;
+use lyon_path::geom::{point, Angle};
+use lyon_path::math::Transform;
+use lyon_path::Event;
+use sg2d::bounds::Bounds;
+use sg2d::marks::group::SceneGroup;
+use sg2d::marks::mark::SceneMark;
+use sg2d::marks::rect::RectMark;
+use sg2d::marks::rule::RuleMark;
+use sg2d::marks::symbol::{SymbolMark, SymbolShape};
+use sg2d::marks::text::TextMark;
+use sg2d::scene_graph::SceneGraph;
+use sg2d::value::StrokeCap;
+
+/// Extent of everything drawn by a scene graph element, in the coordinates of the group that
+/// contains it. Scene graph bounds are in scene coordinates.
+pub trait SceneBounds {
+    /// Bounding box, or None if nothing is drawn
+    fn bounds(&self) -> Option<Bounds>;
+}
+
+/// Extent of each item of a mark, in the coordinates of its group
+pub trait ItemBounds {
+    /// Bounding box of every item, in item order
+    fn item_bounds(&self) -> Vec<Bounds>;
+}
+
+impl ItemBounds for RectMark {
+    fn item_bounds(&self) -> Vec<Bounds> {
+        izip!(
+            self.x_iter(),
+            self.y_iter(),
+            self.width_iter(),
+            self.height_iter()
+        )
+        .map(|(x, y, width, height)| Bounds::new(*x, *y, x + width, y + height))
+        .collect()
+    }
+}
+
+impl ItemBounds for RuleMark {
+    fn item_bounds(&self) -> Vec<Bounds> {
+        izip!(
+            self.x0_iter(),
+            self.y0_iter(),
+            self.x1_iter(),
+            self.y1_iter(),
+            self.stroke_width_iter(),
+            self.stroke_cap_iter()
+        )
+        .map(|(x0, y0, x1, y1, stroke_width, stroke_cap)| {
+            let half_width = stroke_width / 2.0;
+            let (dx, dy) = (x1 - x0, y1 - y0);
+            let len = (dx * dx + dy * dy).sqrt();
+            let line = Bounds::new(*x0, *y0, *x1, *y1);
+            match stroke_cap {
+                // A zero length rule with butt caps draws nothing past its endpoint
+                StrokeCap::Butt if len == 0.0 => return line,
+                // Round caps extend half the stroke width from each endpoint in every direction,
+                // as does the square cap of a zero length rule
+                StrokeCap::Round => return line.expand(half_width),
+                StrokeCap::Square if len == 0.0 => return line.expand(half_width),
+                _ => {}
+            }
+
+            // Vectors along the rule and along its normal, with length half the stroke width.
+            // Square caps extend the rule by half the stroke width at both ends.
+            let (ux, uy) = (dx / len * half_width, dy / len * half_width);
+            let (nx, ny) = (-uy, ux);
+            let (ex, ey) = if *stroke_cap == StrokeCap::Square {
+                (ux, uy)
+            } else {
+                (0.0, 0.0)
+            };
+            Bounds::from_points([
+                [x0 - ex + nx, y0 - ey + ny],
+                [x0 - ex - nx, y0 - ey - ny],
+                [x1 + ex + nx, y1 + ey + ny],
+                [x1 + ex - nx, y1 + ey - ny],
+            ])
+            .unwrap_or(line)
+        })
+        .collect()
+    }
+}
+
+impl ItemBounds for SymbolMark {
+    fn item_bounds(&self) -> Vec<Bounds> {
+        let half_stroke = self.stroke_width.unwrap_or(0.0) / 2.0;
+        izip!(
+            self.x_iter(),
+            self.y_iter(),
+            self.size_iter(),
+            self.angle_iter()
+        )
+        .map(|(x, y, size, angle)| {
+            // Symbol size is an area, so shapes are scaled by its square root
+            let scale = size.sqrt();
+            let shape_bounds = match &self.shape {
+                SymbolShape::Circle => {
+                    let r = scale / 2.0;
+                    Bounds::new(x - r, y - r, x + r, y + r)
+                }
+                SymbolShape::Path(path) => {
+                    let transform = Transform::scale(scale, scale)
+                        .then_rotate(Angle::degrees(*angle))
+                        .then_translate((*x, *y).into());
+                    // Control points bound the curves they define
+                    let points = path.iter().flat_map(|event| match event {
+                        Event::Begin { at } => vec![at],
+                        Event::Line { to, .. } => vec![to],
+                        Event::Quadratic { ctrl, to, .. } => vec![ctrl, to],
+                        Event::Cubic {
+                            ctrl1, ctrl2, to, ..
+                        } => vec![ctrl1, ctrl2, to],
+                        Event::End { .. } => vec![],
+                    });
+                    Bounds::from_points(points.map(|p| {
+                        let p = transform.transform_point(p);
+                        [p.x, p.y]
+                    }))
+                    .unwrap_or(Bounds::new(*x, *y, *x, *y))
+                }
+            };
+            shape_bounds.expand(half_stroke)
+        })
+        .collect()
+    }
+}
+
+impl ItemBounds for TextMark {
+    fn item_bounds(&self) -> Vec<Bounds> {
+        // Items are laid out as in text_mark_outlines, and each bound covers the rotated
+        // line boxes of the text block plus half of the halo stroke
+        map_text_items(self, |_, buffer, transform| {
+            let TextMetrics { width, height, .. } = TextMetrics::from_buffer(buffer);
+            let corners =
+                [[0.0, 0.0], [width, 0.0], [0.0, height], [width, height]].map(|[x, y]| {
+                    let p = transform.transform_point(point(x, y));
+                    [p.x, p.y]
+                });
+            Bounds::from_points(corners).expect("Text block has four corners")
+        })
+        .into_iter()
+        .zip(self.stroke_width_iter())
+        .map(|(bounds, stroke_width)| bounds.expand(stroke_width / 2.0))
+        .collect()
+    }
+}
+
+impl SceneBounds for RectMark {
+    fn bounds(&self) -> Option<Bounds> {
+        Bounds::union_all(self.item_bounds())
+    }
+}
+
+impl SceneBounds for RuleMark {
+    fn bounds(&self) -> Option<Bounds> {
+        Bounds::union_all(self.item_bounds())
+    }
+}
+
+impl SceneBounds for SymbolMark {
+    fn bounds(&self) -> Option<Bounds> {
+        Bounds::union_all(self.item_bounds())
+    }
+}
+
+impl SceneBounds for TextMark {
+    fn bounds(&self) -> Option<Bounds> {
+        Bounds::union_all(self.item_bounds())
+    }
+}
+
+impl SceneBounds for SceneMark {
+    fn bounds(&self) -> Option<Bounds> {
+        match self {
+            SceneMark::Symbol(mark) => mark.bounds(),
+            SceneMark::Rect(mark) => mark.bounds(),
+            SceneMark::Rule(mark) => mark.bounds(),
+            SceneMark::Text(mark) => mark.bounds(),
+            SceneMark::Group(group) => group.bounds(),
+        }
+    }
+}
+
+impl SceneBounds for SceneGroup {
+    /// Union of the bounds of every mark in the group, in the coordinates of the group's parent.
+    ///
+    /// The group's own width and height don't contribute because group backgrounds aren't drawn.
+    /// Rotated groups are bounded by their transformed bounding box, which may be loose.
+    fn bounds(&self) -> Option<Bounds> {
+        let local_transform = self.local_transform();
+        Bounds::union_all(self.marks.iter().filter_map(|mark| mark.bounds()))
+            .map(|bounds| bounds.transformed(&local_transform))
+    }
+}
+
+impl SceneBounds for SceneGraph {
+    fn bounds(&self) -> Option<Bounds> {
+        Bounds::union_all(self.groups.iter().filter_map(|group| group.bounds()))
+    }
+}
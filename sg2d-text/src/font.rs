@@ -0,0 +1,367 @@
+use cosmic_text::{
+    Align, Attrs, Buffer, Family, FontSystem, Metrics, Shaping, Style, Weight, Wrap,
+};
+use sg2d::marks::text::{
+    FontStyleSpec, FontWeightNameSpec, FontWeightSpec, TextAlignSpec, TextDirectionSpec,
+};
+use std::sync::{Mutex, OnceLock};
+
+static FONT_SYSTEM: OnceLock<Mutex<FontSystem>> = OnceLock::new();
+
+/// Fonts embedded in the binary so that text renders the same way with or without system fonts
+#[cfg(feature = "bundled-fonts")]
+const BUNDLED_FONTS: [&[u8]; 7] = [
+    include_bytes!("../fonts/NotoSans-Regular.ttf"),
+    include_bytes!("../fonts/NotoSans-Bold.ttf"),
+    include_bytes!("../fonts/NotoSans-Italic.ttf"),
+    include_bytes!("../fonts/NotoSans-BoldItalic.ttf"),
+    include_bytes!("../fonts/NotoSansArabic-Regular.ttf"),
+    include_bytes!("../fonts/NotoSansHebrew-Regular.ttf"),
+    include_bytes!("../fonts/NotoSansDevanagari-Regular.ttf"),
+];
+
+/// Color emoji font with CBDT bitmap glyphs. It's larger than all the other bundled fonts
+/// together, so it has its own feature.
+#[cfg(feature = "bundled-emoji-font")]
+const BUNDLED_EMOJI_FONT: &[u8] = include_bytes!("../fonts/NotoColorEmoji.ttf");
+
+/// Family of the bundled emoji font. Emoji are shaped with it explicitly, since the
+/// fallback order of cosmic-text prefers monochrome system fonts that cover a few emoji.
+#[cfg(feature = "bundled-emoji-font")]
+const BUNDLED_EMOJI_FAMILY: &str = "Noto Color Emoji";
+
+/// Family used for the generic sans serif font when the bundled fonts are available
+#[cfg(feature = "bundled-fonts")]
+const BUNDLED_SANS_SERIF_FAMILY: &str = "Noto Sans";
+
+/// Groups of fonts with the same metrics, following the metric aliases of fontconfig. Text laid
+/// out for a font that isn't installed, like Vega's default of Helvetica, keeps its width when it's
+/// drawn with an installed font from the same group.
+const METRIC_ALIASES: [&[&str]; 3] = [
+    &[
+        "Helvetica",
+        "Arial",
+        "Liberation Sans",
+        "Arimo",
+        "Nimbus Sans",
+        "TeX Gyre Heros",
+    ],
+    &[
+        "Times",
+        "Times New Roman",
+        "Liberation Serif",
+        "Tinos",
+        "Nimbus Roman",
+        "TeX Gyre Termes",
+    ],
+    &[
+        "Courier",
+        "Courier New",
+        "Liberation Mono",
+        "Cousine",
+        "Nimbus Mono PS",
+        "TeX Gyre Cursor",
+    ],
+];
+
+/// Fonts with the same metrics as a font family, including the family itself, or an empty slice
+pub fn metric_aliases(family: &str) -> &'static [&'static str] {
+    METRIC_ALIASES
+        .into_iter()
+        .find(|aliases| {
+            aliases
+                .iter()
+                .any(|alias| alias.eq_ignore_ascii_case(family))
+        })
+        .unwrap_or_default()
+}
+
+const ELLIPSIS: &str = "\u{2026}";
+const LEFT_TO_RIGHT_MARK: char = '\u{200E}';
+const RIGHT_TO_LEFT_MARK: char = '\u{200F}';
+
+fn make_font_system() -> FontSystem {
+    #[allow(unused_mut)]
+    let mut font_system = FontSystem::new();
+    #[cfg(feature = "bundled-fonts")]
+    {
+        let db = font_system.db_mut();
+        for font in BUNDLED_FONTS {
+            db.load_font_data(font.to_vec());
+        }
+        db.set_sans_serif_family(BUNDLED_SANS_SERIF_FAMILY);
+    }
+    #[cfg(feature = "bundled-emoji-font")]
+    font_system
+        .db_mut()
+        .load_font_data(BUNDLED_EMOJI_FONT.to_vec());
+    font_system
+}
+
+/// Run a closure with exclusive access to the process-wide font database.
+///
+/// Text measurement and every text renderer go through this function, so fonts
+/// registered here are seen by both and their results agree exactly.
+pub fn with_font_system<R>(f: impl FnOnce(&mut FontSystem) -> R) -> R {
+    let font_system = FONT_SYSTEM.get_or_init(|| Mutex::new(make_font_system()));
+    // A panic while holding the lock can't leave the font database in an invalid state,
+    // so it's safe to keep using it after the mutex is poisoned
+    let mut font_system = font_system
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner());
+    f(&mut font_system)
+}
+
+/// Font attributes needed to shape a string of text
+#[derive(Debug, Clone, PartialEq)]
+pub struct FontSpec {
+    pub font: String,
+    pub font_size: f32,
+    pub font_weight: FontWeightSpec,
+    pub font_style: FontStyleSpec,
+}
+
+impl Default for FontSpec {
+    fn default() -> Self {
+        Self {
+            font: "sans serif".to_string(),
+            font_size: 10.0,
+            font_weight: Default::default(),
+            font_style: Default::default(),
+        }
+    }
+}
+
+impl FontSpec {
+    pub fn family(&self) -> Family<'_> {
+        match self.font.to_lowercase().as_str() {
+            "serif" => Family::Serif,
+            "sans serif" => Family::SansSerif,
+            "cursive" => Family::Cursive,
+            "fantasy" => Family::Fantasy,
+            "monospace" => Family::Monospace,
+            _ => Family::Name(self.font.as_str()),
+        }
+    }
+
+    pub fn weight(&self) -> Weight {
+        match self.font_weight {
+            FontWeightSpec::Name(FontWeightNameSpec::Bold) => Weight::BOLD,
+            FontWeightSpec::Name(FontWeightNameSpec::Normal) => Weight::NORMAL,
+            FontWeightSpec::Number(w) => Weight(w as u16),
+        }
+    }
+
+    pub fn style(&self) -> Style {
+        match self.font_style {
+            FontStyleSpec::Normal => Style::Normal,
+            FontStyleSpec::Italic => Style::Italic,
+        }
+    }
+
+    pub fn attrs(&self) -> Attrs<'_> {
+        Attrs::new()
+            .family(self.family())
+            .weight(self.weight())
+            .style(self.style())
+    }
+
+    /// Family to shape text with. A named family that isn't installed is replaced by an
+    /// installed font with the same metrics, if there is one.
+    fn installed_family<'a>(&'a self, font_system: &FontSystem) -> Family<'a> {
+        let family = self.family();
+        let Family::Name(name) = family else {
+            return family;
+        };
+        let aliases = metric_aliases(name);
+        if aliases.is_empty() {
+            return family;
+        }
+        let is_installed = |name: &str| {
+            font_system.db().faces().any(|face| {
+                face.families
+                    .iter()
+                    .any(|(family, _)| family.eq_ignore_ascii_case(name))
+            })
+        };
+        if is_installed(name) {
+            return family;
+        }
+        aliases
+            .iter()
+            .find(|alias| is_installed(alias))
+            .map_or(family, |alias| Family::Name(alias))
+    }
+}
+
+/// Layout attributes that affect how a block of text is shaped
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct TextLayoutSpec {
+    /// Horizontal alignment of each line within the block
+    pub align: TextAlignSpec,
+    /// Base direction of each line
+    pub dir: TextDirectionSpec,
+    /// Maximum line width in pixels before text is truncated with an ellipsis, or 0 for no limit
+    pub limit: f32,
+}
+
+impl TextLayoutSpec {
+    fn align(&self) -> Align {
+        match self.align {
+            TextAlignSpec::Left => Align::Left,
+            TextAlignSpec::Center => Align::Center,
+            TextAlignSpec::Right => Align::Right,
+        }
+    }
+}
+
+/// Shape text into a cosmic-text Buffer with one layout line per line of input text.
+///
+/// Lines are never wrapped, matching Vega, which only breaks text on explicit newlines.
+/// The buffer is sized to the widest line, so glyph positions of both left-to-right and
+/// right-to-left lines fall between zero and the block width.
+pub fn shape_text(
+    font_system: &mut FontSystem,
+    text: &str,
+    font: &FontSpec,
+    layout: &TextLayoutSpec,
+) -> Buffer {
+    // Prefix each line with a directional mark so that the paragraph direction follows
+    // the layout spec rather than the first strong character of the line
+    let mark = match layout.dir {
+        TextDirectionSpec::Ltr => LEFT_TO_RIGHT_MARK,
+        TextDirectionSpec::Rtl => RIGHT_TO_LEFT_MARK,
+    };
+    let text = text
+        .lines()
+        .map(|line| {
+            let line = if layout.limit > 0.0 {
+                truncate_line(font_system, line, font, layout)
+            } else {
+                line.to_string()
+            };
+            format!("{mark}{line}")
+        })
+        .collect::<Vec<_>>()
+        .join("\n");
+
+    let mut buffer = shape_lines(font_system, &text, font);
+    for line in buffer.lines.iter_mut() {
+        line.set_align(Some(layout.align()));
+    }
+    buffer.shape_until_scroll(font_system);
+
+    // Narrow the buffer to the widest line so that alignment is relative to the block
+    let width = buffer
+        .layout_runs()
+        .fold(0.0, |width: f32, run| run.line_w.max(width));
+    buffer.set_size(font_system, width, f32::MAX);
+    buffer
+}
+
+fn shape_lines(font_system: &mut FontSystem, text: &str, font: &FontSpec) -> Buffer {
+    let mut buffer = Buffer::new(font_system, Metrics::new(font.font_size, font.font_size));
+    buffer.set_wrap(font_system, Wrap::None);
+    buffer.set_size(font_system, f32::MAX, f32::MAX);
+    let attrs = font.attrs().family(font.installed_family(font_system));
+    #[cfg(feature = "bundled-emoji-font")]
+    {
+        let emoji_attrs = attrs.family(Family::Name(BUNDLED_EMOJI_FAMILY));
+        let spans = emoji_spans(text)
+            .into_iter()
+            .map(|(span, is_emoji)| (span, if is_emoji { emoji_attrs } else { attrs }));
+        buffer.set_rich_text(font_system, spans, Shaping::Advanced);
+    }
+    #[cfg(not(feature = "bundled-emoji-font"))]
+    buffer.set_text(font_system, text, attrs, Shaping::Advanced);
+    buffer.shape_until_scroll(font_system);
+    buffer
+}
+
+/// Split text into runs of emoji and runs of other characters.
+///
+/// A char starts an emoji if it's a pictograph or is followed by the emoji presentation
+/// selector. Joiners, modifiers and tags continue the current emoji sequence.
+#[cfg(feature = "bundled-emoji-font")]
+fn emoji_spans(text: &str) -> Vec<(&str, bool)> {
+    let mut spans = Vec::new();
+    let mut start = 0;
+    let mut in_emoji = false;
+    let mut after_joiner = false;
+    let mut chars = text.char_indices().peekable();
+    while let Some((i, c)) = chars.next() {
+        let next = chars.peek().map(|&(_, next)| next);
+        let is_emoji = if in_emoji && (after_joiner || continues_emoji(c)) {
+            true
+        } else {
+            matches!(c as u32, 0x1F000..=0x1FAFF) || next == Some('\u{FE0F}')
+        };
+        if is_emoji != in_emoji && i > start {
+            spans.push((&text[start..i], in_emoji));
+            start = i;
+        }
+        in_emoji = is_emoji;
+        after_joiner = c == '\u{200D}';
+    }
+    if start < text.len() {
+        spans.push((&text[start..], in_emoji));
+    }
+    spans
+}
+
+/// Whether a char extends the emoji sequence before it
+#[cfg(feature = "bundled-emoji-font")]
+fn continues_emoji(c: char) -> bool {
+    matches!(
+        c as u32,
+        // Zero width joiner, presentation selector, keycap, skin tones and tags
+        0x200D | 0xFE0F | 0x20E3 | 0x1F3FB..=0x1F3FF | 0xE0020..=0xE007F
+    )
+}
+
+fn line_width(font_system: &mut FontSystem, line: &str, font: &FontSpec) -> f32 {
+    shape_lines(font_system, line, font)
+        .layout_runs()
+        .fold(0.0, |width, run| run.line_w.max(width))
+}
+
+/// Truncate a single line of text to fit within the layout limit, following Vega.
+///
+/// Characters are removed from the end of left-to-right text and from the start of
+/// right-to-left text, and replaced by an ellipsis.
+fn truncate_line(
+    font_system: &mut FontSystem,
+    line: &str,
+    font: &FontSpec,
+    layout: &TextLayoutSpec,
+) -> String {
+    if line_width(font_system, line, font) <= layout.limit {
+        return line.to_string();
+    }
+
+    // Byte offsets of every char boundary, including the end of the line
+    let boundaries = line
+        .char_indices()
+        .map(|(i, _)| i)
+        .chain(std::iter::once(line.len()))
+        .collect::<Vec<_>>();
+    let truncated = |keep: usize| match layout.dir {
+        TextDirectionSpec::Ltr => format!("{}{ELLIPSIS}", &line[..boundaries[keep]]),
+        TextDirectionSpec::Rtl => format!(
+            "{ELLIPSIS}{}",
+            &line[boundaries[boundaries.len() - 1 - keep]..]
+        ),
+    };
+
+    // Binary search for the largest number of chars that fit alongside the ellipsis
+    let (mut lo, mut hi) = (0, boundaries.len() - 1);
+    while lo < hi {
+        let mid = (lo + hi).div_ceil(2);
+        if line_width(font_system, &truncated(mid), font) <= layout.limit {
+            lo = mid;
+        } else {
+            hi = mid - 1;
+        }
+    }
+    truncated(lo)
+}
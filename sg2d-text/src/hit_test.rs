@@ -0,0 +1,273 @@
+//! Hit testing of scene graph marks on the CPU.
+//!
+//! Marks are drawn in order, so the topmost item under a point is the last one that contains it.
+//! Like bounds, hit testing lives here because text extents depend on shaping.
+use crate::metrics::TextMetrics;
+use crate::outline::map_text_items;
+use itertools::izip;
+use lyon_algorithms::hit_test::hit_test_path;
+use lyon_path::geom::{point, Angle, LineSegment};
+use lyon_path::iterator::PathIterator;
+use lyon_path::math::{Point, Transform};
+use lyon_path::{Event, FillRule, Path};
+use sg2d::bounds::Bounds;
+use sg2d::marks::group::SceneGroup;
+use sg2d::marks::mark::SceneMark;
+use sg2d::marks::rect::RectMark;
+use sg2d::marks::rule::RuleMark;
+use sg2d::marks::symbol::{SymbolMark, SymbolShape};
+use sg2d::marks::text::TextMark;
+use sg2d::scene_graph::SceneGraph;
+use sg2d::value::StrokeCap;
+
+/// Tolerance, in pixels, used to flatten curves when hit testing paths
+const PATH_TOLERANCE: f32 = 0.05;
+
+/// Topmost mark item under a point
+#[derive(Debug, Clone, PartialEq)]
+pub struct SceneHit {
+    /// Name of the mark
+    pub mark: String,
+    /// Index of the item within the mark
+    pub index: usize,
+    /// Index of the top-level group in the scene graph, followed by the index of each nested
+    /// group within the marks of its parent
+    pub group_path: Vec<usize>,
+}
+
+/// Find the topmost item of a mark that contains a point in the coordinates of its group
+pub trait MarkHitTest {
+    fn hit_item(&self, point: [f32; 2]) -> Option<usize>;
+}
+
+/// Find the topmost mark item in a scene that contains a point in scene coordinates
+pub trait SceneHitTest {
+    fn hit_test(&self, point: [f32; 2]) -> Option<SceneHit>;
+}
+
+/// Index of the last item for which the hit test passed
+fn last_index(hits: impl Iterator<Item = bool>) -> Option<usize> {
+    hits.enumerate()
+        .filter(|(_, hit)| *hit)
+        .last()
+        .map(|(index, _)| index)
+}
+
+impl MarkHitTest for RectMark {
+    fn hit_item(&self, point: [f32; 2]) -> Option<usize> {
+        // Transparent rects aren't drawn, so they aren't hit
+        let hits = izip!(
+            self.x_iter(),
+            self.y_iter(),
+            self.width_iter(),
+            self.height_iter(),
+            self.fill_iter()
+        )
+        .map(|(x, y, width, height, fill)| {
+            fill.a > 0.0 && Bounds::new(*x, *y, x + width, y + height).contains(point)
+        });
+        last_index(hits)
+    }
+}
+
+impl MarkHitTest for RuleMark {
+    fn hit_item(&self, [px, py]: [f32; 2]) -> Option<usize> {
+        let hits = izip!(
+            self.x0_iter(),
+            self.y0_iter(),
+            self.x1_iter(),
+            self.y1_iter(),
+            self.stroke_width_iter(),
+            self.stroke_cap_iter(),
+            self.stroke_iter()
+        )
+        .map(|(x0, y0, x1, y1, stroke_width, stroke_cap, stroke)| {
+            if stroke.a <= 0.0 {
+                return false;
+            }
+            let half_width = stroke_width / 2.0;
+            let (dx, dy) = (x1 - x0, y1 - y0);
+            let len = (dx * dx + dy * dy).sqrt();
+            if len == 0.0 {
+                // Only caps are drawn for a zero length rule
+                let (ox, oy) = ((px - x0).abs(), (py - y0).abs());
+                return match stroke_cap {
+                    StrokeCap::Butt => false,
+                    StrokeCap::Round => ox * ox + oy * oy <= half_width * half_width,
+                    StrokeCap::Square => ox <= half_width && oy <= half_width,
+                };
+            }
+
+            // Distance of the point along the rule from its start, and from the rule's line
+            let along = ((px - x0) * dx + (py - y0) * dy) / len;
+            let across = ((px - x0) * dy - (py - y0) * dx).abs() / len;
+            match stroke_cap {
+                StrokeCap::Butt => (0.0..=len).contains(&along) && across <= half_width,
+                StrokeCap::Square => {
+                    (-half_width..=len + half_width).contains(&along) && across <= half_width
+                }
+                StrokeCap::Round => {
+                    let overshoot = if along < 0.0 {
+                        -along
+                    } else {
+                        (along - len).max(0.0)
+                    };
+                    overshoot * overshoot + across * across <= half_width * half_width
+                }
+            }
+        });
+        last_index(hits)
+    }
+}
+
+impl MarkHitTest for SymbolMark {
+    fn hit_item(&self, [px, py]: [f32; 2]) -> Option<usize> {
+        let stroke_width = self.stroke_width.unwrap_or(0.0);
+        let hits = izip!(
+            self.x_iter(),
+            self.y_iter(),
+            self.fill_iter(),
+            self.size_iter(),
+            self.stroke_iter(),
+            self.angle_iter()
+        )
+        .map(|(x, y, fill, size, stroke, angle)| {
+            // Only visible parts of a symbol are hit
+            let filled = fill.a > 0.0;
+            let stroked = stroke.a > 0.0 && stroke_width > 0.0;
+            let scale = size.sqrt();
+            match &self.shape {
+                SymbolShape::Circle => {
+                    let r = scale / 2.0;
+                    let dist = ((px - x).powi(2) + (py - y).powi(2)).sqrt();
+                    (filled && dist <= r) || (stroked && (dist - r).abs() <= stroke_width / 2.0)
+                }
+                SymbolShape::Path(path) => {
+                    let transform = Transform::scale(scale, scale)
+                        .then_rotate(Angle::degrees(*angle))
+                        .then_translate((*x, *y).into());
+                    let path = path.clone().transformed(&transform);
+                    let p = point(px, py);
+                    (filled && hit_test_path(&p, path.iter(), FillRule::NonZero, PATH_TOLERANCE))
+                        || (stroked && near_path(&path, p, stroke_width / 2.0))
+                }
+            }
+        });
+        last_index(hits)
+    }
+}
+
+impl MarkHitTest for TextMark {
+    /// Text items are hit anywhere within their rotated text block, padded by half of the
+    /// halo stroke width when the halo is visible
+    fn hit_item(&self, [px, py]: [f32; 2]) -> Option<usize> {
+        let blocks = map_text_items(self, |_, buffer, transform| {
+            // Map the point into the coordinates of the unrotated text block
+            let TextMetrics { width, height, .. } = TextMetrics::from_buffer(buffer);
+            transform.inverse().map(|inverse| {
+                let p = inverse.transform_point(point(px, py));
+                (Bounds::new(0.0, 0.0, width, height), [p.x, p.y])
+            })
+        });
+        let hits = blocks
+            .into_iter()
+            .zip(text_hit_padding(self))
+            .map(|(block, padding)| {
+                block
+                    .zip(padding)
+                    .is_some_and(|((bounds, p), padding)| bounds.expand(padding).contains(p))
+            });
+        last_index(hits)
+    }
+}
+
+/// Padding of the hit region of each text item around its text block: half of the halo stroke
+/// width when the halo is visible, and zero otherwise. Items whose text and halo are both
+/// invisible aren't drawn, so they have no hit region.
+pub fn text_hit_padding(mark: &TextMark) -> impl Iterator<Item = Option<f32>> + '_ {
+    izip!(
+        mark.color_iter(),
+        mark.opacity_iter(),
+        mark.stroke_iter(),
+        mark.stroke_opacity_iter(),
+        mark.stroke_width_iter()
+    )
+    .map(|(color, opacity, stroke, stroke_opacity, stroke_width)| {
+        if *stroke_width > 0.0 && stroke.a * stroke_opacity * opacity > 0.0 {
+            Some(stroke_width / 2.0)
+        } else if color.a * opacity > 0.0 {
+            Some(0.0)
+        } else {
+            None
+        }
+    })
+}
+
+impl SceneHitTest for SceneGraph {
+    fn hit_test(&self, point: [f32; 2]) -> Option<SceneHit> {
+        self.groups
+            .iter()
+            .enumerate()
+            .rev()
+            .find_map(|(i, group)| hit_group(group, point, vec![i]))
+    }
+}
+
+/// Hit test the marks of a group, topmost first.
+///
+/// `point` is in the coordinates of the group's parent, and marks with `clip` set are only hit
+/// inside the width and height of their group, measured from the group offset.
+fn hit_group(group: &SceneGroup, point: [f32; 2], group_path: Vec<usize>) -> Option<SceneHit> {
+    let bounds = &group.bounds;
+    let within = |start: f32, size: Option<f32>, p: f32| match size {
+        Some(size) => (start..=start + size).contains(&p),
+        None => true,
+    };
+    let in_group =
+        within(bounds.x, bounds.width, point[0]) && within(bounds.y, bounds.height, point[1]);
+
+    // Nothing is drawn by groups whose transform collapses the plane
+    let point = group.local_transform().inverse()?.apply(point);
+
+    let hit_mark = |name: &String, clip: bool, mark: &dyn MarkHitTest| {
+        if clip && !in_group {
+            return None;
+        }
+        Some(SceneHit {
+            mark: name.clone(),
+            index: mark.hit_item(point)?,
+            group_path: group_path.clone(),
+        })
+    };
+    group
+        .marks
+        .iter()
+        .enumerate()
+        .rev()
+        .find_map(|(i, mark)| match mark {
+            SceneMark::Group(group) => {
+                let mut group_path = group_path.clone();
+                group_path.push(i);
+                hit_group(group, point, group_path)
+            }
+            SceneMark::Rect(mark) => hit_mark(&mark.name, mark.clip, mark),
+            SceneMark::Rule(mark) => hit_mark(&mark.name, mark.clip, mark),
+            SceneMark::Symbol(mark) => hit_mark(&mark.name, mark.clip, mark),
+            SceneMark::Text(mark) => hit_mark(&mark.name, mark.clip, mark.as_ref()),
+        })
+}
+
+/// Whether a point is within `distance` of the outline of a path
+fn near_path(path: &Path, p: Point, distance: f32) -> bool {
+    path.iter()
+        .flattened(PATH_TOLERANCE)
+        .any(|event| match event {
+            Event::Line { from, to }
+            | Event::End {
+                last: from,
+                first: to,
+                close: true,
+            } => LineSegment { from, to }.distance_to_point(p) <= distance,
+            _ => false,
+        })
+}
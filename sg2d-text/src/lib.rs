@@ -0,0 +1,7 @@
+pub mod bounds;
+pub mod font;
+pub mod hit_test;
+pub mod metrics;
+pub mod outline;
+
+pub use cosmic_text;
@@ -0,0 +1,98 @@
+use crate::font::{shape_text, with_font_system, FontSpec, TextLayoutSpec};
+use cosmic_text::Buffer;
+use sg2d::marks::text::{TextAlignSpec, TextBaselineSpec};
+
+/// Metrics of a single line of shaped text.
+///
+/// Vertical positions are in pixels, measured down from the top of the text block.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct LineMetrics {
+    pub width: f32,
+    pub top: f32,
+    pub baseline: f32,
+    pub ascent: f32,
+    pub descent: f32,
+}
+
+/// Metrics of a block of shaped text
+#[derive(Debug, Clone, PartialEq)]
+pub struct TextMetrics {
+    /// Width of the widest line
+    pub width: f32,
+    /// Total height of all line boxes
+    pub height: f32,
+    pub line_height: f32,
+    /// Ascent of the first line
+    pub ascent: f32,
+    /// Descent of the last line
+    pub descent: f32,
+    pub lines: Vec<LineMetrics>,
+}
+
+impl TextMetrics {
+    /// Compute metrics from a Buffer that has already been shaped
+    pub fn from_buffer(buffer: &Buffer) -> Self {
+        let line_height = buffer.metrics().line_height;
+        let layout_lines = buffer
+            .lines
+            .iter()
+            .filter_map(|line| line.layout_opt().as_ref())
+            .flatten();
+
+        let lines = buffer
+            .layout_runs()
+            .zip(layout_lines)
+            .map(|(run, layout_line)| LineMetrics {
+                width: run.line_w,
+                top: run.line_top,
+                baseline: run.line_y,
+                ascent: layout_line.max_ascent,
+                descent: layout_line.max_descent,
+            })
+            .collect::<Vec<_>>();
+
+        Self {
+            width: lines.iter().fold(0.0, |width, line| line.width.max(width)),
+            height: lines.len() as f32 * line_height,
+            line_height,
+            ascent: lines.first().map(|line| line.ascent).unwrap_or(0.0),
+            descent: lines.last().map(|line| line.descent).unwrap_or(0.0),
+            lines,
+        }
+    }
+
+    /// Offset from a text anchor point to the top-left corner of the text block
+    pub fn block_offset(&self, align: TextAlignSpec, baseline: TextBaselineSpec) -> [f32; 2] {
+        let x = match align {
+            TextAlignSpec::Left => 0.0,
+            TextAlignSpec::Center => -self.width / 2.0,
+            TextAlignSpec::Right => -self.width,
+        };
+
+        let y = match baseline {
+            // The anchor is on the baseline of the first line
+            TextBaselineSpec::Alphabetic => {
+                -self.lines.first().map_or(self.ascent, |line| line.baseline)
+            }
+            // Add half pixel for top baseline for better match with resvg
+            TextBaselineSpec::Top => 0.5,
+            TextBaselineSpec::Middle => -self.height * 0.5,
+            TextBaselineSpec::Bottom => -self.height,
+            TextBaselineSpec::LineTop => 0.0,
+            TextBaselineSpec::LineBottom => -self.height,
+        };
+        [x, y]
+    }
+}
+
+/// Measure text using the shared font database, without requiring a GPU
+pub fn measure_text(text: &str, spec: &FontSpec) -> TextMetrics {
+    measure_text_layout(text, spec, &TextLayoutSpec::default())
+}
+
+/// Measure text shaped with the given direction, alignment and truncation limit
+pub fn measure_text_layout(text: &str, font: &FontSpec, layout: &TextLayoutSpec) -> TextMetrics {
+    with_font_system(|font_system| {
+        TextMetrics::from_buffer(&shape_text(font_system, text, font, layout))
+    })
+}
@@ -0,0 +1,142 @@
+use crate::font::{shape_text, with_font_system, FontSpec, TextLayoutSpec};
+use crate::metrics::TextMetrics;
+use cosmic_text::{Buffer, CacheKey, Command, FontSystem, SwashCache};
+use itertools::izip;
+use lyon_path::geom::{point, Angle};
+use lyon_path::math::Transform;
+use lyon_path::Path;
+use sg2d::marks::text::TextMark;
+
+/// Build a path from the outlines of every glyph in a shaped Buffer.
+///
+/// Coordinates are in pixels relative to the top-left corner of the text block,
+/// with y increasing downward to match the placement of rasterized glyphs.
+pub fn buffer_outline(
+    font_system: &mut FontSystem,
+    cache: &mut SwashCache,
+    buffer: &Buffer,
+) -> Path {
+    let mut builder = Path::builder();
+    for run in buffer.layout_runs() {
+        for glyph in run.glyphs {
+            // Outlines don't depend on subpixel position, so use a zero offset to share
+            // cache entries across all instances of a glyph
+            let (cache_key, _, _) =
+                CacheKey::new(glyph.font_id, glyph.glyph_id, glyph.font_size, (0.0, 0.0));
+            let Some(commands) = cache.get_outline_commands(font_system, cache_key) else {
+                continue;
+            };
+
+            // Glyph outlines are y-up with the origin on the baseline
+            let origin_x = glyph.x + glyph.font_size * glyph.x_offset;
+            let origin_y = run.line_y + glyph.y - glyph.font_size * glyph.y_offset;
+            let pt = |x: f32, y: f32| point(origin_x + x, origin_y - y);
+
+            let mut open = false;
+            for command in commands {
+                match command {
+                    Command::MoveTo(p) => {
+                        if open {
+                            builder.end(false);
+                        }
+                        builder.begin(pt(p.x, p.y));
+                        open = true;
+                    }
+                    Command::LineTo(p) => {
+                        builder.line_to(pt(p.x, p.y));
+                    }
+                    Command::CurveTo(c1, c2, p) => {
+                        builder.cubic_bezier_to(pt(c1.x, c1.y), pt(c2.x, c2.y), pt(p.x, p.y));
+                    }
+                    Command::QuadTo(c, p) => {
+                        builder.quadratic_bezier_to(pt(c.x, c.y), pt(p.x, p.y));
+                    }
+                    Command::Close => {
+                        if open {
+                            builder.end(true);
+                            open = false;
+                        }
+                    }
+                }
+            }
+            if open {
+                builder.end(false);
+            }
+        }
+    }
+    builder.build()
+}
+
+/// Build glyph outline paths for every item of a text mark, in scene coordinates.
+///
+/// Each item is placed like Vega places text: the block is offset from the anchor by its
+/// alignment, baseline and `dx`/`dy`, then rotated by `angle` degrees about the anchor.
+pub fn text_mark_outlines(mark: &TextMark) -> Vec<Path> {
+    let mut cache = SwashCache::new();
+    map_text_items(mark, |font_system, buffer, transform| {
+        buffer_outline(font_system, &mut cache, buffer).transformed(transform)
+    })
+}
+
+/// Shape every item of a text mark and call `f` with the shaped block and the transform
+/// from block coordinates to scene coordinates
+pub fn map_text_items<R>(
+    mark: &TextMark,
+    mut f: impl FnMut(&mut FontSystem, &Buffer, &Transform) -> R,
+) -> Vec<R> {
+    with_font_system(|font_system| {
+        izip!(
+            mark.text_iter(),
+            mark.x_iter(),
+            mark.y_iter(),
+            mark.dx_iter(),
+            mark.dy_iter(),
+            mark.angle_iter(),
+            mark.align_iter(),
+            mark.baseline_iter(),
+            mark.dir_iter(),
+            mark.limit_iter(),
+            izip!(
+                mark.font_iter(),
+                mark.font_size_iter(),
+                mark.font_weight_iter(),
+                mark.font_style_iter(),
+            ),
+        )
+        .map(
+            |(
+                text,
+                x,
+                y,
+                dx,
+                dy,
+                angle,
+                align,
+                baseline,
+                dir,
+                limit,
+                (font, font_size, font_weight, font_style),
+            )| {
+                let font = FontSpec {
+                    font: font.clone(),
+                    font_size: *font_size,
+                    font_weight: *font_weight,
+                    font_style: *font_style,
+                };
+                let layout = TextLayoutSpec {
+                    align: *align,
+                    dir: *dir,
+                    limit: *limit,
+                };
+                let buffer = shape_text(font_system, text, &font, &layout);
+                let [offset_x, offset_y] =
+                    TextMetrics::from_buffer(&buffer).block_offset(*align, *baseline);
+                let transform = Transform::translation(offset_x + dx, offset_y + dy)
+                    .then_rotate(Angle::degrees(*angle))
+                    .then_translate((*x, *y).into());
+                f(font_system, &buffer, &transform)
+            },
+        )
+        .collect()
+    })
+}
@@ -0,0 +1,275 @@
+#[cfg(test)]
+mod tests {
+    use lyon_path::geom::point;
+    use lyon_path::Path;
+    use sg2d::bounds::Bounds;
+    use sg2d::marks::group::{GroupBounds, SceneGroup};
+    use sg2d::marks::mark::SceneMark;
+    use sg2d::marks::rect::RectMark;
+    use sg2d::marks::rule::RuleMark;
+    use sg2d::marks::symbol::{SymbolMark, SymbolShape};
+    use sg2d::marks::text::{TextAlignSpec, TextBaselineSpec, TextMark};
+    use sg2d::scene_graph::SceneGraph;
+    use sg2d::transform::AffineTransform;
+    use sg2d::value::{EncodingValue, StrokeCap};
+    use sg2d_text::bounds::{ItemBounds, SceneBounds};
+    use sg2d_text::font::FontSpec;
+    use sg2d_text::metrics::measure_text;
+
+    fn assert_bounds_eq(actual: Bounds, expected: Bounds) {
+        let close = |a: f32, b: f32| (a - b).abs() < 1e-3;
+        assert!(
+            close(actual.x0, expected.x0)
+                && close(actual.y0, expected.y0)
+                && close(actual.x1, expected.x1)
+                && close(actual.y1, expected.y1),
+            "{actual:?} != {expected:?}"
+        );
+    }
+
+    fn group(marks: Vec<SceneMark>) -> SceneGroup {
+        SceneGroup {
+            bounds: GroupBounds {
+                x: 0.0,
+                y: 0.0,
+                width: None,
+                height: None,
+            },
+            transform: Default::default(),
+            marks,
+        }
+    }
+
+    #[test]
+    fn test_rect_bounds() {
+        let mark = RectMark {
+            len: 2,
+            x: EncodingValue::Array {
+                values: vec![10.0, 30.0],
+            },
+            y: EncodingValue::Scalar { value: 50.0 },
+            width: EncodingValue::Scalar { value: 5.0 },
+            // Negative heights extend upward from y
+            height: EncodingValue::Array {
+                values: vec![20.0, -40.0],
+            },
+            ..Default::default()
+        };
+        let items = mark.item_bounds();
+        assert_eq!(items[0], Bounds::new(10.0, 50.0, 15.0, 70.0));
+        assert_eq!(items[1], Bounds::new(30.0, 10.0, 35.0, 50.0));
+        assert_eq!(mark.bounds(), Some(Bounds::new(10.0, 10.0, 35.0, 70.0)));
+
+        let empty = RectMark { len: 0, ..mark };
+        assert_eq!(empty.bounds(), None);
+    }
+
+    #[test]
+    fn test_rule_caps() {
+        let rule = |stroke_cap| RuleMark {
+            x0: EncodingValue::Scalar { value: 10.0 },
+            y0: EncodingValue::Scalar { value: 20.0 },
+            x1: EncodingValue::Scalar { value: 50.0 },
+            y1: EncodingValue::Scalar { value: 20.0 },
+            stroke_width: EncodingValue::Scalar { value: 4.0 },
+            stroke_cap: EncodingValue::Scalar { value: stroke_cap },
+            ..Default::default()
+        };
+        assert_bounds_eq(
+            rule(StrokeCap::Butt).bounds().unwrap(),
+            Bounds::new(10.0, 18.0, 50.0, 22.0),
+        );
+        assert_bounds_eq(
+            rule(StrokeCap::Square).bounds().unwrap(),
+            Bounds::new(8.0, 18.0, 52.0, 22.0),
+        );
+        assert_bounds_eq(
+            rule(StrokeCap::Round).bounds().unwrap(),
+            Bounds::new(8.0, 18.0, 52.0, 22.0),
+        );
+
+        // The stroke of a diagonal rule widens it along its normal
+        let diagonal = RuleMark {
+            x1: EncodingValue::Scalar { value: 40.0 },
+            y1: EncodingValue::Scalar { value: 50.0 },
+            ..rule(StrokeCap::Butt)
+        };
+        let offset = 2.0 / 2.0f32.sqrt();
+        assert_bounds_eq(
+            diagonal.bounds().unwrap(),
+            Bounds::new(10.0 - offset, 20.0 - offset, 40.0 + offset, 50.0 + offset),
+        );
+    }
+
+    #[test]
+    fn test_symbol_bounds() {
+        let circles = SymbolMark {
+            len: 2,
+            x: EncodingValue::Array {
+                values: vec![10.0, 100.0],
+            },
+            y: EncodingValue::Scalar { value: 20.0 },
+            size: EncodingValue::Array {
+                values: vec![16.0, 64.0],
+            },
+            stroke_width: Some(2.0),
+            ..Default::default()
+        };
+        let items = circles.item_bounds();
+        assert_bounds_eq(items[0], Bounds::new(7.0, 17.0, 13.0, 23.0));
+        assert_bounds_eq(items[1], Bounds::new(95.0, 15.0, 105.0, 25.0));
+
+        // A unit square path centered on the origin, scaled by the square root of the size
+        // and rotated by 45 degrees into a diamond
+        let mut builder = Path::builder();
+        builder.begin(point(-0.5, -0.5));
+        builder.line_to(point(0.5, -0.5));
+        builder.line_to(point(0.5, 0.5));
+        builder.line_to(point(-0.5, 0.5));
+        builder.end(true);
+        let squares = SymbolMark {
+            shape: SymbolShape::Path(builder.build()),
+            x: EncodingValue::Scalar { value: 50.0 },
+            y: EncodingValue::Scalar { value: 50.0 },
+            size: EncodingValue::Scalar { value: 100.0 },
+            ..Default::default()
+        };
+        assert_bounds_eq(
+            squares.bounds().unwrap(),
+            Bounds::new(45.0, 45.0, 55.0, 55.0),
+        );
+
+        let diamonds = SymbolMark {
+            angle: EncodingValue::Scalar { value: 45.0 },
+            ..squares
+        };
+        let half_diagonal = 50.0f32.sqrt();
+        assert_bounds_eq(
+            diamonds.bounds().unwrap(),
+            Bounds::new(
+                50.0 - half_diagonal,
+                50.0 - half_diagonal,
+                50.0 + half_diagonal,
+                50.0 + half_diagonal,
+            ),
+        );
+    }
+
+    #[test]
+    fn test_text_bounds() {
+        let font = FontSpec {
+            font_size: 12.0,
+            ..Default::default()
+        };
+        let metrics = measure_text("Label", &font);
+        let mark = TextMark {
+            text: EncodingValue::Scalar {
+                value: "Label".to_string(),
+            },
+            x: EncodingValue::Scalar { value: 100.0 },
+            y: EncodingValue::Scalar { value: 50.0 },
+            align: EncodingValue::Scalar {
+                value: TextAlignSpec::Right,
+            },
+            baseline: EncodingValue::Scalar {
+                value: TextBaselineSpec::LineTop,
+            },
+            font_size: EncodingValue::Scalar { value: 12.0 },
+            ..Default::default()
+        };
+        assert_bounds_eq(
+            mark.bounds().unwrap(),
+            Bounds::new(100.0 - metrics.width, 50.0, 100.0, 50.0 + metrics.height),
+        );
+
+        // Rotating by 90 degrees about the anchor swaps the extents, and the halo stroke
+        // pads every side
+        let rotated = TextMark {
+            align: EncodingValue::Scalar {
+                value: TextAlignSpec::Left,
+            },
+            angle: EncodingValue::Scalar { value: 90.0 },
+            stroke_width: EncodingValue::Scalar { value: 2.0 },
+            ..mark
+        };
+        assert_bounds_eq(
+            rotated.bounds().unwrap(),
+            Bounds::new(
+                100.0 - metrics.height - 1.0,
+                49.0,
+                101.0,
+                50.0 + metrics.width + 1.0,
+            ),
+        );
+    }
+
+    #[test]
+    fn test_scene_graph_bounds() {
+        let rect = SceneMark::Rect(RectMark {
+            x: EncodingValue::Scalar { value: 10.0 },
+            y: EncodingValue::Scalar { value: 10.0 },
+            width: EncodingValue::Scalar { value: 20.0 },
+            height: EncodingValue::Scalar { value: 20.0 },
+            ..Default::default()
+        });
+        let symbol = SceneMark::Symbol(SymbolMark {
+            x: EncodingValue::Scalar { value: 200.0 },
+            y: EncodingValue::Scalar { value: 100.0 },
+            size: EncodingValue::Scalar { value: 100.0 },
+            ..Default::default()
+        });
+        let empty = SceneMark::Symbol(SymbolMark {
+            len: 0,
+            ..Default::default()
+        });
+        let scene_graph = SceneGraph {
+            groups: vec![
+                group(vec![rect, SceneMark::Group(group(vec![symbol, empty]))]),
+                group(vec![]),
+            ],
+            width: 300.0,
+            height: 200.0,
+            background: None,
+        };
+        assert_bounds_eq(
+            scene_graph.bounds().unwrap(),
+            Bounds::new(10.0, 10.0, 205.0, 105.0),
+        );
+        assert_eq!(group(vec![]).bounds(), None);
+    }
+
+    #[test]
+    fn test_transformed_group_bounds() {
+        // A rect covering [10, 30] x [10, 30] in a group at (100, 50)
+        let transformed = |transform| SceneGroup {
+            bounds: GroupBounds {
+                x: 100.0,
+                y: 50.0,
+                width: None,
+                height: None,
+            },
+            transform,
+            marks: vec![SceneMark::Rect(RectMark {
+                x: EncodingValue::Scalar { value: 10.0 },
+                y: EncodingValue::Scalar { value: 10.0 },
+                width: EncodingValue::Scalar { value: 20.0 },
+                height: EncodingValue::Scalar { value: 20.0 },
+                ..Default::default()
+            })],
+        };
+        assert_bounds_eq(
+            transformed(AffineTransform::IDENTITY).bounds().unwrap(),
+            Bounds::new(110.0, 60.0, 130.0, 80.0),
+        );
+        assert_bounds_eq(
+            transformed(AffineTransform::scale(2.0, 1.0))
+                .bounds()
+                .unwrap(),
+            Bounds::new(120.0, 60.0, 160.0, 80.0),
+        );
+        assert_bounds_eq(
+            transformed(AffineTransform::rotate(90.0)).bounds().unwrap(),
+            Bounds::new(70.0, 60.0, 90.0, 80.0),
+        );
+    }
+}
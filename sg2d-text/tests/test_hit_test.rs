@@ -0,0 +1,412 @@
+#[cfg(test)]
+mod tests {
+    use lyon_path::geom::point;
+    use lyon_path::Path;
+    use sg2d::marks::group::{GroupBounds, SceneGroup};
+    use sg2d::marks::mark::SceneMark;
+    use sg2d::marks::rect::RectMark;
+    use sg2d::marks::rule::RuleMark;
+    use sg2d::marks::symbol::{SymbolMark, SymbolShape};
+    use sg2d::marks::text::{TextAlignSpec, TextBaselineSpec, TextMark};
+    use sg2d::scene_graph::SceneGraph;
+    use sg2d::transform::AffineTransform;
+    use sg2d::value::{Color, EncodingValue, StrokeCap};
+    use sg2d_text::font::FontSpec;
+    use sg2d_text::hit_test::{MarkHitTest, SceneHit, SceneHitTest};
+    use sg2d_text::metrics::measure_text;
+
+    fn group(x: f32, y: f32, size: Option<f32>, marks: Vec<SceneMark>) -> SceneGroup {
+        SceneGroup {
+            bounds: GroupBounds {
+                x,
+                y,
+                width: size,
+                height: size,
+            },
+            transform: Default::default(),
+            marks,
+        }
+    }
+
+    fn rects(name: &str, xs: Vec<f32>) -> RectMark {
+        RectMark {
+            name: name.to_string(),
+            len: xs.len() as u32,
+            x: EncodingValue::Array { values: xs },
+            y: EncodingValue::Scalar { value: 10.0 },
+            width: EncodingValue::Scalar { value: 20.0 },
+            height: EncodingValue::Scalar { value: 20.0 },
+            ..Default::default()
+        }
+    }
+
+    fn circles(fill_alpha: f32, stroke_width: Option<f32>) -> SymbolMark {
+        SymbolMark {
+            name: "points".to_string(),
+            len: 2,
+            x: EncodingValue::Array {
+                values: vec![50.0, 60.0],
+            },
+            y: EncodingValue::Scalar { value: 50.0 },
+            // Radius of 10
+            size: EncodingValue::Scalar { value: 400.0 },
+            fill: EncodingValue::Scalar {
+                value: Color::rgba(1.0, 0.0, 0.0, fill_alpha),
+            },
+            stroke: EncodingValue::Scalar {
+                value: Color::BLACK,
+            },
+            stroke_width,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_hit_circles() {
+        let mark = circles(1.0, None);
+        // Overlapping items resolve to the later, topmost one
+        assert_eq!(mark.hit_item([55.0, 50.0]), Some(1));
+        assert_eq!(mark.hit_item([41.0, 50.0]), Some(0));
+        assert_eq!(mark.hit_item([50.0, 39.0]), None);
+        // Inside the bounding box but outside the circle
+        assert_eq!(mark.hit_item([42.0, 42.0]), None);
+
+        // Unfilled circles are only hit on their stroke
+        let mark = circles(0.0, Some(4.0));
+        assert_eq!(mark.hit_item([38.5, 50.0]), Some(0));
+        assert_eq!(mark.hit_item([41.5, 50.0]), Some(0));
+        assert_eq!(mark.hit_item([44.0, 50.0]), None);
+        assert_eq!(mark.hit_item([37.0, 50.0]), None);
+    }
+
+    #[test]
+    fn test_hit_path_symbols() {
+        // Triangle pointing up, scaled by 20 and centered on each item
+        let mut builder = Path::builder();
+        builder.begin(point(0.0, -0.5));
+        builder.line_to(point(0.5, 0.5));
+        builder.line_to(point(-0.5, 0.5));
+        builder.end(true);
+        let mark = SymbolMark {
+            shape: SymbolShape::Path(builder.build()),
+            ..circles(1.0, None)
+        };
+        assert_eq!(mark.hit_item([50.0, 45.0]), Some(0));
+        assert_eq!(mark.hit_item([45.0, 45.0]), None);
+        assert_eq!(mark.hit_item([69.0, 59.0]), Some(1));
+        assert_eq!(mark.hit_item([69.0, 41.0]), None);
+
+        // Rotating by 180 degrees points the triangle down
+        let rotated = SymbolMark {
+            angle: EncodingValue::Scalar { value: 180.0 },
+            ..mark.clone()
+        };
+        assert_eq!(rotated.hit_item([45.0, 45.0]), Some(0));
+        assert_eq!(rotated.hit_item([45.0, 55.0]), None);
+
+        // Unfilled paths are hit within half of the stroke width of their outline
+        let outline = SymbolMark {
+            fill: EncodingValue::Scalar {
+                value: Color::TRANSPARENT,
+            },
+            stroke_width: Some(2.0),
+            ..mark
+        };
+        assert_eq!(outline.hit_item([45.0, 59.5]), Some(0));
+        assert_eq!(outline.hit_item([50.0, 55.0]), None);
+    }
+
+    #[test]
+    fn test_hit_rects() {
+        let mark = rects("bars", vec![10.0, 20.0, 100.0]);
+        assert_eq!(mark.hit_item([15.0, 15.0]), Some(0));
+        assert_eq!(mark.hit_item([25.0, 15.0]), Some(1));
+        assert_eq!(mark.hit_item([110.0, 30.0]), Some(2));
+        assert_eq!(mark.hit_item([110.0, 31.0]), None);
+        assert_eq!(mark.hit_item([50.0, 15.0]), None);
+
+        // Negative heights extend upward
+        let mark = RectMark {
+            height: EncodingValue::Scalar { value: -5.0 },
+            ..mark
+        };
+        assert_eq!(mark.hit_item([15.0, 7.0]), Some(0));
+        assert_eq!(mark.hit_item([15.0, 12.0]), None);
+
+        // Transparent rects aren't hit, so the rect beneath is
+        let mark = RectMark {
+            fill: EncodingValue::Array {
+                values: vec![Color::BLACK, Color::TRANSPARENT, Color::BLACK],
+            },
+            ..rects("bars", vec![10.0, 12.0, 100.0])
+        };
+        assert_eq!(mark.hit_item([15.0, 15.0]), Some(0));
+    }
+
+    #[test]
+    fn test_hit_rules() {
+        let rule = |stroke_cap| RuleMark {
+            name: "rules".to_string(),
+            x0: EncodingValue::Scalar { value: 10.0 },
+            y0: EncodingValue::Scalar { value: 20.0 },
+            x1: EncodingValue::Scalar { value: 50.0 },
+            y1: EncodingValue::Scalar { value: 20.0 },
+            stroke_width: EncodingValue::Scalar { value: 6.0 },
+            stroke_cap: EncodingValue::Scalar { value: stroke_cap },
+            ..Default::default()
+        };
+        let butt = rule(StrokeCap::Butt);
+        assert_eq!(butt.hit_item([30.0, 22.9]), Some(0));
+        assert_eq!(butt.hit_item([30.0, 23.1]), None);
+        assert_eq!(butt.hit_item([9.0, 20.0]), None);
+
+        let square = rule(StrokeCap::Square);
+        assert_eq!(square.hit_item([8.0, 22.0]), Some(0));
+        assert_eq!(square.hit_item([6.0, 20.0]), None);
+
+        let round = rule(StrokeCap::Round);
+        assert_eq!(round.hit_item([8.0, 20.0]), Some(0));
+        // Inside the square cap but outside the round one
+        assert_eq!(round.hit_item([8.0, 22.5]), None);
+
+        // Diagonal rules are hit along their normal
+        let diagonal = RuleMark {
+            x1: EncodingValue::Scalar { value: 50.0 },
+            y1: EncodingValue::Scalar { value: 60.0 },
+            ..butt
+        };
+        assert_eq!(diagonal.hit_item([32.0, 38.0]), Some(0));
+        assert_eq!(diagonal.hit_item([34.0, 36.0]), None);
+
+        let transparent = RuleMark {
+            stroke: EncodingValue::Scalar {
+                value: Color::TRANSPARENT,
+            },
+            ..rule(StrokeCap::Butt)
+        };
+        assert_eq!(transparent.hit_item([30.0, 20.0]), None);
+    }
+
+    #[test]
+    fn test_hit_text() {
+        let font = FontSpec {
+            font_size: 12.0,
+            ..Default::default()
+        };
+        let metrics = measure_text("Label", &font);
+        let mark = TextMark {
+            name: "labels".to_string(),
+            text: EncodingValue::Scalar {
+                value: "Label".to_string(),
+            },
+            x: EncodingValue::Scalar { value: 100.0 },
+            y: EncodingValue::Scalar { value: 50.0 },
+            align: EncodingValue::Scalar {
+                value: TextAlignSpec::Right,
+            },
+            baseline: EncodingValue::Scalar {
+                value: TextBaselineSpec::LineTop,
+            },
+            font_size: EncodingValue::Scalar { value: 12.0 },
+            ..Default::default()
+        };
+        assert_eq!(mark.hit_item([99.0, 51.0]), Some(0));
+        assert_eq!(mark.hit_item([101.0 - metrics.width, 51.0]), Some(0));
+        assert_eq!(mark.hit_item([99.0 - metrics.width, 51.0]), None);
+        assert_eq!(mark.hit_item([99.0, 49.0]), None);
+
+        // Rotated text is hit within its rotated block
+        let rotated = TextMark {
+            align: EncodingValue::Scalar {
+                value: TextAlignSpec::Left,
+            },
+            angle: EncodingValue::Scalar { value: 90.0 },
+            ..mark
+        };
+        assert_eq!(
+            rotated.hit_item([99.0, 50.0 + metrics.width - 1.0]),
+            Some(0)
+        );
+        assert_eq!(rotated.hit_item([101.0, 52.0]), None);
+    }
+
+    #[test]
+    fn test_hit_invisible_text() {
+        // Four labels at the same position: transparent text without a halo, transparent text
+        // with a halo, text with opacity zero, and text whose halo is fully translucent
+        let mark = TextMark::builder("labels")
+            .text_scalar("Label")
+            .x_scalar(100.0)
+            .y_scalar(50.0)
+            .baseline_scalar(TextBaselineSpec::LineTop)
+            .font_size_scalar(12.0)
+            .color(vec![
+                Color::TRANSPARENT,
+                Color::TRANSPARENT,
+                Color::BLACK,
+                Color::BLACK,
+            ])
+            .opacity(vec![1.0, 1.0, 0.0, 1.0])
+            .stroke_width_scalar(6.0)
+            .stroke(vec![
+                Color::TRANSPARENT,
+                Color::WHITE,
+                Color::WHITE,
+                Color::WHITE,
+            ])
+            .stroke_opacity(vec![1.0, 1.0, 1.0, 0.0])
+            .build()
+            .unwrap();
+
+        // The last label is hit within its text block, but not in the padding of its
+        // invisible halo
+        assert_eq!(mark.hit_item([101.0, 51.0]), Some(3));
+        assert_eq!(mark.hit_item([98.0, 51.0]), Some(1));
+
+        // Only the halo of the second label makes it hit
+        let halo_only = TextMark {
+            color: EncodingValue::Scalar {
+                value: Color::TRANSPARENT,
+            },
+            ..mark.clone()
+        };
+        assert_eq!(halo_only.hit_item([101.0, 51.0]), Some(1));
+
+        // Nothing is hit when neither the text nor the halo is visible
+        let invisible = TextMark {
+            opacity: EncodingValue::Scalar { value: 0.0 },
+            ..mark
+        };
+        assert_eq!(invisible.hit_item([101.0, 51.0]), None);
+    }
+
+    #[test]
+    fn test_hit_scene_z_order_and_groups() {
+        let nested = group(
+            0.0,
+            0.0,
+            None,
+            vec![SceneMark::Rect(rects("nested", vec![15.0]))],
+        );
+        let scene_graph = SceneGraph {
+            groups: vec![
+                group(
+                    0.0,
+                    0.0,
+                    None,
+                    vec![
+                        SceneMark::Rect(rects("under", vec![0.0, 100.0])),
+                        SceneMark::Group(nested),
+                    ],
+                ),
+                group(
+                    0.0,
+                    0.0,
+                    None,
+                    vec![SceneMark::Rect(rects("over", vec![100.0]))],
+                ),
+            ],
+            width: 200.0,
+            height: 100.0,
+            background: None,
+        };
+
+        // Later groups and later marks are drawn on top
+        assert_eq!(
+            scene_graph.hit_test([110.0, 20.0]),
+            Some(SceneHit {
+                mark: "over".to_string(),
+                index: 0,
+                group_path: vec![1],
+            })
+        );
+        assert_eq!(
+            scene_graph.hit_test([20.0, 20.0]),
+            Some(SceneHit {
+                mark: "nested".to_string(),
+                index: 0,
+                group_path: vec![0, 1],
+            })
+        );
+        assert_eq!(
+            scene_graph.hit_test([5.0, 20.0]),
+            Some(SceneHit {
+                mark: "under".to_string(),
+                index: 0,
+                group_path: vec![0],
+            })
+        );
+        assert_eq!(scene_graph.hit_test([60.0, 20.0]), None);
+    }
+
+    #[test]
+    fn test_hit_scene_clip() {
+        // A group at (50, 50) nested in a group at (10, 10), and a mark at the inner group's
+        // origin that extends past the 20 pixel wide group
+        let clipped = |clip| {
+            let mark = RectMark {
+                clip,
+                len: 1,
+                x: EncodingValue::Scalar { value: 0.0 },
+                y: EncodingValue::Scalar { value: 0.0 },
+                width: EncodingValue::Scalar { value: 40.0 },
+                height: EncodingValue::Scalar { value: 40.0 },
+                ..rects("clipped", vec![])
+            };
+            let inner = group(50.0, 50.0, Some(20.0), vec![SceneMark::Rect(mark)]);
+            SceneGraph {
+                groups: vec![group(10.0, 10.0, None, vec![SceneMark::Group(inner)])],
+                width: 200.0,
+                height: 200.0,
+                background: None,
+            }
+        };
+
+        let inside = [65.0, 65.0];
+        let outside = [90.0, 90.0];
+        assert!(clipped(true).hit_test(inside).is_some());
+        assert_eq!(clipped(true).hit_test(outside), None);
+        assert!(clipped(false).hit_test(outside).is_some());
+    }
+
+    #[test]
+    fn test_hit_scene_transformed_group() {
+        // A 20 by 20 rect at (0, 10) in a group at (100, 0)
+        let scene_graph = |transform| {
+            let mut group = group(
+                100.0,
+                0.0,
+                None,
+                vec![SceneMark::Rect(rects("bars", vec![0.0]))],
+            );
+            group.transform = transform;
+            SceneGraph {
+                groups: vec![group],
+                width: 200.0,
+                height: 200.0,
+                background: None,
+            }
+        };
+        let hit = Some(SceneHit {
+            mark: "bars".to_string(),
+            index: 0,
+            group_path: vec![0],
+        });
+
+        // Doubling the size covers [100, 140] x [20, 60]
+        let scaled = scene_graph(AffineTransform::scale(2.0, 2.0));
+        assert_eq!(scaled.hit_test([130.0, 50.0]), hit);
+        assert_eq!(scaled.hit_test([115.0, 15.0]), None);
+
+        // Rotating about the group origin covers [70, 90] x [0, 20]
+        let rotated = scene_graph(AffineTransform::rotate(90.0));
+        assert_eq!(rotated.hit_test([80.0, 10.0]), hit);
+        assert_eq!(rotated.hit_test([110.0, 15.0]), None);
+
+        // Nothing is hit in a group that is collapsed to a point
+        assert_eq!(
+            scene_graph(AffineTransform::scale(0.0, 0.0)).hit_test([100.0, 0.0]),
+            None
+        );
+    }
+}
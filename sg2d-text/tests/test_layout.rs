@@ -0,0 +1,230 @@
+#[cfg(test)]
+mod tests {
+    use sg2d::marks::text::{TextAlignSpec, TextDirectionSpec};
+    use sg2d_text::font::{metric_aliases, shape_text, with_font_system, FontSpec, TextLayoutSpec};
+    use sg2d_text::metrics::{measure_text, measure_text_layout};
+
+    /// Shape text and return the (x, width, rtl) of every glyph along with the block width
+    fn glyph_spans(text: &str, layout: &TextLayoutSpec) -> (Vec<(f32, f32, bool)>, f32) {
+        with_font_system(|font_system| {
+            let buffer = shape_text(font_system, text, &FontSpec::default(), layout);
+            let glyphs = buffer
+                .layout_runs()
+                .flat_map(|run| run.glyphs.iter().map(|g| (g.x, g.w, g.level.is_rtl())))
+                .collect::<Vec<_>>();
+            (glyphs, buffer.size().0)
+        })
+    }
+
+    fn assert_within_block(glyphs: &[(f32, f32, bool)], width: f32) {
+        for (x, w, _) in glyphs {
+            assert!(*x >= -0.01 && x + w <= width + 0.01, "{x} + {w} > {width}");
+        }
+    }
+
+    #[test]
+    fn test_rtl_glyphs_within_block() {
+        for (text, dir) in [
+            ("שלום עולם", TextDirectionSpec::Rtl),
+            ("مرحبا بالعالم", TextDirectionSpec::Rtl),
+            ("مرحبا بالعالم", TextDirectionSpec::Ltr),
+            ("abc שלום 123", TextDirectionSpec::Rtl),
+        ] {
+            let layout = TextLayoutSpec {
+                dir,
+                ..Default::default()
+            };
+            let (glyphs, width) = glyph_spans(text, &layout);
+            assert!(!glyphs.is_empty());
+            assert!(glyphs.iter().any(|(_, _, rtl)| *rtl));
+            assert_within_block(&glyphs, width);
+        }
+    }
+
+    #[test]
+    fn test_complex_script_shaping() {
+        // Arabic letters join, so the shaped word uses contextual forms and the lam-alef
+        // ligature rather than one isolated glyph per character
+        with_font_system(|font_system| {
+            let layout = TextLayoutSpec {
+                dir: TextDirectionSpec::Rtl,
+                ..Default::default()
+            };
+            let buffer = shape_text(font_system, "سلام", &FontSpec::default(), &layout);
+            let glyph_ids = buffer
+                .layout_runs()
+                .flat_map(|run| run.glyphs.iter())
+                .filter(|glyph| glyph.w > 0.0)
+                .map(|glyph| glyph.glyph_id)
+                .collect::<Vec<_>>();
+            assert_eq!(glyph_ids.len(), 3);
+            assert!(glyph_ids.iter().all(|id| *id != 0));
+
+            let isolated = shape_text(font_system, "س", &FontSpec::default(), &layout);
+            let isolated_id = isolated
+                .layout_runs()
+                .flat_map(|run| run.glyphs.iter())
+                .find(|glyph| glyph.w > 0.0)
+                .unwrap()
+                .glyph_id;
+            assert!(!glyph_ids.contains(&isolated_id));
+        });
+    }
+
+    /// Families of the fonts that the visible glyphs of shaped text come from, and the glyph ids
+    fn glyph_fonts(text: &str) -> Vec<(String, u16)> {
+        with_font_system(|font_system| {
+            let buffer = shape_text(
+                font_system,
+                text,
+                &FontSpec::default(),
+                &TextLayoutSpec::default(),
+            );
+            let glyphs = buffer
+                .layout_runs()
+                .flat_map(|run| run.glyphs.iter())
+                .filter(|glyph| glyph.w > 0.0)
+                .map(|glyph| (glyph.font_id, glyph.glyph_id))
+                .collect::<Vec<_>>();
+            glyphs
+                .into_iter()
+                .map(|(font_id, glyph_id)| {
+                    let face = font_system.db().face(font_id).unwrap();
+                    (face.families[0].0.clone(), glyph_id)
+                })
+                .collect()
+        })
+    }
+
+    #[test]
+    fn test_devanagari_shaping() {
+        // The conjunct in "स्ते" is a single glyph, so there are fewer glyphs than characters
+        let text = "नमस्ते";
+        let glyphs = glyph_fonts(text);
+        assert!(!glyphs.is_empty());
+        assert!(glyphs.len() < text.chars().count());
+        for (family, glyph_id) in &glyphs {
+            assert_eq!(family, "Noto Sans Devanagari");
+            assert_ne!(*glyph_id, 0);
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "bundled-emoji-font")]
+    fn test_emoji_shaping() {
+        // The skin tone modifier and the base emoji form a single glyph
+        let glyphs = glyph_fonts("👍🏽");
+        assert_eq!(glyphs.len(), 1);
+        assert_eq!(glyphs[0].0, "Noto Color Emoji");
+        assert_ne!(glyphs[0].1, 0);
+
+        let glyphs = glyph_fonts("ok 😀");
+        assert_eq!(glyphs.first().unwrap().0, "Noto Sans");
+        assert_eq!(glyphs.last().unwrap().0, "Noto Color Emoji");
+
+        // Zero width joiner sequences and keycaps are also single glyphs
+        for text in ["👩\u{200D}💻", "1\u{FE0F}\u{20E3}"] {
+            let glyphs = glyph_fonts(text);
+            assert_eq!(glyphs.len(), 1, "{text}");
+            assert_eq!(glyphs[0].0, "Noto Color Emoji");
+        }
+    }
+
+    #[test]
+    fn test_metric_aliases() {
+        assert!(metric_aliases("helvetica").contains(&"Liberation Sans"));
+        assert!(metric_aliases("Liberation Sans").contains(&"Arial"));
+        assert!(metric_aliases("Courier New").contains(&"Courier"));
+        assert!(metric_aliases("Noto Sans").is_empty());
+    }
+
+    #[test]
+    fn test_base_direction_orders_runs() {
+        // With an rtl base direction, the leading latin run is placed to the right
+        // of the hebrew run
+        let first_glyph_x = |dir| {
+            let layout = TextLayoutSpec {
+                dir,
+                ..Default::default()
+            };
+            let (glyphs, _) = glyph_spans("abc שלום", &layout);
+            glyphs
+                .iter()
+                .filter(|(_, _, rtl)| !rtl)
+                .map(|(x, _, _)| *x)
+                .fold(f32::MAX, f32::min)
+        };
+        assert!(first_glyph_x(TextDirectionSpec::Ltr) < 1.0);
+        assert!(first_glyph_x(TextDirectionSpec::Rtl) > 10.0);
+    }
+
+    #[test]
+    fn test_line_alignment() {
+        let text = "a much longer first line\nshort";
+        for (align, expected) in [
+            (TextAlignSpec::Left, 0.0),
+            (TextAlignSpec::Center, 0.5),
+            (TextAlignSpec::Right, 1.0),
+        ] {
+            let layout = TextLayoutSpec {
+                align,
+                ..Default::default()
+            };
+            with_font_system(|font_system| {
+                let buffer = shape_text(font_system, text, &FontSpec::default(), &layout);
+                let width = buffer.size().0;
+                let runs = buffer.layout_runs().collect::<Vec<_>>();
+                let short = &runs[1];
+                let start = short.glyphs.iter().map(|g| g.x).fold(f32::MAX, f32::min);
+                let expected_start = (width - short.line_w) * expected;
+                assert!((start - expected_start).abs() < 0.5, "{align:?}");
+            });
+        }
+    }
+
+    #[test]
+    fn test_limit_truncation() {
+        let font = FontSpec::default();
+        let text = "Some long text that needs truncation";
+        let full = measure_text(text, &font);
+        let limit = full.width / 2.0;
+
+        for dir in [TextDirectionSpec::Ltr, TextDirectionSpec::Rtl] {
+            let layout = TextLayoutSpec {
+                dir,
+                limit,
+                ..Default::default()
+            };
+            let metrics = measure_text_layout(text, &font, &layout);
+            assert!(metrics.width <= limit);
+            assert!(metrics.width > limit * 0.75);
+        }
+
+        // Text within the limit is unchanged
+        let layout = TextLayoutSpec {
+            limit: full.width + 1.0,
+            ..Default::default()
+        };
+        assert_eq!(measure_text_layout(text, &font, &layout).width, full.width);
+    }
+
+    #[test]
+    fn test_limit_truncation_side() {
+        // Truncated ltr text keeps its start and rtl text keeps its end
+        let text = "iiiiiiiiiiiiiiiiiiiiWWWWWWWWWWWWWWWWWWWW";
+        let limit = measure_text(text, &FontSpec::default()).width / 2.0;
+        let truncated_glyphs = |dir| {
+            let layout = TextLayoutSpec {
+                dir,
+                limit,
+                ..Default::default()
+            };
+            let (glyphs, _) = glyph_spans(text, &layout);
+            glyphs.len()
+        };
+        // Narrow glyphs survive truncation from the end, wide glyphs from the start
+        assert!(
+            truncated_glyphs(TextDirectionSpec::Ltr) > truncated_glyphs(TextDirectionSpec::Rtl)
+        );
+    }
+}
@@ -0,0 +1,90 @@
+#[cfg(test)]
+mod tests {
+    use sg2d::marks::text::{FontWeightNameSpec, FontWeightSpec, TextAlignSpec, TextBaselineSpec};
+    use sg2d_text::font::FontSpec;
+    use sg2d_text::metrics::measure_text;
+
+    #[test]
+    fn test_single_line_metrics() {
+        let spec = FontSpec {
+            font_size: 12.0,
+            ..Default::default()
+        };
+        let metrics = measure_text("Hello, world", &spec);
+
+        assert!(metrics.width > 0.0);
+        assert_eq!(metrics.line_height, 12.0);
+        assert_eq!(metrics.height, 12.0);
+        assert_eq!(metrics.lines.len(), 1);
+        assert!(metrics.ascent > 0.0);
+        assert!(metrics.descent > 0.0);
+
+        let line = metrics.lines[0];
+        assert_eq!(line.width, metrics.width);
+        assert_eq!(line.top, 0.0);
+        assert!(line.baseline > line.top && line.baseline < metrics.height);
+    }
+
+    #[test]
+    fn test_multi_line_metrics() {
+        let spec = FontSpec::default();
+        let metrics = measure_text("short\nmuch longer line\nmid line", &spec);
+        assert_eq!(metrics.lines.len(), 3);
+        assert_eq!(metrics.height, 3.0 * metrics.line_height);
+
+        let widths = metrics
+            .lines
+            .iter()
+            .map(|line| line.width)
+            .collect::<Vec<_>>();
+        assert_eq!(metrics.width, widths[1]);
+        assert!(widths[0] < widths[2] && widths[2] < widths[1]);
+
+        // Each line is one line height below the previous one
+        for (prev, next) in metrics.lines.iter().zip(&metrics.lines[1..]) {
+            assert_eq!(next.top - prev.top, metrics.line_height);
+        }
+    }
+
+    #[test]
+    fn test_metrics_scale_with_font() {
+        let small = measure_text("Scaled text", &FontSpec::default());
+        let large = measure_text(
+            "Scaled text",
+            &FontSpec {
+                font_size: 20.0,
+                ..Default::default()
+            },
+        );
+        assert!(large.width > small.width);
+        assert!(large.ascent > small.ascent);
+
+        let bold = measure_text(
+            "Scaled text",
+            &FontSpec {
+                font_weight: FontWeightSpec::Name(FontWeightNameSpec::Bold),
+                ..Default::default()
+            },
+        );
+        assert!(bold.width >= small.width);
+    }
+
+    #[test]
+    fn test_alphabetic_block_offset() {
+        let metrics = measure_text("first\nsecond", &FontSpec::default());
+        let [_, alphabetic] =
+            metrics.block_offset(TextAlignSpec::Left, TextBaselineSpec::Alphabetic);
+        let [_, bottom] = metrics.block_offset(TextAlignSpec::Left, TextBaselineSpec::Bottom);
+
+        // The anchor is on the first line's baseline, not below the last line
+        assert_eq!(alphabetic, -metrics.lines[0].baseline);
+        assert!(-alphabetic > 0.0 && -alphabetic < metrics.line_height);
+        assert_eq!(bottom, -metrics.height);
+    }
+
+    #[test]
+    fn test_empty_text() {
+        let metrics = measure_text("", &FontSpec::default());
+        assert_eq!(metrics.width, 0.0);
+    }
+}
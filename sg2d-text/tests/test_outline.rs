@@ -0,0 +1,98 @@
+#[cfg(test)]
+mod tests {
+    use sg2d::marks::text::{TextAlignSpec, TextBaselineSpec, TextMark};
+    use sg2d::value::EncodingValue;
+    use sg2d_text::cosmic_text::SwashCache;
+    use sg2d_text::font::{shape_text, with_font_system, FontSpec, TextLayoutSpec};
+    use sg2d_text::metrics::TextMetrics;
+    use sg2d_text::outline::{buffer_outline, text_mark_outlines};
+
+    #[test]
+    fn test_outline_within_text_bounds() {
+        let spec = FontSpec {
+            font_size: 20.0,
+            ..Default::default()
+        };
+        let mut cache = SwashCache::new();
+        let (path, metrics) = with_font_system(|font_system| {
+            let buffer = shape_text(font_system, "Outlined", &spec, &TextLayoutSpec::default());
+            (
+                buffer_outline(font_system, &mut cache, &buffer),
+                TextMetrics::from_buffer(&buffer),
+            )
+        });
+
+        assert!(path.iter().count() > 0);
+        for point in path.iter().map(|event| event.to()) {
+            assert!(point.x >= -1.0 && point.x <= metrics.width + 1.0);
+            assert!(point.y >= -1.0 && point.y <= metrics.height + 1.0);
+        }
+    }
+
+    #[test]
+    fn test_whitespace_has_no_outline() {
+        let mut cache = SwashCache::new();
+        let path = with_font_system(|font_system| {
+            let buffer = shape_text(
+                font_system,
+                "   ",
+                &FontSpec::default(),
+                &TextLayoutSpec::default(),
+            );
+            buffer_outline(font_system, &mut cache, &buffer)
+        });
+        assert_eq!(path.iter().count(), 0);
+    }
+
+    fn path_bounds(path: &lyon_path::Path) -> [f32; 4] {
+        path.iter().map(|event| event.to()).fold(
+            [f32::MAX, f32::MAX, f32::MIN, f32::MIN],
+            |[x0, y0, x1, y1], p| [x0.min(p.x), y0.min(p.y), x1.max(p.x), y1.max(p.y)],
+        )
+    }
+
+    #[test]
+    fn test_text_mark_outlines_placement() {
+        let mark = TextMark {
+            len: 3,
+            text: EncodingValue::Scalar {
+                value: "Placed".to_string(),
+            },
+            x: EncodingValue::Array {
+                values: vec![100.0, 100.0, 100.0],
+            },
+            y: EncodingValue::Scalar { value: 50.0 },
+            align: EncodingValue::Array {
+                values: vec![
+                    TextAlignSpec::Left,
+                    TextAlignSpec::Right,
+                    TextAlignSpec::Left,
+                ],
+            },
+            baseline: EncodingValue::Scalar {
+                value: TextBaselineSpec::Top,
+            },
+            angle: EncodingValue::Array {
+                values: vec![0.0, 0.0, 90.0],
+            },
+            font_size: EncodingValue::Scalar { value: 20.0 },
+            ..Default::default()
+        };
+        let paths = text_mark_outlines(&mark);
+        assert_eq!(paths.len(), 3);
+
+        // Left aligned text starts at the anchor and hangs below it
+        let [x0, y0, x1, y1] = path_bounds(&paths[0]);
+        assert!((x0 - 100.0).abs() < 3.0 && x1 > 130.0);
+        assert!(y0 > 50.0 && y1 < 75.0);
+
+        // Right aligned text ends at the anchor
+        let [_, _, x1, _] = path_bounds(&paths[1]);
+        assert!((x1 - 100.0).abs() < 3.0);
+
+        // Rotating by 90 degrees runs the text downward, to the left of the anchor
+        let [x0, y0, x1, y1] = path_bounds(&paths[2]);
+        assert!(x0 > 75.0 && x1 < 100.0);
+        assert!((y0 - 50.0).abs() < 3.0 && y1 > 80.0);
+    }
+}
@@ -7,7 +7,7 @@ use vl_convert_rs::VlConverter;
 
 /// Generate test data for each Vega spec located in `sg2d-vega-test-data/vega-specs`
 /// For each spec, the following three files are saved to `sg2d-vega-test-data/vega-scenegraphs`
-///   1. spec_name.dims.json: This is a JSON file containing the chart's width, height, and origin
+///   1. spec_name.dims.json: This is a JSON file containing the chart's width, height, origin, and background
 ///   2. spec_name.sg.json: This is a JSON file containing the chart's scene graph
 ///   3. spec_name.png: This is a PNG rendering of the chart using vl-convert with resvg
 fn main() {
@@ -53,7 +53,8 @@ fn main() {
                 "width": width,
                 "height": height,
                 "origin_x": origin.0,
-                "origin_y": origin.1
+                "origin_y": origin.1,
+                "background": vg_spec.get("background")
             }))
             .unwrap();
             fs::write(format!("{output_dir}/{spec_name}.dims.json"), dims_str).unwrap();
@@ -8,4 +8,7 @@ pub struct VegaSceneGraphDims {
     pub height: f32,
     pub origin_x: f32,
     pub origin_y: f32,
+    /// CSS color of the spec's `background` property, if it has one
+    #[serde(default)]
+    pub background: Option<String>,
 }
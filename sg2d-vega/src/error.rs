@@ -5,9 +5,6 @@ pub enum VegaSceneGraphError {
     #[error("SceneGraph error")]
     SceneGraphError(#[from] sg2d::error::SceneGraphError),
 
-    #[error("css color parse error")]
-    InvalidColor(#[from] csscolorparser::ParseColorError),
-
     // ParseError doesn't implement std::Error, so #[from] doesn't seem to work
     #[error("Error parsing SVG path")]
     InvalidSvgPath(lyon_extra::parser::ParseError),
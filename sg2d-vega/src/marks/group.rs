@@ -3,6 +3,7 @@ use crate::marks::mark::{VegaMark, VegaMarkItem};
 use serde::{Deserialize, Serialize};
 use sg2d::marks::group::{GroupBounds, SceneGroup};
 use sg2d::marks::mark::SceneMark;
+use sg2d::value::ColorCache;
 
 #[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct VegaGroupItem {
@@ -18,27 +19,30 @@ pub struct VegaGroupItem {
 impl VegaMarkItem for VegaGroupItem {}
 
 impl VegaGroupItem {
-    pub fn to_scene_graph(&self, origin: [f32; 2]) -> Result<SceneGroup, VegaSceneGraphError> {
-        let new_origin = [self.x + origin[0], self.y + origin[1]];
+    /// Convert to a scene group whose marks keep coordinates relative to the group
+    pub fn to_scene_graph(
+        &self,
+        colors: &mut ColorCache,
+    ) -> Result<SceneGroup, VegaSceneGraphError> {
         let mut marks: Vec<SceneMark> = Vec::new();
         for item in &self.items {
             let item_marks: Vec<_> = match item {
                 VegaMark::Group(group) => group
                     .items
                     .iter()
-                    .map(|item| Ok(SceneMark::Group(item.to_scene_graph(new_origin)?)))
+                    .map(|item| Ok(SceneMark::Group(item.to_scene_graph(colors)?)))
                     .collect::<Result<Vec<_>, VegaSceneGraphError>>()?,
                 VegaMark::Rect(mark) => {
-                    vec![mark.to_scene_graph(new_origin)?]
+                    vec![mark.to_scene_graph(colors)?]
                 }
                 VegaMark::Rule(mark) => {
-                    vec![mark.to_scene_graph(new_origin)?]
+                    vec![mark.to_scene_graph(colors)?]
                 }
                 VegaMark::Symbol(mark) => {
-                    vec![mark.to_scene_graph(new_origin)?]
+                    vec![mark.to_scene_graph(colors)?]
                 }
                 VegaMark::Text(mark) => {
-                    vec![mark.to_scene_graph(new_origin)?]
+                    vec![mark.to_scene_graph(colors)?]
                 }
                 _ => {
                     println!("Mark type not yet supported: {:?}", item);
@@ -54,6 +58,7 @@ impl VegaGroupItem {
                 width: self.width,
                 height: self.height,
             },
+            transform: Default::default(),
             marks,
         })
     }
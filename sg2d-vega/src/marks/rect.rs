@@ -3,7 +3,7 @@ use crate::marks::mark::{VegaMarkContainer, VegaMarkItem};
 use serde::{Deserialize, Serialize};
 use sg2d::marks::mark::SceneMark;
 use sg2d::marks::rect::RectMark;
-use sg2d::value::EncodingValue;
+use sg2d::value::{Color, ColorCache, EncodingValue};
 
 #[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
@@ -21,7 +21,10 @@ pub struct VegaRectItem {
 impl VegaMarkItem for VegaRectItem {}
 
 impl VegaMarkContainer<VegaRectItem> {
-    pub fn to_scene_graph(&self, origin: [f32; 2]) -> Result<SceneMark, VegaSceneGraphError> {
+    pub fn to_scene_graph(
+        &self,
+        colors: &mut ColorCache,
+    ) -> Result<SceneMark, VegaSceneGraphError> {
         let mut mark = RectMark {
             clip: self.clip,
             ..Default::default()
@@ -36,12 +39,12 @@ impl VegaMarkContainer<VegaRectItem> {
         let mut y = Vec::<f32>::new();
         let mut width = Vec::<f32>::new();
         let mut height = Vec::<f32>::new();
-        let mut fill = Vec::<[f32; 3]>::new();
+        let mut fill = Vec::<Color>::new();
 
         // For each item, append explicit values to corresponding vector
         for item in &self.items {
-            x.push(item.x + origin[0]);
-            y.push(item.y + origin[1]);
+            x.push(item.x);
+            y.push(item.y);
             if let Some(v) = item.width {
                 width.push(v);
             }
@@ -49,8 +52,8 @@ impl VegaMarkContainer<VegaRectItem> {
                 height.push(v);
             }
             if let Some(v) = &item.fill {
-                let c = csscolorparser::parse(v)?;
-                fill.push([c.r as f32, c.g as f32, c.b as f32])
+                let c = colors.parse(v)?;
+                fill.push(c.with_alpha(c.a * item.fill_opacity.unwrap_or(1.0)))
             }
         }
 
@@ -3,7 +3,7 @@ use crate::marks::mark::{VegaMarkContainer, VegaMarkItem};
 use serde::{Deserialize, Serialize};
 use sg2d::marks::mark::SceneMark;
 use sg2d::marks::rule::RuleMark;
-use sg2d::value::{EncodingValue, StrokeCap};
+use sg2d::value::{Color, ColorCache, EncodingValue, StrokeCap};
 
 #[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
@@ -20,7 +20,10 @@ pub struct VegaRuleItem {
 impl VegaMarkItem for VegaRuleItem {}
 
 impl VegaMarkContainer<VegaRuleItem> {
-    pub fn to_scene_graph(&self, origin: [f32; 2]) -> Result<SceneMark, VegaSceneGraphError> {
+    pub fn to_scene_graph(
+        &self,
+        colors: &mut ColorCache,
+    ) -> Result<SceneMark, VegaSceneGraphError> {
         // Init mark with scalar defaults
         let mut mark = RuleMark {
             clip: self.clip,
@@ -35,20 +38,19 @@ impl VegaMarkContainer<VegaRuleItem> {
         let mut y0 = Vec::<f32>::new();
         let mut x1 = Vec::<f32>::new();
         let mut y1 = Vec::<f32>::new();
-        let mut stroke = Vec::<[f32; 3]>::new();
+        let mut stroke = Vec::<Color>::new();
         let mut stroke_width = Vec::<f32>::new();
         let mut stroke_cap = Vec::<StrokeCap>::new();
 
         // For each item, append explicit values to corresponding vector
         for item in &self.items {
-            x0.push(item.x + origin[0]);
-            y0.push(item.y + origin[1]);
-            x1.push(item.x2.unwrap_or(item.x) + origin[0]);
-            y1.push(item.y2.unwrap_or(item.y) + origin[1]);
+            x0.push(item.x);
+            y0.push(item.y);
+            x1.push(item.x2.unwrap_or(item.x));
+            y1.push(item.y2.unwrap_or(item.y));
 
             if let Some(s) = &item.stroke {
-                let c = csscolorparser::parse(s)?;
-                stroke.push([c.r as f32, c.g as f32, c.b as f32])
+                stroke.push(colors.parse(s)?)
             }
 
             if let Some(s) = item.stroke_width {
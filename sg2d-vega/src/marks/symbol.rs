@@ -7,7 +7,7 @@ use lyon_path::Winding;
 use serde::{Deserialize, Serialize};
 use sg2d::marks::mark::SceneMark;
 use sg2d::marks::symbol::{SymbolMark, SymbolShape};
-use sg2d::value::EncodingValue;
+use sg2d::value::{Color, ColorCache, EncodingValue};
 
 #[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
@@ -28,9 +28,12 @@ pub struct VegaSymbolItem {
 impl VegaMarkItem for VegaSymbolItem {}
 
 impl VegaMarkContainer<VegaSymbolItem> {
-    pub fn to_scene_graph(&self, origin: [f32; 2]) -> Result<SceneMark, VegaSceneGraphError> {
+    pub fn to_scene_graph(
+        &self,
+        colors: &mut ColorCache,
+    ) -> Result<SceneMark, VegaSceneGraphError> {
         // Get shape of first item and use that for all items for now
-        let first = self.items.get(0);
+        let first = self.items.first();
 
         let first_shape = first
             .and_then(|item| item.shape.clone())
@@ -62,23 +65,23 @@ impl VegaMarkContainer<VegaSymbolItem> {
         // Init vector for each encoding channel
         let mut x = Vec::<f32>::new();
         let mut y = Vec::<f32>::new();
-        let mut fill = Vec::<[f32; 4]>::new();
+        let mut fill = Vec::<Color>::new();
         let mut size = Vec::<f32>::new();
-        let mut stroke = Vec::<[f32; 4]>::new();
+        let mut stroke = Vec::<Color>::new();
         let mut stroke_width = Vec::<f32>::new();
         let mut angle = Vec::<f32>::new();
 
         // For each item, append explicit values to corresponding vector
         for item in &self.items {
-            x.push(item.x + origin[0]);
-            y.push(item.y + origin[1]);
+            x.push(item.x);
+            y.push(item.y);
 
             if let Some(c) = &item.fill {
-                let c = csscolorparser::parse(c)?;
+                let c = colors.parse(c)?;
                 let fill_opacity = item
                     .fill_opacity
                     .unwrap_or_else(|| item.opacity.unwrap_or(1.0));
-                fill.push([c.r as f32, c.g as f32, c.b as f32, fill_opacity])
+                fill.push(c.with_alpha(c.a * fill_opacity))
             }
 
             if let Some(s) = item.size {
@@ -86,11 +89,11 @@ impl VegaMarkContainer<VegaSymbolItem> {
             }
 
             if let Some(c) = &item.stroke {
-                let c = csscolorparser::parse(c)?;
+                let c = colors.parse(c)?;
                 let stroke_opacity = item
                     .fill_opacity
                     .unwrap_or_else(|| item.opacity.unwrap_or(1.0));
-                stroke.push([c.r as f32, c.g as f32, c.b as f32, stroke_opacity])
+                stroke.push(c.with_alpha(c.a * stroke_opacity))
             }
 
             if let Some(s) = item.stroke_width {
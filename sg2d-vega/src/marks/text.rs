@@ -2,8 +2,10 @@ use crate::error::VegaSceneGraphError;
 use crate::marks::mark::{VegaMarkContainer, VegaMarkItem};
 use serde::{Deserialize, Serialize};
 use sg2d::marks::mark::SceneMark;
-use sg2d::marks::text::{FontStyleSpec, FontWeightSpec, TextAlignSpec, TextBaselineSpec, TextMark};
-use sg2d::value::EncodingValue;
+use sg2d::marks::text::{
+    FontStyleSpec, FontWeightSpec, TextAlignSpec, TextBaselineSpec, TextDirectionSpec, TextMark,
+};
+use sg2d::value::{Color, ColorCache, EncodingValue};
 
 #[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
@@ -17,6 +19,7 @@ pub struct VegaTextItem {
     pub align: Option<TextAlignSpec>,
     pub angle: Option<f32>,
     pub baseline: Option<TextBaselineSpec>,
+    pub dir: Option<TextDirectionSpec>,
     pub dx: Option<f32>,
     pub dy: Option<f32>,
     pub fill: Option<String>,
@@ -26,12 +29,18 @@ pub struct VegaTextItem {
     pub font_weight: Option<FontWeightSpec>,
     pub font_style: Option<FontStyleSpec>,
     pub limit: Option<f32>,
+    pub stroke: Option<String>,
+    pub stroke_width: Option<f32>,
+    pub stroke_opacity: Option<f32>,
 }
 
 impl VegaMarkItem for VegaTextItem {}
 
 impl VegaMarkContainer<VegaTextItem> {
-    pub fn to_scene_graph(&self, origin: [f32; 2]) -> Result<SceneMark, VegaSceneGraphError> {
+    pub fn to_scene_graph(
+        &self,
+        colors: &mut ColorCache,
+    ) -> Result<SceneMark, VegaSceneGraphError> {
         // Init mark with scalar defaults
         let mut mark = TextMark {
             clip: self.clip,
@@ -47,9 +56,10 @@ impl VegaMarkContainer<VegaTextItem> {
         let mut y = Vec::<f32>::new();
         let mut align = Vec::<TextAlignSpec>::new();
         let mut baseline = Vec::<TextBaselineSpec>::new();
+        let mut dir = Vec::<TextDirectionSpec>::new();
         let mut opacity = Vec::<f32>::new();
         let mut angle = Vec::<f32>::new();
-        let mut color = Vec::<[f32; 3]>::new();
+        let mut color = Vec::<Color>::new();
         let mut dx = Vec::<f32>::new();
         let mut dy = Vec::<f32>::new();
         let mut font = Vec::<String>::new();
@@ -57,10 +67,14 @@ impl VegaMarkContainer<VegaTextItem> {
         let mut font_weight = Vec::<FontWeightSpec>::new();
         let mut font_style = Vec::<FontStyleSpec>::new();
         let mut limit = Vec::<f32>::new();
+        let mut stroke = Vec::<Color>::new();
+        let mut stroke_width = Vec::<f32>::new();
+        let mut stroke_opacity = Vec::<f32>::new();
+        let mut has_stroke = false;
 
         for item in &self.items {
-            x.push(item.x + origin[0]);
-            y.push(item.y + origin[1]);
+            x.push(item.x);
+            y.push(item.y);
             text.push(item.text.clone());
 
             if let Some(v) = item.align {
@@ -71,6 +85,10 @@ impl VegaMarkContainer<VegaTextItem> {
                 baseline.push(v);
             }
 
+            if let Some(v) = item.dir {
+                dir.push(v);
+            }
+
             if let Some(v) = item.fill_opacity {
                 opacity.push(v);
             }
@@ -80,8 +98,7 @@ impl VegaMarkContainer<VegaTextItem> {
             }
 
             if let Some(v) = &item.fill {
-                let c = csscolorparser::parse(v)?;
-                color.push([c.r as f32, c.g as f32, c.b as f32])
+                color.push(colors.parse(v)?)
             }
 
             if let Some(v) = item.dx {
@@ -111,6 +128,21 @@ impl VegaMarkContainer<VegaTextItem> {
             if let Some(v) = item.limit {
                 limit.push(v);
             }
+
+            // Vega only strokes text that has a stroke color, with a default width of 1.
+            // Items without a stroke get a zero width so that channels stay aligned.
+            if let Some(v) = &item.stroke {
+                stroke.push(colors.parse(v)?);
+                stroke_width.push(item.stroke_width.unwrap_or(1.0));
+                has_stroke = true;
+            } else {
+                stroke.push(Color::BLACK);
+                stroke_width.push(0.0);
+            }
+
+            if let Some(v) = item.stroke_opacity {
+                stroke_opacity.push(v);
+            }
         }
 
         // Override values with vectors
@@ -132,6 +164,9 @@ impl VegaMarkContainer<VegaTextItem> {
         if baseline.len() == len {
             mark.baseline = EncodingValue::Array { values: baseline };
         }
+        if dir.len() == len {
+            mark.dir = EncodingValue::Array { values: dir };
+        }
         if opacity.len() == len {
             mark.opacity = EncodingValue::Array { values: opacity };
         }
@@ -164,6 +199,17 @@ impl VegaMarkContainer<VegaTextItem> {
         if limit.len() == len {
             mark.limit = EncodingValue::Array { values: limit };
         }
+        if has_stroke {
+            mark.stroke = EncodingValue::Array { values: stroke };
+            mark.stroke_width = EncodingValue::Array {
+                values: stroke_width,
+            };
+        }
+        if stroke_opacity.len() == len {
+            mark.stroke_opacity = EncodingValue::Array {
+                values: stroke_opacity,
+            };
+        }
         Ok(SceneMark::Text(Box::new(mark)))
     }
 }
@@ -1,7 +1,9 @@
+use crate::dims::VegaSceneGraphDims;
 use crate::error::VegaSceneGraphError;
 use crate::marks::group::VegaGroupItem;
 use crate::marks::mark::VegaMarkContainer;
 use sg2d::scene_graph::SceneGraph;
+use sg2d::value::{Color, ColorCache};
 
 pub type VegaSceneGraph = VegaMarkContainer<VegaGroupItem>;
 
@@ -12,16 +14,36 @@ impl VegaSceneGraph {
         width: f32,
         height: f32,
     ) -> Result<SceneGraph, VegaSceneGraphError> {
+        // Vega scene graphs repeat a handful of colors across many items
+        let mut colors = ColorCache::new();
         let groups = self
             .items
             .iter()
-            .map(|group| group.to_scene_graph(origin))
+            .map(|group| {
+                // Top-level group offsets are relative to the scene, so include the origin
+                let mut group = group.to_scene_graph(&mut colors)?;
+                group.bounds.x += origin[0];
+                group.bounds.y += origin[1];
+                Ok(group)
+            })
             .collect::<Result<Vec<_>, VegaSceneGraphError>>()?;
 
         Ok(SceneGraph {
             groups,
             width,
             height,
+            background: None,
         })
     }
+
+    /// Convert to a scene graph with the size, origin and background of the dims file
+    pub fn to_scene_graph_with_dims(
+        &self,
+        dims: &VegaSceneGraphDims,
+    ) -> Result<SceneGraph, VegaSceneGraphError> {
+        let mut scene_graph =
+            self.to_scene_graph([dims.origin_x, dims.origin_y], dims.width, dims.height)?;
+        scene_graph.background = dims.background.as_deref().map(Color::parse).transpose()?;
+        Ok(scene_graph)
+    }
 }
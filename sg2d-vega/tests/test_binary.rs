@@ -0,0 +1,64 @@
+#[cfg(test)]
+mod tests {
+    use lyon_path::Path;
+    use sg2d::binary::{read_scene_graph, write_scene_graph};
+    use sg2d::marks::group::SceneGroup;
+    use sg2d::marks::mark::SceneMark;
+    use sg2d::marks::symbol::SymbolShape;
+    use sg2d::scene_graph::SceneGraph;
+    use sg2d_vega::dims::VegaSceneGraphDims;
+    use sg2d_vega::scene_graph::VegaSceneGraph;
+    use std::fs;
+
+    /// Rebuild paths from their events, since paths store a point for each close that their
+    /// events don't include and the binary format doesn't keep
+    fn normalize_paths(group: &mut SceneGroup) {
+        for mark in &mut group.marks {
+            match mark {
+                SceneMark::Symbol(mark) => {
+                    if let SymbolShape::Path(path) = &mark.shape {
+                        mark.shape = SymbolShape::Path(path.iter().collect::<Path>());
+                    }
+                }
+                SceneMark::Group(group) => normalize_paths(group),
+                _ => {}
+            }
+        }
+    }
+
+    fn to_json(mut scene_graph: SceneGraph) -> serde_json::Value {
+        scene_graph.groups.iter_mut().for_each(normalize_paths);
+        serde_json::to_value(scene_graph).unwrap()
+    }
+
+    #[test]
+    fn test_binary_round_trip() {
+        let specs_dir = format!(
+            "{}/../sg2d-vega-test-data/vega-scenegraphs",
+            env!("CARGO_MANIFEST_DIR")
+        );
+        let mut num_specs = 0;
+        for category in fs::read_dir(specs_dir).unwrap() {
+            for entry in fs::read_dir(category.unwrap().path()).unwrap() {
+                let path = entry.unwrap().path();
+                let path = path.to_str().unwrap();
+                let Some(spec_path) = path.strip_suffix(".sg.json") else {
+                    continue;
+                };
+                let scene_spec: VegaSceneGraph =
+                    serde_json::from_str(&fs::read_to_string(path).unwrap()).unwrap();
+                let scene_dims: VegaSceneGraphDims = serde_json::from_str(
+                    &fs::read_to_string(format!("{spec_path}.dims.json")).unwrap(),
+                )
+                .unwrap();
+                let scene_graph = scene_spec.to_scene_graph_with_dims(&scene_dims).unwrap();
+
+                let bytes = write_scene_graph(&scene_graph, Vec::new()).unwrap();
+                let round_tripped = read_scene_graph(&bytes).unwrap();
+                assert_eq!(to_json(round_tripped), to_json(scene_graph), "{spec_path}");
+                num_specs += 1;
+            }
+        }
+        assert!(num_specs > 0);
+    }
+}
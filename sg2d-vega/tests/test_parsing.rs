@@ -1,5 +1,8 @@
 #[cfg(test)]
 mod tests {
+    use sg2d::marks::mark::SceneMark;
+    use sg2d::marks::text::TextDirectionSpec;
+    use sg2d::value::Color;
     use sg2d_vega::scene_graph::VegaSceneGraph;
     use std::fs;
 
@@ -8,7 +11,7 @@ mod tests {
         let category = "rule";
         let spec_name = "wide_rule_axes";
         let specs_dir = format!(
-            "{}/../vega-wgpu-renderer/tests/specs/{category}",
+            "{}/../sg2d-vega-test-data/vega-scenegraphs/{category}",
             env!("CARGO_MANIFEST_DIR")
         );
 
@@ -24,4 +27,98 @@ mod tests {
         let sg = scene_spec.to_scene_graph([0.0, 0.0], 200.0, 300.0).unwrap();
         println!("{sg:#?}");
     }
+
+    #[test]
+    fn test_text_stroke() {
+        let scene_spec: VegaSceneGraph = serde_json::from_value(serde_json::json!({
+            "marktype": "group",
+            "interactive": false,
+            "items": [{
+                "items": [{
+                    "marktype": "text",
+                    "interactive": false,
+                    "items": [
+                        {"x": 0, "y": 0, "text": "a", "stroke": "white", "strokeWidth": 3, "strokeOpacity": 0.5},
+                        {"x": 10, "y": 0, "text": "b", "stroke": "red", "strokeOpacity": 1.0},
+                        {"x": 20, "y": 0, "text": "c", "strokeOpacity": 1.0},
+                    ]
+                }]
+            }]
+        }))
+        .unwrap();
+
+        let sg = scene_spec.to_scene_graph([0.0, 0.0], 100.0, 100.0).unwrap();
+        let SceneMark::Text(mark) = &sg.groups[0].marks[0] else {
+            panic!("Expected text mark")
+        };
+        assert_eq!(
+            mark.stroke_width_iter().copied().collect::<Vec<_>>(),
+            vec![3.0, 1.0, 0.0]
+        );
+        assert_eq!(
+            mark.stroke_iter().copied().collect::<Vec<_>>(),
+            vec![Color::WHITE, Color::rgb(1.0, 0.0, 0.0), Color::BLACK]
+        );
+        assert_eq!(
+            mark.stroke_opacity_iter().copied().collect::<Vec<_>>(),
+            vec![0.5, 1.0, 1.0]
+        );
+    }
+
+    #[test]
+    fn test_rect_fill_alpha() {
+        // The alpha of CSS colors is combined with the fill opacity
+        let scene_spec: VegaSceneGraph = serde_json::from_value(serde_json::json!({
+            "marktype": "group",
+            "interactive": false,
+            "items": [{
+                "items": [{
+                    "marktype": "rect",
+                    "interactive": false,
+                    "items": [
+                        {"x": 0, "y": 0, "fill": "#ff0000"},
+                        {"x": 10, "y": 0, "fill": "rgba(0, 0, 255, 0.5)", "fillOpacity": 0.5},
+                    ]
+                }]
+            }]
+        }))
+        .unwrap();
+
+        let sg = scene_spec.to_scene_graph([0.0, 0.0], 100.0, 100.0).unwrap();
+        let SceneMark::Rect(mark) = &sg.groups[0].marks[0] else {
+            panic!("Expected rect mark")
+        };
+        assert_eq!(
+            mark.fill_iter().copied().collect::<Vec<_>>(),
+            vec![Color::rgb(1.0, 0.0, 0.0), Color::rgba(0.0, 0.0, 1.0, 0.25)]
+        );
+    }
+
+    #[test]
+    fn test_text_dir() {
+        let scene_spec: VegaSceneGraph = serde_json::from_value(serde_json::json!({
+            "marktype": "group",
+            "interactive": false,
+            "items": [{
+                "items": [{
+                    "marktype": "text",
+                    "interactive": false,
+                    "items": [
+                        {"x": 0, "y": 0, "text": "שלום", "dir": "rtl"},
+                        {"x": 10, "y": 0, "text": "hello", "dir": "ltr"},
+                    ]
+                }]
+            }]
+        }))
+        .unwrap();
+
+        let sg = scene_spec.to_scene_graph([0.0, 0.0], 100.0, 100.0).unwrap();
+        let SceneMark::Text(mark) = &sg.groups[0].marks[0] else {
+            panic!("Expected text mark")
+        };
+        assert_eq!(
+            mark.dir_iter().copied().collect::<Vec<_>>(),
+            vec![TextDirectionSpec::Rtl, TextDirectionSpec::Ltr]
+        );
+    }
 }
@@ -0,0 +1,73 @@
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use itertools::izip;
+use sg2d::marks::symbol::SymbolMark;
+use sg2d::value::{Color, ColorSpace, EncodingValue};
+use sg2d_wgpu::marks::symbol::SymbolInstance;
+
+const LEN: usize = 1_000_000;
+
+fn symbol_mark() -> SymbolMark {
+    SymbolMark {
+        len: LEN as u32,
+        x: EncodingValue::Array {
+            values: (0..LEN).map(|i| i as f32).collect(),
+        },
+        y: EncodingValue::Array {
+            values: (0..LEN).map(|i| (i % 500) as f32).collect(),
+        },
+        fill: EncodingValue::Array {
+            values: (0..LEN)
+                .map(|i| Color::rgb((i % 256) as f32 / 255.0, 0.5, 0.25))
+                .collect(),
+        },
+        stroke: EncodingValue::Scalar {
+            value: Color::BLACK,
+        },
+        stroke_width: Some(1.0),
+        ..Default::default()
+    }
+}
+
+/// Boxed iterator over the items of a channel, as instances were built before
+fn boxed_iter<T>(value: &EncodingValue<T>, len: usize) -> Box<dyn Iterator<Item = &T> + '_> {
+    Box::new(value.as_iter(len))
+}
+
+fn boxed_instances(mark: &SymbolMark, color_space: ColorSpace) -> Vec<SymbolInstance> {
+    let len = mark.len as usize;
+    let stroke_width = mark.stroke_width.unwrap_or(0.0);
+    izip!(
+        boxed_iter(&mark.x, len),
+        boxed_iter(&mark.y, len),
+        boxed_iter(&mark.fill, len),
+        boxed_iter(&mark.size, len),
+        boxed_iter(&mark.stroke, len),
+        boxed_iter(&mark.angle, len),
+    )
+    .map(|(x, y, fill, size, stroke, angle)| SymbolInstance {
+        position: [*x, *y],
+        fill_color: fill.to_space(color_space),
+        stroke_color: stroke.to_space(color_space),
+        stroke_width,
+        size: *size,
+        angle: *angle,
+    })
+    .collect()
+}
+
+fn symbol_instances(c: &mut Criterion) {
+    let mark = symbol_mark();
+    let mut group = c.benchmark_group("symbol_instances");
+    for color_space in [ColorSpace::Srgb, ColorSpace::Linear] {
+        group.bench_function(format!("boxed_iter/{color_space:?}"), |b| {
+            b.iter(|| boxed_instances(black_box(&mark), color_space))
+        });
+        group.bench_function(format!("from_spec/{color_space:?}"), |b| {
+            b.iter(|| SymbolInstance::from_spec(black_box(&mark), color_space))
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, symbol_instances);
+criterion_main!(benches);
@@ -1,27 +1,40 @@
 use image::imageops::crop_imm;
 use wgpu::{
-    Adapter, Buffer, BufferAddress, BufferDescriptor, BufferUsages, CommandBuffer,
-    CommandEncoderDescriptor, Device, DeviceDescriptor, Extent3d, ImageCopyBuffer,
-    ImageCopyTexture, ImageDataLayout, LoadOp, MapMode, Operations, Origin3d, PowerPreference,
-    Queue, RenderPassColorAttachment, RenderPassDescriptor, RequestAdapterOptions, StoreOp,
-    Surface, SurfaceConfiguration, SurfaceError, Texture, TextureAspect, TextureDescriptor,
-    TextureDimension, TextureFormat, TextureFormatFeatureFlags, TextureUsages, TextureView,
-    TextureViewDescriptor,
+    Buffer, BufferAddress, BufferDescriptor, BufferUsages, CommandBuffer, CommandEncoderDescriptor,
+    Device, Extent3d, ImageCopyBuffer, ImageCopyTexture, ImageDataLayout, LoadOp, MapMode,
+    Operations, Origin3d, Queue, RenderPassColorAttachment, RenderPassDescriptor, StoreOp, Surface,
+    SurfaceConfiguration, Texture, TextureAspect, TextureDescriptor, TextureDimension,
+    TextureFormat, TextureFormatFeatureFlags, TextureUsages, TextureView, TextureViewDescriptor,
 };
 use winit::dpi::{PhysicalSize, Size};
 use winit::event::WindowEvent;
 use winit::window::Window;
 
+use crate::color::texture_color_space;
+use crate::config::CanvasConfig;
+use crate::context::RenderContext;
 use crate::error::Sg2dWgpuError;
 use crate::marks::mark::GeomMarkRenderer;
+use crate::marks::pipeline::PipelineCache;
 use crate::marks::rect::{RectInstance, RectShader};
 use crate::marks::rule::{RuleInstance, RuleShader};
 use crate::marks::symbol::{SymbolInstance, SymbolShader};
-use crate::marks::text::{TextInstance, TextMarkRenderer};
+use crate::marks::text::{TextContext, TextInstance, TextMarkRenderer, TextRendering};
+use crate::marks::text_block::{TextBlockInstance, TextBlockShader};
+use crate::pick::{PickIndex, Picker};
+use lyon::path::Path;
+use lyon::tessellation::{GeometryBuilderError, TessellationError};
+use sg2d::bounds::Bounds;
+use sg2d::marks::symbol::SymbolShape;
+use sg2d::transform::AffineTransform;
+use sg2d::value::{Color, ColorSpace};
 use sg2d::{
     marks::group::SceneGroup, marks::mark::SceneMark, marks::rect::RectMark, marks::rule::RuleMark,
     marks::symbol::SymbolMark, marks::text::TextMark, scene_graph::SceneGraph,
 };
+use sg2d_text::hit_test::SceneHit;
+use sg2d_text::outline::text_mark_outlines;
+use std::sync::Arc;
 
 #[repr(C)]
 #[derive(Copy, Clone, Debug, bytemuck::Pod, bytemuck::Zeroable)]
@@ -29,11 +42,147 @@ pub struct CanvasUniform {
     pub size: [f32; 2],
     pub scale: f32,
     _pad: [f32; 1], // Pad to 16 bytes
+    /// Columns of the linear part of the transform from group to scene coordinates
+    pub linear: [f32; 4],
+    /// Translation of the transform from group to scene coordinates
+    pub translate: [f32; 2],
+    /// Size of the group's clip region, or negative when marks aren't clipped
+    pub clip_size: [f32; 2],
+    /// Columns of the linear part of the transform from scene to clip region coordinates
+    pub clip_linear: [f32; 4],
+    /// Translation of the transform from scene to clip region coordinates
+    pub clip_translate: [f32; 2],
+    _pad2: [f32; 2], // Pad to 16 bytes
+}
+
+impl CanvasUniform {
+    /// Uniform for marks in scene coordinates
+    pub fn new(size: [f32; 2], scale: f32) -> Self {
+        let [a, b, c, d, e, f] = AffineTransform::IDENTITY.0;
+        Self {
+            size,
+            scale,
+            _pad: [0.0],
+            linear: [a, b, c, d],
+            translate: [e, f],
+            clip_size: [-1.0; 2],
+            clip_linear: [a, b, c, d],
+            clip_translate: [e, f],
+            _pad2: [0.0; 2],
+        }
+    }
+
+    /// Transform from the coordinates of the marks drawn with this uniform to scene coordinates
+    pub fn group_transform(&self) -> AffineTransform {
+        let [a, b, c, d] = self.linear;
+        let [e, f] = self.translate;
+        AffineTransform([a, b, c, d, e, f])
+    }
+
+    /// Uniform for the marks of a group nested in the group drawn with this uniform.
+    ///
+    /// Marks that are clipped are clipped to the width and height of the group, offset like
+    /// the group but not moved by its transform, as in the other renderers.
+    pub fn with_group(&self, group: &SceneGroup) -> Self {
+        let parent = self.group_transform();
+        let [a, b, c, d, e, f] = group.local_transform().then(&parent).0;
+        let mut uniform = Self {
+            linear: [a, b, c, d],
+            translate: [e, f],
+            ..self.without_clip()
+        };
+        let bounds = &group.bounds;
+        let clip_inverse = AffineTransform::translate(bounds.x, bounds.y)
+            .then(&parent)
+            .inverse();
+        if let (Some(width), Some(height), Some(clip_inverse)) =
+            (bounds.width, bounds.height, clip_inverse)
+        {
+            let [a, b, c, d, e, f] = clip_inverse.0;
+            uniform.clip_size = [width, height];
+            uniform.clip_linear = [a, b, c, d];
+            uniform.clip_translate = [e, f];
+        }
+        uniform
+    }
+
+    /// Uniform for marks of the same group that aren't clipped
+    pub fn without_clip(&self) -> Self {
+        let new = Self::new(self.size, self.scale);
+        Self {
+            clip_size: new.clip_size,
+            clip_linear: new.clip_linear,
+            clip_translate: new.clip_translate,
+            ..*self
+        }
+    }
+
+    /// Uniform for marks of the same group whose positions are already in scene coordinates
+    pub fn in_scene(&self) -> Self {
+        let new = Self::new(self.size, self.scale);
+        Self {
+            linear: new.linear,
+            translate: new.translate,
+            ..*self
+        }
+    }
+
+    /// Uniform that draws the marks onto a canvas of the given size, showing the part of the
+    /// scene whose top left corner is at `origin`
+    pub fn viewport(&self, origin: [f32; 2], size: [f32; 2]) -> Self {
+        let [x, y] = origin;
+        let [a, b, c, d, e, f] = self
+            .group_transform()
+            .then(&AffineTransform::translate(-x, -y))
+            .0;
+        // The clip transform maps scene coordinates, so it moves canvas positions back first
+        let [clip_a, clip_b, clip_c, clip_d] = self.clip_linear;
+        let [clip_e, clip_f] = self.clip_translate;
+        let [_, _, _, _, clip_e, clip_f] = AffineTransform::translate(x, y)
+            .then(&AffineTransform([
+                clip_a, clip_b, clip_c, clip_d, clip_e, clip_f,
+            ]))
+            .0;
+        Self {
+            size,
+            linear: [a, b, c, d],
+            translate: [e, f],
+            clip_translate: [clip_e, clip_f],
+            ..*self
+        }
+    }
+
+    /// Bounding box of the clip region in scene coordinates, or None when marks aren't clipped
+    pub fn clip_bounds(&self) -> Option<Bounds> {
+        let [width, height] = self.clip_size;
+        if width < 0.0 {
+            return None;
+        }
+        let [a, b, c, d] = self.clip_linear;
+        let [e, f] = self.clip_translate;
+        let clip_transform = AffineTransform([a, b, c, d, e, f]).inverse()?;
+        Some(Bounds::new(0.0, 0.0, width, height).transformed(&clip_transform))
+    }
 }
 
 pub enum MarkRenderer {
     Geom(GeomMarkRenderer),
     Text(TextMarkRenderer),
+    /// Drawn only into the pick target
+    Pick(GeomMarkRenderer),
+}
+
+impl MarkRenderer {
+    /// Draw the mark onto a canvas of the given size that shows the part of the scene whose top
+    /// left corner is at `origin`, as with `CanvasUniform::viewport`
+    pub fn set_viewport(&mut self, queue: &Queue, origin: [f32; 2], size: [f32; 2]) {
+        match self {
+            MarkRenderer::Geom(mark) | MarkRenderer::Pick(mark) => {
+                mark.set_viewport(queue, origin, size)
+            }
+            MarkRenderer::Text(mark) => mark.set_viewport(origin, size),
+        }
+    }
 }
 
 pub trait Canvas {
@@ -41,20 +190,57 @@ pub trait Canvas {
     fn clear_mark_renderer(&mut self);
     fn device(&self) -> &Device;
     fn queue(&self) -> &Queue;
+    /// Pipelines that mark renderers are created with
+    fn pipelines(&self) -> &PipelineCache;
     fn uniform(&self) -> &CanvasUniform;
     fn scale(&self) -> f32;
 
+    /// Uniform for a mark of the current group, clipped to the group when `clip` is set
+    fn mark_uniform(&self, clip: bool) -> CanvasUniform {
+        if clip {
+            *self.uniform()
+        } else {
+            self.uniform().without_clip()
+        }
+    }
+
     fn set_uniform(&mut self, uniform: CanvasUniform);
 
     fn texture_format(&self) -> TextureFormat;
 
+    /// Color space that mark colors are converted to for the texture format
+    fn color_space(&self) -> ColorSpace {
+        texture_color_space(self.texture_format())
+    }
+
     fn sample_count(&self) -> u32;
 
+    fn text_rendering(&self) -> TextRendering;
+
+    /// Set how text marks are drawn. Takes effect the next time the scene is set.
+    fn set_text_rendering(&mut self, text_rendering: TextRendering);
+
+    /// Pick ids of the current scene, if picking is enabled
+    fn pick_index(&mut self) -> Option<&mut PickIndex>;
+
+    /// Enable or disable the picking pass. Takes effect the next time the scene is set.
+    fn set_picking(&mut self, picking: bool);
+
+    /// Color beneath the marks, from the canvas override or the scene background
+    fn background(&self) -> Color;
+
+    /// Store the background of the scene being set
+    fn set_scene_background(&mut self, background: Option<Color>);
+
     fn add_symbol_mark(&mut self, mark: &SymbolMark) -> Result<(), Sg2dWgpuError> {
-        let instances = SymbolInstance::iter_from_spec(mark).collect::<Vec<_>>();
+        let instances = SymbolInstance::from_spec(mark, self.color_space());
+        let pick_id_offset = self
+            .pick_index()
+            .map(|index| index.add_mark(&mark.name, mark.len));
         self.add_mark_renderer(MarkRenderer::Geom(GeomMarkRenderer::new(
             self.device(),
-            *self.uniform(),
+            self.pipelines(),
+            self.mark_uniform(mark.clip),
             self.texture_format(),
             self.sample_count(),
             Box::new(SymbolShader::try_new(
@@ -63,51 +249,170 @@ pub trait Canvas {
                 mark.stroke_width.is_some(),
             )?),
             instances.as_slice(),
+            pick_id_offset,
         )));
         Ok(())
     }
 
     fn add_rect_mark(&mut self, mark: &RectMark) -> Result<(), Sg2dWgpuError> {
-        let instances = RectInstance::iter_from_spec(mark).collect::<Vec<_>>();
+        let instances = RectInstance::from_spec(mark, self.color_space());
+        let pick_id_offset = self
+            .pick_index()
+            .map(|index| index.add_mark(&mark.name, mark.len));
         self.add_mark_renderer(MarkRenderer::Geom(GeomMarkRenderer::new(
             self.device(),
-            *self.uniform(),
+            self.pipelines(),
+            self.mark_uniform(mark.clip),
             self.texture_format(),
             self.sample_count(),
             Box::new(RectShader::new()),
             instances.as_slice(),
+            pick_id_offset,
         )));
         Ok(())
     }
 
     fn add_rule_mark(&mut self, mark: &RuleMark) -> Result<(), Sg2dWgpuError> {
-        let instances = RuleInstance::iter_from_spec(mark).collect::<Vec<_>>();
+        let instances = RuleInstance::from_spec(mark, self.color_space());
+        let pick_id_offset = self
+            .pick_index()
+            .map(|index| index.add_mark(&mark.name, mark.len));
         self.add_mark_renderer(MarkRenderer::Geom(GeomMarkRenderer::new(
             self.device(),
-            *self.uniform(),
+            self.pipelines(),
+            self.mark_uniform(mark.clip),
             self.texture_format(),
             self.sample_count(),
             Box::new(RuleShader::new()),
             instances.as_slice(),
+            pick_id_offset,
         )));
         Ok(())
     }
 
     fn add_text_mark(&mut self, mark: &TextMark) -> Result<(), Sg2dWgpuError> {
-        let instances = TextInstance::iter_from_spec(mark).collect::<Vec<_>>();
+        // Glyphs are rasterized upright, so text in groups that rotate, skew or reflect it is
+        // drawn as paths
+        let transform = self.uniform().group_transform();
+        if self.text_rendering() == TextRendering::Paths || !transform.is_axis_aligned() {
+            return self.add_text_path_mark(mark);
+        }
+        // Glyphs are positioned in scene coordinates, so the group transform is applied here
+        let instances = TextInstance::from_spec(mark)
+            .into_iter()
+            .map(|instance| instance.transformed(&transform))
+            .collect::<Vec<_>>();
         self.add_mark_renderer(MarkRenderer::Text(TextMarkRenderer::new(
             self.device(),
-            self.queue(),
-            *self.uniform(),
+            self.mark_uniform(mark.clip),
             self.texture_format(),
-            self.sample_count(),
             instances,
         )));
+        self.add_text_pick_mark(mark);
         Ok(())
     }
 
+    fn add_text_path_mark(&mut self, mark: &TextMark) -> Result<(), Sg2dWgpuError> {
+        // Consecutive items with the same colors share symbol renderers, which keeps
+        // the draw order of overlapping text
+        let color_space = self.color_space();
+        let mut runs: Vec<(SymbolInstance, Vec<Path>)> = Vec::new();
+        for (path, instance) in text_mark_outlines(mark)
+            .into_iter()
+            .zip(TextInstance::from_spec(mark))
+        {
+            let paint = SymbolInstance {
+                position: [0.0, 0.0],
+                fill_color: instance.fill_color().to_space(color_space),
+                stroke_color: instance.stroke_color().to_space(color_space),
+                stroke_width: instance.stroke_width,
+                size: 1.0,
+                angle: 0.0,
+            };
+            match runs.last_mut() {
+                Some((run_paint, paths))
+                    if run_paint.fill_color == paint.fill_color
+                        && run_paint.stroke_color == paint.stroke_color
+                        && run_paint.stroke_width == paint.stroke_width =>
+                {
+                    paths.push(path)
+                }
+                _ => runs.push((paint, vec![path])),
+            }
+        }
+
+        for (paint, paths) in runs {
+            // Halos are drawn beneath the fill, matching glyph rendering
+            if paint.stroke_width > 0.0 && paint.stroke_color[3] > 0.0 {
+                self.add_text_path_run(paint, &paths, false, mark.clip)?;
+            }
+            self.add_text_path_run(paint, &paths, true, mark.clip)?;
+        }
+        self.add_text_pick_mark(mark);
+        Ok(())
+    }
+
+    /// Add the blocks of text items to the pick target, so that text is picked wherever
+    /// `sg2d_text::hit_test` hits it
+    fn add_text_pick_mark(&mut self, mark: &TextMark) {
+        let Some(pick_id_offset) = self
+            .pick_index()
+            .map(|index| index.add_mark(&mark.name, mark.len))
+        else {
+            return;
+        };
+        let instances = TextBlockInstance::from_spec(mark);
+        self.add_mark_renderer(MarkRenderer::Pick(GeomMarkRenderer::new(
+            self.device(),
+            self.pipelines(),
+            self.mark_uniform(mark.clip),
+            self.texture_format(),
+            self.sample_count(),
+            Box::new(TextBlockShader::new()),
+            instances.as_slice(),
+            Some(pick_id_offset),
+        )));
+    }
+
+    /// Add a symbol renderer that fills (or strokes) the union of the given glyph outline
+    /// paths, splitting them into several renderers when they don't fit in 16-bit indices
+    fn add_text_path_run(
+        &mut self,
+        paint: SymbolInstance,
+        paths: &[Path],
+        fill: bool,
+        clip: bool,
+    ) -> Result<(), Sg2dWgpuError> {
+        let mut builder = Path::builder();
+        builder.extend_from_paths(&paths.iter().map(|path| path.as_slice()).collect::<Vec<_>>());
+        let shape = SymbolShape::Path(builder.build());
+        match SymbolShader::try_new(shape, fill, !fill) {
+            Ok(shader) => {
+                self.add_mark_renderer(MarkRenderer::Geom(GeomMarkRenderer::new(
+                    self.device(),
+                    self.pipelines(),
+                    self.mark_uniform(clip),
+                    self.texture_format(),
+                    self.sample_count(),
+                    Box::new(shader),
+                    &[paint],
+                    None,
+                )));
+                Ok(())
+            }
+            Err(Sg2dWgpuError::TessellationError(TessellationError::GeometryBuilder(
+                GeometryBuilderError::TooManyVertices,
+            ))) if paths.len() > 1 => {
+                let (first, second) = paths.split_at(paths.len() / 2);
+                self.add_text_path_run(paint, first, fill, clip)?;
+                self.add_text_path_run(paint, second, fill, clip)
+            }
+            Err(err) => Err(err),
+        }
+    }
+
     fn add_group_mark(&mut self, group: &SceneGroup) -> Result<(), Sg2dWgpuError> {
-        for mark in &group.marks {
+        for (i, mark) in group.marks.iter().enumerate() {
             match mark {
                 SceneMark::Symbol(mark) => {
                     self.add_symbol_mark(mark)?;
@@ -122,27 +427,49 @@ pub trait Canvas {
                     self.add_text_mark(mark)?;
                 }
                 SceneMark::Group(group) => {
-                    self.add_group_mark(group)?;
+                    self.add_nested_group_mark(group, i)?;
                 }
             }
         }
         Ok(())
     }
 
+    /// Add a group that is at the given index within its parent, keeping track of the
+    /// group transform and the group path of picked marks
+    fn add_nested_group_mark(
+        &mut self,
+        group: &SceneGroup,
+        index: usize,
+    ) -> Result<(), Sg2dWgpuError> {
+        let parent_uniform = *self.uniform();
+        self.set_uniform(parent_uniform.with_group(group));
+        if let Some(pick_index) = self.pick_index() {
+            pick_index.enter_group(index);
+        }
+        self.add_group_mark(group)?;
+        if let Some(pick_index) = self.pick_index() {
+            pick_index.exit_group();
+        }
+        self.set_uniform(parent_uniform);
+        Ok(())
+    }
+
     fn set_scene(&mut self, scene_graph: &SceneGraph) -> Result<(), Sg2dWgpuError> {
+        scene_graph.validate()?;
+        self.set_scene_background(scene_graph.background);
+
         // Set uniforms
-        self.set_uniform(CanvasUniform {
-            size: [scene_graph.width, scene_graph.height],
-            scale: self.scale(),
-            _pad: [0.0],
-        });
+        self.set_uniform(CanvasUniform::new(
+            [scene_graph.width, scene_graph.height],
+            self.scale(),
+        ));
 
         // Clear existing marks
         self.clear_mark_renderer();
 
         // Add marks
-        for group in &scene_graph.groups {
-            self.add_group_mark(group)?;
+        for (i, group) in scene_graph.groups.iter().enumerate() {
+            self.add_nested_group_mark(group, i)?;
         }
 
         Ok(())
@@ -169,12 +496,7 @@ fn make_background_command<C: Canvas>(
                 view: texture_view,
                 resolve_target,
                 ops: Operations {
-                    load: LoadOp::Clear(wgpu::Color {
-                        r: 1.0,
-                        g: 1.0,
-                        b: 1.0,
-                        a: 1.0,
-                    }),
+                    load: LoadOp::Clear(background_clear_color(canvas)),
                     store: StoreOp::Store,
                 },
             })],
@@ -186,44 +508,101 @@ fn make_background_command<C: Canvas>(
     background_encoder.finish()
 }
 
-fn make_wgpu_instance() -> wgpu::Instance {
-    wgpu::Instance::new(wgpu::InstanceDescriptor {
-        backends: wgpu::Backends::all(),
-        ..Default::default()
-    })
+/// Background in the texture's color space, premultiplied by alpha to match how marks are
+/// blended onto it
+fn background_clear_color<C: Canvas>(canvas: &C) -> wgpu::Color {
+    let [r, g, b, a] = canvas.background().to_space(canvas.color_space());
+    wgpu::Color {
+        r: (r * a) as f64,
+        g: (g * a) as f64,
+        b: (b * a) as f64,
+        a: a as f64,
+    }
+}
+
+/// Convert premultiplied RGBA8 pixels to straight alpha in place
+fn unpremultiply(data: &mut [u8]) {
+    for pixel in data.chunks_exact_mut(4) {
+        let a = pixel[3] as u32;
+        if a == 0 {
+            pixel[..3].fill(0);
+        } else if a < 255 {
+            for c in &mut pixel[..3] {
+                *c = ((*c as u32 * 255 + a / 2) / a).min(255) as u8;
+            }
+        }
+    }
 }
 
-async fn make_wgpu_adapter(
-    instance: &wgpu::Instance,
-    compatible_surface: Option<&Surface>,
-) -> Result<Adapter, Sg2dWgpuError> {
-    instance
-        .request_adapter(&RequestAdapterOptions {
-            power_preference: PowerPreference::default(),
-            compatible_surface,
-            force_fallback_adapter: false,
+pub(crate) fn prepare_text_marks(
+    device: &Device,
+    queue: &Queue,
+    text: &mut TextContext,
+    marks: &mut [MarkRenderer],
+) -> Result<(), Sg2dWgpuError> {
+    let mut text_marks = marks
+        .iter_mut()
+        .filter_map(|mark| match mark {
+            MarkRenderer::Text(mark) => Some(mark),
+            MarkRenderer::Geom(_) | MarkRenderer::Pick(_) => None,
         })
-        .await
-        .ok_or(Sg2dWgpuError::MakeWgpuAdapterError)
+        .collect::<Vec<_>>();
+    text.prepare(device, queue, &mut text_marks)
 }
 
-async fn request_wgpu_device(adapter: &Adapter) -> Result<(Device, Queue), Sg2dWgpuError> {
-    Ok(adapter
-        .request_device(
-            &DeviceDescriptor {
-                label: None,
-                features: wgpu::Features::empty(),
-                // WebGL doesn't support all of wgpu's features, so if
-                // we're building for the web we'll have to disable some.
-                limits: if cfg!(target_arch = "wasm32") {
-                    wgpu::Limits::downlevel_webgl2_defaults()
-                } else {
-                    wgpu::Limits::default()
-                },
+/// Copy a texture with 4 bytes per pixel into a mappable buffer and read it back.
+///
+/// Rows are padded to `padded_width` pixels, and the buffer must hold `padded_height` rows.
+pub(crate) async fn read_texture(
+    device: &Device,
+    queue: &Queue,
+    texture: &Texture,
+    output_buffer: &Buffer,
+    texture_size: Extent3d,
+    padded_width: u32,
+    padded_height: u32,
+) -> Result<Vec<u8>, wgpu::BufferAsyncError> {
+    let mut extract_encoder = device.create_command_encoder(&CommandEncoderDescriptor {
+        label: Some("Extract Texture Encoder"),
+    });
+
+    let u32_size = std::mem::size_of::<u32>() as u32;
+
+    extract_encoder.copy_texture_to_buffer(
+        ImageCopyTexture {
+            aspect: TextureAspect::All,
+            texture,
+            mip_level: 0,
+            origin: Origin3d::ZERO,
+        },
+        ImageCopyBuffer {
+            buffer: output_buffer,
+            layout: ImageDataLayout {
+                offset: 0,
+                bytes_per_row: Some(u32_size * padded_width),
+                rows_per_image: Some(padded_height),
             },
-            None,
-        )
-        .await?)
+        },
+        texture_size,
+    );
+    queue.submit(Some(extract_encoder.finish()));
+
+    let buffer_slice = output_buffer.slice(..);
+
+    // NOTE: We have to create the mapping THEN device.poll() before await
+    // the future. Otherwise the application will freeze.
+    let (tx, rx) = futures_intrusive::channel::shared::oneshot_channel();
+    buffer_slice.map_async(MapMode::Read, move |result| {
+        tx.send(result).unwrap();
+    });
+    device.poll(wgpu::Maintain::Wait);
+    rx.receive()
+        .await
+        .expect("Buffer mapping callback dropped")?;
+
+    let data = buffer_slice.get_mapped_range().to_vec();
+    output_buffer.unmap();
+    Ok(data)
 }
 
 fn create_multisampled_framebuffer(
@@ -268,15 +647,20 @@ fn get_supported_sample_count(sample_flags: TextureFormatFeatureFlags) -> u32 {
 pub struct WindowCanvas {
     window: Window,
     surface: Surface,
-    device: Device,
-    queue: Queue,
     multisampled_framebuffer: TextureView,
     sample_count: u32,
     config: SurfaceConfiguration,
     size: winit::dpi::PhysicalSize<u32>,
     scale: f32,
     marks: Vec<MarkRenderer>,
+    text: TextContext,
+    text_rendering: TextRendering,
+    picker: Option<Picker>,
     uniform: CanvasUniform,
+    background: Option<Color>,
+    scene_background: Option<Color>,
+    // Fields are dropped in order, so the device outlives the resources created with it
+    context: RenderContext,
 }
 
 impl WindowCanvas {
@@ -285,6 +669,16 @@ impl WindowCanvas {
         width: f32,
         height: f32,
         scale: f32,
+    ) -> Result<Self, Sg2dWgpuError> {
+        Self::with_config(window, width, height, scale, &CanvasConfig::default()).await
+    }
+
+    pub async fn with_config(
+        window: Window,
+        width: f32,
+        height: f32,
+        scale: f32,
+        config: &CanvasConfig,
     ) -> Result<Self, Sg2dWgpuError> {
         window.set_inner_size(Size::Physical(PhysicalSize::new(
             (width * scale) as u32,
@@ -293,12 +687,12 @@ impl WindowCanvas {
 
         let size = window.inner_size();
 
-        let instance = make_wgpu_instance();
+        let instance = config.make_instance();
         let surface = unsafe { instance.create_surface(&window) }?;
-        let adapter = make_wgpu_adapter(&instance, Some(&surface)).await?;
-        let (device, queue) = request_wgpu_device(&adapter).await?;
+        let context = RenderContext::request(&instance, config, Some(&surface)).await?;
+        let (adapter, device, queue) = (context.adapter(), context.device(), context.queue());
 
-        let surface_caps = surface.get_capabilities(&adapter);
+        let surface_caps = surface.get_capabilities(adapter);
 
         // Select first non-srgb texture format
         let surface_format = surface_caps
@@ -308,7 +702,7 @@ impl WindowCanvas {
             .find(|f| !f.is_srgb())
             .unwrap_or(surface_caps.formats[0]);
 
-        let config = SurfaceConfiguration {
+        let surface_config = SurfaceConfiguration {
             usage: TextureUsages::RENDER_ATTACHMENT,
             format: surface_format,
             width: size.width,
@@ -317,39 +711,53 @@ impl WindowCanvas {
             alpha_mode: surface_caps.alpha_modes[0],
             view_formats: vec![],
         };
-        surface.configure(&device, &config);
+        surface.configure(device, &surface_config);
 
         let format_flags = adapter.get_texture_format_features(surface_format).flags;
         let sample_count = get_supported_sample_count(format_flags);
         let multisampled_framebuffer = create_multisampled_framebuffer(
-            &device,
-            config.width,
-            config.height,
+            device,
+            surface_config.width,
+            surface_config.height,
             surface_format,
             sample_count,
         );
 
-        let uniform = CanvasUniform {
-            size: [size.width as f32, size.height as f32],
-            scale,
-            _pad: [0.0],
-        };
+        let uniform = CanvasUniform::new([size.width as f32, size.height as f32], scale);
+        let text = TextContext::new(
+            device,
+            queue,
+            context.pipelines(),
+            surface_format,
+            sample_count,
+        );
 
         Ok(Self {
             surface,
-            device,
-            queue,
+            context,
             multisampled_framebuffer,
             sample_count,
-            config,
+            config: surface_config,
             size,
             scale,
             window,
             uniform,
             marks: Vec::new(),
+            text,
+            text_rendering: Default::default(),
+            picker: None,
+            background: None,
+            scene_background: None,
         })
     }
 
+    /// Override the background of every scene, or use the scene's background again with `None`.
+    /// Scenes without a background are drawn over white. Takes effect the next time the canvas
+    /// is rendered.
+    pub fn set_background(&mut self, background: Option<Color>) {
+        self.background = background;
+    }
+
     pub fn get_size(&self) -> winit::dpi::PhysicalSize<u32> {
         self.size
     }
@@ -374,7 +782,7 @@ impl WindowCanvas {
 
     pub fn update(&mut self) {}
 
-    pub fn render(&mut self) -> Result<(), SurfaceError> {
+    pub fn render(&mut self) -> Result<(), Sg2dWgpuError> {
         let output = self.surface.get_current_texture()?;
         let view = output
             .texture
@@ -387,37 +795,77 @@ impl WindowCanvas {
         };
         let mut commands = vec![background_command];
 
-        for mark in &mut self.marks {
+        prepare_text_marks(
+            self.context.device(),
+            self.context.queue(),
+            &mut self.text,
+            &mut self.marks,
+        )?;
+        for mark in &self.marks {
             let command = match mark {
                 MarkRenderer::Geom(mark) => {
                     if self.sample_count > 1 {
-                        mark.render(&self.device, &self.multisampled_framebuffer, Some(&view))
+                        mark.render(
+                            self.context.device(),
+                            &self.multisampled_framebuffer,
+                            Some(&view),
+                        )
                     } else {
-                        mark.render(&self.device, &view, None)
+                        mark.render(self.context.device(), &view, None)
                     }
                 }
                 MarkRenderer::Text(mark) => {
                     if self.sample_count > 1 {
                         mark.render(
-                            &self.device,
-                            &self.queue,
+                            self.context.device(),
+                            &self.text,
                             &self.multisampled_framebuffer,
                             Some(&view),
-                        )
+                        )?
                     } else {
-                        mark.render(&self.device, &self.queue, &view, None)
+                        mark.render(self.context.device(), &self.text, &view, None)?
                     }
                 }
+                MarkRenderer::Pick(_) => continue,
             };
 
             commands.push(command);
         }
 
-        self.queue.submit(commands);
+        self.context.queue().submit(commands);
         output.present();
 
         Ok(())
     }
+
+    /// Topmost item drawn at a point in logical pixels.
+    ///
+    /// Requires picking to be enabled before the scene is set.
+    pub async fn pick(&mut self, x: f32, y: f32) -> Result<Option<SceneHit>, Sg2dWgpuError> {
+        let picker = self.picker.as_mut().ok_or(Sg2dWgpuError::PickingDisabled)?;
+        picker
+            .pick(
+                self.context.device(),
+                self.context.queue(),
+                &self.marks,
+                x,
+                y,
+            )
+            .await
+    }
+
+    /// Every item drawn within a rectangle in logical pixels, in draw order
+    pub async fn pick_rect(&mut self, bounds: &Bounds) -> Result<Vec<SceneHit>, Sg2dWgpuError> {
+        let picker = self.picker.as_mut().ok_or(Sg2dWgpuError::PickingDisabled)?;
+        picker
+            .pick_rect(
+                self.context.device(),
+                self.context.queue(),
+                &self.marks,
+                bounds,
+            )
+            .await
+    }
 }
 
 impl Canvas for WindowCanvas {
@@ -427,14 +875,21 @@ impl Canvas for WindowCanvas {
 
     fn clear_mark_renderer(&mut self) {
         self.marks.clear();
+        if let Some(picker) = &mut self.picker {
+            picker.reset();
+        }
     }
 
     fn device(&self) -> &Device {
-        &self.device
+        self.context.device()
     }
 
     fn queue(&self) -> &Queue {
-        &self.queue
+        self.context.queue()
+    }
+
+    fn pipelines(&self) -> &PipelineCache {
+        self.context.pipelines()
     }
 
     fn uniform(&self) -> &CanvasUniform {
@@ -456,15 +911,51 @@ impl Canvas for WindowCanvas {
     fn sample_count(&self) -> u32 {
         self.sample_count
     }
+
+    fn text_rendering(&self) -> TextRendering {
+        self.text_rendering
+    }
+
+    fn set_text_rendering(&mut self, text_rendering: TextRendering) {
+        self.text_rendering = text_rendering;
+    }
+
+    fn pick_index(&mut self) -> Option<&mut PickIndex> {
+        self.picker.as_mut().map(|picker| picker.index_mut())
+    }
+
+    fn set_picking(&mut self, picking: bool) {
+        self.picker = picking.then(|| {
+            Picker::new(
+                self.context.device(),
+                self.size.width,
+                self.size.height,
+                self.scale,
+            )
+        });
+    }
+
+    fn background(&self) -> Color {
+        self.background
+            .or(self.scene_background)
+            .unwrap_or(Color::WHITE)
+    }
+
+    fn set_scene_background(&mut self, background: Option<Color>) {
+        self.scene_background = background;
+    }
 }
 
 pub struct PngCanvas {
-    device: Device,
-    queue: Queue,
     multisampled_framebuffer: TextureView,
     sample_count: u32,
     marks: Vec<MarkRenderer>,
+    text: TextContext,
+    text_rendering: TextRendering,
+    picker: Option<Picker>,
     uniform: CanvasUniform,
+    background: Option<Color>,
+    scene_background: Option<Color>,
     pub width: f32,
     pub height: f32,
     pub scale: f32,
@@ -476,19 +967,42 @@ pub struct PngCanvas {
     pub padded_height: u32,
     pub physical_width: f32,
     pub physical_height: f32,
+    // Last, so that textures and buffers are dropped before the device
+    context: Arc<RenderContext>,
 }
 
-impl PngCanvas {
-    pub async fn new(width: f32, height: f32, scale: f32) -> Result<Self, Sg2dWgpuError> {
-        let instance = make_wgpu_instance();
-        let adapter = make_wgpu_adapter(&instance, None).await?;
-        let (device, queue) = request_wgpu_device(&adapter).await?;
-        let texture_format = TextureFormat::Rgba8Unorm;
-        let format_flags = adapter.get_texture_format_features(texture_format).flags;
-        let sample_count = get_supported_sample_count(format_flags);
+/// Textures and readback buffer of a `PngCanvas` at one size
+struct OffscreenTarget {
+    texture: Texture,
+    texture_view: TextureView,
+    texture_size: Extent3d,
+    output_buffer: Buffer,
+    padded_width: u32,
+    padded_height: u32,
+    multisampled_framebuffer: TextureView,
+}
+
+impl OffscreenTarget {
+    /// Target of the given size in pixels, which must be at least 1 and at most the maximum
+    /// texture size of the device
+    fn new(
+        device: &Device,
+        physical_width: f32,
+        physical_height: f32,
+        texture_format: TextureFormat,
+        sample_count: u32,
+    ) -> Result<Self, Sg2dWgpuError> {
+        // wgpu panics instead of returning an error for empty or oversized textures
+        let max_size = device.limits().max_texture_dimension_2d;
+        let valid_size = 1.0..=max_size as f32;
+        if !valid_size.contains(&physical_width) || !valid_size.contains(&physical_height) {
+            return Err(Sg2dWgpuError::InvalidCanvasSize {
+                width: physical_width,
+                height: physical_height,
+                max_size,
+            });
+        }
 
-        let physical_width = width * scale;
-        let physical_height = height * scale;
         let texture_desc = TextureDescriptor {
             size: Extent3d {
                 width: physical_width as u32,
@@ -526,14 +1040,8 @@ impl PngCanvas {
         };
         let output_buffer = device.create_buffer(&output_buffer_desc);
 
-        let uniform = CanvasUniform {
-            size: [width, height],
-            scale,
-            _pad: [0.0],
-        };
-
         let multisampled_framebuffer = create_multisampled_framebuffer(
-            &device,
+            device,
             physical_width as u32,
             physical_height as u32,
             texture_format,
@@ -541,8 +1049,75 @@ impl PngCanvas {
         );
 
         Ok(Self {
-            device,
-            queue,
+            texture,
+            texture_view,
+            texture_size,
+            output_buffer,
+            padded_width,
+            padded_height,
+            multisampled_framebuffer,
+        })
+    }
+}
+
+impl PngCanvas {
+    pub async fn new(width: f32, height: f32, scale: f32) -> Result<Self, Sg2dWgpuError> {
+        Self::with_config(width, height, scale, &CanvasConfig::default()).await
+    }
+
+    pub async fn with_config(
+        width: f32,
+        height: f32,
+        scale: f32,
+        config: &CanvasConfig,
+    ) -> Result<Self, Sg2dWgpuError> {
+        let context = RenderContext::new(config).await?;
+        Self::with_context(Arc::new(context), width, height, scale)
+    }
+
+    /// Canvas that renders with a shared context, reusing its device and compiled pipelines
+    pub fn with_context(
+        context: Arc<RenderContext>,
+        width: f32,
+        height: f32,
+        scale: f32,
+    ) -> Result<Self, Sg2dWgpuError> {
+        let texture_format = TextureFormat::Rgba8Unorm;
+        let format_flags = context
+            .adapter()
+            .get_texture_format_features(texture_format)
+            .flags;
+        let sample_count = get_supported_sample_count(format_flags);
+
+        let physical_width = width * scale;
+        let physical_height = height * scale;
+        let OffscreenTarget {
+            texture,
+            texture_view,
+            texture_size,
+            output_buffer,
+            padded_width,
+            padded_height,
+            multisampled_framebuffer,
+        } = OffscreenTarget::new(
+            context.device(),
+            physical_width,
+            physical_height,
+            texture_format,
+            sample_count,
+        )?;
+
+        let uniform = CanvasUniform::new([width, height], scale);
+        let text = TextContext::new(
+            context.device(),
+            context.queue(),
+            context.pipelines(),
+            texture_format,
+            sample_count,
+        );
+
+        Ok(Self {
+            context,
             multisampled_framebuffer,
             sample_count,
             width,
@@ -558,10 +1133,72 @@ impl PngCanvas {
             padded_width,
             padded_height,
             marks: Vec::new(),
+            text,
+            text_rendering: Default::default(),
+            picker: None,
+            background: None,
+            scene_background: None,
         })
     }
 
-    pub async fn render(&mut self) -> Result<image::RgbaImage, SurfaceError> {
+    pub fn context(&self) -> &Arc<RenderContext> {
+        &self.context
+    }
+
+    /// Change the size of the canvas. Clears the scene, so call `set_scene` before rendering
+    /// again. The canvas is left unchanged if the new size is invalid.
+    pub fn resize(&mut self, width: f32, height: f32, scale: f32) -> Result<(), Sg2dWgpuError> {
+        let physical_width = width * scale;
+        let physical_height = height * scale;
+        let target = OffscreenTarget::new(
+            self.context.device(),
+            physical_width,
+            physical_height,
+            self.texture_format(),
+            self.sample_count,
+        )?;
+        self.texture = target.texture;
+        self.texture_view = target.texture_view;
+        self.texture_size = target.texture_size;
+        self.output_buffer = target.output_buffer;
+        self.padded_width = target.padded_width;
+        self.padded_height = target.padded_height;
+        self.multisampled_framebuffer = target.multisampled_framebuffer;
+
+        self.width = width;
+        self.height = height;
+        self.scale = scale;
+        self.physical_width = physical_width;
+        self.physical_height = physical_height;
+        self.uniform = CanvasUniform::new([width, height], scale);
+        self.clear_mark_renderer();
+        if self.picker.is_some() {
+            self.set_picking(true);
+        }
+        Ok(())
+    }
+
+    /// Override the background of every scene, or use the scene's background again with `None`.
+    /// Scenes without a background are drawn over white. Takes effect the next time the canvas
+    /// is rendered.
+    pub fn set_background(&mut self, background: Option<Color>) {
+        self.background = background;
+    }
+
+    /// Show the part of the current scene whose top left corner is at `(x, y)` in scene units,
+    /// at the size of the canvas, without setting the scene again. Picks are relative to the
+    /// origin too, until the scene is set again.
+    pub fn set_origin(&mut self, x: f32, y: f32) {
+        for mark in &mut self.marks {
+            mark.set_viewport(self.context.queue(), [x, y], [self.width, self.height]);
+        }
+        if let Some(picker) = &mut self.picker {
+            picker.clear_ids();
+        }
+    }
+
+    /// Render the scene to an image with straight (not premultiplied) alpha
+    pub async fn render(&mut self) -> Result<image::RgbaImage, Sg2dWgpuError> {
         // Build encoder for chart background
         let background_command = if self.sample_count > 1 {
             make_background_command(
@@ -575,99 +1212,99 @@ impl PngCanvas {
 
         let mut commands = vec![background_command];
 
-        for mark in &mut self.marks {
+        prepare_text_marks(
+            self.context.device(),
+            self.context.queue(),
+            &mut self.text,
+            &mut self.marks,
+        )?;
+        for mark in &self.marks {
             let command = match mark {
                 MarkRenderer::Geom(mark) => {
                     if self.sample_count > 1 {
                         mark.render(
-                            &self.device,
+                            self.context.device(),
                             &self.multisampled_framebuffer,
                             Some(&self.texture_view),
                         )
                     } else {
-                        mark.render(&self.device, &self.texture_view, None)
+                        mark.render(self.context.device(), &self.texture_view, None)
                     }
                 }
                 MarkRenderer::Text(mark) => {
                     if self.sample_count > 1 {
                         mark.render(
-                            &self.device,
-                            &self.queue,
+                            self.context.device(),
+                            &self.text,
                             &self.multisampled_framebuffer,
                             Some(&self.texture_view),
-                        )
+                        )?
                     } else {
-                        mark.render(&self.device, &self.queue, &self.texture_view, None)
+                        mark.render(self.context.device(), &self.text, &self.texture_view, None)?
                     }
                 }
+                MarkRenderer::Pick(_) => continue,
             };
 
             commands.push(command);
         }
 
-        self.queue.submit(commands);
+        self.context.queue().submit(commands);
 
         // Extract texture from GPU
-        let mut extract_encoder = self
-            .device
-            .create_command_encoder(&CommandEncoderDescriptor {
-                label: Some("Extract Texture Encoder"),
-            });
-
-        let u32_size = std::mem::size_of::<u32>() as u32;
-
-        extract_encoder.copy_texture_to_buffer(
-            ImageCopyTexture {
-                aspect: TextureAspect::All,
-                texture: &self.texture,
-                mip_level: 0,
-                origin: Origin3d::ZERO,
-            },
-            ImageCopyBuffer {
-                buffer: &self.output_buffer,
-                layout: ImageDataLayout {
-                    offset: 0,
-                    // bytes_per_row: Some(u32_size * self.width as u32),
-                    bytes_per_row: Some(u32_size * self.padded_width),
-                    rows_per_image: Some(self.padded_height),
-                },
-            },
+        let mut data = read_texture(
+            self.context.device(),
+            self.context.queue(),
+            &self.texture,
+            &self.output_buffer,
             self.texture_size,
-        );
-        self.queue.submit(Some(extract_encoder.finish()));
+            self.padded_width,
+            self.padded_height,
+        )
+        .await?;
+        unpremultiply(&mut data);
 
         // Output to png file
-        let img = {
-            let buffer_slice = self.output_buffer.slice(..);
-
-            // NOTE: We have to create the mapping THEN device.poll() before await
-            // the future. Otherwise the application will freeze.
-            let (tx, rx) = futures_intrusive::channel::shared::oneshot_channel();
-            buffer_slice.map_async(MapMode::Read, move |result| {
-                tx.send(result).unwrap();
-            });
-            self.device.poll(wgpu::Maintain::Wait);
-
-            // TODO: remove panic
-            rx.receive().await.unwrap().unwrap();
-
-            let data = buffer_slice.get_mapped_range();
-            let img_buf =
-                image::RgbaImage::from_vec(self.padded_width, self.padded_height, data.to_vec())
-                    .unwrap();
+        let img_buf = image::RgbaImage::from_vec(self.padded_width, self.padded_height, data)
+            .expect("Output buffer holds the padded texture");
+        let cropped_img = crop_imm(
+            &img_buf,
+            0,
+            0,
+            self.physical_width as u32,
+            self.physical_height as u32,
+        );
+        Ok(cropped_img.to_image())
+    }
 
-            let cropped_img = crop_imm(
-                &img_buf,
-                0,
-                0,
-                self.physical_width as u32,
-                self.physical_height as u32,
-            );
-            cropped_img.to_image()
-        };
+    /// Topmost item drawn at a point in logical pixels.
+    ///
+    /// Requires picking to be enabled before the scene is set. The pick target is rendered
+    /// and read back once per scene, so later picks in the same scene are cheap.
+    pub async fn pick(&mut self, x: f32, y: f32) -> Result<Option<SceneHit>, Sg2dWgpuError> {
+        let picker = self.picker.as_mut().ok_or(Sg2dWgpuError::PickingDisabled)?;
+        picker
+            .pick(
+                self.context.device(),
+                self.context.queue(),
+                &self.marks,
+                x,
+                y,
+            )
+            .await
+    }
 
-        self.output_buffer.unmap();
-        Ok(img)
+    /// Every item drawn within a rectangle in logical pixels, in draw order
+    pub async fn pick_rect(&mut self, bounds: &Bounds) -> Result<Vec<SceneHit>, Sg2dWgpuError> {
+        let picker = self.picker.as_mut().ok_or(Sg2dWgpuError::PickingDisabled)?;
+        picker
+            .pick_rect(
+                self.context.device(),
+                self.context.queue(),
+                &self.marks,
+                bounds,
+            )
+            .await
     }
 }
 
@@ -678,14 +1315,21 @@ impl Canvas for PngCanvas {
 
     fn clear_mark_renderer(&mut self) {
         self.marks.clear();
+        if let Some(picker) = &mut self.picker {
+            picker.reset();
+        }
     }
 
     fn device(&self) -> &Device {
-        &self.device
+        self.context.device()
     }
 
     fn queue(&self) -> &Queue {
-        &self.queue
+        self.context.queue()
+    }
+
+    fn pipelines(&self) -> &PipelineCache {
+        self.context.pipelines()
     }
 
     fn uniform(&self) -> &CanvasUniform {
@@ -707,4 +1351,37 @@ impl Canvas for PngCanvas {
     fn sample_count(&self) -> u32 {
         self.sample_count
     }
+
+    fn text_rendering(&self) -> TextRendering {
+        self.text_rendering
+    }
+
+    fn set_text_rendering(&mut self, text_rendering: TextRendering) {
+        self.text_rendering = text_rendering;
+    }
+
+    fn pick_index(&mut self) -> Option<&mut PickIndex> {
+        self.picker.as_mut().map(|picker| picker.index_mut())
+    }
+
+    fn set_picking(&mut self, picking: bool) {
+        self.picker = picking.then(|| {
+            Picker::new(
+                self.context.device(),
+                self.physical_width as u32,
+                self.physical_height as u32,
+                self.scale,
+            )
+        });
+    }
+
+    fn background(&self) -> Color {
+        self.background
+            .or(self.scene_background)
+            .unwrap_or(Color::WHITE)
+    }
+
+    fn set_scene_background(&mut self, background: Option<Color>) {
+        self.scene_background = background;
+    }
 }
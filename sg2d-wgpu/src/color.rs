@@ -0,0 +1,24 @@
+use glyphon::ColorMode;
+use sg2d::value::ColorSpace;
+use wgpu::TextureFormat;
+
+/// Color space that shaders write colors to a texture format in.
+///
+/// The GPU encodes values written to sRGB textures, so shaders write linear colors to them.
+/// Other formats store the sRGB colors as is, and blend in sRGB like browsers do.
+pub fn texture_color_space(format: TextureFormat) -> ColorSpace {
+    if format.is_srgb() {
+        ColorSpace::Linear
+    } else {
+        ColorSpace::Srgb
+    }
+}
+
+/// Glyph color mode that matches `texture_color_space`, so that text and other marks of the
+/// same color look the same
+pub fn text_color_mode(format: TextureFormat) -> ColorMode {
+    match texture_color_space(format) {
+        ColorSpace::Linear => ColorMode::Accurate,
+        ColorSpace::Srgb => ColorMode::Web,
+    }
+}
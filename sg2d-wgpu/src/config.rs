@@ -0,0 +1,126 @@
+//! Options for choosing the wgpu adapter and device that a canvas renders with.
+//!
+//! `PngCanvas::new` and `WindowCanvas::new` use `CanvasConfig::default()`, which searches every
+//! backend and falls back to a software adapter (such as lavapipe, llvmpipe or WARP) when no
+//! other adapter is available. Use `with_config` on either canvas to change this.
+use crate::error::Sg2dWgpuError;
+use wgpu::{Adapter, Backends, Instance, Limits, PowerPreference, RequestAdapterOptions, Surface};
+
+/// Adapter and device requirements of a canvas, set with chained builder methods
+#[derive(Debug, Clone)]
+pub struct CanvasConfig {
+    /// Backends to search for adapters
+    pub backends: Backends,
+    pub power_preference: PowerPreference,
+    /// Only use the fallback adapter, which is usually a software renderer
+    pub force_fallback_adapter: bool,
+    /// Use the fallback adapter if no other adapter is available
+    pub software_fallback: bool,
+    /// Limits the device is required to support
+    pub limits: Limits,
+}
+
+impl Default for CanvasConfig {
+    fn default() -> Self {
+        Self {
+            backends: Backends::all(),
+            power_preference: PowerPreference::default(),
+            force_fallback_adapter: false,
+            software_fallback: true,
+            // WebGL doesn't support all of wgpu's features, so if
+            // we're building for the web we'll have to disable some.
+            limits: if cfg!(target_arch = "wasm32") {
+                Limits::downlevel_webgl2_defaults()
+            } else {
+                Limits::default()
+            },
+        }
+    }
+}
+
+impl CanvasConfig {
+    pub fn backends(mut self, backends: Backends) -> Self {
+        self.backends = backends;
+        self
+    }
+
+    pub fn power_preference(mut self, power_preference: PowerPreference) -> Self {
+        self.power_preference = power_preference;
+        self
+    }
+
+    pub fn force_fallback_adapter(mut self, force_fallback_adapter: bool) -> Self {
+        self.force_fallback_adapter = force_fallback_adapter;
+        self
+    }
+
+    pub fn software_fallback(mut self, software_fallback: bool) -> Self {
+        self.software_fallback = software_fallback;
+        self
+    }
+
+    pub fn limits(mut self, limits: Limits) -> Self {
+        self.limits = limits;
+        self
+    }
+
+    pub(crate) fn make_instance(&self) -> Instance {
+        Instance::new(wgpu::InstanceDescriptor {
+            backends: self.backends,
+            ..Default::default()
+        })
+    }
+
+    /// Request an adapter that satisfies this config, retrying with the fallback adapter
+    /// if allowed
+    pub(crate) async fn request_adapter(
+        &self,
+        instance: &Instance,
+        compatible_surface: Option<&Surface>,
+    ) -> Result<Adapter, Sg2dWgpuError> {
+        let mut options = RequestAdapterOptions {
+            power_preference: self.power_preference,
+            compatible_surface,
+            force_fallback_adapter: self.force_fallback_adapter,
+        };
+        let mut adapter = instance.request_adapter(&options).await;
+        if adapter.is_none() && self.software_fallback && !self.force_fallback_adapter {
+            options.force_fallback_adapter = true;
+            adapter = instance.request_adapter(&options).await;
+        }
+        let Some(adapter) = adapter else {
+            return Err(Sg2dWgpuError::MakeWgpuAdapterError {
+                adapters: available_adapters(instance, self.backends),
+            });
+        };
+
+        let mut unsupported = Vec::new();
+        self.limits.check_limits_with_fail_fn(
+            &adapter.limits(),
+            false,
+            |name, required, allowed| {
+                unsupported.push(format!("{name} (required {required}, supported {allowed})"))
+            },
+        );
+        if !unsupported.is_empty() {
+            return Err(Sg2dWgpuError::UnsupportedLimits {
+                adapter: describe_adapter(&adapter),
+                limits: unsupported,
+            });
+        }
+        Ok(adapter)
+    }
+}
+
+/// Descriptions of the adapters the instance can use, for error messages
+fn available_adapters(instance: &Instance, backends: Backends) -> Vec<String> {
+    instance
+        .enumerate_adapters(backends)
+        .map(|adapter| describe_adapter(&adapter))
+        .collect()
+}
+
+fn describe_adapter(adapter: &Adapter) -> String {
+    let info = adapter.get_info();
+    format!("{} ({:?}, {:?})", info.name, info.backend, info.device_type)
+}
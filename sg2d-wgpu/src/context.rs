@@ -0,0 +1,66 @@
+//! Device, queue and pipeline cache that several canvases can share.
+//!
+//! Creating a device and compiling mark pipelines takes much longer than rendering a typical
+//! chart, so batch exports should create one `RenderContext` and pass it to every `PngCanvas`
+//! with `PngCanvas::with_context`.
+use crate::config::CanvasConfig;
+use crate::error::Sg2dWgpuError;
+use crate::marks::pipeline::PipelineCache;
+use wgpu::{Adapter, Device, DeviceDescriptor, Instance, Queue, Surface};
+
+pub struct RenderContext {
+    // Fields are dropped in order, so the pipelines go before the device
+    pipelines: PipelineCache,
+    queue: Queue,
+    device: Device,
+    adapter: Adapter,
+}
+
+impl RenderContext {
+    /// Request an adapter and device that satisfy the config
+    pub async fn new(config: &CanvasConfig) -> Result<Self, Sg2dWgpuError> {
+        Self::request(&config.make_instance(), config, None).await
+    }
+
+    /// Request an adapter that can present to the surface, if there is one, and its device
+    pub(crate) async fn request(
+        instance: &Instance,
+        config: &CanvasConfig,
+        compatible_surface: Option<&Surface>,
+    ) -> Result<Self, Sg2dWgpuError> {
+        let adapter = config.request_adapter(instance, compatible_surface).await?;
+        let (device, queue) = adapter
+            .request_device(
+                &DeviceDescriptor {
+                    label: None,
+                    features: wgpu::Features::empty(),
+                    limits: config.limits.clone(),
+                },
+                None,
+            )
+            .await?;
+        let pipelines = PipelineCache::new(&device);
+        Ok(Self {
+            pipelines,
+            queue,
+            device,
+            adapter,
+        })
+    }
+
+    pub fn adapter(&self) -> &Adapter {
+        &self.adapter
+    }
+
+    pub fn device(&self) -> &Device {
+        &self.device
+    }
+
+    pub fn queue(&self) -> &Queue {
+        &self.queue
+    }
+
+    pub fn pipelines(&self) -> &PipelineCache {
+        &self.pipelines
+    }
+}
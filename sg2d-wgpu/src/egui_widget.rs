@@ -0,0 +1,215 @@
+//! egui widget that draws scene graphs with the wgpu device of egui's renderer.
+//!
+//! Call `register` once with the `egui_wgpu` render state, for example from the eframe app
+//! creator, then show scene graphs with `SceneGraphView`. Each view keeps a `SceneRenderer` in
+//! the callback resources of egui's renderer, and only sets its scene again when the scene graph
+//! or the pixel scale changes. Renderers of views that weren't painted in the previous frame are
+//! dropped. egui's render pass must not have a depth buffer.
+use crate::marks::pipeline::PipelineCache;
+use crate::renderer::SceneRenderer;
+use egui::{Id, Pos2, Rect, Response, Sense, Ui, Vec2, ViewportId};
+use egui_wgpu::{CallbackResources, CallbackTrait, RenderState};
+use sg2d::scene_graph::SceneGraph;
+use sg2d_text::hit_test::{SceneHit, SceneHitTest};
+use std::collections::HashMap;
+use std::hash::Hash;
+use std::sync::Arc;
+use wgpu::{CommandBuffer, CommandEncoder, Device, Queue, RenderPass, TextureFormat};
+
+/// Renderers of every view, stored in the callback resources of egui's renderer
+struct ViewRenderers {
+    texture_format: TextureFormat,
+    sample_count: u32,
+    /// Pipelines shared by the renderers of every view
+    pipelines: Arc<PipelineCache>,
+    views: HashMap<Id, ViewRenderer>,
+}
+
+struct ViewRenderer {
+    renderer: SceneRenderer,
+    /// Viewport and frame number that the view was last painted in
+    viewport: ViewportId,
+    frame_nr: u64,
+    /// Scene graph that was last set, so that unchanged scenes aren't set again
+    scene_graph: Option<Arc<SceneGraph>>,
+    /// Whether the last scene graph was set without errors
+    valid: bool,
+}
+
+/// Add the resources that scene graph views render with to egui's renderer. `sample_count` must
+/// match the multisampling egui renders with, which is 1 unless it was configured otherwise.
+pub fn register(render_state: &RenderState, sample_count: u32) {
+    render_state
+        .renderer
+        .write()
+        .callback_resources
+        .insert(ViewRenderers {
+            texture_format: render_state.target_format,
+            sample_count,
+            pipelines: Arc::new(PipelineCache::new(&render_state.device)),
+            views: HashMap::new(),
+        });
+}
+
+/// Widget that draws a scene graph, scaled to fit its size while keeping its aspect ratio.
+///
+/// The scene graph is shared with an `Arc`, and the view sets its scene again when it's shown
+/// with a different `Arc`, so replace the `Arc` rather than the scene graph to update a chart.
+pub struct SceneGraphView {
+    id: Id,
+    scene_graph: Arc<SceneGraph>,
+    size: Option<Vec2>,
+}
+
+/// Response of a scene graph view, with the marks under the pointer
+pub struct SceneGraphResponse {
+    pub response: Response,
+    /// Topmost mark item under the pointer
+    pub hovered: Option<SceneHit>,
+    /// Topmost mark item under the pointer when the view was clicked this frame
+    pub clicked: Option<SceneHit>,
+}
+
+impl SceneGraphView {
+    /// View with an id that must be unique among the views shown with the same renderer
+    pub fn new(id_source: impl Hash, scene_graph: Arc<SceneGraph>) -> Self {
+        Self {
+            id: Id::new(id_source),
+            scene_graph,
+            size: None,
+        }
+    }
+
+    /// Size of the view in points, which defaults to the size of the scene graph
+    pub fn size(mut self, size: Vec2) -> Self {
+        self.size = Some(size);
+        self
+    }
+
+    pub fn show(self, ui: &mut Ui) -> SceneGraphResponse {
+        let scene_size = Vec2::new(self.scene_graph.width, self.scene_graph.height);
+        let (rect, response) =
+            ui.allocate_exact_size(self.size.unwrap_or(scene_size), Sense::click());
+        let zoom = (rect.width() / scene_size.x).min(rect.height() / scene_size.y);
+        let scene_rect = Rect::from_min_size(rect.min, scene_size * zoom);
+
+        let hit = |pos: Option<Pos2>| {
+            let pos = pos.filter(|pos| scene_rect.contains(*pos))?;
+            let point = (pos - scene_rect.min) / zoom;
+            self.scene_graph.hit_test([point.x, point.y])
+        };
+        let hovered = hit(response.hover_pos());
+        let clicked = if response.clicked() {
+            hit(response.interact_pointer_pos())
+        } else {
+            None
+        };
+
+        if zoom > 0.0 && ui.is_rect_visible(scene_rect) {
+            ui.painter().add(egui_wgpu::Callback::new_paint_callback(
+                scene_rect,
+                SceneGraphCallback {
+                    id: self.id,
+                    scene_graph: self.scene_graph,
+                    scale: ui.ctx().pixels_per_point() * zoom,
+                    viewport: ui.ctx().viewport_id(),
+                    frame_nr: ui.ctx().frame_nr(),
+                },
+            ));
+        }
+
+        SceneGraphResponse {
+            response,
+            hovered,
+            clicked,
+        }
+    }
+}
+
+struct SceneGraphCallback {
+    id: Id,
+    scene_graph: Arc<SceneGraph>,
+    /// Physical pixels per scene pixel
+    scale: f32,
+    viewport: ViewportId,
+    frame_nr: u64,
+}
+
+impl CallbackTrait for SceneGraphCallback {
+    fn prepare(
+        &self,
+        device: &Device,
+        queue: &Queue,
+        egui_encoder: &mut CommandEncoder,
+        callback_resources: &mut CallbackResources,
+    ) -> Vec<CommandBuffer> {
+        let Some(renderers) = callback_resources.get_mut::<ViewRenderers>() else {
+            log::warn!("sg2d_wgpu::egui_widget::register must be called before showing views");
+            return Vec::new();
+        };
+        // Drop the renderers of views in this viewport that weren't painted in the last frame
+        renderers
+            .views
+            .retain(|_, view| view.viewport != self.viewport || view.frame_nr + 1 >= self.frame_nr);
+
+        let (texture_format, sample_count) = (renderers.texture_format, renderers.sample_count);
+        let pipelines = &renderers.pipelines;
+        let view = renderers
+            .views
+            .entry(self.id)
+            .or_insert_with(|| ViewRenderer {
+                renderer: SceneRenderer::with_pipelines(
+                    device,
+                    queue,
+                    pipelines.clone(),
+                    texture_format,
+                    sample_count,
+                    self.scale,
+                ),
+                viewport: self.viewport,
+                frame_nr: self.frame_nr,
+                scene_graph: None,
+                valid: false,
+            });
+        view.viewport = self.viewport;
+        view.frame_nr = self.frame_nr;
+
+        let same_scene = view
+            .scene_graph
+            .as_ref()
+            .is_some_and(|scene_graph| Arc::ptr_eq(scene_graph, &self.scene_graph));
+        if !same_scene || view.renderer.scale() != self.scale {
+            view.renderer.set_scale(self.scale);
+            view.valid = match view.renderer.set_scene(device, queue, &self.scene_graph) {
+                Ok(()) => true,
+                Err(err) => {
+                    log::warn!("Failed to set the scene of view {:?}: {err}", self.id);
+                    false
+                }
+            };
+            view.scene_graph = Some(self.scene_graph.clone());
+        }
+        if view.valid {
+            if let Err(err) = view.renderer.prepare(device, queue, egui_encoder) {
+                log::warn!("Failed to prepare view {:?}: {err}", self.id);
+            }
+        }
+        Vec::new()
+    }
+
+    fn paint<'a>(
+        &'a self,
+        _info: egui::PaintCallbackInfo,
+        render_pass: &mut RenderPass<'a>,
+        callback_resources: &'a CallbackResources,
+    ) {
+        let view = callback_resources
+            .get::<ViewRenderers>()
+            .and_then(|renderers| renderers.views.get(&self.id));
+        if let Some(view) = view.filter(|view| view.valid) {
+            if let Err(err) = view.renderer.draw(render_pass) {
+                log::warn!("Failed to draw view {:?}: {err}", self.id);
+            }
+        }
+    }
+}
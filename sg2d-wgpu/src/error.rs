@@ -3,7 +3,7 @@ use thiserror::Error;
 
 #[derive(Error, Debug)]
 pub enum Sg2dWgpuError {
-    #[error("SceneGraph error")]
+    #[error("SceneGraph error: {0}")]
     SceneGraphError(#[from] sg2d::error::SceneGraphError),
 
     #[error("Device request failed")]
@@ -12,9 +12,45 @@ pub enum Sg2dWgpuError {
     #[error("Failed to create surface")]
     CreateSurfaceError(#[from] wgpu::CreateSurfaceError),
 
-    #[error("WGPU adapter creation failed")]
-    MakeWgpuAdapterError,
+    #[error("No WGPU adapter matches the canvas config. Available adapters: {adapters:?}")]
+    MakeWgpuAdapterError { adapters: Vec<String> },
+
+    #[error("WGPU adapter {adapter} doesn't support the required limits: {limits:?}")]
+    UnsupportedLimits {
+        adapter: String,
+        limits: Vec<String>,
+    },
 
     #[error("lyon tessellation error")]
     TessellationError(#[from] TessellationError),
+
+    #[error("Failed to prepare text: {0}")]
+    TextPrepareError(#[from] glyphon::PrepareError),
+
+    #[error("Failed to render text: {0}")]
+    TextRenderError(#[from] glyphon::RenderError),
+
+    #[error("Failed to read back buffer from GPU")]
+    BufferAsyncError(#[from] wgpu::BufferAsyncError),
+
+    #[error(
+        "Can't create a canvas of {width}x{height} pixels, sizes must be between 1 and {max_size}"
+    )]
+    InvalidCanvasSize {
+        width: f32,
+        height: f32,
+        max_size: u32,
+    },
+
+    #[error("Picking is not enabled for this canvas")]
+    PickingDisabled,
+
+    #[error("Failed to render to texture")]
+    SurfaceError(#[from] wgpu::SurfaceError),
+
+    #[error("PNG encoding error: {0}")]
+    PngEncodingError(#[from] png::EncodingError),
+
+    #[error("IO error: {0}")]
+    IoError(#[from] std::io::Error),
 }
@@ -1,8 +1,17 @@
 pub mod canvas;
+pub mod color;
+pub mod config;
+pub mod context;
+#[cfg(feature = "egui")]
+pub mod egui_widget;
 pub mod error;
 pub mod marks;
+pub mod pick;
+pub mod renderer;
+pub mod tiled;
 
 use crate::canvas::{Canvas, WindowCanvas};
+use crate::error::Sg2dWgpuError;
 use sg2d::scene_graph::SceneGraph;
 use sg2d_vega::dims::VegaSceneGraphDims;
 use sg2d_vega::scene_graph::VegaSceneGraph;
@@ -58,13 +67,12 @@ pub async fn run() {
     .unwrap();
 
     // Extract dims and set window size
-    let origin = [scene_dims.origin_x, scene_dims.origin_y];
     let width = scene_dims.width;
     let height = scene_dims.height;
     let scale = 3.0;
 
     let scene_graph: SceneGraph = scene_spec
-        .to_scene_graph(origin, width, height)
+        .to_scene_graph_with_dims(&scene_dims)
         .expect("Failed to parse scene graph");
 
     // Save to png
@@ -79,43 +87,42 @@ pub async fn run() {
             Event::WindowEvent {
                 ref event,
                 window_id,
-            } if window_id == canvas.window().id() => {
-                if !canvas.input(event) {
-                    // UPDATED!
-                    match event {
-                        WindowEvent::CloseRequested
-                        | WindowEvent::KeyboardInput {
-                            input:
-                                KeyboardInput {
-                                    state: ElementState::Pressed,
-                                    virtual_keycode: Some(VirtualKeyCode::Escape),
-                                    ..
-                                },
+            } if window_id == canvas.window().id() && !canvas.input(event) => match event {
+                WindowEvent::CloseRequested
+                | WindowEvent::KeyboardInput {
+                    input:
+                        KeyboardInput {
+                            state: ElementState::Pressed,
+                            virtual_keycode: Some(VirtualKeyCode::Escape),
                             ..
-                        } => *control_flow = ControlFlow::Exit,
-                        WindowEvent::Resized(physical_size) => {
-                            canvas.resize(*physical_size);
-                        }
-                        WindowEvent::ScaleFactorChanged { new_inner_size, .. } => {
-                            // new_inner_size is &&mut so w have to dereference it twice
-                            canvas.resize(**new_inner_size);
-                        }
-                        _ => {}
-                    }
+                        },
+                    ..
+                } => *control_flow = ControlFlow::Exit,
+                WindowEvent::Resized(physical_size) => {
+                    canvas.resize(*physical_size);
                 }
-            }
+                WindowEvent::ScaleFactorChanged { new_inner_size, .. } => {
+                    // new_inner_size is &&mut so w have to dereference it twice
+                    canvas.resize(**new_inner_size);
+                }
+                _ => {}
+            },
             Event::RedrawRequested(window_id) if window_id == canvas.window().id() => {
                 canvas.update();
                 match canvas.render() {
                     Ok(_) => {}
                     // Reconfigure the surface if it's lost or outdated
-                    Err(wgpu::SurfaceError::Lost | wgpu::SurfaceError::Outdated) => {
-                        canvas.resize(canvas.get_size())
-                    }
+                    Err(Sg2dWgpuError::SurfaceError(
+                        wgpu::SurfaceError::Lost | wgpu::SurfaceError::Outdated,
+                    )) => canvas.resize(canvas.get_size()),
                     // The system is out of memory, we should probably quit
-                    Err(wgpu::SurfaceError::OutOfMemory) => *control_flow = ControlFlow::Exit,
-
-                    Err(wgpu::SurfaceError::Timeout) => log::warn!("Surface timeout"),
+                    Err(Sg2dWgpuError::SurfaceError(wgpu::SurfaceError::OutOfMemory)) => {
+                        *control_flow = ControlFlow::Exit
+                    }
+                    Err(Sg2dWgpuError::SurfaceError(wgpu::SurfaceError::Timeout)) => {
+                        log::warn!("Surface timeout")
+                    }
+                    Err(err) => log::warn!("Failed to render: {err}"),
                 }
             }
             Event::RedrawEventsCleared => {
@@ -4,11 +4,57 @@ struct ChartUniform {
     size: vec2<f32>,
     scale: f32,
     _pad: f32, // for 16 byte alignment
+    // Transform from group coordinates to scene coordinates, with the columns of the linear
+    // part packed into a vector
+    linear: vec4<f32>,
+    translate: vec2<f32>,
+    // Size of the group's clip region, negative when marks aren't clipped
+    clip_size: vec2<f32>,
+    // Transform from scene coordinates to clip region coordinates, packed like the group
+    // transform
+    clip_linear: vec4<f32>,
+    clip_translate: vec2<f32>,
+    _pad2: vec2<f32>, // for 16 byte alignment
 };
 
 @group(0) @binding(0)
 var<uniform> chart_uniforms: ChartUniform;
 
+// Map a position in scene coordinates, with y pointing down, to clip coordinates
+fn scene_to_clip(position: vec2<f32>) -> vec4<f32> {
+    let normalized = 2.0 * position / chart_uniforms.size - 1.0;
+    return vec4<f32>(normalized[0], -normalized[1], 0.0, 1.0);
+}
+
+fn group_linear() -> mat2x2<f32> {
+    return mat2x2<f32>(chart_uniforms.linear.xy, chart_uniforms.linear.zw);
+}
+
+// Map a position in group coordinates to scene coordinates
+fn group_to_scene(position: vec2<f32>) -> vec2<f32> {
+    return group_linear() * position + chart_uniforms.translate;
+}
+
+// Whether a position in scene coordinates is outside the clip region of the group
+fn is_clipped(position: vec2<f32>) -> bool {
+    if (chart_uniforms.clip_size[0] < 0.0) {
+        return false;
+    }
+    let clip_linear = mat2x2<f32>(chart_uniforms.clip_linear.xy, chart_uniforms.clip_linear.zw);
+    let p = clip_linear * position + chart_uniforms.clip_translate;
+    return any(p < vec2<f32>(0.0)) || any(p > chart_uniforms.clip_size);
+}
+
+struct PickUniform {
+    id_offset: u32,
+    _pad0: u32, // for 16 byte alignment
+    _pad1: u32,
+    _pad2: u32,
+};
+
+@group(1) @binding(0)
+var<uniform> pick_uniforms: PickUniform;
+
 struct VertexInput {
     @location(0) position: vec2<f32>,
     @location(1) normal: vec2<f32>,
@@ -31,6 +77,10 @@ struct VertexOutput {
     @location(3) fill_color: vec4<f32>,
     @location(4) stroke_color: vec4<f32>,
     @location(5) stroke_width: f32,
+    // Position in physical pixels relative to the scene, which is independent of where the
+    // viewport is in the target
+    @location(6) pixel_position: vec2<f32>,
+    @location(7) @interpolate(flat) instance: u32,
 };
 
 
@@ -38,25 +88,32 @@ struct VertexOutput {
 fn vs_main(
     model: VertexInput,
     instance: InstanceInput,
+    @builtin(instance_index) instance_index: u32,
 ) -> VertexOutput {
     var out: VertexOutput;
+    out.instance = instance_index;
 
     // Pass through colors and stroke_width
     out.fill_color = instance.fill_color;
     out.stroke_color = instance.stroke_color;
-    out.stroke_width = instance.stroke_width;
 
-    // Compute normalized position of vertex
-    let size_scale = sqrt(instance.size);
-    let clip_x = 2.0 * (model.position[0] * size_scale + instance.position[0]) / chart_uniforms.size[0] - 1.0;
-    let clip_y = 2.0 * (model.position[1] * size_scale + (chart_uniforms.size[1] - instance.position[1])) / chart_uniforms.size[1] - 1.0;
-    out.clip_position = vec4<f32>(clip_x, clip_y, 0.0, 1.0);
+    // Circles stay circular under the group transform, scaled by the square root of its
+    // determinant
+    let transform_det = determinant(group_linear());
+    let transform_scale = sqrt(abs(transform_det));
+    out.stroke_width = instance.stroke_width * transform_scale;
+
+    // Compute position of vertex in scene coordinates, where model y points up. The quad is
+    // mirrored under reflecting transforms so that its winding matches the other marks.
+    let size_scale = sqrt(instance.size) * transform_scale;
+    let center = group_linear() * instance.position + chart_uniforms.translate;
+    let mirror = select(1.0, -1.0, transform_det < 0.0);
+    let position = center + vec2<f32>(mirror * model.position[0], -model.position[1]) * size_scale;
+    out.clip_position = scene_to_clip(position);
+    out.pixel_position = position * chart_uniforms.scale;
 
     // Compute circle center in fragment shader coordinates
-    out.center = vec2<f32>(
-        instance.position[0] * chart_uniforms.scale,
-        instance.position[1] * chart_uniforms.scale,
-    );
+    out.center = center * chart_uniforms.scale;
 
     // Compute radius in fragment shader coordinates
     out.radius = size_scale * chart_uniforms.scale / 2.0;
@@ -68,8 +125,11 @@ fn vs_main(
 fn fs_main(
     in: VertexOutput,
 ) -> @location(0) vec4<f32> {
+    if (is_clipped(in.pixel_position / chart_uniforms.scale)) {
+        discard;
+    }
     let buffer = 0.5 * chart_uniforms.scale;
-    let dist = length(in.center - vec2<f32>(in.clip_position[0], in.clip_position[1]));
+    let dist = length(in.center - in.pixel_position);
 
     if (in.stroke_width > 0.0) {
         let inner_radius = in.radius - in.stroke_width * chart_uniforms.scale / 2.0;
@@ -78,6 +138,9 @@ fn fs_main(
             discard;
         } else {
             let alpha_factor = 1.0 - smoothstep(outer_radius - buffer, outer_radius + buffer, dist);
+            // Pixels take either the fill or the stroke color, mixed only across the inner edge
+            // of the stroke, so a translucent stroke shows what's behind the circle rather than
+            // its fill. The SVG, skia and PDF renderers only fill inside the stroke to match.
             let mix_factor = 1.0 - smoothstep(inner_radius - buffer, inner_radius + buffer, dist);
             var mixed_color: vec4<f32> = mix(in.stroke_color, in.fill_color, mix_factor);
             mixed_color[3] *= alpha_factor;
@@ -94,3 +157,19 @@ fn fs_main(
         }
     }
 }
+
+// Picking fragment shader, writes the id of the instance where the fill or stroke is visible
+
+@fragment
+fn fs_pick(
+    in: VertexOutput,
+) -> @location(0) u32 {
+    let dist = length(in.center - in.pixel_position);
+    let half_stroke = in.stroke_width * chart_uniforms.scale / 2.0;
+    let filled = in.fill_color[3] > 0.0 && dist <= in.radius;
+    let stroked = in.stroke_color[3] > 0.0 && in.stroke_width > 0.0 && abs(dist - in.radius) <= half_stroke;
+    if (!(filled || stroked) || is_clipped(in.pixel_position / chart_uniforms.scale)) {
+        discard;
+    }
+    return pick_uniforms.id_offset + in.instance;
+}
@@ -1,6 +1,8 @@
 use crate::canvas::CanvasUniform;
+use crate::marks::pipeline::PipelineCache;
+use std::sync::Arc;
 use wgpu::util::DeviceExt;
-use wgpu::{CommandBuffer, Device, TextureFormat, TextureView};
+use wgpu::{CommandBuffer, Device, Queue, RenderPass, TextureFormat, TextureView};
 
 pub trait MarkShader {
     type Instance: bytemuck::Pod + bytemuck::Zeroable;
@@ -11,28 +13,53 @@ pub trait MarkShader {
     fn shader(&self) -> &str;
     fn vertex_entry_point(&self) -> &str;
     fn fragment_entry_point(&self) -> &str;
+    /// Fragment entry point that writes the pick id of each instance to an `R32Uint` target
+    fn pick_entry_point(&self) -> &str {
+        "fs_pick"
+    }
     fn instance_desc(&self) -> wgpu::VertexBufferLayout<'static>;
     fn vertex_desc(&self) -> wgpu::VertexBufferLayout<'static>;
 }
 
 pub struct GeomMarkRenderer {
-    render_pipeline: wgpu::RenderPipeline,
+    render_pipeline: Arc<wgpu::RenderPipeline>,
     vertex_buffer: wgpu::Buffer,
     index_buffer: wgpu::Buffer,
     num_indices: u32,
     instance_buffer: wgpu::Buffer,
     num_instances: u32,
+    uniform: CanvasUniform,
+    uniform_buffer: wgpu::Buffer,
     uniform_bind_group: wgpu::BindGroup,
+    pick: Option<PickPipeline>,
+}
+
+/// Pipeline that draws pick ids instead of colors, along with the bind group holding
+/// the id of the first instance
+struct PickPipeline {
+    render_pipeline: Arc<wgpu::RenderPipeline>,
+    bind_group: wgpu::BindGroup,
+}
+
+#[repr(C)]
+#[derive(Copy, Clone, Debug, bytemuck::Pod, bytemuck::Zeroable)]
+struct PickUniform {
+    id_offset: u32,
+    _pad: [u32; 3], // Pad to 16 bytes
 }
 
 impl GeomMarkRenderer {
+    /// Upload the instances of a mark, taking its pipelines from the cache
+    #[allow(clippy::too_many_arguments)]
     pub fn new<I, V>(
         device: &Device,
+        pipelines: &PipelineCache,
         uniform: CanvasUniform,
         texture_format: TextureFormat,
         sample_count: u32,
         mark_shader: Box<dyn MarkShader<Instance = I, Vertex = V>>,
         instances: &[I],
+        pick_id_offset: Option<u32>,
     ) -> Self
     where
         I: bytemuck::Pod + bytemuck::Zeroable,
@@ -45,22 +72,8 @@ impl GeomMarkRenderer {
             usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
         });
 
-        let uniform_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
-            entries: &[wgpu::BindGroupLayoutEntry {
-                binding: 0,
-                visibility: wgpu::ShaderStages::VERTEX | wgpu::ShaderStages::FRAGMENT,
-                ty: wgpu::BindingType::Buffer {
-                    ty: wgpu::BufferBindingType::Uniform,
-                    has_dynamic_offset: false,
-                    min_binding_size: None,
-                },
-                count: None,
-            }],
-            label: Some("chart_uniform_layout"),
-        });
-
         let uniform_bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
-            layout: &uniform_layout,
+            layout: pipelines.uniform_layout(),
             entries: &[wgpu::BindGroupEntry {
                 binding: 0,
                 resource: uniform_buffer.as_entire_binding(),
@@ -68,59 +81,23 @@ impl GeomMarkRenderer {
             label: Some("uniform_bind_group"),
         });
 
-        let _render_pipeline_layout =
-            device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
-                label: Some("Render Pipeline Layout"),
-                bind_group_layouts: &[&uniform_layout],
-                push_constant_ranges: &[],
-            });
-
-        // Shaders
-        let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
-            label: Some("Shader"),
-            source: wgpu::ShaderSource::Wgsl(mark_shader.shader().into()),
-        });
+        // Group transforms that reflect marks reverse the winding of their triangles
+        let front_face = if uniform.group_transform().determinant() < 0.0 {
+            wgpu::FrontFace::Cw
+        } else {
+            wgpu::FrontFace::Ccw
+        };
 
-        let render_pipeline_layout =
-            device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
-                label: Some("Render Pipeline Layout"),
-                bind_group_layouts: &[&uniform_layout],
-                push_constant_ranges: &[],
-            });
+        let render_pipeline = pipelines.render_pipeline(
+            device,
+            &*mark_shader,
+            texture_format,
+            sample_count,
+            front_face,
+        );
 
-        let render_pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
-            label: Some("Render Pipeline"),
-            layout: Some(&render_pipeline_layout),
-            vertex: wgpu::VertexState {
-                module: &shader,
-                entry_point: mark_shader.vertex_entry_point(),
-                buffers: &[mark_shader.vertex_desc(), mark_shader.instance_desc()],
-            },
-            fragment: Some(wgpu::FragmentState {
-                module: &shader,
-                entry_point: mark_shader.fragment_entry_point(),
-                targets: &[Some(wgpu::ColorTargetState {
-                    format: texture_format,
-                    blend: Some(wgpu::BlendState::ALPHA_BLENDING),
-                    write_mask: wgpu::ColorWrites::ALL,
-                })],
-            }),
-            primitive: wgpu::PrimitiveState {
-                topology: wgpu::PrimitiveTopology::TriangleList,
-                strip_index_format: None,
-                front_face: wgpu::FrontFace::Ccw,
-                cull_mode: Some(wgpu::Face::Back),
-                polygon_mode: wgpu::PolygonMode::Fill,
-                unclipped_depth: false,
-                conservative: false,
-            },
-            depth_stencil: None,
-            multisample: wgpu::MultisampleState {
-                count: sample_count,
-                mask: !0,
-                alpha_to_coverage_enabled: false,
-            },
-            multiview: None,
+        let pick = pick_id_offset.map(|id_offset| {
+            make_pick_pipeline(device, pipelines, &*mark_shader, front_face, id_offset)
         });
 
         let vertex_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
@@ -150,10 +127,20 @@ impl GeomMarkRenderer {
             num_indices,
             instance_buffer,
             num_instances,
+            uniform,
+            uniform_buffer,
             uniform_bind_group,
+            pick,
         }
     }
 
+    /// Move the mark onto a canvas of the given size that shows the part of the scene whose top
+    /// left corner is at `origin`, by rewriting its uniform
+    pub fn set_viewport(&self, queue: &Queue, origin: [f32; 2], size: [f32; 2]) {
+        let uniform = self.uniform.viewport(origin, size);
+        queue.write_buffer(&self.uniform_buffer, 0, bytemuck::cast_slice(&[uniform]));
+    }
+
     pub fn render(
         &self,
         device: &Device,
@@ -180,14 +167,95 @@ impl GeomMarkRenderer {
                 timestamp_writes: None,
             });
 
-            render_pass.set_pipeline(&self.render_pipeline);
+            self.draw(&mut render_pass);
+        }
+
+        mark_encoder.finish()
+    }
+
+    /// Record the draw commands of the mark into a render pass that targets a texture with the
+    /// format and sample count the renderer was created with
+    pub fn draw<'a>(&'a self, render_pass: &mut RenderPass<'a>) {
+        render_pass.set_pipeline(&self.render_pipeline);
+        render_pass.set_bind_group(0, &self.uniform_bind_group, &[]);
+        render_pass.set_vertex_buffer(0, self.vertex_buffer.slice(..));
+        render_pass.set_vertex_buffer(1, self.instance_buffer.slice(..));
+        render_pass.set_index_buffer(self.index_buffer.slice(..), wgpu::IndexFormat::Uint16);
+        render_pass.draw_indexed(0..self.num_indices, 0, 0..self.num_instances);
+    }
+
+    /// Draw the pick id of every instance into an `R32Uint` target, or return None if the
+    /// renderer was created without picking
+    pub fn render_pick(
+        &self,
+        device: &Device,
+        texture_view: &TextureView,
+    ) -> Option<CommandBuffer> {
+        let pick = self.pick.as_ref()?;
+        let mut pick_encoder = device.create_command_encoder(&wgpu::CommandEncoderDescriptor {
+            label: Some("Mark Pick Encoder"),
+        });
+
+        {
+            let mut render_pass = pick_encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+                label: Some("Mark Pick Pass"),
+                color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                    view: texture_view,
+                    resolve_target: None,
+                    ops: wgpu::Operations {
+                        load: wgpu::LoadOp::Load,
+                        store: wgpu::StoreOp::Store,
+                    },
+                })],
+                depth_stencil_attachment: None,
+                occlusion_query_set: None,
+                timestamp_writes: None,
+            });
+
+            render_pass.set_pipeline(&pick.render_pipeline);
             render_pass.set_bind_group(0, &self.uniform_bind_group, &[]);
+            render_pass.set_bind_group(1, &pick.bind_group, &[]);
             render_pass.set_vertex_buffer(0, self.vertex_buffer.slice(..));
             render_pass.set_vertex_buffer(1, self.instance_buffer.slice(..));
             render_pass.set_index_buffer(self.index_buffer.slice(..), wgpu::IndexFormat::Uint16);
             render_pass.draw_indexed(0..self.num_indices, 0, 0..self.num_instances);
         }
 
-        mark_encoder.finish()
+        Some(pick_encoder.finish())
+    }
+}
+
+fn make_pick_pipeline<I, V>(
+    device: &Device,
+    pipelines: &PipelineCache,
+    mark_shader: &dyn MarkShader<Instance = I, Vertex = V>,
+    front_face: wgpu::FrontFace,
+    id_offset: u32,
+) -> PickPipeline
+where
+    I: bytemuck::Pod + bytemuck::Zeroable,
+    V: bytemuck::Pod + bytemuck::Zeroable,
+{
+    let pick_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+        label: Some("Pick Uniform Buffer"),
+        contents: bytemuck::cast_slice(&[PickUniform {
+            id_offset,
+            _pad: [0; 3],
+        }]),
+        usage: wgpu::BufferUsages::UNIFORM,
+    });
+
+    let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+        layout: pipelines.pick_layout(),
+        entries: &[wgpu::BindGroupEntry {
+            binding: 0,
+            resource: pick_buffer.as_entire_binding(),
+        }],
+        label: Some("pick_bind_group"),
+    });
+
+    PickPipeline {
+        render_pipeline: pipelines.pick_pipeline(device, mark_shader, front_face),
+        bind_group,
     }
 }
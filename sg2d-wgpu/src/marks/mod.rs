@@ -1,5 +1,7 @@
 pub mod mark;
+pub mod pipeline;
 pub mod rect;
 pub mod rule;
 pub mod symbol;
 pub mod text;
+pub mod text_block;
@@ -0,0 +1,259 @@
+//! Cache of the shader modules and render pipelines that mark renderers draw with.
+//!
+//! Marks of the same kind share a shader, so a scene usually needs only a handful of pipelines.
+//! Caching them lets canvases that share a `RenderContext` skip shader compilation and pipeline
+//! creation for every mark after the first of its kind.
+use crate::marks::mark::MarkShader;
+use crate::pick::PICK_TEXTURE_FORMAT;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use wgpu::{BindGroupLayout, Device, FrontFace, RenderPipeline, ShaderModule, TextureFormat};
+
+/// Everything a mark pipeline depends on besides its shader's vertex layouts, which are fixed
+/// by the shader source
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+struct PipelineKey {
+    shader: String,
+    vertex_entry_point: String,
+    fragment_entry_point: String,
+    texture_format: TextureFormat,
+    sample_count: u32,
+    front_face: FrontFace,
+}
+
+pub struct PipelineCache {
+    uniform_layout: BindGroupLayout,
+    pick_layout: BindGroupLayout,
+    modules: Mutex<HashMap<String, Arc<ShaderModule>>>,
+    pipelines: Mutex<HashMap<PipelineKey, Arc<RenderPipeline>>>,
+}
+
+impl PipelineCache {
+    pub fn new(device: &Device) -> Self {
+        let uniform_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            entries: &[wgpu::BindGroupLayoutEntry {
+                binding: 0,
+                visibility: wgpu::ShaderStages::VERTEX | wgpu::ShaderStages::FRAGMENT,
+                ty: wgpu::BindingType::Buffer {
+                    ty: wgpu::BufferBindingType::Uniform,
+                    has_dynamic_offset: false,
+                    min_binding_size: None,
+                },
+                count: None,
+            }],
+            label: Some("chart_uniform_layout"),
+        });
+        let pick_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            entries: &[wgpu::BindGroupLayoutEntry {
+                binding: 0,
+                visibility: wgpu::ShaderStages::FRAGMENT,
+                ty: wgpu::BindingType::Buffer {
+                    ty: wgpu::BufferBindingType::Uniform,
+                    has_dynamic_offset: false,
+                    min_binding_size: None,
+                },
+                count: None,
+            }],
+            label: Some("pick_uniform_layout"),
+        });
+        Self {
+            uniform_layout,
+            pick_layout,
+            modules: Default::default(),
+            pipelines: Default::default(),
+        }
+    }
+
+    /// Layout of the bind group holding the `CanvasUniform` of a mark
+    pub fn uniform_layout(&self) -> &BindGroupLayout {
+        &self.uniform_layout
+    }
+
+    /// Layout of the bind group holding the pick id of a mark's first instance
+    pub fn pick_layout(&self) -> &BindGroupLayout {
+        &self.pick_layout
+    }
+
+    /// Number of pipelines that have been created
+    pub fn pipeline_count(&self) -> usize {
+        self.pipelines.lock().unwrap().len()
+    }
+
+    /// Pipeline that draws a mark shader into a texture with the given format and sample count
+    pub fn render_pipeline<I, V>(
+        &self,
+        device: &Device,
+        mark_shader: &dyn MarkShader<Instance = I, Vertex = V>,
+        texture_format: TextureFormat,
+        sample_count: u32,
+        front_face: FrontFace,
+    ) -> Arc<RenderPipeline>
+    where
+        I: bytemuck::Pod + bytemuck::Zeroable,
+        V: bytemuck::Pod + bytemuck::Zeroable,
+    {
+        let key = PipelineKey {
+            shader: mark_shader.shader().to_string(),
+            vertex_entry_point: mark_shader.vertex_entry_point().to_string(),
+            fragment_entry_point: mark_shader.fragment_entry_point().to_string(),
+            texture_format,
+            sample_count,
+            front_face,
+        };
+        self.get_or_create(key, || {
+            let shader = self.shader_module(device, mark_shader.shader());
+            let layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+                label: Some("Render Pipeline Layout"),
+                bind_group_layouts: &[&self.uniform_layout],
+                push_constant_ranges: &[],
+            });
+            device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+                label: Some("Render Pipeline"),
+                layout: Some(&layout),
+                vertex: wgpu::VertexState {
+                    module: &shader,
+                    entry_point: mark_shader.vertex_entry_point(),
+                    buffers: &[mark_shader.vertex_desc(), mark_shader.instance_desc()],
+                },
+                fragment: Some(wgpu::FragmentState {
+                    module: &shader,
+                    entry_point: mark_shader.fragment_entry_point(),
+                    targets: &[Some(wgpu::ColorTargetState {
+                        format: texture_format,
+                        blend: Some(wgpu::BlendState::ALPHA_BLENDING),
+                        write_mask: wgpu::ColorWrites::ALL,
+                    })],
+                }),
+                primitive: primitive_state(front_face),
+                depth_stencil: None,
+                multisample: wgpu::MultisampleState {
+                    count: sample_count,
+                    mask: !0,
+                    alpha_to_coverage_enabled: false,
+                },
+                multiview: None,
+            })
+        })
+    }
+
+    /// Pipeline that draws the pick ids of a mark shader into an `R32Uint` target
+    pub fn pick_pipeline<I, V>(
+        &self,
+        device: &Device,
+        mark_shader: &dyn MarkShader<Instance = I, Vertex = V>,
+        front_face: FrontFace,
+    ) -> Arc<RenderPipeline>
+    where
+        I: bytemuck::Pod + bytemuck::Zeroable,
+        V: bytemuck::Pod + bytemuck::Zeroable,
+    {
+        let key = PipelineKey {
+            shader: mark_shader.shader().to_string(),
+            vertex_entry_point: mark_shader.vertex_entry_point().to_string(),
+            fragment_entry_point: mark_shader.pick_entry_point().to_string(),
+            texture_format: PICK_TEXTURE_FORMAT,
+            sample_count: 1,
+            front_face,
+        };
+        self.get_or_create(key, || {
+            let shader = self.shader_module(device, mark_shader.shader());
+            let layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+                label: Some("Pick Pipeline Layout"),
+                bind_group_layouts: &[&self.uniform_layout, &self.pick_layout],
+                push_constant_ranges: &[],
+            });
+
+            // Ids can't be blended or multisampled, so each pixel takes the id of the last
+            // instance drawn over its center
+            device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+                label: Some("Pick Pipeline"),
+                layout: Some(&layout),
+                vertex: wgpu::VertexState {
+                    module: &shader,
+                    entry_point: mark_shader.vertex_entry_point(),
+                    buffers: &[mark_shader.vertex_desc(), mark_shader.instance_desc()],
+                },
+                fragment: Some(wgpu::FragmentState {
+                    module: &shader,
+                    entry_point: mark_shader.pick_entry_point(),
+                    targets: &[Some(wgpu::ColorTargetState {
+                        format: PICK_TEXTURE_FORMAT,
+                        blend: None,
+                        write_mask: wgpu::ColorWrites::ALL,
+                    })],
+                }),
+                primitive: primitive_state(front_face),
+                depth_stencil: None,
+                multisample: wgpu::MultisampleState::default(),
+                multiview: None,
+            })
+        })
+    }
+
+    /// Pipeline for a shader that isn't drawn as a `MarkShader`, such as the text halo shaders.
+    /// `create` builds it from the shader module the first time the shader is requested for a
+    /// texture format and sample count. The shader's entry points must be `vs_main` and
+    /// `fs_main`.
+    pub(crate) fn shader_pipeline(
+        &self,
+        device: &Device,
+        shader: &str,
+        texture_format: TextureFormat,
+        sample_count: u32,
+        create: impl FnOnce(&ShaderModule) -> RenderPipeline,
+    ) -> Arc<RenderPipeline> {
+        let key = PipelineKey {
+            shader: shader.to_string(),
+            vertex_entry_point: "vs_main".to_string(),
+            fragment_entry_point: "fs_main".to_string(),
+            texture_format,
+            sample_count,
+            front_face: FrontFace::Ccw,
+        };
+        self.get_or_create(key, || create(&self.shader_module(device, shader)))
+    }
+
+    fn get_or_create(
+        &self,
+        key: PipelineKey,
+        create: impl FnOnce() -> RenderPipeline,
+    ) -> Arc<RenderPipeline> {
+        if let Some(pipeline) = self.pipelines.lock().unwrap().get(&key) {
+            return pipeline.clone();
+        }
+        // Pipelines are created without holding the lock, so another thread may create the
+        // same pipeline in the meantime. The first one stored is kept.
+        let pipeline = Arc::new(create());
+        self.pipelines
+            .lock()
+            .unwrap()
+            .entry(key)
+            .or_insert(pipeline)
+            .clone()
+    }
+
+    fn shader_module(&self, device: &Device, source: &str) -> Arc<ShaderModule> {
+        let mut modules = self.modules.lock().unwrap();
+        modules
+            .entry(source.to_string())
+            .or_insert_with(|| {
+                Arc::new(device.create_shader_module(wgpu::ShaderModuleDescriptor {
+                    label: Some("Shader"),
+                    source: wgpu::ShaderSource::Wgsl(source.into()),
+                }))
+            })
+            .clone()
+    }
+}
+
+fn primitive_state(front_face: FrontFace) -> wgpu::PrimitiveState {
+    wgpu::PrimitiveState {
+        topology: wgpu::PrimitiveTopology::TriangleList,
+        strip_index_format: None,
+        front_face,
+        cull_mode: Some(wgpu::Face::Back),
+        polygon_mode: wgpu::PolygonMode::Fill,
+        unclipped_depth: false,
+        conservative: false,
+    }
+}
@@ -4,11 +4,57 @@ struct ChartUniform {
     size: vec2<f32>,
     scale: f32,
     _pad: f32, // for 16 byte alignment
+    // Transform from group coordinates to scene coordinates, with the columns of the linear
+    // part packed into a vector
+    linear: vec4<f32>,
+    translate: vec2<f32>,
+    // Size of the group's clip region, negative when marks aren't clipped
+    clip_size: vec2<f32>,
+    // Transform from scene coordinates to clip region coordinates, packed like the group
+    // transform
+    clip_linear: vec4<f32>,
+    clip_translate: vec2<f32>,
+    _pad2: vec2<f32>, // for 16 byte alignment
 };
 
 @group(0) @binding(0)
 var<uniform> chart_uniforms: ChartUniform;
 
+// Map a position in scene coordinates, with y pointing down, to clip coordinates
+fn scene_to_clip(position: vec2<f32>) -> vec4<f32> {
+    let normalized = 2.0 * position / chart_uniforms.size - 1.0;
+    return vec4<f32>(normalized[0], -normalized[1], 0.0, 1.0);
+}
+
+fn group_linear() -> mat2x2<f32> {
+    return mat2x2<f32>(chart_uniforms.linear.xy, chart_uniforms.linear.zw);
+}
+
+// Map a position in group coordinates to scene coordinates
+fn group_to_scene(position: vec2<f32>) -> vec2<f32> {
+    return group_linear() * position + chart_uniforms.translate;
+}
+
+// Whether a position in scene coordinates is outside the clip region of the group
+fn is_clipped(position: vec2<f32>) -> bool {
+    if (chart_uniforms.clip_size[0] < 0.0) {
+        return false;
+    }
+    let clip_linear = mat2x2<f32>(chart_uniforms.clip_linear.xy, chart_uniforms.clip_linear.zw);
+    let p = clip_linear * position + chart_uniforms.clip_translate;
+    return any(p < vec2<f32>(0.0)) || any(p > chart_uniforms.clip_size);
+}
+
+struct PickUniform {
+    id_offset: u32,
+    _pad0: u32, // for 16 byte alignment
+    _pad1: u32,
+    _pad2: u32,
+};
+
+@group(1) @binding(0)
+var<uniform> pick_uniforms: PickUniform;
+
 struct VertexInput {
     @location(0) position: vec2<f32>,
     @location(1) normal: vec2<f32>,
@@ -27,6 +73,8 @@ struct InstanceInput {
 struct VertexOutput {
     @builtin(position) clip_position: vec4<f32>,
     @location(0) color: vec4<f32>,
+    @location(6) scene_position: vec2<f32>,
+    @location(7) @interpolate(flat) instance: u32,
 };
 
 const PI = 3.14159265359;
@@ -35,24 +83,24 @@ const PI = 3.14159265359;
 fn vs_main(
     model: VertexInput,
     instance: InstanceInput,
+    @builtin(instance_index) instance_index: u32,
 ) -> VertexOutput {
     var out: VertexOutput;
+    out.instance = instance_index;
     let size_scale = sqrt(instance.size);
 
-    // Compute scenegraph x and y coordinates
+    // Compute offset of the vertex from the symbol position. Model y points up, so the
+    // offset is flipped into group coordinates
     let angle_rad = PI * instance.angle / 180.0;
     let rot = mat2x2(cos(angle_rad), -sin(angle_rad), sin(angle_rad), cos(angle_rad));
     let rotated_pos = rot * model.position;
-    let sg_x = rotated_pos[0] * size_scale + instance.position[0];
-    let sg_y = rotated_pos[1] * size_scale + (chart_uniforms.size[1] - instance.position[1]);
-    let pos = vec2(sg_x, sg_y);
+    let offset = vec2(rotated_pos[0], -rotated_pos[1]) * size_scale;
 
     if (model.kind == 0u) {
         // fill vertex
         out.color = instance.fill_color;
-
-        let normalized_pos = 2.0 * pos / chart_uniforms.size - 1.0;
-        out.clip_position = vec4<f32>(normalized_pos, 0.0, 1.0);
+        out.scene_position = group_to_scene(instance.position + offset);
+        out.clip_position = scene_to_clip(out.scene_position);
     } else {
         // stroke vertex
         out.color = instance.stroke_color;
@@ -67,10 +115,9 @@ fn vs_main(
         // of the "normal" vector will increase the line width by 2.
         let normal = rot * model.normal;
         var diff = scaled_stroke_width - instance.stroke_width;
-        let adjusted_pos = pos - diff * normal / 2.0;
-
-        let normalized_pos = 2.0 * adjusted_pos / chart_uniforms.size - 1.0;
-        out.clip_position = vec4<f32>(normalized_pos, 0.0, 1.0);
+        let adjusted_offset = offset - diff * vec2(normal[0], -normal[1]) / 2.0;
+        out.scene_position = group_to_scene(instance.position + adjusted_offset);
+        out.clip_position = scene_to_clip(out.scene_position);
     }
 
     return out;
@@ -80,5 +127,18 @@ fn vs_main(
 
 @fragment
 fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
+    if (is_clipped(in.scene_position)) {
+        discard;
+    }
     return in.color;
 }
+
+// Picking fragment shader, writes the id of the instance where the fill or stroke is visible
+
+@fragment
+fn fs_pick(in: VertexOutput) -> @location(0) u32 {
+    if (in.color[3] == 0.0 || is_clipped(in.scene_position)) {
+        discard;
+    }
+    return pick_uniforms.id_offset + in.instance;
+}
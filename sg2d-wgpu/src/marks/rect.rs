@@ -1,6 +1,6 @@
 use crate::marks::mark::MarkShader;
-use itertools::izip;
 use sg2d::marks::rect::RectMark;
+use sg2d::value::ColorSpace;
 use wgpu::VertexBufferLayout;
 
 #[repr(C)]
@@ -27,33 +27,30 @@ impl RectVertex {
 #[derive(Copy, Clone, Debug, bytemuck::Pod, bytemuck::Zeroable)]
 pub struct RectInstance {
     pub position: [f32; 2],
-    pub color: [f32; 3],
+    pub color: [f32; 4],
     pub width: f32,
     pub height: f32,
 }
 
 const INSTANCE_ATTRIBUTES: [wgpu::VertexAttribute; 4] = wgpu::vertex_attr_array![
     1 => Float32x2,     // position
-    2 => Float32x3,     // color
+    2 => Float32x4,     // color
     3 => Float32,       // width
     4 => Float32,       // height
 ];
 
 impl RectInstance {
-    pub fn iter_from_spec(mark: &RectMark) -> impl Iterator<Item = RectInstance> + '_ {
-        izip!(
-            mark.x_iter(),
-            mark.y_iter(),
-            mark.width_iter(),
-            mark.height_iter(),
-            mark.fill_iter(),
-        )
-        .map(|(x, y, width, height, fill)| RectInstance {
-            position: [*x, *y],
-            width: *width,
-            height: *height,
-            color: *fill,
-        })
+    /// Instances of a mark, with colors in the color space of the target texture
+    pub fn from_spec(mark: &RectMark, color_space: ColorSpace) -> Vec<RectInstance> {
+        let fill = mark.fill.map(|c| c.to_space(color_space));
+        (0..mark.len as usize)
+            .map(|i| RectInstance {
+                position: [*mark.x.item(i), *mark.y.item(i)],
+                width: *mark.width.item(i),
+                height: *mark.height.item(i),
+                color: *fill.item(i),
+            })
+            .collect()
     }
 }
 
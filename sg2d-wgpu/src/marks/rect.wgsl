@@ -4,37 +4,91 @@ struct ChartUniform {
     size: vec2<f32>,
     scale: f32,
     _pad: f32, // for 16 byte alignment
+    // Transform from group coordinates to scene coordinates, with the columns of the linear
+    // part packed into a vector
+    linear: vec4<f32>,
+    translate: vec2<f32>,
+    // Size of the group's clip region, negative when marks aren't clipped
+    clip_size: vec2<f32>,
+    // Transform from scene coordinates to clip region coordinates, packed like the group
+    // transform
+    clip_linear: vec4<f32>,
+    clip_translate: vec2<f32>,
+    _pad2: vec2<f32>, // for 16 byte alignment
 };
 
 @group(0) @binding(0)
 var<uniform> chart_uniforms: ChartUniform;
 
+// Map a position in scene coordinates, with y pointing down, to clip coordinates
+fn scene_to_clip(position: vec2<f32>) -> vec4<f32> {
+    let normalized = 2.0 * position / chart_uniforms.size - 1.0;
+    return vec4<f32>(normalized[0], -normalized[1], 0.0, 1.0);
+}
+
+fn group_linear() -> mat2x2<f32> {
+    return mat2x2<f32>(chart_uniforms.linear.xy, chart_uniforms.linear.zw);
+}
+
+// Map a position in group coordinates to scene coordinates
+fn group_to_scene(position: vec2<f32>) -> vec2<f32> {
+    return group_linear() * position + chart_uniforms.translate;
+}
+
+// Whether a position in scene coordinates is outside the clip region of the group
+fn is_clipped(position: vec2<f32>) -> bool {
+    if (chart_uniforms.clip_size[0] < 0.0) {
+        return false;
+    }
+    let clip_linear = mat2x2<f32>(chart_uniforms.clip_linear.xy, chart_uniforms.clip_linear.zw);
+    let p = clip_linear * position + chart_uniforms.clip_translate;
+    return any(p < vec2<f32>(0.0)) || any(p > chart_uniforms.clip_size);
+}
+
+struct PickUniform {
+    id_offset: u32,
+    _pad0: u32, // for 16 byte alignment
+    _pad1: u32,
+    _pad2: u32,
+};
+
+@group(1) @binding(0)
+var<uniform> pick_uniforms: PickUniform;
+
 struct VertexInput {
     @location(0) position: vec2<f32>,
 };
 
 struct InstanceInput {
     @location(1) position: vec2<f32>,
-    @location(2) color: vec3<f32>,
+    @location(2) color: vec4<f32>,
     @location(3) width: f32,
     @location(4) height: f32,
 };
 
 struct VertexOutput {
     @builtin(position) clip_position: vec4<f32>,
-    @location(0) color: vec3<f32>,
+    @location(0) color: vec4<f32>,
+    @location(6) scene_position: vec2<f32>,
+    @location(7) @interpolate(flat) instance: u32,
 };
 
 @vertex
 fn vs_main(
     model: VertexInput,
     instance: InstanceInput,
+    @builtin(instance_index) instance_index: u32,
 ) -> VertexOutput {
     var out: VertexOutput;
+    out.instance = instance_index;
     out.color = instance.color;
-    let x = 2.0 * (model.position[0] * instance.width + instance.position[0]) / chart_uniforms.size[0] - 1.0;
-    let y = 2.0 * (model.position[1] * instance.height + (chart_uniforms.size[1] - instance.position[1] - instance.height)) / chart_uniforms.size[1] - 1.0;
-    out.clip_position = vec4<f32>(x, y, 0.0, 1.0);
+    // Model y points up, so the bottom of the unit square is at y + height
+    let position = vec2<f32>(
+        instance.position[0] + model.position[0] * instance.width,
+        instance.position[1] + (1.0 - model.position[1]) * instance.height,
+    );
+    out.scene_position = group_to_scene(position);
+    out.clip_position = scene_to_clip(out.scene_position);
     return out;
 }
 
@@ -42,5 +96,18 @@ fn vs_main(
 
 @fragment
 fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
-    return vec4<f32>(in.color, 1.0);
+    if (is_clipped(in.scene_position)) {
+        discard;
+    }
+    return in.color;
+}
+
+// Picking fragment shader, writes the id of the instance where it's visible
+
+@fragment
+fn fs_pick(in: VertexOutput) -> @location(0) u32 {
+    if (in.color[3] == 0.0 || is_clipped(in.scene_position)) {
+        discard;
+    }
+    return pick_uniforms.id_offset + in.instance;
 }
@@ -1,6 +1,6 @@
 use crate::marks::mark::MarkShader;
-use itertools::izip;
 use sg2d::marks::rule::RuleMark;
+use sg2d::value::ColorSpace;
 use wgpu::VertexBufferLayout;
 
 #[repr(C)]
@@ -30,7 +30,7 @@ pub struct RuleInstance {
     pub y0: f32,
     pub x1: f32,
     pub y1: f32,
-    pub stroke: [f32; 3],
+    pub stroke: [f32; 4],
     pub stroke_width: f32,
 }
 
@@ -39,28 +39,24 @@ const INSTANCE_ATTRIBUTES: [wgpu::VertexAttribute; 6] = wgpu::vertex_attr_array!
     2 => Float32,     // y0
     3 => Float32,     // x1
     4 => Float32,     // y1
-    5 => Float32x3,   // stroke
+    5 => Float32x4,   // stroke
     6 => Float32,     // stroke_width
 ];
 
 impl RuleInstance {
-    pub fn iter_from_spec(mark: &RuleMark) -> impl Iterator<Item = RuleInstance> + '_ {
-        izip!(
-            mark.x0_iter(),
-            mark.y0_iter(),
-            mark.x1_iter(),
-            mark.y1_iter(),
-            mark.stroke_iter(),
-            mark.stroke_width_iter(),
-        )
-        .map(|(x0, y0, x1, y1, stroke, stroke_width)| RuleInstance {
-            x0: *x0,
-            y0: *y0,
-            x1: *x1,
-            y1: *y1,
-            stroke: *stroke,
-            stroke_width: *stroke_width,
-        })
+    /// Instances of a mark, with colors in the color space of the target texture
+    pub fn from_spec(mark: &RuleMark, color_space: ColorSpace) -> Vec<RuleInstance> {
+        let stroke = mark.stroke.map(|c| c.to_space(color_space));
+        (0..mark.len as usize)
+            .map(|i| RuleInstance {
+                x0: *mark.x0.item(i),
+                y0: *mark.y0.item(i),
+                x1: *mark.x1.item(i),
+                y1: *mark.y1.item(i),
+                stroke: *stroke.item(i),
+                stroke_width: *mark.stroke_width.item(i),
+            })
+            .collect()
     }
 }
 
@@ -4,11 +4,57 @@ struct ChartUniform {
     size: vec2<f32>,
     scale: f32,
     _pad: f32, // for 16 byte alignment
+    // Transform from group coordinates to scene coordinates, with the columns of the linear
+    // part packed into a vector
+    linear: vec4<f32>,
+    translate: vec2<f32>,
+    // Size of the group's clip region, negative when marks aren't clipped
+    clip_size: vec2<f32>,
+    // Transform from scene coordinates to clip region coordinates, packed like the group
+    // transform
+    clip_linear: vec4<f32>,
+    clip_translate: vec2<f32>,
+    _pad2: vec2<f32>, // for 16 byte alignment
 };
 
 @group(0) @binding(0)
 var<uniform> chart_uniforms: ChartUniform;
 
+// Map a position in scene coordinates, with y pointing down, to clip coordinates
+fn scene_to_clip(position: vec2<f32>) -> vec4<f32> {
+    let normalized = 2.0 * position / chart_uniforms.size - 1.0;
+    return vec4<f32>(normalized[0], -normalized[1], 0.0, 1.0);
+}
+
+fn group_linear() -> mat2x2<f32> {
+    return mat2x2<f32>(chart_uniforms.linear.xy, chart_uniforms.linear.zw);
+}
+
+// Map a position in group coordinates to scene coordinates
+fn group_to_scene(position: vec2<f32>) -> vec2<f32> {
+    return group_linear() * position + chart_uniforms.translate;
+}
+
+// Whether a position in scene coordinates is outside the clip region of the group
+fn is_clipped(position: vec2<f32>) -> bool {
+    if (chart_uniforms.clip_size[0] < 0.0) {
+        return false;
+    }
+    let clip_linear = mat2x2<f32>(chart_uniforms.clip_linear.xy, chart_uniforms.clip_linear.zw);
+    let p = clip_linear * position + chart_uniforms.clip_translate;
+    return any(p < vec2<f32>(0.0)) || any(p > chart_uniforms.clip_size);
+}
+
+struct PickUniform {
+    id_offset: u32,
+    _pad0: u32, // for 16 byte alignment
+    _pad1: u32,
+    _pad2: u32,
+};
+
+@group(1) @binding(0)
+var<uniform> pick_uniforms: PickUniform;
+
 struct VertexInput {
     @location(0) position: vec2<f32>,
 };
@@ -18,13 +64,15 @@ struct InstanceInput {
     @location(2) y0: f32,
     @location(3) x1: f32,
     @location(4) y1: f32,
-    @location(5) stroke: vec3<f32>,
+    @location(5) stroke: vec4<f32>,
     @location(6) stroke_width: f32,
 };
 
 struct VertexOutput {
     @builtin(position) clip_position: vec4<f32>,
-    @location(0) color: vec3<f32>,
+    @location(0) color: vec4<f32>,
+    @location(6) scene_position: vec2<f32>,
+    @location(7) @interpolate(flat) instance: u32,
 };
 
 const PI = 3.14159265359;
@@ -33,8 +81,10 @@ const PI = 3.14159265359;
 fn vs_main(
     model: VertexInput,
     instance: InstanceInput,
+    @builtin(instance_index) instance_index: u32,
 ) -> VertexOutput {
     var out: VertexOutput;
+    out.instance = instance_index;
     out.color = instance.stroke;
 
     let p0 = vec2(instance.x0, instance.y0);
@@ -48,10 +98,9 @@ fn vs_main(
     let rot = mat2x2(cos(angle), -sin(angle), sin(angle), cos(angle));
 
     let rot_pos = rot * vec2(model.position[0] * width, model.position[1] * len);
-    let x = 2.0 * (rot_pos[0] + mid[0]) / chart_uniforms.size[0] - 1.0;
-    let y = 2.0 * (rot_pos[1] + (chart_uniforms.size[1] - mid[1])) / chart_uniforms.size[1] - 1.0;
-
-    out.clip_position = vec4<f32>(x, y, 0.0, 1.0);
+    // The rotated offset has y pointing up, so flip it into group coordinates
+    out.scene_position = group_to_scene(mid + vec2(rot_pos[0], -rot_pos[1]));
+    out.clip_position = scene_to_clip(out.scene_position);
     return out;
 }
 
@@ -59,5 +108,18 @@ fn vs_main(
 
 @fragment
 fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
-    return vec4<f32>(in.color, 1.0);
+    if (is_clipped(in.scene_position)) {
+        discard;
+    }
+    return in.color;
+}
+
+// Picking fragment shader, writes the id of the instance where it's visible
+
+@fragment
+fn fs_pick(in: VertexOutput) -> @location(0) u32 {
+    if (in.color[3] == 0.0 || is_clipped(in.scene_position)) {
+        discard;
+    }
+    return pick_uniforms.id_offset + in.instance;
 }
@@ -1,12 +1,12 @@
 use crate::error::Sg2dWgpuError;
 use crate::marks::mark::MarkShader;
-use itertools::izip;
 use lyon::lyon_tessellation::{
     BuffersBuilder, FillVertex, FillVertexConstructor, StrokeVertex, StrokeVertexConstructor,
 };
 use lyon::tessellation::geometry_builder::VertexBuffers;
 use lyon::tessellation::{FillOptions, FillTessellator, StrokeOptions, StrokeTessellator};
 use sg2d::marks::symbol::{SymbolMark, SymbolShape};
+use sg2d::value::ColorSpace;
 use wgpu::VertexBufferLayout;
 
 const FILL_KIND: u32 = 0;
@@ -59,24 +59,22 @@ const INSTANCE_ATTRIBUTES: [wgpu::VertexAttribute; 6] = wgpu::vertex_attr_array!
 ];
 
 impl SymbolInstance {
-    pub fn iter_from_spec(mark: &SymbolMark) -> impl Iterator<Item = SymbolInstance> + '_ {
+    /// Instances of a mark, with colors in the color space of the target texture
+    pub fn from_spec(mark: &SymbolMark, color_space: ColorSpace) -> Vec<SymbolInstance> {
         let stroke_width = mark.stroke_width.unwrap_or(0.0);
-        izip!(
-            mark.x_iter(),
-            mark.y_iter(),
-            mark.fill_iter(),
-            mark.size_iter(),
-            mark.stroke_iter(),
-            mark.angle_iter(),
-        )
-        .map(move |(x, y, fill, size, stroke, angle)| SymbolInstance {
-            position: [*x, *y],
-            fill_color: *fill,
-            stroke_color: *stroke,
-            stroke_width,
-            size: *size,
-            angle: *angle,
-        })
+        // Scalar colors are converted once
+        let fill = mark.fill.map(|c| c.to_space(color_space));
+        let stroke = mark.stroke.map(|c| c.to_space(color_space));
+        (0..mark.len as usize)
+            .map(|i| SymbolInstance {
+                position: [*mark.x.item(i), *mark.y.item(i)],
+                fill_color: *fill.item(i),
+                stroke_color: *stroke.item(i),
+                stroke_width,
+                size: *mark.size.item(i),
+                angle: *mark.angle.item(i),
+            })
+            .collect()
     }
 }
 
@@ -1,26 +1,56 @@
 use crate::canvas::CanvasUniform;
+use crate::color::{text_color_mode, texture_color_space};
+use crate::error::Sg2dWgpuError;
+use crate::marks::pipeline::PipelineCache;
 use glyphon::{
-    Attrs, Buffer, Color, Family, FontSystem, Metrics, Resolution, Shaping, SwashCache, TextArea,
-    TextAtlas, TextBounds, TextRenderer, Weight,
+    Buffer, FontSystem, Resolution, SwashCache, TextArea, TextAtlas, TextBounds, TextRenderer,
 };
-use itertools::izip;
+use lyon::geom::euclid::Vector2D;
+use lyon::lyon_tessellation::{
+    BuffersBuilder, LineJoin, StrokeOptions, StrokeTessellator, StrokeVertex, VertexBuffers,
+};
+use sg2d::bounds::Bounds;
 use sg2d::marks::text::{
-    FontStyleSpec, FontWeightNameSpec, FontWeightSpec, TextAlignSpec, TextBaselineSpec, TextMark,
+    FontStyleSpec, FontWeightSpec, TextAlignSpec, TextBaselineSpec, TextDirectionSpec, TextMark,
 };
+use sg2d::transform::AffineTransform;
+use sg2d::value::{Color, ColorSpace};
+use sg2d_text::font::{shape_text, with_font_system, FontSpec, TextLayoutSpec};
+use sg2d_text::metrics::TextMetrics;
+use sg2d_text::outline::buffer_outline;
+use std::sync::Arc;
+use wgpu::util::DeviceExt;
 use wgpu::{
-    CommandBuffer, CommandEncoderDescriptor, Device, MultisampleState, Operations, Queue,
-    RenderPassColorAttachment, RenderPassDescriptor, TextureFormat, TextureView,
+    CommandBuffer, CommandEncoder, CommandEncoderDescriptor, Device, MultisampleState, Operations,
+    Queue, RenderPass, RenderPassColorAttachment, RenderPassDescriptor, TextureFormat, TextureView,
+    VertexBufferLayout,
 };
 
+/// How a canvas draws text marks
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum TextRendering {
+    /// Rasterize glyphs into the canvas glyph atlas. Text in groups whose transform rotates,
+    /// skews or reflects it is drawn as paths.
+    #[default]
+    Glyphs,
+    /// Tessellate glyph outlines like symbol paths. This keeps large text crisp at any
+    /// scale and is the only mode that honors the text angle.
+    Paths,
+}
+
 #[derive(Clone, Debug)]
 pub struct TextInstance {
     pub text: String,
     pub position: [f32; 2],
-    pub color: [f32; 3],
+    pub color: Color,
     pub opacity: f32,
+    pub stroke: Color,
+    pub stroke_width: f32,
+    pub stroke_opacity: f32,
     pub align: TextAlignSpec,
     pub angle: f32,
     pub baseline: TextBaselineSpec,
+    pub dir: TextDirectionSpec,
     pub dx: f32,
     pub dy: f32,
     pub font: String,
@@ -31,213 +61,428 @@ pub struct TextInstance {
 }
 
 impl TextInstance {
-    pub fn iter_from_spec(mark: &TextMark) -> impl Iterator<Item = TextInstance> + '_ {
-        izip!(
-            mark.text_iter(),
-            mark.x_iter(),
-            mark.y_iter(),
-            mark.color_iter(),
-            mark.opacity_iter(),
-            mark.align_iter(),
-            mark.angle_iter(),
-            mark.baseline_iter(),
-            mark.dx_iter(),
-            mark.dy_iter(),
-            mark.font_iter(),
-            mark.font_size_iter(),
-            mark.font_weight_iter(),
-            mark.font_style_iter(),
-            mark.limit_iter(),
-        )
-        .map(
-            |(
-                text,
-                x,
-                y,
-                color,
-                opacity,
-                align,
-                angle,
-                baseline,
-                dx,
-                dy,
-                font,
-                font_size,
-                font_weight,
-                font_style,
-                limit,
-            )| {
-                TextInstance {
-                    text: text.clone(),
-                    position: [*x, *y],
-                    color: *color,
-                    opacity: *opacity,
-                    align: *align,
-                    angle: *angle,
-                    baseline: *baseline,
-                    dx: *dx,
-                    dy: *dy,
-                    font: font.clone(),
-                    font_size: *font_size,
-                    font_weight: *font_weight,
-                    font_style: *font_style,
-                    limit: *limit,
-                }
-            },
-        )
+    pub fn from_spec(mark: &TextMark) -> Vec<TextInstance> {
+        (0..mark.len as usize)
+            .map(|i| TextInstance {
+                text: mark.text.item(i).clone(),
+                position: [*mark.x.item(i), *mark.y.item(i)],
+                color: *mark.color.item(i),
+                opacity: *mark.opacity.item(i),
+                stroke: *mark.stroke.item(i),
+                stroke_width: *mark.stroke_width.item(i),
+                stroke_opacity: *mark.stroke_opacity.item(i),
+                align: *mark.align.item(i),
+                angle: *mark.angle.item(i),
+                baseline: *mark.baseline.item(i),
+                dir: *mark.dir.item(i),
+                dx: *mark.dx.item(i),
+                dy: *mark.dy.item(i),
+                font: mark.font.item(i).clone(),
+                font_size: *mark.font_size.item(i),
+                font_weight: *mark.font_weight.item(i),
+                font_style: *mark.font_style.item(i),
+                limit: *mark.limit.item(i),
+            })
+            .collect()
+    }
+
+    /// Fill color, with the opacity of the text applied to its alpha
+    pub fn fill_color(&self) -> Color {
+        self.color.with_alpha(self.color.a * self.opacity)
+    }
+
+    /// Stroke color, with the stroke opacity and the opacity of the text applied to its alpha
+    pub fn stroke_color(&self) -> Color {
+        self.stroke
+            .with_alpha(self.stroke.a * self.stroke_opacity * self.opacity)
+    }
+
+    /// Apply a group transform to the instance. Glyphs are rasterized upright, so the transform
+    /// moves the anchor and scales the text uniformly, but doesn't rotate or skew glyphs.
+    /// Canvases draw text in groups with such transforms as paths instead.
+    pub fn transformed(mut self, transform: &AffineTransform) -> Self {
+        if transform.is_identity() {
+            return self;
+        }
+        let scale = transform.scale_factor();
+        self.position = transform.apply(self.position);
+        self.angle += transform.rotation();
+        self.dx *= scale;
+        self.dy *= scale;
+        self.font_size *= scale;
+        self.stroke_width *= scale;
+        self.limit *= scale;
+        self
+    }
+
+    pub fn font_spec(&self) -> FontSpec {
+        FontSpec {
+            font: self.font.clone(),
+            font_size: self.font_size,
+            font_weight: self.font_weight,
+            font_style: self.font_style,
+        }
+    }
+
+    pub fn layout_spec(&self) -> TextLayoutSpec {
+        TextLayoutSpec {
+            align: self.align,
+            dir: self.dir,
+            limit: self.limit,
+        }
     }
 }
 
-pub struct TextMarkRenderer {
-    pub font_system: FontSystem,
-    pub cache: SwashCache,
-    pub atlas: TextAtlas,
-    pub text_renderer: TextRenderer,
-    pub instances: Vec<TextInstance>,
-    pub uniform: CanvasUniform,
+#[repr(C)]
+#[derive(Copy, Clone, Debug, bytemuck::Pod, bytemuck::Zeroable)]
+pub struct TextHaloVertex {
+    pub position: [f32; 2],
+    pub color: [f32; 4],
 }
 
-impl TextMarkRenderer {
+const HALO_VERTEX_ATTRIBUTES: [wgpu::VertexAttribute; 2] = wgpu::vertex_attr_array![
+    0 => Float32x2,     // position
+    1 => Float32x4,     // color
+];
+
+impl TextHaloVertex {
+    pub fn desc() -> VertexBufferLayout<'static> {
+        VertexBufferLayout {
+            array_stride: std::mem::size_of::<TextHaloVertex>() as wgpu::BufferAddress,
+            step_mode: wgpu::VertexStepMode::Vertex,
+            attributes: &HALO_VERTEX_ATTRIBUTES,
+        }
+    }
+}
+
+/// GPU buffers holding the tessellated outline strokes of all stroked text instances
+struct TextHaloGeometry {
+    vertex_buffer: wgpu::Buffer,
+    index_buffer: wgpu::Buffer,
+    num_indices: u32,
+    /// Made when the mark is prepared
+    layer: Option<TextHaloLayer>,
+}
+
+/// Texture that the halo strokes of a mark are drawn into, and the bind group that composites it
+struct TextHaloLayer {
+    view: wgpu::TextureView,
+    multisampled_view: Option<wgpu::TextureView>,
+    bind_group: wgpu::BindGroup,
+    size: wgpu::Extent3d,
+    /// Uniform of the mark in scene coordinates, whose size and clip region apply to the halo
+    /// strokes
+    uniform_buffer: wgpu::Buffer,
+    uniform_bind_group: wgpu::BindGroup,
+}
+
+/// Text rendering resources shared by every text mark in a canvas.
+///
+/// All text marks rasterize glyphs into a single atlas and draw halos with a single
+/// pipeline, so the cost of these resources doesn't grow with the number of marks.
+pub struct TextContext {
+    cache: SwashCache,
+    atlas: TextAtlas,
+    texture_format: TextureFormat,
+    multisample: MultisampleState,
+    halo_pipeline: Arc<wgpu::RenderPipeline>,
+    halo_layer_pipeline: Arc<wgpu::RenderPipeline>,
+    halo_layer_sampler: wgpu::Sampler,
+}
+
+impl TextContext {
     pub fn new(
         device: &Device,
         queue: &Queue,
-        uniform: CanvasUniform,
+        pipelines: &PipelineCache,
         texture_format: TextureFormat,
         sample_count: u32,
-        instances: Vec<TextInstance>,
     ) -> Self {
-        let font_system = FontSystem::new();
-        let cache = SwashCache::new();
-        let mut atlas = TextAtlas::new(device, queue, texture_format);
-        let text_renderer = TextRenderer::new(
-            &mut atlas,
-            device,
-            MultisampleState {
+        Self {
+            cache: SwashCache::new(),
+            atlas: TextAtlas::with_color_mode(
+                device,
+                queue,
+                texture_format,
+                text_color_mode(texture_format),
+            ),
+            texture_format,
+            multisample: MultisampleState {
                 count: sample_count,
                 mask: !0,
                 alpha_to_coverage_enabled: false,
             },
-            None,
-        );
+            halo_pipeline: make_halo_pipeline(device, pipelines, texture_format, sample_count),
+            halo_layer_pipeline: make_halo_layer_pipeline(
+                device,
+                pipelines,
+                texture_format,
+                sample_count,
+            ),
+            halo_layer_sampler: device.create_sampler(&wgpu::SamplerDescriptor {
+                label: Some("Text Halo Layer Sampler"),
+                mag_filter: wgpu::FilterMode::Linear,
+                min_filter: wgpu::FilterMode::Linear,
+                ..Default::default()
+            }),
+        }
+    }
 
-        Self {
-            font_system,
-            cache,
-            atlas,
-            text_renderer,
-            uniform,
-            instances,
+    /// Prepare text marks that haven't been prepared since they were created.
+    ///
+    /// Marks that are already prepared keep their glyphs in the atlas, so the atlas is only
+    /// trimmed, and every mark re-prepared, when at least one mark has new text.
+    pub fn prepare(
+        &mut self,
+        device: &Device,
+        queue: &Queue,
+        marks: &mut [&mut TextMarkRenderer],
+    ) -> Result<(), Sg2dWgpuError> {
+        if marks.iter().all(|mark| mark.prepared) {
+            return Ok(());
         }
+        self.atlas.trim();
+        for mark in marks.iter_mut() {
+            mark.prepare(device, queue, self)?;
+        }
+        Ok(())
     }
 
-    pub fn render(
+    /// Make a layer the size of the target for the halo strokes of a mark.
+    ///
+    /// The strokes of a glyph outline overlap where they turn and where they meet, so blending
+    /// them straight onto the target would darken those spots. Drawn into a layer without
+    /// blending, every pixel of a halo is covered once, and the layer is then composited like
+    /// a single translucent shape.
+    fn make_halo_layer(&self, device: &Device, uniform: &CanvasUniform) -> TextHaloLayer {
+        let size = halo_layer_size(uniform);
+        let layer_desc = wgpu::TextureDescriptor {
+            label: Some("Text Halo Layer"),
+            size,
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format: self.texture_format,
+            usage: wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::TEXTURE_BINDING,
+            view_formats: &[],
+        };
+        let view = device
+            .create_texture(&layer_desc)
+            .create_view(&Default::default());
+        let multisampled_view = (self.multisample.count > 1).then(|| {
+            device
+                .create_texture(&wgpu::TextureDescriptor {
+                    label: Some("Multisampled Text Halo Layer"),
+                    sample_count: self.multisample.count,
+                    usage: wgpu::TextureUsages::RENDER_ATTACHMENT,
+                    ..layer_desc
+                })
+                .create_view(&Default::default())
+        });
+        let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            layout: &self.halo_layer_pipeline.get_bind_group_layout(0),
+            entries: &[
+                wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: wgpu::BindingResource::TextureView(&view),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 1,
+                    resource: wgpu::BindingResource::Sampler(&self.halo_layer_sampler),
+                },
+            ],
+            label: Some("text_halo_layer_bind_group"),
+        });
+
+        let uniform_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("Text Halo Uniform Buffer"),
+            contents: bytemuck::cast_slice(&[*uniform]),
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+        });
+        let uniform_bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            layout: &self.halo_pipeline.get_bind_group_layout(0),
+            entries: &[wgpu::BindGroupEntry {
+                binding: 0,
+                resource: uniform_buffer.as_entire_binding(),
+            }],
+            label: Some("text_halo_uniform_bind_group"),
+        });
+        TextHaloLayer {
+            view,
+            multisampled_view,
+            bind_group,
+            size,
+            uniform_buffer,
+            uniform_bind_group,
+        }
+    }
+}
+
+/// Size in pixels of the halo layers of marks drawn with a uniform
+fn halo_layer_size(uniform: &CanvasUniform) -> wgpu::Extent3d {
+    wgpu::Extent3d {
+        width: ((uniform.size[0] * uniform.scale) as u32).max(1),
+        height: ((uniform.size[1] * uniform.scale) as u32).max(1),
+        depth_or_array_layers: 1,
+    }
+}
+
+pub struct TextMarkRenderer {
+    pub instances: Vec<TextInstance>,
+    pub uniform: CanvasUniform,
+    /// Top left corner and size of the part of the scene that the canvas shows
+    origin: [f32; 2],
+    size: [f32; 2],
+    buffers: Vec<Buffer>,
+    origins: Vec<(f32, f32)>,
+    /// Boxed, since most text marks have no halo
+    halo_geometry: Option<Box<TextHaloGeometry>>,
+    text_renderer: Option<TextRenderer>,
+    prepared: bool,
+}
+
+impl TextMarkRenderer {
+    /// Shape and lay out text instances, and tessellate their halos.
+    ///
+    /// Glyphs are rasterized later, when the canvas prepares its text marks before rendering.
+    pub fn new(
+        device: &Device,
+        uniform: CanvasUniform,
+        texture_format: TextureFormat,
+        instances: Vec<TextInstance>,
+    ) -> Self {
+        with_font_system(|font_system| {
+            let buffers = instances
+                .iter()
+                .map(|instance| {
+                    shape_text(
+                        font_system,
+                        &instance.text,
+                        &instance.font_spec(),
+                        &instance.layout_spec(),
+                    )
+                })
+                .collect::<Vec<_>>();
+
+            // Compute the top-left corner of each text block
+            let origins = buffers
+                .iter()
+                .zip(&instances)
+                .map(|(buffer, instance)| {
+                    let [offset_x, offset_y] = TextMetrics::from_buffer(buffer)
+                        .block_offset(instance.align, instance.baseline);
+                    (
+                        instance.position[0] + instance.dx + offset_x,
+                        instance.position[1] + instance.dy + offset_y,
+                    )
+                })
+                .collect::<Vec<_>>();
+
+            let halo_geometry = make_halo_geometry(
+                device,
+                font_system,
+                &buffers,
+                &instances,
+                &origins,
+                texture_color_space(texture_format),
+            );
+
+            Self {
+                instances,
+                uniform,
+                origin: [0.0, 0.0],
+                size: uniform.size,
+                buffers,
+                origins,
+                halo_geometry,
+                text_renderer: None,
+                prepared: false,
+            }
+        })
+    }
+
+    /// Move the mark onto a canvas of the given size that shows the part of the scene whose top
+    /// left corner is at `origin`. Glyphs are placed again the next time the mark is prepared.
+    pub fn set_viewport(&mut self, origin: [f32; 2], size: [f32; 2]) {
+        self.origin = origin;
+        self.size = size;
+        self.prepared = false;
+    }
+
+    fn prepare(
         &mut self,
         device: &Device,
         queue: &Queue,
-        texture_view: &TextureView,
-        resolve_target: Option<&TextureView>,
-    ) -> CommandBuffer {
-        // Collect buffer into a vector first so that they live as long as the text areas
-        // that reference them below
-        let buffers = self
-            .instances
-            .iter()
-            .map(|instance| {
-                let mut buffer = Buffer::new(
-                    &mut self.font_system,
-                    Metrics::new(instance.font_size, instance.font_size),
-                );
-                let family = match instance.font.to_lowercase().as_str() {
-                    "serif" => Family::Serif,
-                    "sans serif" => Family::SansSerif,
-                    "cursive" => Family::Cursive,
-                    "fantasy" => Family::Fantasy,
-                    "monospace" => Family::Monospace,
-                    _ => Family::Name(instance.font.as_str()),
-                };
-                let weight = match instance.font_weight {
-                    FontWeightSpec::Name(FontWeightNameSpec::Bold) => Weight::BOLD,
-                    FontWeightSpec::Name(FontWeightNameSpec::Normal) => Weight::NORMAL,
-                    FontWeightSpec::Number(w) => Weight(w as u16),
-                };
-
-                buffer.set_text(
-                    &mut self.font_system,
-                    &instance.text,
-                    Attrs::new().family(family).weight(weight),
-                    Shaping::Advanced,
-                );
-                buffer.set_size(
-                    &mut self.font_system,
-                    self.uniform.size[0],
-                    self.uniform.size[1],
-                );
-                buffer.shape_until_scroll(&mut self.font_system);
-
-                buffer
-            })
-            .collect::<Vec<_>>();
+        context: &mut TextContext,
+    ) -> Result<(), Sg2dWgpuError> {
+        let uniform = self.uniform.viewport(self.origin, self.size);
+        let [origin_x, origin_y] = self.origin;
+        let text_renderer = self.text_renderer.get_or_insert_with(|| {
+            TextRenderer::new(&mut context.atlas, device, context.multisample, None)
+        });
 
-        let areas = buffers
+        let areas = self
+            .buffers
             .iter()
             .zip(&self.instances)
-            .map(|(buffer, instance)| {
-                let (width, height) = measure(buffer);
-
-                let left = match instance.align {
-                    TextAlignSpec::Left => instance.position[0],
-                    TextAlignSpec::Center => instance.position[0] - width / 2.0,
-                    TextAlignSpec::Right => instance.position[0] - width,
-                };
-
-                let top = match instance.baseline {
-                    TextBaselineSpec::Alphabetic => instance.position[1] - height,
-                    // Add half pixel for top baseline for better match with resvg
-                    TextBaselineSpec::Top => instance.position[1] + 0.5,
-                    TextBaselineSpec::Middle => instance.position[1] - height * 0.5,
-                    TextBaselineSpec::Bottom => instance.position[1] - height,
-                    TextBaselineSpec::LineTop => todo!(),
-                    TextBaselineSpec::LineBottom => todo!(),
-                };
-
-                TextArea {
-                    buffer,
-                    left,
-                    top,
-                    scale: 1.0,
-                    bounds: TextBounds {
-                        left: 0,
-                        top: 0,
-                        right: self.uniform.size[0] as i32,
-                        bottom: self.uniform.size[1] as i32,
-                    },
-                    default_color: Color::rgb(
-                        (instance.color[0] * 255.0) as u8,
-                        (instance.color[1] * 255.0) as u8,
-                        (instance.color[2] * 255.0) as u8,
-                    ),
-                }
+            .zip(&self.origins)
+            .map(|((buffer, instance), (left, top))| TextArea {
+                buffer,
+                left: left - origin_x,
+                top: top - origin_y,
+                scale: 1.0,
+                bounds: text_bounds(&uniform),
+                // The atlas color mode converts sRGB glyph colors for the texture format
+                default_color: {
+                    let [r, g, b, a] = instance.fill_color().to_array().map(|c| (c * 255.0) as u8);
+                    glyphon::Color::rgba(r, g, b, a)
+                },
             })
             .collect::<Vec<_>>();
 
-        self.text_renderer
-            .prepare(
+        with_font_system(|font_system| {
+            text_renderer.prepare(
                 device,
                 queue,
-                &mut self.font_system,
-                &mut self.atlas,
+                font_system,
+                &mut context.atlas,
                 Resolution {
-                    width: self.uniform.size[0] as u32,
-                    height: self.uniform.size[1] as u32,
+                    width: uniform.size[0] as u32,
+                    height: uniform.size[1] as u32,
                 },
                 areas,
-                &mut self.cache,
+                &mut context.cache,
             )
-            .unwrap();
+        })?;
+        if let Some(halo) = &mut self.halo_geometry {
+            // Halo vertices are in scene coordinates, so the group transform is left out
+            let halo_uniform = self.uniform.in_scene().viewport(self.origin, self.size);
+            match &halo.layer {
+                Some(layer) if layer.size == halo_layer_size(&halo_uniform) => {
+                    queue.write_buffer(
+                        &layer.uniform_buffer,
+                        0,
+                        bytemuck::cast_slice(&[halo_uniform]),
+                    );
+                }
+                _ => halo.layer = Some(context.make_halo_layer(device, &halo_uniform)),
+            }
+        }
+        self.prepared = true;
+        Ok(())
+    }
 
+    pub fn render(
+        &self,
+        device: &Device,
+        context: &TextContext,
+        texture_view: &TextureView,
+        resolve_target: Option<&TextureView>,
+    ) -> Result<CommandBuffer, Sg2dWgpuError> {
         let mut encoder = device.create_command_encoder(&CommandEncoderDescriptor {
             label: Some("Text render"),
         });
+        self.draw_halo_layer(context, &mut encoder);
         {
             let mut pass = encoder.begin_render_pass(&RenderPassDescriptor {
                 label: None,
@@ -254,18 +499,229 @@ impl TextMarkRenderer {
                 occlusion_query_set: None,
             });
 
-            self.text_renderer.render(&self.atlas, &mut pass).unwrap();
+            self.draw(context, &mut pass)?;
+        }
+
+        Ok(encoder.finish())
+    }
+
+    /// Record the pass that draws the halo strokes into the mark's layer. It must be recorded
+    /// before the render pass that draws the mark, after the mark was prepared.
+    pub fn draw_halo_layer(&self, context: &TextContext, encoder: &mut CommandEncoder) {
+        let Some((halo, layer)) = self
+            .halo_geometry
+            .as_ref()
+            .and_then(|halo| Some((halo, halo.layer.as_ref()?)))
+        else {
+            return;
+        };
+        let (view, resolve_target) = match &layer.multisampled_view {
+            Some(multisampled_view) => (multisampled_view, Some(&layer.view)),
+            None => (&layer.view, None),
+        };
+        let mut pass = encoder.begin_render_pass(&RenderPassDescriptor {
+            label: Some("Text Halo Layer Pass"),
+            color_attachments: &[Some(RenderPassColorAttachment {
+                view,
+                resolve_target,
+                ops: Operations {
+                    load: wgpu::LoadOp::Clear(wgpu::Color::TRANSPARENT),
+                    store: wgpu::StoreOp::Store,
+                },
+            })],
+            depth_stencil_attachment: None,
+            timestamp_writes: None,
+            occlusion_query_set: None,
+        });
+        pass.set_pipeline(&context.halo_pipeline);
+        pass.set_bind_group(0, &layer.uniform_bind_group, &[]);
+        pass.set_vertex_buffer(0, halo.vertex_buffer.slice(..));
+        pass.set_index_buffer(halo.index_buffer.slice(..), wgpu::IndexFormat::Uint32);
+        pass.draw_indexed(0..halo.num_indices, 0, 0..1);
+    }
+
+    /// Record the draw commands of the halos and glyphs into a render pass. The mark must have
+    /// been prepared with the same text context, and its halo layer drawn.
+    pub fn draw<'a>(
+        &'a self,
+        context: &'a TextContext,
+        pass: &mut RenderPass<'a>,
+    ) -> Result<(), Sg2dWgpuError> {
+        if let Some(layer) = self
+            .halo_geometry
+            .as_ref()
+            .and_then(|halo| halo.layer.as_ref())
+        {
+            pass.set_pipeline(&context.halo_layer_pipeline);
+            pass.set_bind_group(0, &layer.bind_group, &[]);
+            pass.draw(0..3, 0..1);
         }
 
-        encoder.finish()
+        if let Some(text_renderer) = &self.text_renderer {
+            text_renderer.render(&context.atlas, pass)?;
+        }
+        Ok(())
+    }
+}
+
+/// Region that glyphs are drawn in: the scene, or the bounding box of the clip region.
+///
+/// Glyphs are clipped to whole logical pixels, and only to the bounding box of clip regions
+/// that are rotated by a group transform.
+fn text_bounds(uniform: &CanvasUniform) -> TextBounds {
+    let [width, height] = uniform.size;
+    let scene = Bounds::new(0.0, 0.0, width, height);
+    let clip = uniform.clip_bounds().unwrap_or(scene);
+    TextBounds {
+        left: clip.x0.max(0.0).round() as i32,
+        top: clip.y0.max(0.0).round() as i32,
+        right: clip.x1.min(width).round() as i32,
+        bottom: clip.y1.min(height).round() as i32,
+    }
+}
+
+/// Stroke glyph outlines of text instances with a stroke to draw halos beneath the fill
+fn make_halo_geometry(
+    device: &Device,
+    font_system: &mut FontSystem,
+    buffers: &[Buffer],
+    instances: &[TextInstance],
+    origins: &[(f32, f32)],
+    color_space: ColorSpace,
+) -> Option<Box<TextHaloGeometry>> {
+    // Outlines aren't cached, so a local cache doesn't duplicate work done by the atlas
+    let mut cache = SwashCache::new();
+    let mut halo_buffers: VertexBuffers<TextHaloVertex, u32> = VertexBuffers::new();
+    let mut stroke_tessellator = StrokeTessellator::new();
+    for ((buffer, instance), (left, top)) in buffers.iter().zip(instances).zip(origins) {
+        if instance.stroke_width <= 0.0 || instance.stroke_color().a <= 0.0 {
+            continue;
+        }
+        let path = buffer_outline(font_system, &mut cache, buffer)
+            .transformed(&lyon::geom::Translation::from(Vector2D::new(*left, *top)));
+        let color = instance.stroke_color().to_space(color_space);
+        let stroke_options = StrokeOptions::default()
+            .with_line_width(instance.stroke_width)
+            .with_line_join(LineJoin::Round)
+            .with_tolerance(0.01);
+        let mut builder =
+            BuffersBuilder::new(&mut halo_buffers, |vertex: StrokeVertex| TextHaloVertex {
+                position: vertex.position().to_array(),
+                color,
+            });
+        // Skip halos for text whose outline can't be tessellated rather than
+        // failing the whole mark
+        if let Err(err) = stroke_tessellator.tessellate_path(&path, &stroke_options, &mut builder) {
+            log::warn!("Failed to tessellate text halo: {err:?}");
+        }
     }
+    if halo_buffers.indices.is_empty() {
+        return None;
+    }
+
+    Some(Box::new(TextHaloGeometry {
+        vertex_buffer: device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("Text Halo Vertex Buffer"),
+            contents: bytemuck::cast_slice(&halo_buffers.vertices),
+            usage: wgpu::BufferUsages::VERTEX,
+        }),
+        index_buffer: device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("Text Halo Index Buffer"),
+            contents: bytemuck::cast_slice(&halo_buffers.indices),
+            usage: wgpu::BufferUsages::INDEX,
+        }),
+        num_indices: halo_buffers.indices.len() as u32,
+        layer: None,
+    }))
 }
 
-pub fn measure(buffer: &Buffer) -> (f32, f32) {
-    let (width, total_lines) = buffer
-        .layout_runs()
-        .fold((0.0, 0usize), |(width, total_lines), run| {
-            (run.line_w.max(width), total_lines + 1)
+/// Pipeline that draws halo strokes into a layer, replacing what's there instead of blending
+fn make_halo_pipeline(
+    device: &Device,
+    pipelines: &PipelineCache,
+    texture_format: TextureFormat,
+    sample_count: u32,
+) -> Arc<wgpu::RenderPipeline> {
+    let source = include_str!("text_halo.wgsl");
+    pipelines.shader_pipeline(device, source, texture_format, sample_count, |shader| {
+        let layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("Text Halo Pipeline Layout"),
+            bind_group_layouts: &[pipelines.uniform_layout()],
+            push_constant_ranges: &[],
         });
-    (width, (total_lines as f32 * buffer.metrics().line_height))
+        device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: Some("Text Halo Pipeline"),
+            layout: Some(&layout),
+            vertex: wgpu::VertexState {
+                module: shader,
+                entry_point: "vs_main",
+                buffers: &[TextHaloVertex::desc()],
+            },
+            fragment: Some(wgpu::FragmentState {
+                module: shader,
+                entry_point: "fs_main",
+                targets: &[Some(wgpu::ColorTargetState {
+                    format: texture_format,
+                    blend: None,
+                    write_mask: wgpu::ColorWrites::ALL,
+                })],
+            }),
+            primitive: wgpu::PrimitiveState {
+                topology: wgpu::PrimitiveTopology::TriangleList,
+                strip_index_format: None,
+                front_face: wgpu::FrontFace::Ccw,
+                // Stroke tessellation doesn't produce consistently wound triangles
+                cull_mode: None,
+                polygon_mode: wgpu::PolygonMode::Fill,
+                unclipped_depth: false,
+                conservative: false,
+            },
+            depth_stencil: None,
+            multisample: wgpu::MultisampleState {
+                count: sample_count,
+                mask: !0,
+                alpha_to_coverage_enabled: false,
+            },
+            multiview: None,
+        })
+    })
+}
+
+/// Pipeline that composites a halo layer over the target
+fn make_halo_layer_pipeline(
+    device: &Device,
+    pipelines: &PipelineCache,
+    texture_format: TextureFormat,
+    sample_count: u32,
+) -> Arc<wgpu::RenderPipeline> {
+    let source = include_str!("text_halo_layer.wgsl");
+    pipelines.shader_pipeline(device, source, texture_format, sample_count, |shader| {
+        // The layout is derived from the shader, and bind groups get it from the pipeline
+        device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: Some("Text Halo Layer Pipeline"),
+            layout: None,
+            vertex: wgpu::VertexState {
+                module: shader,
+                entry_point: "vs_main",
+                buffers: &[],
+            },
+            fragment: Some(wgpu::FragmentState {
+                module: shader,
+                entry_point: "fs_main",
+                targets: &[Some(wgpu::ColorTargetState {
+                    format: texture_format,
+                    blend: Some(wgpu::BlendState::PREMULTIPLIED_ALPHA_BLENDING),
+                    write_mask: wgpu::ColorWrites::ALL,
+                })],
+            }),
+            primitive: wgpu::PrimitiveState::default(),
+            depth_stencil: None,
+            multisample: wgpu::MultisampleState {
+                count: sample_count,
+                mask: !0,
+                alpha_to_coverage_enabled: false,
+            },
+            multiview: None,
+        })
+    })
 }
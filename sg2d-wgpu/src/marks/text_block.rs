@@ -0,0 +1,132 @@
+use crate::marks::mark::MarkShader;
+use crate::marks::rect::RectVertex;
+use sg2d::marks::text::TextMark;
+use sg2d_text::hit_test::text_hit_padding;
+use sg2d_text::metrics::TextMetrics;
+use sg2d_text::outline::map_text_items;
+use wgpu::VertexBufferLayout;
+
+/// Block of a text item, drawn only into the pick target so that text is picked where it's
+/// hit on the CPU
+#[repr(C)]
+#[derive(Copy, Clone, Debug, bytemuck::Pod, bytemuck::Zeroable)]
+pub struct TextBlockInstance {
+    /// Top-left corner of the block in group coordinates
+    pub origin: [f32; 2],
+    /// Top edge of the block, from left to right
+    pub x_axis: [f32; 2],
+    /// Left edge of the block, from top to bottom
+    pub y_axis: [f32; 2],
+    /// Zero when neither the text nor its halo is visible, so that the block isn't picked
+    pub visible: u32,
+}
+
+const INSTANCE_ATTRIBUTES: [wgpu::VertexAttribute; 4] = wgpu::vertex_attr_array![
+    1 => Float32x2,     // origin
+    2 => Float32x2,     // x_axis
+    3 => Float32x2,     // y_axis
+    4 => Uint32,        // visible
+];
+
+impl TextBlockInstance {
+    /// Rotated blocks of the items of a text mark, padded like the hit regions of the CPU hit
+    /// test
+    pub fn from_spec(mark: &TextMark) -> Vec<TextBlockInstance> {
+        let blocks = map_text_items(mark, |_, buffer, transform| {
+            let TextMetrics { width, height, .. } = TextMetrics::from_buffer(buffer);
+            (*transform, width, height)
+        });
+        blocks
+            .into_iter()
+            .zip(text_hit_padding(mark))
+            .map(|((transform, width, height), padding)| {
+                let pad = padding.unwrap_or(0.0);
+                let origin = transform.transform_point([-pad, -pad].into());
+                let x_axis = transform.transform_vector([width + 2.0 * pad, 0.0].into());
+                let y_axis = transform.transform_vector([0.0, height + 2.0 * pad].into());
+                TextBlockInstance {
+                    origin: origin.to_array(),
+                    x_axis: x_axis.to_array(),
+                    y_axis: y_axis.to_array(),
+                    visible: padding.is_some() as u32,
+                }
+            })
+            .collect()
+    }
+}
+
+pub struct TextBlockShader {
+    verts: Vec<RectVertex>,
+    indices: Vec<u16>,
+    shader: String,
+    vertex_entry_point: String,
+    fragment_entry_point: String,
+}
+
+impl Default for TextBlockShader {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl TextBlockShader {
+    pub fn new() -> Self {
+        Self {
+            verts: vec![
+                RectVertex {
+                    position: [0.0, 0.0],
+                },
+                RectVertex {
+                    position: [1.0, 0.0],
+                },
+                RectVertex {
+                    position: [1.0, 1.0],
+                },
+                RectVertex {
+                    position: [0.0, 1.0],
+                },
+            ],
+            indices: vec![0, 1, 2, 0, 2, 3],
+            shader: include_str!("text_block.wgsl").to_string(),
+            vertex_entry_point: "vs_main".to_string(),
+            fragment_entry_point: "fs_main".to_string(),
+        }
+    }
+}
+
+impl MarkShader for TextBlockShader {
+    type Instance = TextBlockInstance;
+    type Vertex = RectVertex;
+
+    fn verts(&self) -> &[Self::Vertex] {
+        self.verts.as_slice()
+    }
+
+    fn indices(&self) -> &[u16] {
+        self.indices.as_slice()
+    }
+
+    fn shader(&self) -> &str {
+        self.shader.as_str()
+    }
+
+    fn vertex_entry_point(&self) -> &str {
+        self.vertex_entry_point.as_str()
+    }
+
+    fn fragment_entry_point(&self) -> &str {
+        self.fragment_entry_point.as_str()
+    }
+
+    fn instance_desc(&self) -> wgpu::VertexBufferLayout<'static> {
+        wgpu::VertexBufferLayout {
+            array_stride: std::mem::size_of::<TextBlockInstance>() as wgpu::BufferAddress,
+            step_mode: wgpu::VertexStepMode::Instance,
+            attributes: &INSTANCE_ATTRIBUTES,
+        }
+    }
+
+    fn vertex_desc(&self) -> VertexBufferLayout<'static> {
+        RectVertex::desc()
+    }
+}
@@ -0,0 +1,67 @@
+// Vertex shader
+
+struct ChartUniform {
+    size: vec2<f32>,
+    scale: f32,
+    _pad: f32, // for 16 byte alignment
+    // Halo vertices are in scene coordinates, so only the translation, which moves the scene
+    // to the canvas origin, is used
+    linear: vec4<f32>,
+    translate: vec2<f32>,
+    // Size of the group's clip region, negative when text isn't clipped
+    clip_size: vec2<f32>,
+    // Transform from scene coordinates to clip region coordinates
+    clip_linear: vec4<f32>,
+    clip_translate: vec2<f32>,
+    _pad2: vec2<f32>, // for 16 byte alignment
+};
+
+@group(0) @binding(0)
+var<uniform> chart_uniforms: ChartUniform;
+
+// Whether a position in scene coordinates is outside the clip region of the group
+fn is_clipped(position: vec2<f32>) -> bool {
+    if (chart_uniforms.clip_size[0] < 0.0) {
+        return false;
+    }
+    let clip_linear = mat2x2<f32>(chart_uniforms.clip_linear.xy, chart_uniforms.clip_linear.zw);
+    let p = clip_linear * position + chart_uniforms.clip_translate;
+    return any(p < vec2<f32>(0.0)) || any(p > chart_uniforms.clip_size);
+}
+
+struct VertexInput {
+    @location(0) position: vec2<f32>,
+    @location(1) color: vec4<f32>,
+};
+
+struct VertexOutput {
+    @builtin(position) clip_position: vec4<f32>,
+    @location(0) color: vec4<f32>,
+    @location(1) scene_position: vec2<f32>,
+};
+
+@vertex
+fn vs_main(
+    model: VertexInput,
+) -> VertexOutput {
+    var out: VertexOutput;
+    out.color = model.color;
+    let position = model.position + chart_uniforms.translate;
+    out.scene_position = position;
+    let x = 2.0 * position[0] / chart_uniforms.size[0] - 1.0;
+    let y = 2.0 * (chart_uniforms.size[1] - position[1]) / chart_uniforms.size[1] - 1.0;
+    out.clip_position = vec4<f32>(x, y, 0.0, 1.0);
+    return out;
+}
+
+// Fragment shader
+
+// Halos are drawn into a layer without blending, so overlapping strokes cover a pixel once.
+// The layer holds premultiplied colors, which resolve and composite correctly.
+@fragment
+fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
+    if (is_clipped(in.scene_position)) {
+        discard;
+    }
+    return vec4<f32>(in.color.rgb * in.color.a, in.color.a);
+}
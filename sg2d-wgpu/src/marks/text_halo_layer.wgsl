@@ -0,0 +1,32 @@
+// Composites the halo layer of a text mark, which holds premultiplied colors, over the target
+
+@group(0) @binding(0)
+var layer_texture: texture_2d<f32>;
+@group(0) @binding(1)
+var layer_sampler: sampler;
+
+struct VertexOutput {
+    @builtin(position) clip_position: vec4<f32>,
+    @location(0) tex_coords: vec2<f32>,
+};
+
+// Vertex shader
+
+// A single triangle that covers the whole viewport
+@vertex
+fn vs_main(
+    @builtin(vertex_index) vertex_index: u32,
+) -> VertexOutput {
+    var out: VertexOutput;
+    let uv = vec2<f32>(f32((vertex_index << 1u) & 2u), f32(vertex_index & 2u));
+    out.tex_coords = uv;
+    out.clip_position = vec4<f32>(2.0 * uv.x - 1.0, 1.0 - 2.0 * uv.y, 0.0, 1.0);
+    return out;
+}
+
+// Fragment shader
+
+@fragment
+fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
+    return textureSample(layer_texture, layer_sampler, in.tex_coords);
+}
@@ -0,0 +1,267 @@
+use crate::canvas::{read_texture, MarkRenderer};
+use crate::error::Sg2dWgpuError;
+use sg2d::bounds::Bounds;
+use sg2d_text::hit_test::SceneHit;
+use wgpu::{
+    Buffer, BufferAddress, BufferDescriptor, BufferUsages, CommandEncoderDescriptor, Device,
+    Extent3d, LoadOp, Operations, Queue, RenderPassColorAttachment, RenderPassDescriptor, StoreOp,
+    Texture, TextureDescriptor, TextureDimension, TextureFormat, TextureUsages, TextureView,
+};
+
+/// Format of the picking target. Each pixel holds the pick id of the topmost instance, or 0.
+pub const PICK_TEXTURE_FORMAT: TextureFormat = TextureFormat::R32Uint;
+
+#[derive(Debug, Clone)]
+struct PickMark {
+    first_id: u32,
+    len: u32,
+    name: String,
+    group_path: Vec<usize>,
+}
+
+/// Assigns a range of pick ids to the items of each pickable mark, and maps ids back to
+/// mark items.
+///
+/// Ids are assigned in draw order starting from 1, so 0 means that nothing was drawn.
+#[derive(Debug, Clone)]
+pub struct PickIndex {
+    marks: Vec<PickMark>,
+    group_path: Vec<usize>,
+    next_id: u32,
+}
+
+impl Default for PickIndex {
+    fn default() -> Self {
+        Self {
+            marks: Vec::new(),
+            group_path: Vec::new(),
+            next_id: 1,
+        }
+    }
+}
+
+impl PickIndex {
+    /// Marks added until the matching `exit_group` belong to the group at this index
+    pub fn enter_group(&mut self, index: usize) {
+        self.group_path.push(index);
+    }
+
+    pub fn exit_group(&mut self) {
+        self.group_path.pop();
+    }
+
+    /// Reserve ids for the items of a mark, returning the id of the first item
+    pub fn add_mark(&mut self, name: &str, len: u32) -> u32 {
+        let first_id = self.next_id;
+        self.marks.push(PickMark {
+            first_id,
+            len,
+            name: name.to_string(),
+            group_path: self.group_path.clone(),
+        });
+        self.next_id += len;
+        first_id
+    }
+
+    /// Mark item drawn with a pick id
+    pub fn lookup(&self, id: u32) -> Option<SceneHit> {
+        let position = self.marks.partition_point(|mark| mark.first_id <= id);
+        let mark = self.marks.get(position.checked_sub(1)?)?;
+        let index = id - mark.first_id;
+        (index < mark.len).then(|| SceneHit {
+            mark: mark.name.clone(),
+            index: index as usize,
+            group_path: mark.group_path.clone(),
+        })
+    }
+}
+
+/// Offscreen `R32Uint` target that pickable mark renderers draw their ids into, along with
+/// the ids read back from it for the current scene
+pub struct Picker {
+    index: PickIndex,
+    texture: Texture,
+    texture_view: TextureView,
+    output_buffer: Buffer,
+    texture_size: Extent3d,
+    padded_width: u32,
+    padded_height: u32,
+    scale: f32,
+    ids: Option<Vec<u32>>,
+}
+
+impl Picker {
+    pub fn new(device: &Device, physical_width: u32, physical_height: u32, scale: f32) -> Self {
+        let texture_desc = TextureDescriptor {
+            size: Extent3d {
+                width: physical_width,
+                height: physical_height,
+                depth_or_array_layers: 1,
+            },
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: TextureDimension::D2,
+            format: PICK_TEXTURE_FORMAT,
+            usage: TextureUsages::COPY_SRC | TextureUsages::RENDER_ATTACHMENT,
+            label: Some("Pick Texture"),
+            view_formats: &[PICK_TEXTURE_FORMAT],
+        };
+        let texture_size = texture_desc.size;
+        let texture = device.create_texture(&texture_desc);
+        let texture_view = texture.create_view(&Default::default());
+
+        // Rows of ids are padded to 256 pixels for copying from the texture, like image rows
+        let padded_width = 256 * physical_width.div_ceil(256);
+        let padded_height = 256 * physical_height.div_ceil(256);
+        let u32_size = std::mem::size_of::<u32>() as u32;
+        let output_buffer = device.create_buffer(&BufferDescriptor {
+            size: (u32_size * padded_width * padded_height) as BufferAddress,
+            usage: BufferUsages::COPY_DST | BufferUsages::MAP_READ,
+            label: Some("Pick Output Buffer"),
+            mapped_at_creation: false,
+        });
+
+        Self {
+            index: Default::default(),
+            texture,
+            texture_view,
+            output_buffer,
+            texture_size,
+            padded_width,
+            padded_height,
+            scale,
+            ids: None,
+        }
+    }
+
+    pub fn index_mut(&mut self) -> &mut PickIndex {
+        &mut self.index
+    }
+
+    /// Forget the marks and ids of the previous scene
+    pub fn reset(&mut self) {
+        self.index = Default::default();
+        self.ids = None;
+    }
+
+    /// Forget the ids read back from the pick target, after the marks drawn into it moved
+    pub fn clear_ids(&mut self) {
+        self.ids = None;
+    }
+
+    /// Topmost pickable mark item at a point in logical pixels
+    pub async fn pick(
+        &mut self,
+        device: &Device,
+        queue: &Queue,
+        marks: &[MarkRenderer],
+        x: f32,
+        y: f32,
+    ) -> Result<Option<SceneHit>, Sg2dWgpuError> {
+        let bounds = Bounds::new(x, y, x, y);
+        Ok(self
+            .pick_ids(device, queue, marks, &bounds)
+            .await?
+            .first()
+            .and_then(|id| self.index.lookup(*id)))
+    }
+
+    /// Every pickable mark item drawn on a pixel within a rectangle in logical pixels,
+    /// in draw order
+    pub async fn pick_rect(
+        &mut self,
+        device: &Device,
+        queue: &Queue,
+        marks: &[MarkRenderer],
+        bounds: &Bounds,
+    ) -> Result<Vec<SceneHit>, Sg2dWgpuError> {
+        let mut ids = self.pick_ids(device, queue, marks, bounds).await?;
+        ids.sort_unstable();
+        ids.dedup();
+        Ok(ids
+            .into_iter()
+            .filter_map(|id| self.index.lookup(id))
+            .collect())
+    }
+
+    /// Non-zero ids of the pixels within logical bounds, rendering and reading back the
+    /// pick target the first time it's needed for a scene
+    async fn pick_ids(
+        &mut self,
+        device: &Device,
+        queue: &Queue,
+        marks: &[MarkRenderer],
+        bounds: &Bounds,
+    ) -> Result<Vec<u32>, Sg2dWgpuError> {
+        if self.ids.is_none() {
+            self.ids = Some(self.render(device, queue, marks).await?);
+        }
+        let ids = self.ids.as_deref().unwrap_or_default();
+
+        // Physical pixels that overlap the bounds, clamped to the target
+        let pixels = |v0: f32, v1: f32, size: u32| {
+            let start = (v0 * self.scale).floor().max(0.0) as u32;
+            let end = ((v1 * self.scale).floor() + 1.0).clamp(0.0, size as f32) as u32;
+            start..end
+        };
+        let xs = pixels(bounds.x0, bounds.x1, self.texture_size.width);
+        let ys = pixels(bounds.y0, bounds.y1, self.texture_size.height);
+        Ok(ys
+            .flat_map(|y| xs.clone().map(move |x| (x, y)))
+            .map(|(x, y)| ids[(y * self.padded_width + x) as usize])
+            .filter(|id| *id != 0)
+            .collect())
+    }
+
+    async fn render(
+        &self,
+        device: &Device,
+        queue: &Queue,
+        marks: &[MarkRenderer],
+    ) -> Result<Vec<u32>, Sg2dWgpuError> {
+        let mut clear_encoder = device.create_command_encoder(&CommandEncoderDescriptor {
+            label: Some("Pick Clear Encoder"),
+        });
+        {
+            let _render_pass = clear_encoder.begin_render_pass(&RenderPassDescriptor {
+                label: Some("Pick Clear Pass"),
+                color_attachments: &[Some(RenderPassColorAttachment {
+                    view: &self.texture_view,
+                    resolve_target: None,
+                    ops: Operations {
+                        load: LoadOp::Clear(wgpu::Color::TRANSPARENT),
+                        store: StoreOp::Store,
+                    },
+                })],
+                depth_stencil_attachment: None,
+                occlusion_query_set: None,
+                timestamp_writes: None,
+            });
+        }
+
+        // Text is picked by the block renderers that follow it, rather than by its glyphs
+        let mut commands = vec![clear_encoder.finish()];
+        commands.extend(marks.iter().filter_map(|mark| match mark {
+            MarkRenderer::Geom(mark) | MarkRenderer::Pick(mark) => {
+                mark.render_pick(device, &self.texture_view)
+            }
+            MarkRenderer::Text(_) => None,
+        }));
+        queue.submit(commands);
+
+        let data = read_texture(
+            device,
+            queue,
+            &self.texture,
+            &self.output_buffer,
+            self.texture_size,
+            self.padded_width,
+            self.padded_height,
+        )
+        .await?;
+        Ok(data
+            .chunks_exact(4)
+            .map(|id| u32::from_ne_bytes([id[0], id[1], id[2], id[3]]))
+            .collect())
+    }
+}
@@ -0,0 +1,334 @@
+//! Render scene graphs with a device, queue and target owned by a host application.
+//!
+//! `SceneRenderer` keeps the mark renderers of a scene, but borrows the host's `Device` and
+//! `Queue` for each call and records its draw commands into the host's `CommandEncoder`, so
+//! charts can be drawn into frames of an existing wgpu application.
+use crate::canvas::{prepare_text_marks, Canvas, CanvasUniform, MarkRenderer};
+use crate::color::texture_color_space;
+use crate::error::Sg2dWgpuError;
+use crate::marks::mark::GeomMarkRenderer;
+use crate::marks::pipeline::PipelineCache;
+use crate::marks::rect::{RectInstance, RectShader};
+use crate::marks::text::{TextContext, TextRendering};
+use crate::pick::PickIndex;
+use sg2d::scene_graph::SceneGraph;
+use sg2d::value::Color;
+use std::sync::Arc;
+use wgpu::{
+    CommandEncoder, Device, LoadOp, Operations, Queue, RenderPass, RenderPassColorAttachment,
+    RenderPassDescriptor, StoreOp, TextureFormat, TextureView,
+};
+
+/// Region of the target that the scene is drawn into, in physical pixels.
+///
+/// It must lie within the target. The scene is stretched to fill it, so text is crispest when
+/// the viewport is the scene size times the renderer scale.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Viewport {
+    pub x: f32,
+    pub y: f32,
+    pub width: f32,
+    pub height: f32,
+}
+
+/// Texture view to draw into, with the format and sample count the renderer was created with
+#[derive(Debug, Clone, Copy)]
+pub struct RenderTarget<'a> {
+    pub view: &'a TextureView,
+    /// Single-sampled view to resolve a multisampled `view` into
+    pub resolve_target: Option<&'a TextureView>,
+    /// Region to draw into, or the whole target if `None`
+    pub viewport: Option<Viewport>,
+}
+
+/// Renderer for scene graphs that draws into textures owned by the host.
+///
+/// The target's existing content is kept, and the background, when set, is blended over it
+/// like any other mark. Picking isn't supported.
+pub struct SceneRenderer {
+    texture_format: TextureFormat,
+    sample_count: u32,
+    scale: f32,
+    pipelines: Arc<PipelineCache>,
+    marks: Vec<MarkRenderer>,
+    text: TextContext,
+    text_rendering: TextRendering,
+    uniform: CanvasUniform,
+    background: Option<Color>,
+    scene_background: Option<Color>,
+    /// Rect that fills the scene with the background, and the color it was made with
+    background_mark: Option<(Color, GeomMarkRenderer)>,
+}
+
+impl SceneRenderer {
+    pub fn new(
+        device: &Device,
+        queue: &Queue,
+        texture_format: TextureFormat,
+        sample_count: u32,
+        scale: f32,
+    ) -> Self {
+        let pipelines = Arc::new(PipelineCache::new(device));
+        Self::with_pipelines(
+            device,
+            queue,
+            pipelines,
+            texture_format,
+            sample_count,
+            scale,
+        )
+    }
+
+    /// Renderer that shares compiled pipelines with other renderers on the same device
+    pub fn with_pipelines(
+        device: &Device,
+        queue: &Queue,
+        pipelines: Arc<PipelineCache>,
+        texture_format: TextureFormat,
+        sample_count: u32,
+        scale: f32,
+    ) -> Self {
+        let uniform = CanvasUniform::new([1.0, 1.0], scale);
+        let text = TextContext::new(device, queue, &pipelines, texture_format, sample_count);
+        Self {
+            texture_format,
+            sample_count,
+            scale,
+            pipelines,
+            marks: Vec::new(),
+            text,
+            text_rendering: Default::default(),
+            uniform,
+            background: None,
+            scene_background: None,
+            background_mark: None,
+        }
+    }
+
+    pub fn texture_format(&self) -> TextureFormat {
+        self.texture_format
+    }
+
+    pub fn sample_count(&self) -> u32 {
+        self.sample_count
+    }
+
+    pub fn scale(&self) -> f32 {
+        self.scale
+    }
+
+    /// Size of the current scene in logical pixels
+    pub fn size(&self) -> [f32; 2] {
+        self.uniform.size
+    }
+
+    pub fn set_scene(
+        &mut self,
+        device: &Device,
+        queue: &Queue,
+        scene_graph: &SceneGraph,
+    ) -> Result<(), Sg2dWgpuError> {
+        HostCanvas {
+            renderer: self,
+            device,
+            queue,
+        }
+        .set_scene(scene_graph)
+    }
+
+    /// Set the number of physical pixels per logical pixel. Takes effect the next time the
+    /// scene is set.
+    pub fn set_scale(&mut self, scale: f32) {
+        self.scale = scale;
+    }
+
+    /// Set how text marks are drawn. Takes effect the next time the scene is set.
+    pub fn set_text_rendering(&mut self, text_rendering: TextRendering) {
+        self.text_rendering = text_rendering;
+    }
+
+    /// Override the background of every scene, or use the scene's background again with `None`
+    pub fn set_background(&mut self, background: Option<Color>) {
+        self.background = background;
+    }
+
+    /// Color drawn beneath the marks: the renderer override, then the scene background.
+    /// Without either, the marks are drawn directly over the target.
+    pub fn background(&self) -> Option<Color> {
+        self.background.or(self.scene_background)
+    }
+
+    /// Record the commands that draw the current scene into the host's encoder
+    pub fn render(
+        &mut self,
+        device: &Device,
+        queue: &Queue,
+        encoder: &mut CommandEncoder,
+        target: &RenderTarget,
+    ) -> Result<(), Sg2dWgpuError> {
+        self.prepare(device, queue, encoder)?;
+        let mut render_pass = encoder.begin_render_pass(&RenderPassDescriptor {
+            label: Some("Scene Render Pass"),
+            color_attachments: &[Some(RenderPassColorAttachment {
+                view: target.view,
+                resolve_target: target.resolve_target,
+                ops: Operations {
+                    load: LoadOp::Load,
+                    store: StoreOp::Store,
+                },
+            })],
+            depth_stencil_attachment: None,
+            occlusion_query_set: None,
+            timestamp_writes: None,
+        });
+        if let Some(viewport) = &target.viewport {
+            render_pass.set_viewport(
+                viewport.x,
+                viewport.y,
+                viewport.width,
+                viewport.height,
+                0.0,
+                1.0,
+            );
+        }
+
+        self.draw(&mut render_pass)
+    }
+
+    /// Upload the glyphs and background of the current scene, and record the passes that draw
+    /// text halos into their layers. Call this before `draw`, with the encoder of the render
+    /// pass before the pass begins, or with an encoder that's submitted before it.
+    pub fn prepare(
+        &mut self,
+        device: &Device,
+        queue: &Queue,
+        encoder: &mut CommandEncoder,
+    ) -> Result<(), Sg2dWgpuError> {
+        prepare_text_marks(device, queue, &mut self.text, &mut self.marks)?;
+        self.update_background_mark(device);
+        for mark in &self.marks {
+            if let MarkRenderer::Text(mark) = mark {
+                mark.draw_halo_layer(&self.text, encoder);
+            }
+        }
+        Ok(())
+    }
+
+    /// Record the draw commands of the current scene into a render pass of the host. The scene
+    /// fills the viewport of the render pass.
+    pub fn draw<'a>(&'a self, render_pass: &mut RenderPass<'a>) -> Result<(), Sg2dWgpuError> {
+        if let Some((_, mark)) = &self.background_mark {
+            mark.draw(render_pass);
+        }
+        for mark in &self.marks {
+            match mark {
+                MarkRenderer::Geom(mark) => mark.draw(render_pass),
+                MarkRenderer::Text(mark) => mark.draw(&self.text, render_pass)?,
+                MarkRenderer::Pick(_) => {}
+            }
+        }
+        Ok(())
+    }
+
+    /// Make the background rect if the background changed since it was last made
+    fn update_background_mark(&mut self, device: &Device) {
+        let Some(color) = self.background().filter(|color| color.a > 0.0) else {
+            self.background_mark = None;
+            return;
+        };
+        if matches!(&self.background_mark, Some((made_with, _)) if *made_with == color) {
+            return;
+        }
+        let [width, height] = self.uniform.size;
+        let instance = RectInstance {
+            position: [0.0, 0.0],
+            color: color.to_space(texture_color_space(self.texture_format)),
+            width,
+            height,
+        };
+        let mark = GeomMarkRenderer::new(
+            device,
+            &self.pipelines,
+            CanvasUniform::new(self.uniform.size, self.scale),
+            self.texture_format,
+            self.sample_count,
+            Box::new(RectShader::new()),
+            &[instance],
+            None,
+        );
+        self.background_mark = Some((color, mark));
+    }
+}
+
+/// Canvas view of a renderer while a scene is set, with the host's device and queue
+struct HostCanvas<'a> {
+    renderer: &'a mut SceneRenderer,
+    device: &'a Device,
+    queue: &'a Queue,
+}
+
+impl Canvas for HostCanvas<'_> {
+    fn add_mark_renderer(&mut self, mark_renderer: MarkRenderer) {
+        self.renderer.marks.push(mark_renderer);
+    }
+
+    fn clear_mark_renderer(&mut self) {
+        self.renderer.marks.clear();
+        // The background rect is sized to the previous scene
+        self.renderer.background_mark = None;
+    }
+
+    fn device(&self) -> &Device {
+        self.device
+    }
+
+    fn queue(&self) -> &Queue {
+        self.queue
+    }
+
+    fn pipelines(&self) -> &PipelineCache {
+        &self.renderer.pipelines
+    }
+
+    fn uniform(&self) -> &CanvasUniform {
+        &self.renderer.uniform
+    }
+
+    fn scale(&self) -> f32 {
+        self.renderer.scale
+    }
+
+    fn set_uniform(&mut self, uniform: CanvasUniform) {
+        self.renderer.uniform = uniform;
+    }
+
+    fn texture_format(&self) -> TextureFormat {
+        self.renderer.texture_format
+    }
+
+    fn sample_count(&self) -> u32 {
+        self.renderer.sample_count
+    }
+
+    fn text_rendering(&self) -> TextRendering {
+        self.renderer.text_rendering
+    }
+
+    fn set_text_rendering(&mut self, text_rendering: TextRendering) {
+        self.renderer.text_rendering = text_rendering;
+    }
+
+    fn pick_index(&mut self) -> Option<&mut PickIndex> {
+        None
+    }
+
+    fn set_picking(&mut self, _picking: bool) {}
+
+    fn background(&self) -> Color {
+        self.renderer.background().unwrap_or(Color::TRANSPARENT)
+    }
+
+    fn set_scene_background(&mut self, background: Option<Color>) {
+        self.renderer.scene_background = background;
+    }
+}
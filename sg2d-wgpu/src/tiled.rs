@@ -0,0 +1,126 @@
+//! Render images larger than the maximum texture size in tiles.
+//!
+//! The scene is set on a tile-sized `PngCanvas` once, and each tile is rendered by moving the
+//! canvas origin to the tile's part of the image. Tiles are stitched together a row at a time. `render_png`
+//! encodes each row of tiles as soon as it's rendered, so only one row is held in memory.
+use crate::canvas::{Canvas, PngCanvas};
+use crate::context::RenderContext;
+use crate::error::Sg2dWgpuError;
+use image::RgbaImage;
+use sg2d::scene_graph::SceneGraph;
+use sg2d::value::Color;
+use std::io::Write;
+use std::sync::Arc;
+
+pub struct TiledRenderer {
+    canvas: PngCanvas,
+}
+
+impl TiledRenderer {
+    /// Renderer with square tiles of at most `tile_size` pixels, which is clamped to the
+    /// maximum texture size of the device
+    pub fn new(
+        context: Arc<RenderContext>,
+        scale: f32,
+        tile_size: u32,
+    ) -> Result<Self, Sg2dWgpuError> {
+        let max_tile_size = context.device().limits().max_texture_dimension_2d;
+        let tile_size = tile_size.clamp(1, max_tile_size) as f32 / scale;
+
+        // Raster text snaps glyphs to whole scene units, so tiles that span a whole number of
+        // units, and a whole number of pixels, place glyphs as a single texture would
+        let max_units = tile_size.floor() as u32;
+        let tile_size = (max_units.div_ceil(2).max(1)..=max_units)
+            .rev()
+            .map(|units| units as f32)
+            .find(|units| (units * scale).fract() == 0.0)
+            .unwrap_or(tile_size);
+        Ok(Self {
+            canvas: PngCanvas::with_context(context, tile_size, tile_size, scale)?,
+        })
+    }
+
+    /// Renderer with tiles as large as the device allows
+    pub fn with_max_tile_size(
+        context: Arc<RenderContext>,
+        scale: f32,
+    ) -> Result<Self, Sg2dWgpuError> {
+        Self::new(context, scale, u32::MAX)
+    }
+
+    /// Override the background of every scene, as with `PngCanvas::set_background`
+    pub fn set_background(&mut self, background: Option<Color>) {
+        self.canvas.set_background(background);
+    }
+
+    /// Width and height of tiles in pixels
+    pub fn tile_size(&self) -> (u32, u32) {
+        (
+            self.canvas.texture_size.width,
+            self.canvas.texture_size.height,
+        )
+    }
+
+    /// Size in pixels of the image of a scene, the same as `PngCanvas` renders
+    pub fn image_size(&self, scene_graph: &SceneGraph) -> (u32, u32) {
+        (
+            (scene_graph.width * self.canvas.scale) as u32,
+            (scene_graph.height * self.canvas.scale) as u32,
+        )
+    }
+
+    /// Render a scene to an image with straight alpha
+    pub async fn render(&mut self, scene_graph: &SceneGraph) -> Result<RgbaImage, Sg2dWgpuError> {
+        let (width, height) = self.image_size(scene_graph);
+        let mut img = RgbaImage::new(width, height);
+        self.canvas.set_scene(scene_graph)?;
+        let (_, tile_height) = self.tile_size();
+        for y in (0..height).step_by(tile_height as usize) {
+            let row = self.render_row(width, height, y).await?;
+            image::imageops::replace(&mut img, &row, 0, y as i64);
+        }
+        Ok(img)
+    }
+
+    /// Render a scene and write it to `writer` as a PNG, encoding one row of tiles at a time
+    pub async fn render_png<W: Write>(
+        &mut self,
+        scene_graph: &SceneGraph,
+        writer: W,
+    ) -> Result<(), Sg2dWgpuError> {
+        let (width, height) = self.image_size(scene_graph);
+        let mut encoder = png::Encoder::new(writer, width, height);
+        encoder.set_color(png::ColorType::Rgba);
+        encoder.set_depth(png::BitDepth::Eight);
+        let mut writer = encoder.write_header()?;
+        let mut stream = writer.stream_writer()?;
+
+        self.canvas.set_scene(scene_graph)?;
+        let (_, tile_height) = self.tile_size();
+        for y in (0..height).step_by(tile_height as usize) {
+            let row = self.render_row(width, height, y).await?;
+            stream.write_all(row.as_raw())?;
+        }
+        stream.finish()?;
+        Ok(())
+    }
+
+    /// Render the row of tiles that starts at pixel row `y` of an image of the given size
+    async fn render_row(
+        &mut self,
+        width: u32,
+        height: u32,
+        y: u32,
+    ) -> Result<RgbaImage, Sg2dWgpuError> {
+        let (tile_width, tile_height) = self.tile_size();
+        let mut row = RgbaImage::new(width, tile_height.min(height - y));
+        for x in (0..width).step_by(tile_width as usize) {
+            let scale = self.canvas.scale;
+            self.canvas.set_origin(x as f32 / scale, y as f32 / scale);
+            let tile = self.canvas.render().await?;
+            // Tiles at the right and bottom edges are cropped to the image
+            image::imageops::replace(&mut row, &tile, x as i64, 0);
+        }
+        Ok(row)
+    }
+}
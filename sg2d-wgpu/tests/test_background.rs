@@ -0,0 +1,73 @@
+#[cfg(test)]
+mod test_background {
+    use sg2d::marks::group::SceneGroup;
+    use sg2d::marks::rect::RectMark;
+    use sg2d::scene_graph::SceneGraph;
+    use sg2d::value::Color;
+    use sg2d_wgpu::canvas::{Canvas, PngCanvas};
+
+    /// Translucent red rect over the left half of the scene
+    fn scene_graph(background: Color) -> SceneGraph {
+        SceneGraph::builder(10.0, 10.0)
+            .background(background)
+            .group(
+                SceneGroup::builder().mark(
+                    RectMark::builder("bars")
+                        .x_scalar(0.0)
+                        .y_scalar(0.0)
+                        .width_scalar(5.0)
+                        .height_scalar(10.0)
+                        .fill_scalar([1.0, 0.0, 0.0, 0.5]),
+                ),
+            )
+            .build()
+            .unwrap()
+    }
+
+    fn assert_pixel_near(actual: [u8; 4], expected: [u8; 4]) {
+        assert!(
+            actual.iter().zip(expected).all(|(a, e)| a.abs_diff(e) <= 1),
+            "{actual:?} != {expected:?}"
+        );
+    }
+
+    #[test]
+    fn test_transparent() {
+        let mut png_canvas = pollster::block_on(PngCanvas::new(10.0, 10.0, 1.0)).unwrap();
+        png_canvas
+            .set_scene(&scene_graph(Color::TRANSPARENT))
+            .unwrap();
+        let img = pollster::block_on(png_canvas.render()).unwrap();
+        assert_eq!(img.get_pixel(8, 5).0, [0, 0, 0, 0]);
+        // Readback has straight alpha, so the rect keeps its full red
+        assert_pixel_near(img.get_pixel(2, 5).0, [255, 0, 0, 128]);
+    }
+
+    #[test]
+    fn test_scene_background() {
+        let mut png_canvas = pollster::block_on(PngCanvas::new(10.0, 10.0, 1.0)).unwrap();
+        png_canvas
+            .set_scene(&scene_graph(Color::rgb(0.0, 0.0, 0.0)))
+            .unwrap();
+        let img = pollster::block_on(png_canvas.render()).unwrap();
+        assert_eq!(img.get_pixel(8, 5).0, [0, 0, 0, 255]);
+        assert_pixel_near(img.get_pixel(2, 5).0, [128, 0, 0, 255]);
+    }
+
+    #[test]
+    fn test_override() {
+        let mut png_canvas = pollster::block_on(PngCanvas::new(10.0, 10.0, 1.0)).unwrap();
+        png_canvas.set_background(Some(Color::rgb(0.0, 0.0, 1.0)));
+        png_canvas
+            .set_scene(&scene_graph(Color::TRANSPARENT))
+            .unwrap();
+        assert_eq!(png_canvas.background(), Color::rgb(0.0, 0.0, 1.0));
+        let img = pollster::block_on(png_canvas.render()).unwrap();
+        assert_eq!(img.get_pixel(8, 5).0, [0, 0, 255, 255]);
+
+        // Without the override, the scene background is used again
+        png_canvas.set_background(None);
+        let img = pollster::block_on(png_canvas.render()).unwrap();
+        assert_eq!(img.get_pixel(8, 5).0, [0, 0, 0, 0]);
+    }
+}
@@ -0,0 +1,45 @@
+#[cfg(test)]
+mod test_canvas {
+    use sg2d::error::SceneGraphError;
+    use sg2d::marks::group::{GroupBounds, SceneGroup};
+    use sg2d::marks::mark::SceneMark;
+    use sg2d::marks::rect::RectMark;
+    use sg2d::scene_graph::SceneGraph;
+    use sg2d::value::EncodingValue;
+    use sg2d_wgpu::canvas::{Canvas, PngCanvas};
+    use sg2d_wgpu::error::Sg2dWgpuError;
+
+    #[test]
+    fn test_set_scene_validates() {
+        let scene_graph = SceneGraph {
+            groups: vec![SceneGroup {
+                bounds: GroupBounds {
+                    x: 0.0,
+                    y: 0.0,
+                    width: None,
+                    height: None,
+                },
+                transform: Default::default(),
+                marks: vec![SceneMark::Rect(RectMark {
+                    name: "bars".to_string(),
+                    len: 2,
+                    x: EncodingValue::Array { values: vec![0.0] },
+                    ..Default::default()
+                })],
+            }],
+            width: 100.0,
+            height: 100.0,
+            background: None,
+        };
+
+        let mut png_canvas = pollster::block_on(PngCanvas::new(100.0, 100.0, 1.0)).unwrap();
+        let err = png_canvas.set_scene(&scene_graph).unwrap_err();
+        assert!(matches!(
+            err,
+            Sg2dWgpuError::SceneGraphError(SceneGraphError::ArrayLengthMismatch {
+                channel: "x",
+                ..
+            })
+        ));
+    }
+}
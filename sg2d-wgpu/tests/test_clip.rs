@@ -0,0 +1,158 @@
+#[cfg(test)]
+mod test_clip {
+    use image::RgbaImage;
+    use rstest::rstest;
+    use sg2d::marks::group::SceneGroup;
+    use sg2d::marks::mark::SceneMark;
+    use sg2d::marks::rect::RectMark;
+    use sg2d::marks::rule::RuleMark;
+    use sg2d::marks::symbol::SymbolMark;
+    use sg2d::marks::text::TextMark;
+    use sg2d::scene_graph::SceneGraph;
+    use sg2d::transform::AffineTransform;
+    use sg2d::value::Color;
+    use sg2d_wgpu::canvas::{Canvas, PngCanvas};
+    use sg2d_wgpu::marks::text::TextRendering;
+
+    const SCALE: f32 = 2.0;
+
+    fn render(scene_graph: &SceneGraph, text_rendering: TextRendering) -> RgbaImage {
+        let mut canvas =
+            pollster::block_on(PngCanvas::new(scene_graph.width, scene_graph.height, SCALE))
+                .unwrap();
+        canvas.set_text_rendering(text_rendering);
+        canvas.set_scene(scene_graph).unwrap();
+        pollster::block_on(canvas.render()).unwrap()
+    }
+
+    /// Columns of an image, in logical pixels, with any pixel that isn't white
+    fn inked_columns(img: &RgbaImage) -> Vec<f32> {
+        let mut columns = img
+            .enumerate_pixels()
+            .filter(|(_, _, pixel)| pixel.0 != [255, 255, 255, 255])
+            .map(|(x, _, _)| x as f32 / SCALE)
+            .collect::<Vec<_>>();
+        columns.sort_by(f32::total_cmp);
+        columns.dedup();
+        columns
+    }
+
+    fn rect_scene(clip: bool) -> SceneGraph {
+        SceneGraph::builder(40.0, 20.0)
+            .group(
+                SceneGroup::builder()
+                    .origin(10.0, 0.0)
+                    .size(10.0, 20.0)
+                    .transform(AffineTransform::scale(2.0, 1.0))
+                    .mark(
+                        RectMark::builder("bars")
+                            .x_scalar(0.0)
+                            .y_scalar(0.0)
+                            .width_scalar(10.0)
+                            .height_scalar(20.0)
+                            .fill_scalar(Color::rgb(1.0, 0.0, 0.0))
+                            .clip(clip),
+                    ),
+            )
+            .build()
+            .unwrap()
+    }
+
+    #[test]
+    fn test_clip_rect() {
+        let img = render(&rect_scene(false), TextRendering::Glyphs);
+        assert_eq!(img.get_pixel(30, 20).0, [255, 0, 0, 255]);
+        assert_eq!(img.get_pixel(50, 20).0, [255, 0, 0, 255]);
+
+        // The clip region is 10 wide and isn't scaled by the group transform
+        let img = render(&rect_scene(true), TextRendering::Glyphs);
+        assert_eq!(img.get_pixel(30, 20).0, [255, 0, 0, 255]);
+        assert_eq!(img.get_pixel(50, 20).0, [255, 255, 255, 255]);
+    }
+
+    /// Marks that overflow a group 20 wide at x = 30
+    fn overflowing_mark(kind: &str) -> SceneMark {
+        match kind {
+            "symbol" => SymbolMark::builder("points")
+                .x_scalar(10.0)
+                .y_scalar(20.0)
+                .size_scalar(1600.0)
+                .fill_scalar(Color::rgb(1.0, 0.0, 0.0))
+                .clip(true)
+                .into(),
+            "rule" => RuleMark::builder("line")
+                .x0_scalar(-20.0)
+                .y0_scalar(10.0)
+                .x1_scalar(40.0)
+                .y1_scalar(30.0)
+                .stroke_width_scalar(4.0)
+                .clip(true)
+                .into(),
+            "text" => TextMark::builder("label")
+                .text_scalar("Overflowing text")
+                .x_scalar(-15.0)
+                .y_scalar(20.0)
+                .font_size_scalar(14.0)
+                .stroke_scalar(Color::rgb(1.0, 0.0, 0.0))
+                .stroke_width_scalar(3.0)
+                .clip(true)
+                .into(),
+            _ => unreachable!(),
+        }
+    }
+
+    #[rstest]
+    #[case("symbol", TextRendering::Glyphs)]
+    #[case("rule", TextRendering::Glyphs)]
+    #[case("text", TextRendering::Glyphs)]
+    #[case("text", TextRendering::Paths)]
+    fn test_clip_marks(#[case] kind: &str, #[case] text_rendering: TextRendering) {
+        let scene_graph = SceneGraph::builder(80.0, 40.0)
+            .group(
+                SceneGroup::builder()
+                    .origin(30.0, 0.0)
+                    .size(20.0, 40.0)
+                    .mark(overflowing_mark(kind)),
+            )
+            .build()
+            .unwrap();
+        let columns = inked_columns(&render(&scene_graph, text_rendering));
+        let (first, last) = (columns[0], columns[columns.len() - 1]);
+        assert!((30.0..31.0).contains(&first), "{kind}: {first}..{last}");
+        assert!((49.0..50.0).contains(&last), "{kind}: {first}..{last}");
+    }
+
+    #[test]
+    fn test_clip_under_parent_transform() {
+        // The clip region of a nested group is moved by the transform of its parent
+        let scene_graph = SceneGraph::builder(80.0, 40.0)
+            .group(
+                SceneGroup::builder()
+                    .transform(AffineTransform::translate(20.0, 0.0))
+                    .mark(
+                        SceneGroup::builder()
+                            .origin(10.0, 0.0)
+                            .size(20.0, 40.0)
+                            .mark(
+                                RectMark::builder("bars")
+                                    .x_scalar(-10.0)
+                                    .y_scalar(0.0)
+                                    .width_scalar(60.0)
+                                    .height_scalar(40.0)
+                                    .fill_scalar(Color::rgb(1.0, 0.0, 0.0))
+                                    .clip(true),
+                            )
+                            .build()
+                            .unwrap(),
+                    ),
+            )
+            .build()
+            .unwrap();
+        let columns = inked_columns(&render(&scene_graph, TextRendering::Glyphs));
+        assert_eq!(columns.first(), Some(&30.0));
+        assert_eq!(columns.last(), Some(&49.5));
+    }
+
+    #[test]
+    fn test_marker() {} // Help IDE detect test module
+}
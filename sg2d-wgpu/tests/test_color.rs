@@ -0,0 +1,89 @@
+#[cfg(test)]
+mod test_color {
+    use image::RgbaImage;
+    use rstest::rstest;
+    use sg2d::marks::group::{GroupBounds, SceneGroup};
+    use sg2d::marks::mark::SceneMark;
+    use sg2d::marks::rect::RectMark;
+    use sg2d::marks::text::TextMark;
+    use sg2d::scene_graph::SceneGraph;
+    use sg2d::value::{Color, EncodingValue};
+    use sg2d_wgpu::canvas::{Canvas, PngCanvas};
+    use sg2d_wgpu::marks::text::TextRendering;
+
+    fn render(marks: Vec<SceneMark>, text_rendering: TextRendering) -> RgbaImage {
+        let scene_graph = SceneGraph {
+            groups: vec![SceneGroup {
+                bounds: GroupBounds {
+                    x: 0.0,
+                    y: 0.0,
+                    width: None,
+                    height: None,
+                },
+                transform: Default::default(),
+                marks,
+            }],
+            width: 100.0,
+            height: 100.0,
+            background: None,
+        };
+        let mut png_canvas = pollster::block_on(PngCanvas::new(100.0, 100.0, 1.0)).unwrap();
+        png_canvas.set_text_rendering(text_rendering);
+        png_canvas.set_scene(&scene_graph).unwrap();
+        pollster::block_on(png_canvas.render()).unwrap()
+    }
+
+    fn rect(fill: Color) -> SceneMark {
+        SceneMark::Rect(RectMark {
+            x: EncodingValue::Scalar { value: 20.0 },
+            y: EncodingValue::Scalar { value: 20.0 },
+            width: EncodingValue::Scalar { value: 60.0 },
+            height: EncodingValue::Scalar { value: 60.0 },
+            fill: EncodingValue::Scalar { value: fill },
+            ..Default::default()
+        })
+    }
+
+    #[test]
+    fn test_rect_alpha() {
+        // Half transparent red blends with the white background in sRGB
+        let img = render(
+            vec![rect(Color::rgba(1.0, 0.0, 0.0, 0.5))],
+            TextRendering::Glyphs,
+        );
+        let [r, g, b, a] = img.get_pixel(50, 50).0;
+        assert_eq!((r, a), (255, 255));
+        assert!(g.abs_diff(128) <= 1 && b.abs_diff(128) <= 1, "{g}, {b}");
+        assert_eq!(img.get_pixel(5, 5).0, [255, 255, 255, 255]);
+    }
+
+    #[rstest]
+    #[case(TextRendering::Glyphs)]
+    #[case(TextRendering::Paths)]
+    fn test_text_color_matches_marks(#[case] text_rendering: TextRendering) {
+        // The darkest pixels of thick, gray text are as gray as a rect of the same color
+        let gray = Color::parse("#808080").unwrap();
+        let text = SceneMark::Text(Box::new(TextMark {
+            text: EncodingValue::Scalar {
+                value: "\u{2588}".to_string(),
+            },
+            x: EncodingValue::Scalar { value: 20.0 },
+            y: EncodingValue::Scalar { value: 80.0 },
+            font_size: EncodingValue::Scalar { value: 60.0 },
+            color: EncodingValue::Scalar { value: gray },
+            ..Default::default()
+        }));
+        let text_min = render(vec![text], text_rendering)
+            .pixels()
+            .map(|pixel| pixel.0[0])
+            .min()
+            .unwrap();
+        let rect_min = render(vec![rect(gray)], text_rendering)
+            .pixels()
+            .map(|pixel| pixel.0[0])
+            .min()
+            .unwrap();
+        assert_eq!(rect_min, 128);
+        assert!(text_min.abs_diff(rect_min) <= 2, "{text_min} != {rect_min}");
+    }
+}
@@ -0,0 +1,63 @@
+#[cfg(test)]
+mod test_config {
+    use sg2d::marks::group::SceneGroup;
+    use sg2d::marks::rect::RectMark;
+    use sg2d::scene_graph::SceneGraph;
+    use sg2d_wgpu::canvas::{Canvas, PngCanvas};
+    use sg2d_wgpu::config::CanvasConfig;
+    use sg2d_wgpu::error::Sg2dWgpuError;
+    use wgpu::{Backends, Limits};
+
+    #[test]
+    fn test_no_backends() {
+        let config = CanvasConfig::default().backends(Backends::empty());
+        let Err(err) = pollster::block_on(PngCanvas::with_config(10.0, 10.0, 1.0, &config)) else {
+            panic!("Expected an error");
+        };
+        assert!(matches!(
+            err,
+            Sg2dWgpuError::MakeWgpuAdapterError { adapters } if adapters.is_empty()
+        ));
+    }
+
+    #[test]
+    fn test_unsupported_limits() {
+        let config = CanvasConfig::default().limits(Limits {
+            max_texture_dimension_2d: u32::MAX,
+            ..Default::default()
+        });
+        let Err(err) = pollster::block_on(PngCanvas::with_config(10.0, 10.0, 1.0, &config)) else {
+            panic!("Expected an error");
+        };
+        let Sg2dWgpuError::UnsupportedLimits { limits, .. } = err else {
+            panic!("Expected UnsupportedLimits, got {err:?}");
+        };
+        assert_eq!(limits.len(), 1);
+        assert!(limits[0].starts_with("max_texture_dimension_2d"));
+    }
+
+    #[test]
+    fn test_render_with_config() {
+        let scene_graph = SceneGraph::builder(10.0, 10.0)
+            .group(
+                SceneGroup::builder().mark(
+                    RectMark::builder("bars")
+                        .x_scalar(0.0)
+                        .y_scalar(0.0)
+                        .width_scalar(10.0)
+                        .height_scalar(10.0)
+                        .fill_scalar([1.0, 0.0, 0.0, 1.0]),
+                ),
+            )
+            .build()
+            .unwrap();
+        let config = CanvasConfig::default()
+            .limits(Limits::downlevel_defaults())
+            .power_preference(wgpu::PowerPreference::LowPower);
+        let mut png_canvas =
+            pollster::block_on(PngCanvas::with_config(10.0, 10.0, 1.0, &config)).unwrap();
+        png_canvas.set_scene(&scene_graph).unwrap();
+        let img = pollster::block_on(png_canvas.render()).unwrap();
+        assert_eq!(img.get_pixel(5, 5).0, [255, 0, 0, 255]);
+    }
+}
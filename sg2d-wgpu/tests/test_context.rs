@@ -0,0 +1,124 @@
+#[cfg(test)]
+mod test_context {
+    use sg2d::marks::group::SceneGroup;
+    use sg2d::marks::rect::RectMark;
+    use sg2d::marks::rule::RuleMark;
+    use sg2d::marks::symbol::SymbolMark;
+    use sg2d::scene_graph::SceneGraph;
+    use sg2d_text::hit_test::SceneHit;
+    use sg2d_wgpu::canvas::{Canvas, PngCanvas};
+    use sg2d_wgpu::config::CanvasConfig;
+    use sg2d_wgpu::context::RenderContext;
+    use sg2d_wgpu::error::Sg2dWgpuError;
+    use std::sync::Arc;
+
+    fn scene_graph(width: f32, height: f32) -> SceneGraph {
+        SceneGraph::builder(width, height)
+            .group(
+                SceneGroup::builder()
+                    .mark(
+                        RectMark::builder("bars")
+                            .x(vec![0.0, 10.0])
+                            .y_scalar(0.0)
+                            .width_scalar(5.0)
+                            .height(vec![10.0, 20.0])
+                            .fill_scalar([1.0, 0.0, 0.0, 1.0]),
+                    )
+                    .mark(
+                        RuleMark::builder("axis")
+                            .x0_scalar(0.0)
+                            .y0_scalar(0.0)
+                            .x1_scalar(width)
+                            .y1_scalar(height),
+                    )
+                    .mark(
+                        SymbolMark::builder("points")
+                            .x(vec![5.0, 15.0])
+                            .y(vec![5.0, 15.0])
+                            .size_scalar(20.0),
+                    ),
+            )
+            .build()
+            .unwrap()
+    }
+
+    fn context() -> Arc<RenderContext> {
+        Arc::new(pollster::block_on(RenderContext::new(&CanvasConfig::default())).unwrap())
+    }
+
+    #[test]
+    fn test_shared_pipelines() {
+        let context = context();
+        let sg = scene_graph(20.0, 20.0);
+
+        let mut first = PngCanvas::with_context(context.clone(), 20.0, 20.0, 2.0).unwrap();
+        first.set_scene(&sg).unwrap();
+        let first_img = pollster::block_on(first.render()).unwrap();
+        let pipeline_count = context.pipelines().pipeline_count();
+        assert!(pipeline_count > 0);
+
+        let mut second = PngCanvas::with_context(context.clone(), 20.0, 20.0, 2.0).unwrap();
+        second.set_scene(&sg).unwrap();
+        let second_img = pollster::block_on(second.render()).unwrap();
+
+        assert_eq!(context.pipelines().pipeline_count(), pipeline_count);
+        assert_eq!(first_img, second_img);
+    }
+
+    #[test]
+    fn test_resize() {
+        let mut png_canvas = PngCanvas::with_context(context(), 20.0, 20.0, 1.0).unwrap();
+        png_canvas.set_scene(&scene_graph(20.0, 20.0)).unwrap();
+        let img = pollster::block_on(png_canvas.render()).unwrap();
+        assert_eq!(img.dimensions(), (20, 20));
+
+        png_canvas.resize(30.0, 25.0, 2.0).unwrap();
+        png_canvas.set_scene(&scene_graph(30.0, 25.0)).unwrap();
+        let resized_img = pollster::block_on(png_canvas.render()).unwrap();
+        assert_eq!(resized_img.dimensions(), (60, 50));
+
+        // Same pixels as a canvas created at the new size
+        let mut expected_canvas =
+            PngCanvas::with_context(png_canvas.context().clone(), 30.0, 25.0, 2.0).unwrap();
+        expected_canvas.set_scene(&scene_graph(30.0, 25.0)).unwrap();
+        let expected_img = pollster::block_on(expected_canvas.render()).unwrap();
+        assert_eq!(resized_img, expected_img);
+    }
+
+    #[test]
+    fn test_invalid_size() {
+        let context = context();
+        let max_size = context.device().limits().max_texture_dimension_2d as f32;
+        assert!(matches!(
+            PngCanvas::with_context(context.clone(), max_size + 1.0, 10.0, 1.0),
+            Err(Sg2dWgpuError::InvalidCanvasSize { .. })
+        ));
+
+        // A failed resize keeps the previous size
+        let mut png_canvas = PngCanvas::with_context(context, 20.0, 20.0, 1.0).unwrap();
+        assert!(matches!(
+            png_canvas.resize(0.0, 20.0, 1.0),
+            Err(Sg2dWgpuError::InvalidCanvasSize { .. })
+        ));
+        png_canvas.set_scene(&scene_graph(20.0, 20.0)).unwrap();
+        let img = pollster::block_on(png_canvas.render()).unwrap();
+        assert_eq!(img.dimensions(), (20, 20));
+    }
+
+    #[test]
+    fn test_resize_picking() {
+        let mut png_canvas = PngCanvas::with_context(context(), 20.0, 20.0, 1.0).unwrap();
+        png_canvas.set_picking(true);
+        png_canvas.resize(40.0, 40.0, 1.0).unwrap();
+        png_canvas.set_scene(&scene_graph(40.0, 40.0)).unwrap();
+        let hit = pollster::block_on(png_canvas.pick(12.0, 18.0)).unwrap();
+        assert_eq!(
+            hit,
+            Some(SceneHit {
+                mark: "bars".to_string(),
+                index: 1,
+                group_path: vec![0],
+            })
+        );
+    }
+}
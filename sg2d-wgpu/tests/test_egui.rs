@@ -0,0 +1,239 @@
+#[cfg(all(test, feature = "egui"))]
+mod test_egui {
+    use egui::mutex::RwLock;
+    use egui::{pos2, vec2, Event, Modifiers, PointerButton, Pos2, RawInput, Rect};
+    use egui_wgpu::renderer::ScreenDescriptor;
+    use egui_wgpu::{RenderState, Renderer};
+    use sg2d::marks::group::SceneGroup;
+    use sg2d::marks::rect::RectMark;
+    use sg2d::scene_graph::SceneGraph;
+    use sg2d::value::Color;
+    use sg2d_text::hit_test::SceneHit;
+    use sg2d_wgpu::egui_widget::{register, SceneGraphView};
+    use std::sync::Arc;
+    use wgpu::{
+        BufferDescriptor, BufferUsages, CommandEncoderDescriptor, Device, Extent3d,
+        ImageCopyBuffer, ImageCopyTexture, ImageDataLayout, LoadOp, MapMode, Operations, Origin3d,
+        Queue, RenderPassColorAttachment, RenderPassDescriptor, StoreOp, Texture, TextureAspect,
+        TextureDescriptor, TextureDimension, TextureFormat, TextureUsages,
+    };
+
+    const FORMAT: TextureFormat = TextureFormat::Rgba8Unorm;
+    const SCREEN_SIZE: u32 = 64;
+
+    /// Blue rect over the left half of a 20 by 10 scene
+    fn scene_graph() -> Arc<SceneGraph> {
+        let scene_graph = SceneGraph::builder(20.0, 10.0)
+            .group(
+                SceneGroup::builder().mark(
+                    RectMark::builder("bars")
+                        .x_scalar(0.0)
+                        .y_scalar(0.0)
+                        .width_scalar(10.0)
+                        .height_scalar(10.0)
+                        .fill_scalar(Color::rgb(0.0, 0.0, 1.0)),
+                ),
+            )
+            .build()
+            .unwrap();
+        Arc::new(scene_graph)
+    }
+
+    /// Run a frame that shows the scene graph at twice its size in the top left of the screen
+    fn run_frame(
+        ctx: &egui::Context,
+        scene_graph: &Arc<SceneGraph>,
+        events: Vec<Event>,
+    ) -> (egui::FullOutput, Option<SceneHit>, Option<SceneHit>) {
+        let mut hits = (None, None);
+        let input = RawInput {
+            screen_rect: Some(Rect::from_min_size(
+                Pos2::ZERO,
+                vec2(SCREEN_SIZE as f32, SCREEN_SIZE as f32),
+            )),
+            events,
+            ..Default::default()
+        };
+        let output = ctx.run(input, |ctx| {
+            egui::CentralPanel::default()
+                .frame(egui::Frame::none())
+                .show(ctx, |ui| {
+                    let response = SceneGraphView::new("chart", scene_graph.clone())
+                        .size(vec2(40.0, 20.0))
+                        .show(ui);
+                    hits = (response.hovered, response.clicked);
+                });
+        });
+        (output, hits.0, hits.1)
+    }
+
+    fn click(pos: Pos2, pressed: bool) -> Event {
+        Event::PointerButton {
+            pos,
+            button: PointerButton::Primary,
+            pressed,
+            modifiers: Modifiers::NONE,
+        }
+    }
+
+    #[test]
+    fn test_hover_and_click() {
+        let ctx = egui::Context::default();
+        let scene_graph = scene_graph();
+        let bar = SceneHit {
+            mark: "bars".to_string(),
+            index: 0,
+            group_path: vec![0],
+        };
+
+        // Points are scaled to scene coordinates, so the bar covers the left 20 points
+        let (_, hovered, _) = run_frame(
+            &ctx,
+            &scene_graph,
+            vec![Event::PointerMoved(pos2(15.0, 5.0))],
+        );
+        assert_eq!(hovered, Some(bar.clone()));
+        let (_, hovered, _) = run_frame(
+            &ctx,
+            &scene_graph,
+            vec![Event::PointerMoved(pos2(25.0, 5.0))],
+        );
+        assert_eq!(hovered, None);
+
+        let pos = pos2(5.0, 15.0);
+        run_frame(&ctx, &scene_graph, vec![Event::PointerMoved(pos)]);
+        let (_, _, clicked) = run_frame(&ctx, &scene_graph, vec![click(pos, true)]);
+        assert_eq!(clicked, None);
+        let (_, _, clicked) = run_frame(&ctx, &scene_graph, vec![click(pos, false)]);
+        assert_eq!(clicked, Some(bar));
+    }
+
+    fn render_state() -> RenderState {
+        let instance = wgpu::Instance::default();
+        let adapter = pollster::block_on(instance.request_adapter(&Default::default()))
+            .or_else(|| {
+                pollster::block_on(instance.request_adapter(&wgpu::RequestAdapterOptions {
+                    force_fallback_adapter: true,
+                    ..Default::default()
+                }))
+            })
+            .unwrap();
+        let (device, queue) =
+            pollster::block_on(adapter.request_device(&Default::default(), None)).unwrap();
+        let renderer = Renderer::new(&device, FORMAT, None, 1);
+        RenderState {
+            adapter: Arc::new(adapter),
+            device: Arc::new(device),
+            queue: Arc::new(queue),
+            target_format: FORMAT,
+            renderer: Arc::new(RwLock::new(renderer)),
+        }
+    }
+
+    /// Read back a texture as rows of RGBA pixels
+    fn read_pixels(device: &Device, queue: &Queue, texture: &Texture) -> Vec<Vec<[u8; 4]>> {
+        // Rows are padded to 256 bytes
+        let padded_row = 256;
+        let buffer = device.create_buffer(&BufferDescriptor {
+            label: None,
+            size: (padded_row * SCREEN_SIZE) as u64,
+            usage: BufferUsages::COPY_DST | BufferUsages::MAP_READ,
+            mapped_at_creation: false,
+        });
+        let mut encoder = device.create_command_encoder(&CommandEncoderDescriptor::default());
+        encoder.copy_texture_to_buffer(
+            ImageCopyTexture {
+                texture,
+                mip_level: 0,
+                origin: Origin3d::ZERO,
+                aspect: TextureAspect::All,
+            },
+            ImageCopyBuffer {
+                buffer: &buffer,
+                layout: ImageDataLayout {
+                    offset: 0,
+                    bytes_per_row: Some(padded_row),
+                    rows_per_image: Some(SCREEN_SIZE),
+                },
+            },
+            texture.size(),
+        );
+        queue.submit(Some(encoder.finish()));
+        let slice = buffer.slice(..);
+        slice.map_async(MapMode::Read, |result| result.unwrap());
+        device.poll(wgpu::Maintain::Wait);
+        let data = slice.get_mapped_range();
+        data.chunks(padded_row as usize)
+            .map(|row| {
+                row[..(SCREEN_SIZE * 4) as usize]
+                    .chunks(4)
+                    .map(|pixel| [pixel[0], pixel[1], pixel[2], pixel[3]])
+                    .collect()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_paint() {
+        let render_state = render_state();
+        register(&render_state, 1);
+        let (device, queue) = (&render_state.device, &render_state.queue);
+
+        let ctx = egui::Context::default();
+        let (output, _, _) = run_frame(&ctx, &scene_graph(), Vec::new());
+        let paint_jobs = ctx.tessellate(output.shapes, output.pixels_per_point);
+        let screen_descriptor = ScreenDescriptor {
+            size_in_pixels: [SCREEN_SIZE, SCREEN_SIZE],
+            pixels_per_point: output.pixels_per_point,
+        };
+
+        let texture = device.create_texture(&TextureDescriptor {
+            label: None,
+            size: Extent3d {
+                width: SCREEN_SIZE,
+                height: SCREEN_SIZE,
+                depth_or_array_layers: 1,
+            },
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: TextureDimension::D2,
+            format: FORMAT,
+            usage: TextureUsages::COPY_SRC | TextureUsages::RENDER_ATTACHMENT,
+            view_formats: &[],
+        });
+        let view = texture.create_view(&Default::default());
+
+        let mut renderer = render_state.renderer.write();
+        for (id, delta) in &output.textures_delta.set {
+            renderer.update_texture(device, queue, *id, delta);
+        }
+        let mut encoder = device.create_command_encoder(&CommandEncoderDescriptor::default());
+        let mut commands =
+            renderer.update_buffers(device, queue, &mut encoder, &paint_jobs, &screen_descriptor);
+        {
+            let mut render_pass = encoder.begin_render_pass(&RenderPassDescriptor {
+                label: None,
+                color_attachments: &[Some(RenderPassColorAttachment {
+                    view: &view,
+                    resolve_target: None,
+                    ops: Operations {
+                        load: LoadOp::Clear(wgpu::Color::GREEN),
+                        store: StoreOp::Store,
+                    },
+                })],
+                depth_stencil_attachment: None,
+                occlusion_query_set: None,
+                timestamp_writes: None,
+            });
+            renderer.render(&mut render_pass, &paint_jobs, &screen_descriptor);
+        }
+        commands.push(encoder.finish());
+        queue.submit(commands);
+
+        // The bar is drawn at twice its size, and the rest of the view shows the panel beneath
+        let pixels = read_pixels(device, queue, &texture);
+        assert_eq!(pixels[10][10], [0, 0, 255, 255]);
+        assert_eq!(pixels[10][30], [0, 255, 0, 255]);
+        assert_eq!(pixels[30][10], [0, 255, 0, 255]);
+    }
+}
@@ -59,7 +59,6 @@ mod test_image_baselines {
         let scene_dims: VegaSceneGraphDims = serde_json::from_str(&scene_dims_str).unwrap();
         let width = scene_dims.width;
         let height = scene_dims.height;
-        let origin = [scene_dims.origin_x, scene_dims.origin_y];
 
         // Read expected png
         let expected_dssim = dssim::load_image(
@@ -71,7 +70,7 @@ mod test_image_baselines {
 
         // Build scene graph
         let scene_graph: SceneGraph = scene_spec
-            .to_scene_graph(origin, width, height)
+            .to_scene_graph_with_dims(&scene_dims)
             .expect("Failed to parse scene graph");
 
         let mut png_canvas = pollster::block_on(PngCanvas::new(width, height, 2.0)).unwrap();
@@ -0,0 +1,112 @@
+#[cfg(test)]
+mod test_instances {
+    use sg2d::marks::rule::RuleMark;
+    use sg2d::marks::symbol::SymbolMark;
+    use sg2d::marks::text::TextMark;
+    use sg2d::value::{Color, ColorSpace, EncodingValue};
+    use sg2d_wgpu::marks::rule::RuleInstance;
+    use sg2d_wgpu::marks::symbol::SymbolInstance;
+    use sg2d_wgpu::marks::text::TextInstance;
+
+    #[test]
+    fn test_symbol_instances() {
+        let mark = SymbolMark {
+            len: 3,
+            x: EncodingValue::Array {
+                values: vec![1.0, 2.0, 3.0],
+            },
+            y: EncodingValue::Scalar { value: 5.0 },
+            fill: EncodingValue::Array {
+                values: vec![Color::WHITE, Color::BLACK, Color::rgb(0.5, 0.5, 0.5)],
+            },
+            stroke: EncodingValue::Scalar {
+                value: Color::rgb(0.5, 0.5, 0.5),
+            },
+            stroke_width: Some(2.0),
+            ..Default::default()
+        };
+        let instances = SymbolInstance::from_spec(&mark, ColorSpace::Linear);
+        assert_eq!(instances.len(), 3);
+        for (i, instance) in instances.iter().enumerate() {
+            assert_eq!(instance.position, [i as f32 + 1.0, 5.0]);
+            assert_eq!(instance.size, *mark.size.item(i));
+            assert_eq!(instance.stroke_width, 2.0);
+            assert_eq!(
+                instance.fill_color,
+                mark.fill.item(i).to_space(ColorSpace::Linear)
+            );
+            assert_eq!(
+                instance.stroke_color,
+                Color::rgb(0.5, 0.5, 0.5).to_space(ColorSpace::Linear)
+            );
+        }
+    }
+
+    #[test]
+    fn test_rule_instances() {
+        let mark = RuleMark {
+            len: 2,
+            x0: EncodingValue::Scalar { value: 1.0 },
+            x1: EncodingValue::Array {
+                values: vec![10.0, 20.0],
+            },
+            stroke_width: EncodingValue::Array {
+                values: vec![1.0, 3.0],
+            },
+            ..Default::default()
+        };
+        let instances = RuleInstance::from_spec(&mark, ColorSpace::Srgb);
+        assert_eq!(
+            instances
+                .iter()
+                .map(|instance| (instance.x0, instance.x1, instance.stroke_width))
+                .collect::<Vec<_>>(),
+            vec![(1.0, 10.0, 1.0), (1.0, 20.0, 3.0)]
+        );
+    }
+
+    #[test]
+    fn test_text_instances() {
+        let mark = TextMark {
+            len: 2,
+            text: EncodingValue::Array {
+                values: vec!["a".to_string(), "b".to_string()],
+            },
+            font_size: EncodingValue::Scalar { value: 14.0 },
+            ..Default::default()
+        };
+        let instances = TextInstance::from_spec(&mark);
+        assert_eq!(
+            instances
+                .iter()
+                .map(|instance| (instance.text.as_str(), instance.font_size))
+                .collect::<Vec<_>>(),
+            vec![("a", 14.0), ("b", 14.0)]
+        );
+    }
+
+    #[test]
+    fn test_text_stroke_opacity() {
+        let mark = TextMark {
+            len: 1,
+            text: EncodingValue::Scalar {
+                value: "a".to_string(),
+            },
+            color: EncodingValue::Scalar {
+                value: Color::rgb(0.0, 0.0, 1.0),
+            },
+            stroke: EncodingValue::Scalar {
+                value: Color::rgb(1.0, 0.0, 0.0),
+            },
+            stroke_width: EncodingValue::Scalar { value: 2.0 },
+            stroke_opacity: EncodingValue::Scalar { value: 0.5 },
+            opacity: EncodingValue::Scalar { value: 0.4 },
+            ..Default::default()
+        };
+        let instance = &TextInstance::from_spec(&mark)[0];
+        assert_eq!(instance.fill_color(), Color::rgba(0.0, 0.0, 1.0, 0.4));
+
+        // The halo fades with the text, as well as with its own opacity
+        assert_eq!(instance.stroke_color(), Color::rgba(1.0, 0.0, 0.0, 0.2));
+    }
+}
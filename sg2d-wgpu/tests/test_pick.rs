@@ -0,0 +1,349 @@
+#[cfg(test)]
+mod test_pick {
+    use rstest::rstest;
+    use sg2d::bounds::Bounds;
+    use sg2d::marks::group::{GroupBounds, SceneGroup};
+    use sg2d::marks::mark::SceneMark;
+    use sg2d::marks::rect::RectMark;
+    use sg2d::marks::rule::RuleMark;
+    use sg2d::marks::symbol::SymbolMark;
+    use sg2d::marks::text::TextMark;
+    use sg2d::scene_graph::SceneGraph;
+    use sg2d::transform::AffineTransform;
+    use sg2d::value::{Color, EncodingValue};
+    use sg2d_text::hit_test::{SceneHit, SceneHitTest};
+    use sg2d_wgpu::canvas::{Canvas, PngCanvas};
+    use sg2d_wgpu::error::Sg2dWgpuError;
+    use sg2d_wgpu::marks::text::TextRendering;
+    use std::collections::HashSet;
+
+    fn group(marks: Vec<SceneMark>) -> SceneGroup {
+        SceneGroup {
+            bounds: GroupBounds {
+                x: 0.0,
+                y: 0.0,
+                width: None,
+                height: None,
+            },
+            transform: Default::default(),
+            marks,
+        }
+    }
+
+    fn hit(mark: &str, index: usize, group_path: Vec<usize>) -> Option<SceneHit> {
+        Some(SceneHit {
+            mark: mark.to_string(),
+            index,
+            group_path,
+        })
+    }
+
+    fn scene_graph() -> SceneGraph {
+        let bars = SceneMark::Rect(RectMark {
+            name: "bars".to_string(),
+            len: 3,
+            x: EncodingValue::Array {
+                values: vec![10.0, 40.0, 70.0],
+            },
+            y: EncodingValue::Scalar { value: 20.0 },
+            width: EncodingValue::Scalar { value: 20.0 },
+            height: EncodingValue::Scalar { value: 60.0 },
+            ..Default::default()
+        });
+        let points = SceneMark::Symbol(SymbolMark {
+            name: "points".to_string(),
+            len: 2,
+            x: EncodingValue::Array {
+                values: vec![50.0, 58.0],
+            },
+            y: EncodingValue::Scalar { value: 50.0 },
+            size: EncodingValue::Scalar { value: 400.0 },
+            fill: EncodingValue::Scalar {
+                value: Color::rgb(1.0, 0.0, 0.0),
+            },
+            ..Default::default()
+        });
+        let rules = SceneMark::Rule(RuleMark {
+            name: "rules".to_string(),
+            x0: EncodingValue::Scalar { value: 0.0 },
+            y0: EncodingValue::Scalar { value: 90.0 },
+            x1: EncodingValue::Scalar { value: 120.0 },
+            y1: EncodingValue::Scalar { value: 90.0 },
+            stroke_width: EncodingValue::Scalar { value: 4.0 },
+            ..Default::default()
+        });
+        let labels = SceneMark::Text(Box::new(TextMark {
+            name: "labels".to_string(),
+            text: EncodingValue::Scalar {
+                value: "Label".to_string(),
+            },
+            x: EncodingValue::Scalar { value: 100.0 },
+            y: EncodingValue::Scalar { value: 10.0 },
+            ..Default::default()
+        }));
+        SceneGraph {
+            groups: vec![
+                group(vec![bars, SceneMark::Group(group(vec![points]))]),
+                group(vec![rules, labels]),
+            ],
+            width: 120.0,
+            height: 100.0,
+            background: None,
+        }
+    }
+
+    #[test]
+    fn test_pick_point() {
+        let scene_graph = scene_graph();
+        let mut png_canvas = pollster::block_on(PngCanvas::new(120.0, 100.0, 2.0)).unwrap();
+        png_canvas.set_picking(true);
+        png_canvas.set_scene(&scene_graph).unwrap();
+
+        let cases = [
+            // Bars beneath the points
+            ([15.0, 25.0], hit("bars", 0, vec![0])),
+            ([75.0, 75.0], hit("bars", 2, vec![0])),
+            // Overlapping points resolve to the later one, and report their nested group
+            ([54.0, 50.0], hit("points", 1, vec![0, 1])),
+            ([42.0, 50.0], hit("points", 0, vec![0, 1])),
+            ([52.0, 66.0], hit("bars", 1, vec![0])),
+            ([30.0, 90.0], hit("rules", 0, vec![1])),
+            ([30.0, 95.0], None),
+            ([100.0, 50.0], None),
+        ];
+        for (point, expected) in cases {
+            let picked = pollster::block_on(png_canvas.pick(point[0], point[1])).unwrap();
+            assert_eq!(picked, expected, "{point:?}");
+            // GPU picking agrees with CPU hit testing away from edges
+            assert_eq!(scene_graph.hit_test(point), expected, "{point:?}");
+        }
+
+        // Text is picked within its block, and points outside the canvas hit nothing
+        assert_eq!(
+            pollster::block_on(png_canvas.pick(101.0, 5.0)).unwrap(),
+            hit("labels", 0, vec![1])
+        );
+        assert_eq!(
+            scene_graph.hit_test([101.0, 5.0]),
+            hit("labels", 0, vec![1])
+        );
+        assert_eq!(
+            pollster::block_on(png_canvas.pick(-5.0, 50.0)).unwrap(),
+            None
+        );
+        assert_eq!(
+            pollster::block_on(png_canvas.pick(200.0, 50.0)).unwrap(),
+            None
+        );
+    }
+
+    #[test]
+    fn test_pick_rect() {
+        let mut png_canvas = pollster::block_on(PngCanvas::new(120.0, 100.0, 1.0)).unwrap();
+        png_canvas.set_picking(true);
+        png_canvas.set_scene(&scene_graph()).unwrap();
+
+        // Brush across the bottom of the first two bars
+        let picked =
+            pollster::block_on(png_canvas.pick_rect(&Bounds::new(12.0, 70.0, 42.0, 75.0))).unwrap();
+        assert_eq!(
+            picked,
+            vec![
+                hit("bars", 0, vec![0]).unwrap(),
+                hit("bars", 1, vec![0]).unwrap(),
+            ]
+        );
+
+        let picked =
+            pollster::block_on(png_canvas.pick_rect(&Bounds::new(0.0, 0.0, 120.0, 100.0))).unwrap();
+        assert_eq!(picked.len(), 7);
+
+        // Setting a new scene replaces the pick ids
+        png_canvas
+            .set_scene(&SceneGraph {
+                groups: vec![],
+                ..scene_graph()
+            })
+            .unwrap();
+        assert_eq!(
+            pollster::block_on(png_canvas.pick(15.0, 25.0)).unwrap(),
+            None
+        );
+    }
+
+    /// Clipped, transparent, stroked and rotated marks in transformed groups
+    fn overlapping_scene_graph() -> SceneGraph {
+        SceneGraph::builder(120.0, 100.0)
+            .group(
+                SceneGroup::builder()
+                    .origin(10.0, 10.0)
+                    .size(60.0, 50.0)
+                    .mark(
+                        RectMark::builder("bars")
+                            .x(vec![0.0, 20.0, 40.0])
+                            .y_scalar(10.0)
+                            .width_scalar(25.0)
+                            .height_scalar(70.0)
+                            .fill(vec![
+                                Color::rgb(0.0, 0.0, 1.0),
+                                Color::TRANSPARENT,
+                                Color::rgba(0.0, 0.0, 1.0, 0.5),
+                            ])
+                            .clip(true),
+                    )
+                    .mark(
+                        SymbolMark::builder("points")
+                            .x(vec![15.0, 50.0])
+                            .y_scalar(30.0)
+                            .size_scalar(900.0)
+                            .fill(vec![Color::TRANSPARENT, Color::rgb(1.0, 0.0, 0.0)])
+                            .stroke_scalar(Color::BLACK)
+                            .stroke_width(4.0),
+                    )
+                    .mark(
+                        RuleMark::builder("rules")
+                            .x0(vec![-10.0, 0.0])
+                            .y0(vec![45.0, 0.0])
+                            .x1(vec![90.0, 60.0])
+                            .y1(vec![45.0, 50.0])
+                            .stroke(vec![Color::BLACK, Color::TRANSPARENT])
+                            .stroke_width_scalar(4.0)
+                            .clip(true),
+                    )
+                    .mark(
+                        TextMark::builder("labels")
+                            .text_scalar("Clipped label")
+                            .x_scalar(30.0)
+                            .y_scalar(5.0)
+                            .angle_scalar(30.0)
+                            .font_size_scalar(12.0)
+                            .stroke_width_scalar(4.0)
+                            .clip(true),
+                    ),
+            )
+            .group(
+                SceneGroup::builder()
+                    .origin(70.0, 20.0)
+                    .transform(
+                        AffineTransform::rotate(15.0).then(&AffineTransform::scale(1.2, 0.8)),
+                    )
+                    .mark(
+                        RectMark::builder("boxes")
+                            .x(vec![0.0, 15.0])
+                            .y_scalar(0.0)
+                            .width_scalar(20.0)
+                            .height_scalar(40.0),
+                    )
+                    .mark(
+                        TextMark::builder("titles")
+                            .text_scalar("Title")
+                            .x_scalar(5.0)
+                            .y_scalar(60.0)
+                            .angle_scalar(-20.0)
+                            .font_size_scalar(14.0),
+                    ),
+            )
+            .build()
+            .unwrap()
+    }
+
+    #[rstest]
+    #[case(TextRendering::Glyphs)]
+    #[case(TextRendering::Paths)]
+    fn test_pick_matches_hit_test(#[case] text_rendering: TextRendering) {
+        let scene_graph = overlapping_scene_graph();
+        let mut png_canvas = pollster::block_on(PngCanvas::new(120.0, 100.0, 2.0)).unwrap();
+        png_canvas.set_text_rendering(text_rendering);
+        png_canvas.set_picking(true);
+        png_canvas.set_scene(&scene_graph).unwrap();
+
+        // Sample the centers of pixels across the canvas. GPU picking only disagrees with CPU
+        // hit testing where an edge passes between the point and the pixel center.
+        let points = (0..240)
+            .step_by(3)
+            .flat_map(|x| (0..200).step_by(3).map(move |y| (x, y)))
+            .map(|(x, y)| [(x as f32 + 0.5) / 2.0, (y as f32 + 0.5) / 2.0])
+            .collect::<Vec<_>>();
+        let mut hit_marks = HashSet::new();
+        let mismatches = points
+            .iter()
+            .filter(|point| {
+                let picked = pollster::block_on(png_canvas.pick(point[0], point[1])).unwrap();
+                let expected = scene_graph.hit_test(**point);
+                if let Some(hit) = &expected {
+                    hit_marks.insert(hit.mark.clone());
+                }
+                picked != expected
+            })
+            .count();
+        assert!(
+            mismatches * 200 < points.len(),
+            "{mismatches} of {} points differ",
+            points.len()
+        );
+        assert_eq!(
+            hit_marks,
+            HashSet::from(
+                ["bars", "points", "rules", "labels", "boxes", "titles"].map(String::from)
+            )
+        );
+
+        let cases = [
+            // The transparent bar and the transparent fill of the first point aren't picked
+            ([42.0, 50.0], None),
+            ([25.0, 40.0], hit("bars", 0, vec![0])),
+            ([10.5, 40.0], hit("points", 0, vec![0])),
+            // The translucent bar is picked, and so is the text block above it
+            ([52.0, 58.0], hit("bars", 2, vec![0])),
+            ([60.0, 25.0], hit("labels", 0, vec![0])),
+            // The bars are clipped to the group, and the rule overflows it
+            ([15.0, 65.0], None),
+            ([5.0, 55.0], None),
+            ([40.0, 55.0], hit("rules", 0, vec![0])),
+        ];
+        for (point, expected) in cases {
+            let picked = pollster::block_on(png_canvas.pick(point[0], point[1])).unwrap();
+            assert_eq!(picked, expected, "{point:?}");
+            assert_eq!(scene_graph.hit_test(point), expected, "{point:?}");
+        }
+    }
+
+    #[test]
+    fn test_invisible_text_not_picked() {
+        // The second label is hidden by its opacity and the third has a transparent fill and
+        // halo, so the first label, beneath them, is picked
+        let scene_graph = SceneGraph::builder(120.0, 100.0)
+            .group(
+                SceneGroup::builder().mark(
+                    TextMark::builder("labels")
+                        .text_scalar("Label")
+                        .x_scalar(60.0)
+                        .y_scalar(50.0)
+                        .font_size_scalar(16.0)
+                        .color(vec![Color::BLACK, Color::BLACK, Color::TRANSPARENT])
+                        .opacity(vec![1.0, 0.0, 1.0])
+                        .stroke_scalar(Color::TRANSPARENT)
+                        .stroke_width_scalar(4.0),
+                ),
+            )
+            .build()
+            .unwrap();
+        let mut png_canvas = pollster::block_on(PngCanvas::new(120.0, 100.0, 2.0)).unwrap();
+        png_canvas.set_picking(true);
+        png_canvas.set_scene(&scene_graph).unwrap();
+        let expected = hit("labels", 0, vec![0]);
+        let picked = pollster::block_on(png_canvas.pick(62.0, 45.0)).unwrap();
+        assert_eq!(picked, expected);
+        assert_eq!(scene_graph.hit_test([62.0, 45.0]), expected);
+    }
+
+    #[test]
+    fn test_picking_disabled() {
+        let mut png_canvas = pollster::block_on(PngCanvas::new(120.0, 100.0, 1.0)).unwrap();
+        png_canvas.set_scene(&scene_graph()).unwrap();
+        assert!(matches!(
+            pollster::block_on(png_canvas.pick(15.0, 25.0)),
+            Err(Sg2dWgpuError::PickingDisabled)
+        ));
+    }
+}
@@ -0,0 +1,247 @@
+#[cfg(test)]
+mod test_renderer {
+    use sg2d::marks::group::SceneGroup;
+    use sg2d::marks::rect::RectMark;
+    use sg2d::marks::symbol::SymbolMark;
+    use sg2d::marks::text::TextMark;
+    use sg2d::scene_graph::SceneGraph;
+    use sg2d::value::Color;
+    use sg2d_wgpu::marks::pipeline::PipelineCache;
+    use sg2d_wgpu::renderer::{RenderTarget, SceneRenderer, Viewport};
+    use std::sync::Arc;
+    use wgpu::{
+        BufferDescriptor, BufferUsages, CommandEncoderDescriptor, Device, Extent3d,
+        ImageCopyBuffer, ImageCopyTexture, ImageDataLayout, LoadOp, MapMode, Operations, Origin3d,
+        Queue, RenderPassColorAttachment, RenderPassDescriptor, StoreOp, Texture, TextureAspect,
+        TextureDescriptor, TextureDimension, TextureFormat, TextureUsages,
+    };
+
+    const FORMAT: TextureFormat = TextureFormat::Rgba8Unorm;
+    const WIDTH: u32 = 40;
+    const HEIGHT: u32 = 20;
+
+    /// Device and queue standing in for those of a host application
+    fn host_device() -> (Device, Queue) {
+        let instance = wgpu::Instance::default();
+        let adapter = pollster::block_on(instance.request_adapter(&Default::default()))
+            .or_else(|| {
+                pollster::block_on(instance.request_adapter(&wgpu::RequestAdapterOptions {
+                    force_fallback_adapter: true,
+                    ..Default::default()
+                }))
+            })
+            .unwrap();
+        pollster::block_on(adapter.request_device(&Default::default(), None)).unwrap()
+    }
+
+    /// Host texture cleared to green
+    fn host_texture(device: &Device, queue: &Queue) -> Texture {
+        let texture = device.create_texture(&TextureDescriptor {
+            label: None,
+            size: Extent3d {
+                width: WIDTH,
+                height: HEIGHT,
+                depth_or_array_layers: 1,
+            },
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: TextureDimension::D2,
+            format: FORMAT,
+            usage: TextureUsages::COPY_SRC | TextureUsages::RENDER_ATTACHMENT,
+            view_formats: &[],
+        });
+        let view = texture.create_view(&Default::default());
+        let mut encoder = device.create_command_encoder(&CommandEncoderDescriptor::default());
+        encoder.begin_render_pass(&RenderPassDescriptor {
+            label: None,
+            color_attachments: &[Some(RenderPassColorAttachment {
+                view: &view,
+                resolve_target: None,
+                ops: Operations {
+                    load: LoadOp::Clear(wgpu::Color::GREEN),
+                    store: StoreOp::Store,
+                },
+            })],
+            depth_stencil_attachment: None,
+            occlusion_query_set: None,
+            timestamp_writes: None,
+        });
+        queue.submit(Some(encoder.finish()));
+        texture
+    }
+
+    /// Read back the host texture as rows of RGBA pixels
+    fn read_pixels(device: &Device, queue: &Queue, texture: &Texture) -> Vec<Vec<[u8; 4]>> {
+        // Rows are padded to 256 bytes
+        let padded_row = 256;
+        let buffer = device.create_buffer(&BufferDescriptor {
+            label: None,
+            size: (padded_row * HEIGHT) as u64,
+            usage: BufferUsages::COPY_DST | BufferUsages::MAP_READ,
+            mapped_at_creation: false,
+        });
+        let mut encoder = device.create_command_encoder(&CommandEncoderDescriptor::default());
+        encoder.copy_texture_to_buffer(
+            ImageCopyTexture {
+                texture,
+                mip_level: 0,
+                origin: Origin3d::ZERO,
+                aspect: TextureAspect::All,
+            },
+            ImageCopyBuffer {
+                buffer: &buffer,
+                layout: ImageDataLayout {
+                    offset: 0,
+                    bytes_per_row: Some(padded_row),
+                    rows_per_image: Some(HEIGHT),
+                },
+            },
+            texture.size(),
+        );
+        queue.submit(Some(encoder.finish()));
+        let slice = buffer.slice(..);
+        slice.map_async(MapMode::Read, |result| result.unwrap());
+        device.poll(wgpu::Maintain::Wait);
+        let data = slice.get_mapped_range();
+        data.chunks(padded_row as usize)
+            .map(|row| {
+                row[..(WIDTH * 4) as usize]
+                    .chunks(4)
+                    .map(|pixel| [pixel[0], pixel[1], pixel[2], pixel[3]])
+                    .collect()
+            })
+            .collect()
+    }
+
+    /// Blue rect over the left half of a 10 by 10 scene, with a red circle in the right half
+    fn scene_graph() -> SceneGraph {
+        SceneGraph::builder(10.0, 10.0)
+            .group(
+                SceneGroup::builder()
+                    .mark(
+                        RectMark::builder("bars")
+                            .x_scalar(0.0)
+                            .y_scalar(0.0)
+                            .width_scalar(5.0)
+                            .height_scalar(10.0)
+                            .fill_scalar(Color::rgb(0.0, 0.0, 1.0)),
+                    )
+                    .mark(
+                        SymbolMark::builder("points")
+                            .x(vec![7.5])
+                            .y(vec![5.0])
+                            .size_scalar(16.0)
+                            .fill_scalar(Color::rgb(1.0, 0.0, 0.0)),
+                    ),
+            )
+            .build()
+            .unwrap()
+    }
+
+    /// Draw the scene into a host texture, and read the texture back
+    fn render(
+        scene_graph: &SceneGraph,
+        background: Option<Color>,
+        viewport: Option<Viewport>,
+    ) -> Vec<Vec<[u8; 4]>> {
+        let (device, queue) = host_device();
+        let texture = host_texture(&device, &queue);
+        let view = texture.create_view(&Default::default());
+        let mut renderer = SceneRenderer::new(&device, &queue, FORMAT, 1, 1.0);
+        renderer.set_background(background);
+        renderer.set_scene(&device, &queue, scene_graph).unwrap();
+        assert_eq!(renderer.size(), [scene_graph.width, scene_graph.height]);
+
+        let mut encoder = device.create_command_encoder(&CommandEncoderDescriptor::default());
+        renderer
+            .render(
+                &device,
+                &queue,
+                &mut encoder,
+                &RenderTarget {
+                    view: &view,
+                    resolve_target: None,
+                    viewport,
+                },
+            )
+            .unwrap();
+        queue.submit(Some(encoder.finish()));
+        read_pixels(&device, &queue, &texture)
+    }
+
+    #[test]
+    fn test_shared_pipelines() {
+        let (device, queue) = host_device();
+        let pipelines = Arc::new(PipelineCache::new(&device));
+        let mut first =
+            SceneRenderer::with_pipelines(&device, &queue, pipelines.clone(), FORMAT, 1, 1.0);
+        first.set_scene(&device, &queue, &scene_graph()).unwrap();
+        let pipeline_count = pipelines.pipeline_count();
+        assert!(pipeline_count > 0);
+
+        let mut second =
+            SceneRenderer::with_pipelines(&device, &queue, pipelines.clone(), FORMAT, 1, 2.0);
+        second.set_scene(&device, &queue, &scene_graph()).unwrap();
+        assert_eq!(pipelines.pipeline_count(), pipeline_count);
+    }
+
+    #[test]
+    fn test_viewport() {
+        let pixels = render(
+            &scene_graph(),
+            None,
+            Some(Viewport {
+                x: 20.0,
+                y: 5.0,
+                width: 10.0,
+                height: 10.0,
+            }),
+        );
+
+        // The host's content is kept outside of the viewport, and under the scene
+        let green = [0, 255, 0, 255];
+        assert_eq!(pixels[2][2], green);
+        assert_eq!(pixels[10][10], green);
+        assert_eq!(pixels[10][35], green);
+        assert_eq!(pixels[10][22], [0, 0, 255, 255]);
+        assert_eq!(pixels[10][27], [255, 0, 0, 255]);
+        assert_eq!(pixels[6][28], green);
+    }
+
+    #[test]
+    fn test_background() {
+        // Without a viewport, the scene is stretched over the whole target
+        let pixels = render(&scene_graph(), Some(Color::rgb(1.0, 1.0, 1.0)), None);
+        assert_eq!(pixels[10][5], [0, 0, 255, 255]);
+        assert_eq!(pixels[2][22], [255, 255, 255, 255]);
+    }
+
+    #[test]
+    fn test_text_halo() {
+        // The halo is drawn into its layer in the host's encoder, before the scene pass
+        // composites it
+        let scene_graph = SceneGraph::builder(WIDTH as f32, HEIGHT as f32)
+            .group(
+                SceneGroup::builder().mark(
+                    TextMark::builder("label")
+                        .text_scalar("WW")
+                        .x_scalar(2.0)
+                        .y_scalar(18.0)
+                        .font_size_scalar(16.0)
+                        .color_scalar(Color::TRANSPARENT)
+                        .stroke_scalar(Color::rgb(1.0, 0.0, 0.0))
+                        .stroke_width_scalar(3.0),
+                ),
+            )
+            .build()
+            .unwrap();
+        let pixels = render(&scene_graph, None, None);
+        let red = pixels
+            .iter()
+            .flatten()
+            .filter(|pixel| **pixel == [255, 0, 0, 255])
+            .count();
+        assert!(red > 20, "{red}");
+        assert_eq!(pixels[0][WIDTH as usize - 1], [0, 255, 0, 255]);
+    }
+}
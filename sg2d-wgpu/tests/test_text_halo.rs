@@ -0,0 +1,57 @@
+#[cfg(test)]
+mod test_text_halo {
+    use sg2d::marks::group::SceneGroup;
+    use sg2d::marks::text::TextMark;
+    use sg2d::scene_graph::SceneGraph;
+    use sg2d::value::Color;
+    use sg2d_wgpu::canvas::{Canvas, PngCanvas};
+
+    /// Green channel of every pixel of text with a thick red halo and no fill
+    fn halo_greens(stroke_width: f32, stroke_opacity: f32, opacity: f32) -> Vec<u8> {
+        let scene_graph = SceneGraph::builder(160.0, 60.0)
+            .group(
+                SceneGroup::builder().mark(
+                    TextMark::builder("label")
+                        .text_scalar("WAVE@")
+                        .x_scalar(10.0)
+                        .y_scalar(45.0)
+                        .font_size_scalar(40.0)
+                        .color_scalar(Color::TRANSPARENT)
+                        .stroke_scalar(Color::rgb(1.0, 0.0, 0.0))
+                        .stroke_width_scalar(stroke_width)
+                        .stroke_opacity_scalar(stroke_opacity)
+                        .opacity_scalar(opacity),
+                ),
+            )
+            .build()
+            .unwrap();
+        let mut png_canvas = pollster::block_on(PngCanvas::new(160.0, 60.0, 2.0)).unwrap();
+        png_canvas.set_scene(&scene_graph).unwrap();
+        let img = pollster::block_on(png_canvas.render()).unwrap();
+        img.pixels().map(|pixel| pixel.0[1]).collect()
+    }
+
+    #[test]
+    fn test_overlapping_strokes_blend_once() {
+        // Red at half opacity over white has a green of 127 wherever the halo covers a pixel.
+        // The strokes of each outline overlap at every turn, and blending them twice would
+        // leave darker seams there.
+        let greens = halo_greens(6.0, 0.5, 1.0);
+        assert!(greens.iter().filter(|green| **green == 127).count() > 1000);
+        let darkest = greens.iter().min().unwrap();
+        assert!(*darkest >= 126, "{darkest}");
+    }
+
+    #[test]
+    fn test_halo_fades_with_text_opacity() {
+        let greens = halo_greens(6.0, 1.0, 0.5);
+        assert!(greens.iter().filter(|green| **green == 127).count() > 1000);
+        let darkest = greens.iter().min().unwrap();
+        assert!(*darkest >= 126, "{darkest}");
+    }
+
+    #[test]
+    fn test_no_halo_without_stroke_width() {
+        assert!(halo_greens(0.0, 1.0, 1.0).iter().all(|green| *green == 255));
+    }
+}
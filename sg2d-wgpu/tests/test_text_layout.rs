@@ -0,0 +1,243 @@
+#[cfg(test)]
+mod test_text_layout {
+    use image::RgbaImage;
+    use rstest::rstest;
+    use sg2d::marks::group::{GroupBounds, SceneGroup};
+    use sg2d::marks::mark::SceneMark;
+    use sg2d::marks::text::{TextAlignSpec, TextDirectionSpec, TextMark};
+    use sg2d::scene_graph::SceneGraph;
+    use sg2d::value::EncodingValue;
+    use sg2d_wgpu::canvas::{Canvas, PngCanvas};
+    use sg2d_wgpu::marks::text::TextRendering;
+
+    const SCALE: f32 = 2.0;
+
+    /// Render a single text mark on a transparent background
+    fn render_text(mark: TextMark, text_rendering: TextRendering) -> RgbaImage {
+        let scene_graph = SceneGraph {
+            groups: vec![SceneGroup {
+                bounds: GroupBounds {
+                    x: 0.0,
+                    y: 0.0,
+                    width: None,
+                    height: None,
+                },
+                transform: Default::default(),
+                marks: vec![SceneMark::Text(Box::new(mark))],
+            }],
+            width: 300.0,
+            height: 100.0,
+            background: None,
+        };
+
+        let mut png_canvas = pollster::block_on(PngCanvas::new(300.0, 100.0, SCALE)).unwrap();
+        png_canvas.set_text_rendering(text_rendering);
+        png_canvas.set_scene(&scene_graph).unwrap();
+        pollster::block_on(png_canvas.render()).expect("Failed to render PNG image")
+    }
+
+    /// Render a single text mark and return the (left, top, right, bottom) extent of the ink,
+    /// in logical pixels
+    fn ink_bounds(mark: TextMark, text_rendering: TextRendering) -> [f32; 4] {
+        let img = render_text(mark, text_rendering);
+        let bounds = img
+            .enumerate_pixels()
+            .filter(|(_, _, pixel)| pixel.0[0] < 128)
+            .fold(
+                [f32::MAX, f32::MAX, f32::MIN, f32::MIN],
+                |[x0, y0, x1, y1], (x, y, _)| {
+                    let (x, y) = (x as f32 / SCALE, y as f32 / SCALE);
+                    [x0.min(x), y0.min(y), x1.max(x), y1.max(y)]
+                },
+            );
+        assert!(bounds[0] <= bounds[2], "No ink rendered");
+        bounds
+    }
+
+    /// Render a single text instance and return the horizontal pixel extent of the ink,
+    /// in logical pixels
+    fn ink_extent(text: &str, x: f32, align: TextAlignSpec, dir: TextDirectionSpec) -> (f32, f32) {
+        let [left, _, right, _] = ink_bounds(text_mark(text, x, align, dir), TextRendering::Glyphs);
+        (left, right)
+    }
+
+    /// Like `ink_extent`, with text drawn as tessellated glyph outlines
+    fn path_ink_extent(
+        text: &str,
+        x: f32,
+        align: TextAlignSpec,
+        dir: TextDirectionSpec,
+    ) -> (f32, f32) {
+        let [left, _, right, _] = ink_bounds(text_mark(text, x, align, dir), TextRendering::Paths);
+        (left, right)
+    }
+
+    fn text_mark(text: &str, x: f32, align: TextAlignSpec, dir: TextDirectionSpec) -> TextMark {
+        TextMark {
+            len: 1,
+            text: EncodingValue::Scalar {
+                value: text.to_string(),
+            },
+            x: EncodingValue::Scalar { value: x },
+            y: EncodingValue::Scalar { value: 30.0 },
+            align: EncodingValue::Scalar { value: align },
+            dir: EncodingValue::Scalar { value: dir },
+            font_size: EncodingValue::Scalar { value: 16.0 },
+            ..Default::default()
+        }
+    }
+
+    #[rstest(
+        text,
+        dir,
+        case("שלום עולם", TextDirectionSpec::Rtl),
+        case("مرحبا بالعالم", TextDirectionSpec::Rtl),
+        case("Hello עולם", TextDirectionSpec::Rtl),
+        case("Hello world", TextDirectionSpec::Ltr),
+        case("नमस्ते दुनिया", TextDirectionSpec::Ltr)
+    )]
+    fn test_text_alignment(text: &str, dir: TextDirectionSpec) {
+        // Ink should start at the anchor for left alignment, end there for right alignment,
+        // and straddle it for center alignment
+        let (left, _) = ink_extent(text, 150.0, TextAlignSpec::Left, dir);
+        assert!((left - 150.0).abs() < 3.0, "left: {left}");
+
+        let (_, right) = ink_extent(text, 150.0, TextAlignSpec::Right, dir);
+        assert!((right - 150.0).abs() < 3.0, "right: {right}");
+
+        let (start, end) = ink_extent(text, 150.0, TextAlignSpec::Center, dir);
+        assert!(
+            ((start + end) / 2.0 - 150.0).abs() < 3.0,
+            "center: {start}..{end}"
+        );
+    }
+
+    #[rstest(
+        text,
+        dir,
+        case("שלום עולם", TextDirectionSpec::Rtl),
+        case("مرحبا بالعالم", TextDirectionSpec::Rtl),
+        case("Hello עולם", TextDirectionSpec::Rtl),
+        case("Hello world", TextDirectionSpec::Ltr),
+        case("नमस्ते दुनिया", TextDirectionSpec::Ltr)
+    )]
+    fn test_text_alignment_paths(text: &str, dir: TextDirectionSpec) {
+        // Outline paths are placed with the same layout as rasterized glyphs
+        let (left, _) = path_ink_extent(text, 150.0, TextAlignSpec::Left, dir);
+        assert!((left - 150.0).abs() < 3.0, "left: {left}");
+
+        let (_, right) = path_ink_extent(text, 150.0, TextAlignSpec::Right, dir);
+        assert!((right - 150.0).abs() < 3.0, "right: {right}");
+
+        let (start, end) = path_ink_extent(text, 150.0, TextAlignSpec::Center, dir);
+        assert!(
+            ((start + end) / 2.0 - 150.0).abs() < 3.0,
+            "center: {start}..{end}"
+        );
+
+        // Both modes put the ink in the same place
+        let glyphs = ink_extent(text, 150.0, TextAlignSpec::Left, dir);
+        let paths = path_ink_extent(text, 150.0, TextAlignSpec::Left, dir);
+        assert!((glyphs.0 - paths.0).abs() < 1.0, "{glyphs:?} != {paths:?}");
+        assert!((glyphs.1 - paths.1).abs() < 1.0, "{glyphs:?} != {paths:?}");
+    }
+
+    #[test]
+    fn test_rotated_text_paths() {
+        let mark = |angle: f32| TextMark {
+            len: 1,
+            text: EncodingValue::Scalar {
+                value: "Rotated".to_string(),
+            },
+            x: EncodingValue::Scalar { value: 150.0 },
+            y: EncodingValue::Scalar { value: 20.0 },
+            angle: EncodingValue::Scalar { value: angle },
+            font_size: EncodingValue::Scalar { value: 16.0 },
+            ..Default::default()
+        };
+        let [x0, y0, x1, y1] = ink_bounds(mark(0.0), TextRendering::Paths);
+        assert!(x1 - x0 > 2.0 * (y1 - y0));
+
+        // Rotated text runs downward from the anchor
+        let [x0, y0, x1, y1] = ink_bounds(mark(90.0), TextRendering::Paths);
+        assert!(y1 - y0 > 2.0 * (x1 - x0));
+        assert!((y0 - 20.0).abs() < 3.0);
+    }
+
+    #[test]
+    #[cfg(feature = "bundled-emoji-font")]
+    fn test_color_emoji() {
+        // Color glyphs keep their own colors instead of taking the black fill
+        let mark = TextMark {
+            len: 1,
+            text: EncodingValue::Scalar {
+                value: "😀".to_string(),
+            },
+            x: EncodingValue::Scalar { value: 150.0 },
+            y: EncodingValue::Scalar { value: 50.0 },
+            font_size: EncodingValue::Scalar { value: 32.0 },
+            ..Default::default()
+        };
+        let img = render_text(mark, TextRendering::Glyphs);
+        let colored = img
+            .pixels()
+            .filter(|pixel| {
+                let [r, g, b, a] = pixel.0;
+                a == 255 && r.max(g).max(b) - r.min(g).min(b) > 64
+            })
+            .count();
+        assert!(colored > 100, "colored pixels: {colored}");
+    }
+
+    #[test]
+    fn test_many_text_marks_render_repeatedly() {
+        // Every mark shares the canvas glyph atlas, and rendering again without a new scene
+        // reuses the prepared text
+        let groups = (0..40)
+            .map(|i| SceneGroup {
+                bounds: GroupBounds {
+                    x: 0.0,
+                    y: 0.0,
+                    width: None,
+                    height: None,
+                },
+                transform: Default::default(),
+                marks: vec![SceneMark::Text(Box::new(TextMark {
+                    len: 1,
+                    text: EncodingValue::Scalar {
+                        value: format!("Label {i}"),
+                    },
+                    x: EncodingValue::Scalar {
+                        value: 10.0 + 70.0 * (i % 4) as f32,
+                    },
+                    y: EncodingValue::Scalar {
+                        value: 20.0 + 20.0 * (i / 4) as f32,
+                    },
+                    font_size: EncodingValue::Scalar { value: 12.0 },
+                    ..Default::default()
+                }))],
+            })
+            .collect::<Vec<_>>();
+        let scene_graph = SceneGraph {
+            groups,
+            width: 300.0,
+            height: 220.0,
+            background: None,
+        };
+
+        let mut png_canvas = pollster::block_on(PngCanvas::new(300.0, 220.0, SCALE)).unwrap();
+        png_canvas.set_scene(&scene_graph).unwrap();
+        let first = pollster::block_on(png_canvas.render()).unwrap();
+        let second = pollster::block_on(png_canvas.render()).unwrap();
+        assert!(first.pixels().any(|pixel| pixel.0[0] < 128));
+        assert_eq!(first, second);
+
+        // Setting the scene again prepares the text from scratch with the same result
+        png_canvas.set_scene(&scene_graph).unwrap();
+        let third = pollster::block_on(png_canvas.render()).unwrap();
+        assert_eq!(first, third);
+    }
+
+    #[test]
+    fn test_marker() {} // Help IDE detect test module
+}
@@ -0,0 +1,138 @@
+#[cfg(test)]
+mod test_tiled {
+    use sg2d::marks::group::SceneGroup;
+    use sg2d::marks::mark::SceneMark;
+    use sg2d::marks::rect::RectMark;
+    use sg2d::marks::rule::RuleMark;
+    use sg2d::marks::symbol::SymbolMark;
+    use sg2d::marks::text::TextMark;
+    use sg2d::scene_graph::SceneGraph;
+    use sg2d::value::{Color, EncodingValue};
+    use sg2d_wgpu::canvas::{Canvas, PngCanvas};
+    use sg2d_wgpu::config::CanvasConfig;
+    use sg2d_wgpu::context::RenderContext;
+    use sg2d_wgpu::tiled::TiledRenderer;
+    use std::sync::Arc;
+
+    /// Marks that cross tile boundaries in two clipped groups
+    fn scene_graph() -> SceneGraph {
+        SceneGraph::builder(50.0, 30.0)
+            .background(Color::rgb(0.9, 0.9, 0.9))
+            .group(
+                SceneGroup::builder()
+                    .origin(3.0, 2.0)
+                    .size(40.0, 25.0)
+                    .mark(
+                        RectMark::builder("bars")
+                            .x(vec![0.0, 12.0, 24.0])
+                            .y(vec![5.0, 10.0, 0.0])
+                            .width_scalar(9.0)
+                            .height(vec![20.0, 15.0, 25.0])
+                            .fill_scalar([0.2, 0.4, 0.8, 0.7])
+                            .clip(true),
+                    )
+                    .mark(
+                        SymbolMark::builder("points")
+                            .x(vec![5.0, 21.0, 37.0])
+                            .y(vec![8.0, 16.0, 4.0])
+                            .size_scalar(60.0)
+                            .fill_scalar([1.0, 0.0, 0.0, 1.0]),
+                    )
+                    .mark(
+                        RuleMark::builder("line")
+                            .x0_scalar(0.0)
+                            .y0_scalar(25.0)
+                            .x1_scalar(40.0)
+                            .y1_scalar(0.0)
+                            .stroke_width_scalar(2.0),
+                    ),
+            )
+            .group(
+                SceneGroup::builder().origin(20.0, 20.0).mark(
+                    TextMark::builder("label")
+                        .text_scalar("Tiles")
+                        .x_scalar(0.0)
+                        .y_scalar(0.0)
+                        .font_size_scalar(8.0),
+                ),
+            )
+            .build()
+            .unwrap()
+    }
+
+    fn context() -> Arc<RenderContext> {
+        Arc::new(pollster::block_on(RenderContext::new(&CanvasConfig::default())).unwrap())
+    }
+
+    /// Fraction of pixels that differ by more than a couple of levels in any channel
+    fn mismatch(a: &image::RgbaImage, b: &image::RgbaImage) -> f64 {
+        let differing = a
+            .pixels()
+            .zip(b.pixels())
+            .filter(|(a, b)| a.0.iter().zip(b.0).any(|(a, b)| a.abs_diff(b) > 2))
+            .count();
+        differing as f64 / (a.width() * a.height()) as f64
+    }
+
+    #[test]
+    fn test_matches_single_texture() {
+        let context = context();
+        let sg = scene_graph();
+
+        let mut png_canvas = PngCanvas::with_context(context.clone(), 50.0, 30.0, 1.5).unwrap();
+        png_canvas.set_scene(&sg).unwrap();
+        let expected = pollster::block_on(png_canvas.render()).unwrap();
+
+        let mut tiled = TiledRenderer::new(context, 1.5, 16).unwrap();
+        let img = pollster::block_on(tiled.render(&sg)).unwrap();
+        assert_eq!(img.dimensions(), expected.dimensions());
+        assert_eq!(img.dimensions(), (75, 45));
+
+        // Raster glyphs are stretched from scene units to pixels, and a few pixels of them
+        // sample the glyph atlas differently in a tile
+        let mismatch = mismatch(&img, &expected);
+        assert!(mismatch < 0.01, "{mismatch}");
+    }
+
+    #[test]
+    fn test_set_origin() {
+        let context = context();
+        let mut sg = scene_graph();
+        // A clipped halo, whose layer is moved with the glyphs
+        sg.groups[1].bounds.width = Some(15.0);
+        sg.groups[1].bounds.height = Some(10.0);
+        if let SceneMark::Text(label) = &mut sg.groups[1].marks[0] {
+            label.stroke = EncodingValue::Scalar {
+                value: Color::rgb(1.0, 1.0, 0.0),
+            };
+            label.stroke_width = EncodingValue::Scalar { value: 2.0 };
+            label.clip = true;
+        }
+
+        let mut png_canvas = PngCanvas::with_context(context.clone(), 50.0, 30.0, 1.0).unwrap();
+        png_canvas.set_scene(&sg).unwrap();
+        let full = pollster::block_on(png_canvas.render()).unwrap();
+        let expected = image::imageops::crop_imm(&full, 10, 5, 30, 20).to_image();
+
+        let mut png_canvas = PngCanvas::with_context(context, 30.0, 20.0, 1.0).unwrap();
+        png_canvas.set_scene(&sg).unwrap();
+        png_canvas.set_origin(10.0, 5.0);
+        let img = pollster::block_on(png_canvas.render()).unwrap();
+        let mismatch = mismatch(&img, &expected);
+        assert!(mismatch < 0.01, "{mismatch}");
+    }
+
+    #[test]
+    fn test_render_png() {
+        let sg = scene_graph();
+        let mut tiled = TiledRenderer::new(context(), 2.0, 32).unwrap();
+        tiled.set_background(Some(Color::TRANSPARENT));
+        let img = pollster::block_on(tiled.render(&sg)).unwrap();
+
+        let mut png = Vec::new();
+        pollster::block_on(tiled.render_png(&sg, &mut png)).unwrap();
+        let decoded = image::load_from_memory(&png).unwrap().to_rgba8();
+        assert_eq!(decoded, img);
+        assert_eq!(decoded.get_pixel(0, 0).0, [0, 0, 0, 0]);
+    }
+}
@@ -0,0 +1,217 @@
+#[cfg(test)]
+mod test_transform {
+    use image::RgbaImage;
+    use sg2d::marks::group::{GroupBounds, SceneGroup};
+    use sg2d::marks::mark::SceneMark;
+    use sg2d::marks::rect::RectMark;
+    use sg2d::marks::rule::RuleMark;
+    use sg2d::marks::symbol::SymbolMark;
+    use sg2d::marks::text::TextMark;
+    use sg2d::scene_graph::SceneGraph;
+    use sg2d::transform::AffineTransform;
+    use sg2d::value::{Color, EncodingValue};
+    use sg2d_text::hit_test::SceneHit;
+    use sg2d_wgpu::canvas::{Canvas, PngCanvas};
+    use sg2d_wgpu::marks::text::TextRendering;
+
+    fn group(x: f32, y: f32, transform: AffineTransform, marks: Vec<SceneMark>) -> SceneGroup {
+        SceneGroup {
+            bounds: GroupBounds {
+                x,
+                y,
+                width: None,
+                height: None,
+            },
+            transform,
+            marks,
+        }
+    }
+
+    fn rect(x: f32, y: f32, width: f32, height: f32) -> SceneMark {
+        SceneMark::Rect(RectMark {
+            name: "bars".to_string(),
+            x: EncodingValue::Scalar { value: x },
+            y: EncodingValue::Scalar { value: y },
+            width: EncodingValue::Scalar { value: width },
+            height: EncodingValue::Scalar { value: height },
+            fill: EncodingValue::Scalar {
+                value: Color::rgb(0.0, 0.0, 1.0),
+            },
+            ..Default::default()
+        })
+    }
+
+    fn rule(x0: f32, x1: f32, y: f32, stroke_width: f32) -> SceneMark {
+        SceneMark::Rule(RuleMark {
+            name: "rules".to_string(),
+            x0: EncodingValue::Scalar { value: x0 },
+            y0: EncodingValue::Scalar { value: y },
+            x1: EncodingValue::Scalar { value: x1 },
+            y1: EncodingValue::Scalar { value: y },
+            stroke_width: EncodingValue::Scalar {
+                value: stroke_width,
+            },
+            ..Default::default()
+        })
+    }
+
+    fn circle(x: f32, y: f32, size: f32) -> SceneMark {
+        SceneMark::Symbol(SymbolMark {
+            name: "points".to_string(),
+            x: EncodingValue::Scalar { value: x },
+            y: EncodingValue::Scalar { value: y },
+            size: EncodingValue::Scalar { value: size },
+            fill: EncodingValue::Scalar {
+                value: Color::rgb(1.0, 0.0, 0.0),
+            },
+            ..Default::default()
+        })
+    }
+
+    fn scene_graph(groups: Vec<SceneGroup>) -> SceneGraph {
+        SceneGraph {
+            groups,
+            width: 120.0,
+            height: 80.0,
+            background: None,
+        }
+    }
+
+    fn render(scene_graph: &SceneGraph) -> RgbaImage {
+        render_text_as(scene_graph, TextRendering::Glyphs)
+    }
+
+    fn render_text_as(scene_graph: &SceneGraph, text_rendering: TextRendering) -> RgbaImage {
+        let mut png_canvas = pollster::block_on(PngCanvas::new(120.0, 80.0, 2.0)).unwrap();
+        png_canvas.set_text_rendering(text_rendering);
+        png_canvas.set_scene(scene_graph).unwrap();
+        pollster::block_on(png_canvas.render()).unwrap()
+    }
+
+    /// Number of pixels with a channel that differs by more than a rounding error
+    fn count_diff_pixels(a: &RgbaImage, b: &RgbaImage) -> usize {
+        a.pixels()
+            .zip(b.pixels())
+            .filter(|(a, b)| a.0.iter().zip(b.0).any(|(a, b)| a.abs_diff(b) > 2))
+            .count()
+    }
+
+    #[test]
+    fn test_group_transform_matches_scene_coordinates() {
+        // Marks in a group at (20, 10) that doubles their size, and in a nested group offset by
+        // 5 before the scaling
+        let transformed = scene_graph(vec![group(
+            20.0,
+            10.0,
+            AffineTransform::scale(2.0, 2.0),
+            vec![
+                rect(5.0, 5.0, 10.0, 5.0),
+                rule(0.0, 20.0, 20.0, 2.0),
+                SceneMark::Group(group(
+                    5.0,
+                    0.0,
+                    AffineTransform::IDENTITY,
+                    vec![circle(25.0, 10.0, 100.0)],
+                )),
+            ],
+        )]);
+        let expected = scene_graph(vec![group(
+            0.0,
+            0.0,
+            AffineTransform::IDENTITY,
+            vec![
+                rect(30.0, 20.0, 20.0, 10.0),
+                rule(20.0, 60.0, 50.0, 4.0),
+                circle(80.0, 30.0, 400.0),
+            ],
+        )]);
+        assert_eq!(
+            count_diff_pixels(&render(&transformed), &render(&expected)),
+            0
+        );
+    }
+
+    #[test]
+    fn test_reflected_group() {
+        // Mirroring a group about its origin reverses the winding of its triangles, which
+        // must not cull them
+        let transformed = scene_graph(vec![group(
+            100.0,
+            0.0,
+            AffineTransform::scale(-1.0, 1.0),
+            vec![rect(10.0, 20.0, 20.0, 30.0), circle(50.0, 40.0, 100.0)],
+        )]);
+        let expected = scene_graph(vec![group(
+            0.0,
+            0.0,
+            AffineTransform::IDENTITY,
+            vec![rect(70.0, 20.0, 20.0, 30.0), circle(50.0, 40.0, 100.0)],
+        )]);
+        assert_eq!(
+            count_diff_pixels(&render(&transformed), &render(&expected)),
+            0
+        );
+    }
+
+    #[test]
+    fn test_rotated_group_text_drawn_as_paths() {
+        // Glyphs can't be rotated, so text in a rotated group is drawn as paths in both modes
+        let label = SceneMark::Text(Box::new(TextMark {
+            name: "labels".to_string(),
+            text: EncodingValue::Scalar {
+                value: "Rotated".to_string(),
+            },
+            font_size: EncodingValue::Scalar { value: 14.0 },
+            ..Default::default()
+        }));
+        let rotated = scene_graph(vec![group(
+            60.0,
+            10.0,
+            AffineTransform::rotate(90.0),
+            vec![label],
+        )]);
+        let glyphs = render_text_as(&rotated, TextRendering::Glyphs);
+        let paths = render_text_as(&rotated, TextRendering::Paths);
+        assert_eq!(count_diff_pixels(&glyphs, &paths), 0);
+
+        // The text runs down from the group origin
+        let ink = glyphs
+            .enumerate_pixels()
+            .filter(|(_, _, pixel)| pixel.0[0] < 128)
+            .map(|(x, y, _)| (x, y))
+            .collect::<Vec<_>>();
+        let height =
+            ink.iter().map(|(_, y)| y).max().unwrap() - ink.iter().map(|(_, y)| y).min().unwrap();
+        let width =
+            ink.iter().map(|(x, _)| x).max().unwrap() - ink.iter().map(|(x, _)| x).min().unwrap();
+        assert!(height > 2 * width, "{width}x{height}");
+    }
+
+    #[test]
+    fn test_pick_rotated_group() {
+        // Rotating by 90 degrees about the group origin at (60, 10) maps the local rect
+        // [0, 40] x [0, 10] to [50, 60] x [10, 50]
+        let scene_graph = scene_graph(vec![group(
+            60.0,
+            10.0,
+            AffineTransform::rotate(90.0),
+            vec![rect(0.0, 0.0, 40.0, 10.0)],
+        )]);
+        let mut png_canvas = pollster::block_on(PngCanvas::new(120.0, 80.0, 2.0)).unwrap();
+        png_canvas.set_picking(true);
+        png_canvas.set_scene(&scene_graph).unwrap();
+
+        assert_eq!(
+            pollster::block_on(png_canvas.pick(55.0, 45.0)).unwrap(),
+            Some(SceneHit {
+                mark: "bars".to_string(),
+                index: 0,
+                group_path: vec![0],
+            })
+        );
+        assert_eq!(
+            pollster::block_on(png_canvas.pick(80.0, 15.0)).unwrap(),
+            None
+        );
+    }
+}
@@ -0,0 +1,273 @@
+//! Build marks from the columns of Arrow record batches.
+//!
+//! Each mapped column fills one channel, with one mark item per row:
+//!
+//! - Numeric channels read `Float32` and `Float64` columns. Rows with a null in any numeric
+//!   column are left out of the mark, as Vega does for invalid values.
+//! - Color channels read CSS color strings from `Utf8` columns, or from dictionary-encoded
+//!   string columns whose dictionary values are only parsed once. Null colors are transparent.
+//! - Text channels read `Utf8` columns, plain or dictionary-encoded. Null strings are empty.
+//!
+//! Other channels keep their default values, and can be set on the returned mark.
+use crate::error::{ArrowConversionError, SceneGraphError};
+use crate::marks::rect::RectMark;
+use crate::marks::rule::RuleMark;
+use crate::marks::symbol::SymbolMark;
+use crate::marks::text::TextMark;
+use crate::value::{Color, ColorCache, EncodingValue};
+use arrow_array::cast::AsArray;
+use arrow_array::types::{Float32Type, Float64Type};
+use arrow_array::{Array, ArrayRef, GenericStringArray, OffsetSizeTrait, RecordBatch};
+use arrow_buffer::NullBuffer;
+use arrow_schema::DataType;
+
+/// Columns of a record batch to read into the channels of a mark
+struct Columns<'a> {
+    columns: Vec<(&'a str, &'a str, &'a ArrayRef)>,
+    /// Rows to keep, if some rows have null numbers
+    keep: Option<NullBuffer>,
+    len: usize,
+}
+
+impl<'a> Columns<'a> {
+    fn new(
+        batch: &'a RecordBatch,
+        columns: &'a [(&'a str, &'a str)],
+        channels: &[&str],
+        numeric_channels: &[&str],
+    ) -> Result<Self, ArrowConversionError> {
+        let columns = columns
+            .iter()
+            .map(|(channel, column)| {
+                if !channels.contains(channel) {
+                    return Err(ArrowConversionError::UnknownChannel(channel.to_string()));
+                }
+                let array = batch
+                    .column_by_name(column)
+                    .ok_or_else(|| ArrowConversionError::MissingColumn(column.to_string()))?;
+                Ok((*channel, *column, array))
+            })
+            .collect::<Result<Vec<_>, ArrowConversionError>>()?;
+
+        let keep = columns
+            .iter()
+            .filter(|(channel, ..)| numeric_channels.contains(channel))
+            .fold(None, |keep, (.., array)| {
+                NullBuffer::union(keep.as_ref(), array.logical_nulls().as_ref())
+            })
+            .filter(|keep| keep.null_count() > 0);
+        let len = keep
+            .as_ref()
+            .map_or(batch.num_rows(), |keep| keep.len() - keep.null_count());
+        Ok(Self { columns, keep, len })
+    }
+
+    fn find(&self, channel: &str) -> Option<(&'a str, &'a ArrayRef)> {
+        self.columns
+            .iter()
+            .find(|(c, ..)| *c == channel)
+            .map(|(_, column, array)| (*column, *array))
+    }
+
+    /// Collect the values of the kept rows
+    fn select<T>(&self, values: impl Iterator<Item = T>) -> Vec<T> {
+        match &self.keep {
+            None => values.collect(),
+            Some(keep) => values
+                .zip(keep.iter())
+                .filter_map(|(value, keep)| keep.then_some(value))
+                .collect(),
+        }
+    }
+
+    fn unsupported(column: &str, channel: &'static str, array: &ArrayRef) -> ArrowConversionError {
+        ArrowConversionError::UnsupportedType {
+            column: column.to_string(),
+            channel,
+            data_type: array.data_type().clone(),
+        }
+    }
+
+    fn f32_channel(
+        &self,
+        channel: &'static str,
+    ) -> Result<Option<EncodingValue<f32>>, ArrowConversionError> {
+        let Some((column, array)) = self.find(channel) else {
+            return Ok(None);
+        };
+        let values = match array.data_type() {
+            DataType::Float32 if self.keep.is_none() => {
+                array.as_primitive::<Float32Type>().values().to_vec()
+            }
+            DataType::Float32 => {
+                self.select(array.as_primitive::<Float32Type>().values().iter().copied())
+            }
+            DataType::Float64 => self.select(
+                array
+                    .as_primitive::<Float64Type>()
+                    .values()
+                    .iter()
+                    .map(|v| *v as f32),
+            ),
+            _ => return Err(Self::unsupported(column, channel, array)),
+        };
+        Ok(Some(EncodingValue::Array { values }))
+    }
+
+    fn color_channel(
+        &self,
+        channel: &'static str,
+        cache: &mut ColorCache,
+    ) -> Result<Option<EncodingValue<Color>>, ArrowConversionError> {
+        let Some((column, array)) = self.find(channel) else {
+            return Ok(None);
+        };
+        let values = if let Some(dictionary) = array.as_any_dictionary_opt() {
+            let palette = map_strings(dictionary.values(), |s| {
+                s.map_or(Ok(Color::TRANSPARENT), |s| cache.parse(s))
+            })
+            .ok_or_else(|| Self::unsupported(column, channel, array))??;
+            let keys = dictionary.normalized_keys();
+            let nulls = array.logical_nulls();
+            self.select(keys.iter().enumerate().map(|(i, key)| {
+                if nulls.as_ref().is_some_and(|nulls| nulls.is_null(i)) {
+                    Color::TRANSPARENT
+                } else {
+                    palette[*key]
+                }
+            }))
+        } else {
+            let colors = map_strings(array, |s| {
+                s.map_or(Ok(Color::TRANSPARENT), |s| cache.parse(s))
+            })
+            .ok_or_else(|| Self::unsupported(column, channel, array))??;
+            self.select(colors.into_iter())
+        };
+        Ok(Some(EncodingValue::Array { values }))
+    }
+
+    fn string_channel(
+        &self,
+        channel: &'static str,
+    ) -> Result<Option<EncodingValue<String>>, ArrowConversionError> {
+        let Some((column, array)) = self.find(channel) else {
+            return Ok(None);
+        };
+        let to_string = |s: Option<&str>| -> Result<String, SceneGraphError> {
+            Ok(s.unwrap_or_default().to_string())
+        };
+        let values = if let Some(dictionary) = array.as_any_dictionary_opt() {
+            let strings = map_strings(dictionary.values(), to_string)
+                .ok_or_else(|| Self::unsupported(column, channel, array))??;
+            let keys = dictionary.normalized_keys();
+            let nulls = array.logical_nulls();
+            self.select(keys.iter().enumerate().map(|(i, key)| {
+                if nulls.as_ref().is_some_and(|nulls| nulls.is_null(i)) {
+                    String::new()
+                } else {
+                    strings[*key].clone()
+                }
+            }))
+        } else {
+            let strings = map_strings(array, to_string)
+                .ok_or_else(|| Self::unsupported(column, channel, array))??;
+            self.select(strings.into_iter())
+        };
+        Ok(Some(EncodingValue::Array { values }))
+    }
+}
+
+/// Map the values of a `Utf8` or `LargeUtf8` array, or return None for other types
+fn map_strings<T>(
+    array: &dyn Array,
+    f: impl FnMut(Option<&str>) -> Result<T, SceneGraphError>,
+) -> Option<Result<Vec<T>, SceneGraphError>> {
+    fn map<O: OffsetSizeTrait, T>(
+        array: &GenericStringArray<O>,
+        f: impl FnMut(Option<&str>) -> Result<T, SceneGraphError>,
+    ) -> Result<Vec<T>, SceneGraphError> {
+        array.iter().map(f).collect()
+    }
+    match array.data_type() {
+        DataType::Utf8 => Some(map(array.as_string::<i32>(), f)),
+        DataType::LargeUtf8 => Some(map(array.as_string::<i64>(), f)),
+        _ => None,
+    }
+}
+
+/// Channels of each mark type that can be read from columns, by value type
+macro_rules! arrow_mark {
+    ($mark:ident {
+        numbers: [$($number:ident),*],
+        colors: [$($color:ident),*],
+        strings: [$($string:ident),*]
+    }) => {
+        impl $mark {
+            /// Build and validate a mark with an item per row of `batch`, reading channels from
+            /// the columns of `(channel, column)` pairs
+            pub fn from_record_batch(
+                name: impl Into<String>,
+                batch: &RecordBatch,
+                columns: &[(&str, &str)],
+            ) -> Result<Self, ArrowConversionError> {
+                let channels = [$(stringify!($number),)* $(stringify!($color),)* $(stringify!($string),)*];
+                let columns = Columns::new(batch, columns, &channels, &[$(stringify!($number)),*])?;
+                let mut cache = ColorCache::new();
+                let mut mark = $mark {
+                    name: name.into(),
+                    len: columns.len as u32,
+                    ..Default::default()
+                };
+                $(
+                    if let Some(value) = columns.f32_channel(stringify!($number))? {
+                        mark.$number = value;
+                    }
+                )*
+                $(
+                    if let Some(value) = columns.color_channel(stringify!($color), &mut cache)? {
+                        mark.$color = value;
+                    }
+                )*
+                $(
+                    if let Some(value) = columns.string_channel(stringify!($string))? {
+                        mark.$string = value;
+                    }
+                )*
+                mark.validate()?;
+                Ok(mark)
+            }
+        }
+    };
+}
+
+arrow_mark!(SymbolMark {
+    numbers: [x, y, size, angle],
+    colors: [fill, stroke],
+    strings: []
+});
+arrow_mark!(RectMark {
+    numbers: [x, y, width, height],
+    colors: [fill],
+    strings: []
+});
+arrow_mark!(RuleMark {
+    numbers: [x0, y0, x1, y1, stroke_width],
+    colors: [stroke],
+    strings: []
+});
+arrow_mark!(TextMark {
+    numbers: [
+        x,
+        y,
+        opacity,
+        angle,
+        stroke_width,
+        stroke_opacity,
+        dx,
+        dy,
+        font_size,
+        limit
+    ],
+    colors: [color, stroke],
+    strings: [text, font]
+});
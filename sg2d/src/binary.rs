@@ -0,0 +1,1059 @@
+//! Versioned binary encoding of scene graphs.
+//!
+//! A scene graph is written as a header followed by a stream of records in drawing order:
+//!
+//! ```text
+//! header  := "SG2D" version:u32 width:f32 height:f32 background:opt_color
+//! record  := 0x01 x:f32 y:f32 width:opt_f32 height:opt_f32 transform:f32[6]   begin group
+//!          | 0x02                                                            end group
+//!          | 0x03 kind:u8 name:str clip:u8 len:u32 [shape stroke_width:opt_f32]
+//!               channel_count:u16 channel*                                   mark
+//!          | 0x00                                                            end
+//! channel := name:str type:u8 scalar:u8 count:u32 byte_len:u32 padding data
+//! ```
+//!
+//! Numbers are little-endian, `str` is a `u32` byte length followed by UTF-8, `opt_f32` is a
+//! `u8` flag followed by an `f32`, and `opt_color` is a `u8` flag followed by four `f32`s. Version
+//! 1 headers end after the height, and have no background. Only symbols have a shape. Channel
+//! data is padded to start at a multiple of 4 bytes from the start of the header, so numeric and
+//! color columns can be borrowed in place with `MarkRecord::f32_values`. Readers ignore channels
+//! they don't know, so channels can be added without a new version.
+use crate::error::BinaryFormatError;
+use crate::marks::group::{GroupBounds, SceneGroup};
+use crate::marks::mark::SceneMark;
+use crate::marks::rect::RectMark;
+use crate::marks::rule::RuleMark;
+use crate::marks::symbol::{SymbolMark, SymbolShape};
+use crate::marks::text::{
+    FontStyleSpec, FontWeightNameSpec, FontWeightSpec, TextAlignSpec, TextBaselineSpec,
+    TextDirectionSpec, TextMark,
+};
+use crate::scene_graph::SceneGraph;
+use crate::transform::AffineTransform;
+use crate::value::{Color, EncodingValue, StrokeCap};
+use lyon_path::geom::point;
+use lyon_path::{Event, Path};
+use std::borrow::Cow;
+use std::io::Write;
+
+pub const MAGIC: [u8; 4] = *b"SG2D";
+
+/// Latest version of the format, which is the version that is written
+pub const VERSION: u32 = 2;
+
+const END: u8 = 0;
+const BEGIN_GROUP: u8 = 1;
+const END_GROUP: u8 = 2;
+const MARK: u8 = 3;
+
+const CIRCLE: u8 = 0;
+const PATH: u8 = 1;
+
+const PATH_BEGIN: u8 = 0;
+const PATH_LINE: u8 = 1;
+const PATH_QUADRATIC: u8 = 2;
+const PATH_CUBIC: u8 = 3;
+const PATH_END: u8 = 4;
+const PATH_CLOSE: u8 = 5;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MarkKind {
+    Symbol,
+    Rect,
+    Rule,
+    Text,
+}
+
+impl MarkKind {
+    fn code(self) -> u8 {
+        match self {
+            MarkKind::Symbol => 0,
+            MarkKind::Rect => 1,
+            MarkKind::Rule => 2,
+            MarkKind::Text => 3,
+        }
+    }
+
+    fn from_code(code: u8) -> Result<Self, BinaryFormatError> {
+        Ok(match code {
+            0 => MarkKind::Symbol,
+            1 => MarkKind::Rect,
+            2 => MarkKind::Rule,
+            3 => MarkKind::Text,
+            _ => return Err(invalid(format!("Unknown mark kind {code}"))),
+        })
+    }
+}
+
+fn invalid(message: impl Into<String>) -> BinaryFormatError {
+    BinaryFormatError::InvalidData(message.into())
+}
+
+fn padding(offset: usize) -> usize {
+    (4 - offset % 4) % 4
+}
+
+fn write_f32s<W: Write>(values: &[f32], out: &mut W) -> std::io::Result<()> {
+    if cfg!(target_endian = "little") {
+        out.write_all(bytemuck::cast_slice(values))
+    } else {
+        values
+            .iter()
+            .try_for_each(|value| out.write_all(&value.to_le_bytes()))
+    }
+}
+
+/// Borrow little-endian floats in place when they are aligned, and copy them otherwise
+fn read_f32s(data: &[u8]) -> Cow<'_, [f32]> {
+    if cfg!(target_endian = "little") {
+        if let Ok(values) = bytemuck::try_cast_slice(data) {
+            return Cow::Borrowed(values);
+        }
+    }
+    Cow::Owned(
+        data.chunks_exact(4)
+            .map(|bytes| f32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+            .collect(),
+    )
+}
+
+/// Value type of an encoding channel, and the layout of its values
+trait ChannelValue: Sized {
+    const TYPE: u8;
+
+    fn byte_len(values: &[Self]) -> usize;
+
+    fn write_values<W: Write>(values: &[Self], out: &mut W) -> std::io::Result<()>;
+
+    /// Read exactly `count` values that take up all of `data`
+    fn read_values(data: &[u8], count: usize) -> Result<Vec<Self>, BinaryFormatError>;
+}
+
+fn check_byte_len(data: &[u8], expected: usize) -> Result<(), BinaryFormatError> {
+    if data.len() == expected {
+        Ok(())
+    } else {
+        Err(invalid(format!(
+            "Channel has {} bytes of data, expected {expected}",
+            data.len()
+        )))
+    }
+}
+
+impl ChannelValue for f32 {
+    const TYPE: u8 = 1;
+
+    fn byte_len(values: &[Self]) -> usize {
+        values.len() * 4
+    }
+
+    fn write_values<W: Write>(values: &[Self], out: &mut W) -> std::io::Result<()> {
+        write_f32s(values, out)
+    }
+
+    fn read_values(data: &[u8], count: usize) -> Result<Vec<Self>, BinaryFormatError> {
+        check_byte_len(data, count * 4)?;
+        Ok(read_f32s(data).into_owned())
+    }
+}
+
+impl ChannelValue for Color {
+    const TYPE: u8 = 2;
+
+    fn byte_len(values: &[Self]) -> usize {
+        values.len() * 16
+    }
+
+    fn write_values<W: Write>(values: &[Self], out: &mut W) -> std::io::Result<()> {
+        let components = values
+            .iter()
+            .flat_map(|color| color.to_array())
+            .collect::<Vec<_>>();
+        write_f32s(&components, out)
+    }
+
+    fn read_values(data: &[u8], count: usize) -> Result<Vec<Self>, BinaryFormatError> {
+        check_byte_len(data, count * 16)?;
+        Ok(read_f32s(data)
+            .chunks_exact(4)
+            .map(|c| Color::rgba(c[0], c[1], c[2], c[3]))
+            .collect())
+    }
+}
+
+impl ChannelValue for String {
+    const TYPE: u8 = 3;
+
+    fn byte_len(values: &[Self]) -> usize {
+        values.iter().map(|value| 4 + value.len()).sum()
+    }
+
+    fn write_values<W: Write>(values: &[Self], out: &mut W) -> std::io::Result<()> {
+        for value in values {
+            out.write_all(&(value.len() as u32).to_le_bytes())?;
+            out.write_all(value.as_bytes())?;
+        }
+        Ok(())
+    }
+
+    fn read_values(data: &[u8], count: usize) -> Result<Vec<Self>, BinaryFormatError> {
+        let mut cursor = Cursor::new(data);
+        let values = (0..count)
+            .map(|_| Ok(cursor.str()?.to_string()))
+            .collect::<Result<Vec<_>, BinaryFormatError>>()?;
+        check_byte_len(data, cursor.pos)?;
+        Ok(values)
+    }
+}
+
+/// Font weights take a byte for the name, or for a number that follows it
+impl ChannelValue for FontWeightSpec {
+    const TYPE: u8 = 4;
+
+    fn byte_len(values: &[Self]) -> usize {
+        values.len() * 5
+    }
+
+    fn write_values<W: Write>(values: &[Self], out: &mut W) -> std::io::Result<()> {
+        for value in values {
+            let (code, weight) = match value {
+                FontWeightSpec::Name(FontWeightNameSpec::Normal) => (0u8, 0.0f32),
+                FontWeightSpec::Name(FontWeightNameSpec::Bold) => (1, 0.0),
+                FontWeightSpec::Number(weight) => (2, *weight),
+            };
+            out.write_all(&[code])?;
+            out.write_all(&weight.to_le_bytes())?;
+        }
+        Ok(())
+    }
+
+    fn read_values(data: &[u8], count: usize) -> Result<Vec<Self>, BinaryFormatError> {
+        check_byte_len(data, count * 5)?;
+        let mut cursor = Cursor::new(data);
+        (0..count)
+            .map(|_| {
+                let code = cursor.u8()?;
+                let weight = cursor.f32()?;
+                Ok(match code {
+                    0 => FontWeightSpec::Name(FontWeightNameSpec::Normal),
+                    1 => FontWeightSpec::Name(FontWeightNameSpec::Bold),
+                    2 => FontWeightSpec::Number(weight),
+                    _ => return Err(invalid(format!("Unknown font weight {code}"))),
+                })
+            })
+            .collect()
+    }
+}
+
+/// Enums without data are written as the index of their variant in a byte
+macro_rules! enum_channel_value {
+    ($t:ty, $type:literal, [$($variant:expr),*]) => {
+        impl ChannelValue for $t {
+            const TYPE: u8 = $type;
+
+            fn byte_len(values: &[Self]) -> usize {
+                values.len()
+            }
+
+            fn write_values<W: Write>(values: &[Self], out: &mut W) -> std::io::Result<()> {
+                let variants: &[$t] = &[$($variant),*];
+                let codes = values
+                    .iter()
+                    .map(|value| variants.iter().position(|v| v == value).unwrap_or(0) as u8)
+                    .collect::<Vec<_>>();
+                out.write_all(&codes)
+            }
+
+            fn read_values(data: &[u8], count: usize) -> Result<Vec<Self>, BinaryFormatError> {
+                let variants: &[$t] = &[$($variant),*];
+                check_byte_len(data, count)?;
+                data.iter()
+                    .map(|code| {
+                        variants.get(*code as usize).copied().ok_or_else(|| {
+                            invalid(format!("Unknown {} {code}", stringify!($t)))
+                        })
+                    })
+                    .collect()
+            }
+        }
+    };
+}
+
+enum_channel_value!(
+    StrokeCap,
+    5,
+    [StrokeCap::Butt, StrokeCap::Round, StrokeCap::Square]
+);
+enum_channel_value!(
+    TextAlignSpec,
+    6,
+    [
+        TextAlignSpec::Left,
+        TextAlignSpec::Center,
+        TextAlignSpec::Right
+    ]
+);
+enum_channel_value!(
+    TextBaselineSpec,
+    7,
+    [
+        TextBaselineSpec::Alphabetic,
+        TextBaselineSpec::Top,
+        TextBaselineSpec::Middle,
+        TextBaselineSpec::Bottom,
+        TextBaselineSpec::LineTop,
+        TextBaselineSpec::LineBottom
+    ]
+);
+enum_channel_value!(
+    TextDirectionSpec,
+    8,
+    [TextDirectionSpec::Ltr, TextDirectionSpec::Rtl]
+);
+enum_channel_value!(
+    FontStyleSpec,
+    9,
+    [FontStyleSpec::Normal, FontStyleSpec::Italic]
+);
+
+/// Channels of each mark type, which are written in this order and read by name
+macro_rules! mark_channels {
+    ($mark:ty { $($channel:ident),* }) => {
+        impl $mark {
+            fn write_channels<W: Write>(
+                &self,
+                writer: &mut SceneGraphWriter<W>,
+            ) -> Result<(), BinaryFormatError> {
+                writer.u16([$(stringify!($channel)),*].len() as u16)?;
+                $(writer.channel(stringify!($channel), &self.$channel)?;)*
+                Ok(())
+            }
+
+            fn read_channels(&mut self, record: &MarkRecord) -> Result<(), BinaryFormatError> {
+                $(
+                    if let Some(value) = record.channel(stringify!($channel))? {
+                        self.$channel = value;
+                    }
+                )*
+                Ok(())
+            }
+        }
+    };
+}
+
+mark_channels!(SymbolMark {
+    x,
+    y,
+    fill,
+    size,
+    stroke,
+    angle
+});
+mark_channels!(RectMark {
+    x,
+    y,
+    width,
+    height,
+    fill
+});
+mark_channels!(RuleMark {
+    x0,
+    y0,
+    x1,
+    y1,
+    stroke,
+    stroke_width,
+    stroke_cap
+});
+mark_channels!(TextMark {
+    text,
+    x,
+    y,
+    align,
+    baseline,
+    dir,
+    opacity,
+    angle,
+    color,
+    stroke,
+    stroke_width,
+    stroke_opacity,
+    dx,
+    dy,
+    font,
+    font_size,
+    font_weight,
+    font_style,
+    limit
+});
+
+/// Streaming writer of binary scene graphs.
+///
+/// Groups are opened with `begin_group` and closed with `end_group`, and marks are added to the
+/// innermost open group, so a scene graph can be written without holding all of it in memory.
+/// Small values are written one at a time, so wrap unbuffered writers in a `BufWriter`.
+pub struct SceneGraphWriter<W: Write> {
+    writer: W,
+    offset: usize,
+    depth: usize,
+}
+
+impl<W: Write> SceneGraphWriter<W> {
+    /// Write the header of a scene graph with the given dimensions
+    pub fn new(writer: W, width: f32, height: f32) -> Result<Self, BinaryFormatError> {
+        Self::with_background(writer, width, height, None)
+    }
+
+    /// Write the header of a scene graph with the given dimensions and background color
+    pub fn with_background(
+        writer: W,
+        width: f32,
+        height: f32,
+        background: Option<Color>,
+    ) -> Result<Self, BinaryFormatError> {
+        let mut this = Self {
+            writer,
+            offset: 0,
+            depth: 0,
+        };
+        this.bytes(&MAGIC)?;
+        this.u32(VERSION)?;
+        this.f32(width)?;
+        this.f32(height)?;
+        this.u8(background.is_some() as u8)?;
+        background
+            .unwrap_or_default()
+            .to_array()
+            .into_iter()
+            .try_for_each(|c| this.f32(c))?;
+        Ok(this)
+    }
+
+    pub fn begin_group(
+        &mut self,
+        bounds: &GroupBounds,
+        transform: &AffineTransform,
+    ) -> Result<(), BinaryFormatError> {
+        self.u8(BEGIN_GROUP)?;
+        self.f32(bounds.x)?;
+        self.f32(bounds.y)?;
+        self.opt_f32(bounds.width)?;
+        self.opt_f32(bounds.height)?;
+        transform.0.iter().try_for_each(|v| self.f32(*v))?;
+        self.depth += 1;
+        Ok(())
+    }
+
+    pub fn end_group(&mut self) -> Result<(), BinaryFormatError> {
+        if self.depth == 0 {
+            return Err(invalid("No group to end"));
+        }
+        self.u8(END_GROUP)?;
+        self.depth -= 1;
+        Ok(())
+    }
+
+    /// Write a group and all of its marks
+    pub fn write_group(&mut self, group: &SceneGroup) -> Result<(), BinaryFormatError> {
+        self.begin_group(&group.bounds, &group.transform)?;
+        group
+            .marks
+            .iter()
+            .try_for_each(|mark| self.write_mark(mark))?;
+        self.end_group()
+    }
+
+    /// Add a mark to the innermost open group
+    pub fn write_mark(&mut self, mark: &SceneMark) -> Result<(), BinaryFormatError> {
+        if self.depth == 0 {
+            return Err(invalid("Marks must be written inside a group"));
+        }
+        match mark {
+            SceneMark::Symbol(mark) => {
+                self.mark_header(MarkKind::Symbol, &mark.name, mark.clip, mark.len)?;
+                self.shape(&mark.shape)?;
+                self.opt_f32(mark.stroke_width)?;
+                mark.write_channels(self)
+            }
+            SceneMark::Rect(mark) => {
+                self.mark_header(MarkKind::Rect, &mark.name, mark.clip, mark.len)?;
+                mark.write_channels(self)
+            }
+            SceneMark::Rule(mark) => {
+                self.mark_header(MarkKind::Rule, &mark.name, mark.clip, mark.len)?;
+                mark.write_channels(self)
+            }
+            SceneMark::Text(mark) => {
+                self.mark_header(MarkKind::Text, &mark.name, mark.clip, mark.len)?;
+                mark.write_channels(self)
+            }
+            SceneMark::Group(group) => self.write_group(group),
+        }
+    }
+
+    /// Write the end of the scene graph, and return the underlying writer
+    pub fn finish(mut self) -> Result<W, BinaryFormatError> {
+        if self.depth != 0 {
+            return Err(invalid(format!("{} groups weren't ended", self.depth)));
+        }
+        self.u8(END)?;
+        self.writer.flush()?;
+        Ok(self.writer)
+    }
+
+    fn mark_header(
+        &mut self,
+        kind: MarkKind,
+        name: &str,
+        clip: bool,
+        len: u32,
+    ) -> Result<(), BinaryFormatError> {
+        self.u8(MARK)?;
+        self.u8(kind.code())?;
+        self.str(name)?;
+        self.u8(clip as u8)?;
+        self.u32(len)
+    }
+
+    fn shape(&mut self, shape: &SymbolShape) -> Result<(), BinaryFormatError> {
+        let SymbolShape::Path(path) = shape else {
+            return self.u8(CIRCLE);
+        };
+        let mut verbs = Vec::new();
+        let mut points = Vec::new();
+        for event in path.iter() {
+            match event {
+                Event::Begin { at } => {
+                    verbs.push(PATH_BEGIN);
+                    points.extend([at.x, at.y]);
+                }
+                Event::Line { to, .. } => {
+                    verbs.push(PATH_LINE);
+                    points.extend([to.x, to.y]);
+                }
+                Event::Quadratic { ctrl, to, .. } => {
+                    verbs.push(PATH_QUADRATIC);
+                    points.extend([ctrl.x, ctrl.y, to.x, to.y]);
+                }
+                Event::Cubic {
+                    ctrl1, ctrl2, to, ..
+                } => {
+                    verbs.push(PATH_CUBIC);
+                    points.extend([ctrl1.x, ctrl1.y, ctrl2.x, ctrl2.y, to.x, to.y]);
+                }
+                Event::End { close, .. } => {
+                    verbs.push(if close { PATH_CLOSE } else { PATH_END });
+                }
+            }
+        }
+        self.u8(PATH)?;
+        self.u32(verbs.len() as u32)?;
+        self.bytes(&verbs)?;
+        self.u32(points.len() as u32)?;
+        self.pad()?;
+        write_f32s(&points, &mut self.writer)?;
+        self.offset += points.len() * 4;
+        Ok(())
+    }
+
+    fn channel<T: ChannelValue>(
+        &mut self,
+        name: &str,
+        value: &EncodingValue<T>,
+    ) -> Result<(), BinaryFormatError> {
+        let (scalar, values) = match value {
+            EncodingValue::Scalar { value } => (true, std::slice::from_ref(value)),
+            EncodingValue::Array { values } => (false, values.as_slice()),
+        };
+        let byte_len = T::byte_len(values);
+        self.str(name)?;
+        self.u8(T::TYPE)?;
+        self.u8(scalar as u8)?;
+        self.u32(values.len() as u32)?;
+        self.u32(byte_len as u32)?;
+        self.pad()?;
+        T::write_values(values, &mut self.writer)?;
+        self.offset += byte_len;
+        Ok(())
+    }
+
+    fn bytes(&mut self, bytes: &[u8]) -> Result<(), BinaryFormatError> {
+        self.writer.write_all(bytes)?;
+        self.offset += bytes.len();
+        Ok(())
+    }
+
+    fn pad(&mut self) -> Result<(), BinaryFormatError> {
+        self.bytes(&[0; 3][..padding(self.offset)])
+    }
+
+    fn u8(&mut self, value: u8) -> Result<(), BinaryFormatError> {
+        self.bytes(&[value])
+    }
+
+    fn u16(&mut self, value: u16) -> Result<(), BinaryFormatError> {
+        self.bytes(&value.to_le_bytes())
+    }
+
+    fn u32(&mut self, value: u32) -> Result<(), BinaryFormatError> {
+        self.bytes(&value.to_le_bytes())
+    }
+
+    fn f32(&mut self, value: f32) -> Result<(), BinaryFormatError> {
+        self.bytes(&value.to_le_bytes())
+    }
+
+    fn opt_f32(&mut self, value: Option<f32>) -> Result<(), BinaryFormatError> {
+        self.u8(value.is_some() as u8)?;
+        self.f32(value.unwrap_or(0.0))
+    }
+
+    fn str(&mut self, value: &str) -> Result<(), BinaryFormatError> {
+        self.u32(value.len() as u32)?;
+        self.bytes(value.as_bytes())
+    }
+}
+
+struct Cursor<'a> {
+    bytes: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Cursor<'a> {
+    fn new(bytes: &'a [u8]) -> Self {
+        Self { bytes, pos: 0 }
+    }
+
+    fn take(&mut self, len: usize) -> Result<&'a [u8], BinaryFormatError> {
+        let end = self
+            .pos
+            .checked_add(len)
+            .filter(|end| *end <= self.bytes.len())
+            .ok_or(BinaryFormatError::UnexpectedEof)?;
+        let bytes = &self.bytes[self.pos..end];
+        self.pos = end;
+        Ok(bytes)
+    }
+
+    fn align(&mut self) -> Result<(), BinaryFormatError> {
+        self.take(padding(self.pos)).map(|_| ())
+    }
+
+    fn u8(&mut self) -> Result<u8, BinaryFormatError> {
+        Ok(self.take(1)?[0])
+    }
+
+    fn bool(&mut self) -> Result<bool, BinaryFormatError> {
+        match self.u8()? {
+            0 => Ok(false),
+            1 => Ok(true),
+            value => Err(invalid(format!("Invalid flag {value}"))),
+        }
+    }
+
+    fn u16(&mut self) -> Result<u16, BinaryFormatError> {
+        let bytes = self.take(2)?;
+        Ok(u16::from_le_bytes([bytes[0], bytes[1]]))
+    }
+
+    fn u32(&mut self) -> Result<u32, BinaryFormatError> {
+        let bytes = self.take(4)?;
+        Ok(u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+    }
+
+    fn f32(&mut self) -> Result<f32, BinaryFormatError> {
+        Ok(f32::from_bits(self.u32()?))
+    }
+
+    fn opt_f32(&mut self) -> Result<Option<f32>, BinaryFormatError> {
+        let some = self.bool()?;
+        let value = self.f32()?;
+        Ok(some.then_some(value))
+    }
+
+    fn str(&mut self) -> Result<&'a str, BinaryFormatError> {
+        let len = self.u32()? as usize;
+        std::str::from_utf8(self.take(len)?).map_err(|err| invalid(err.to_string()))
+    }
+
+    fn shape(&mut self) -> Result<SymbolShape, BinaryFormatError> {
+        match self.u8()? {
+            CIRCLE => return Ok(SymbolShape::Circle),
+            PATH => {}
+            code => return Err(invalid(format!("Unknown symbol shape {code}"))),
+        }
+        let num_verbs = self.u32()? as usize;
+        let verbs = self.take(num_verbs)?;
+        let num_points = self.u32()? as usize;
+        self.align()?;
+        let points = read_f32s(self.take(num_points.saturating_mul(4))?);
+        let mut points = points.chunks_exact(2).map(|p| point(p[0], p[1]));
+        let mut next_point = || {
+            points
+                .next()
+                .ok_or_else(|| invalid("Path is missing points"))
+        };
+
+        let mut builder = Path::builder();
+        let mut open = false;
+        for verb in verbs {
+            match (*verb, open) {
+                (PATH_BEGIN, false) => {
+                    builder.begin(next_point()?);
+                    open = true;
+                }
+                (PATH_LINE, true) => {
+                    builder.line_to(next_point()?);
+                }
+                (PATH_QUADRATIC, true) => {
+                    builder.quadratic_bezier_to(next_point()?, next_point()?);
+                }
+                (PATH_CUBIC, true) => {
+                    builder.cubic_bezier_to(next_point()?, next_point()?, next_point()?);
+                }
+                (PATH_END | PATH_CLOSE, true) => {
+                    builder.end(*verb == PATH_CLOSE);
+                    open = false;
+                }
+                (verb, _) => return Err(invalid(format!("Unexpected path verb {verb}"))),
+            }
+        }
+        if open {
+            return Err(invalid("Path has an unended sub-path"));
+        }
+        Ok(SymbolShape::Path(builder.build()))
+    }
+}
+
+/// Record read from a binary scene graph
+#[derive(Debug, Clone)]
+pub enum SceneEvent<'a> {
+    BeginGroup {
+        bounds: GroupBounds,
+        transform: AffineTransform,
+    },
+    Mark(MarkRecord<'a>),
+    EndGroup,
+}
+
+#[derive(Debug, Clone)]
+struct ChannelRecord<'a> {
+    name: &'a str,
+    value_type: u8,
+    scalar: bool,
+    count: usize,
+    data: &'a [u8],
+}
+
+/// Mark read from a binary scene graph, whose channels are decoded on demand
+#[derive(Debug, Clone)]
+pub struct MarkRecord<'a> {
+    pub kind: MarkKind,
+    pub name: &'a str,
+    pub clip: bool,
+    pub len: u32,
+    shape: SymbolShape,
+    stroke_width: Option<f32>,
+    channels: Vec<ChannelRecord<'a>>,
+}
+
+impl<'a> MarkRecord<'a> {
+    fn read(cursor: &mut Cursor<'a>) -> Result<Self, BinaryFormatError> {
+        let kind = MarkKind::from_code(cursor.u8()?)?;
+        let name = cursor.str()?;
+        let clip = cursor.bool()?;
+        let len = cursor.u32()?;
+        let (shape, stroke_width) = if kind == MarkKind::Symbol {
+            (cursor.shape()?, cursor.opt_f32()?)
+        } else {
+            (SymbolShape::Circle, None)
+        };
+        let channels = (0..cursor.u16()?)
+            .map(|_| {
+                let name = cursor.str()?;
+                let value_type = cursor.u8()?;
+                let scalar = cursor.bool()?;
+                let count = cursor.u32()? as usize;
+                let byte_len = cursor.u32()? as usize;
+                cursor.align()?;
+                let data = cursor.take(byte_len)?;
+                if scalar && count != 1 {
+                    return Err(invalid(format!(
+                        "Scalar channel `{name}` has {count} values"
+                    )));
+                }
+                Ok(ChannelRecord {
+                    name,
+                    value_type,
+                    scalar,
+                    count,
+                    data,
+                })
+            })
+            .collect::<Result<Vec<_>, BinaryFormatError>>()?;
+        Ok(Self {
+            kind,
+            name,
+            clip,
+            len,
+            shape,
+            stroke_width,
+            channels,
+        })
+    }
+
+    pub fn channel_names(&self) -> impl Iterator<Item = &'a str> + '_ {
+        self.channels.iter().map(|channel| channel.name)
+    }
+
+    /// Values of a numeric or color channel, with four values per color. The values are borrowed
+    /// from the input when it is 4-byte aligned, as allocations are, and copied otherwise.
+    ///
+    /// Returns None if the mark has no channel with this name, or if the channel isn't numeric.
+    pub fn f32_values(&self, name: &str) -> Option<Cow<'a, [f32]>> {
+        let channel = self.channels.iter().find(|channel| channel.name == name)?;
+        [f32::TYPE, Color::TYPE]
+            .contains(&channel.value_type)
+            .then(|| read_f32s(channel.data))
+    }
+
+    fn channel<T: ChannelValue>(
+        &self,
+        name: &str,
+    ) -> Result<Option<EncodingValue<T>>, BinaryFormatError> {
+        let Some(channel) = self.channels.iter().find(|channel| channel.name == name) else {
+            return Ok(None);
+        };
+        if channel.value_type != T::TYPE {
+            return Err(invalid(format!(
+                "Channel `{name}` of mark `{}` has type {}, expected {}",
+                self.name,
+                channel.value_type,
+                T::TYPE
+            )));
+        }
+        let mut values = T::read_values(channel.data, channel.count)?;
+        Ok(Some(match (channel.scalar, values.pop()) {
+            (true, Some(value)) => EncodingValue::Scalar { value },
+            (true, None) => return Err(invalid(format!("Scalar channel `{name}` is empty"))),
+            (false, last) => {
+                values.extend(last);
+                EncodingValue::Array { values }
+            }
+        }))
+    }
+
+    /// Decode every channel of the mark
+    pub fn to_mark(&self) -> Result<SceneMark, BinaryFormatError> {
+        let name = self.name.to_string();
+        Ok(match self.kind {
+            MarkKind::Symbol => {
+                let mut mark = SymbolMark {
+                    name,
+                    clip: self.clip,
+                    len: self.len,
+                    shape: self.shape.clone(),
+                    stroke_width: self.stroke_width,
+                    ..Default::default()
+                };
+                mark.read_channels(self)?;
+                SceneMark::Symbol(mark)
+            }
+            MarkKind::Rect => {
+                let mut mark = RectMark {
+                    name,
+                    clip: self.clip,
+                    len: self.len,
+                    ..Default::default()
+                };
+                mark.read_channels(self)?;
+                SceneMark::Rect(mark)
+            }
+            MarkKind::Rule => {
+                let mut mark = RuleMark {
+                    name,
+                    clip: self.clip,
+                    len: self.len,
+                    ..Default::default()
+                };
+                mark.read_channels(self)?;
+                SceneMark::Rule(mark)
+            }
+            MarkKind::Text => {
+                let mut mark = TextMark {
+                    name,
+                    clip: self.clip,
+                    len: self.len,
+                    ..Default::default()
+                };
+                mark.read_channels(self)?;
+                SceneMark::Text(Box::new(mark))
+            }
+        })
+    }
+}
+
+/// Streaming reader of binary scene graphs, which reads one record at a time from a buffer,
+/// such as a memory-mapped file
+pub struct SceneGraphReader<'a> {
+    cursor: Cursor<'a>,
+    width: f32,
+    height: f32,
+    background: Option<Color>,
+    depth: usize,
+    done: bool,
+}
+
+impl<'a> SceneGraphReader<'a> {
+    /// Read the header of a binary scene graph
+    pub fn new(bytes: &'a [u8]) -> Result<Self, BinaryFormatError> {
+        if !bytes.starts_with(&MAGIC) {
+            return Err(BinaryFormatError::InvalidMagic);
+        }
+        let mut cursor = Cursor::new(bytes);
+        cursor.take(MAGIC.len())?;
+        let version = cursor.u32()?;
+        if version == 0 || version > VERSION {
+            return Err(BinaryFormatError::UnsupportedVersion(version));
+        }
+        let width = cursor.f32()?;
+        let height = cursor.f32()?;
+        let background = if version >= 2 {
+            let some = cursor.bool()?;
+            let color = Color::rgba(cursor.f32()?, cursor.f32()?, cursor.f32()?, cursor.f32()?);
+            some.then_some(color)
+        } else {
+            None
+        };
+        Ok(Self {
+            cursor,
+            width,
+            height,
+            background,
+            depth: 0,
+            done: false,
+        })
+    }
+
+    pub fn width(&self) -> f32 {
+        self.width
+    }
+
+    pub fn height(&self) -> f32 {
+        self.height
+    }
+
+    pub fn background(&self) -> Option<Color> {
+        self.background
+    }
+
+    /// Read the next record, or None at the end of the scene graph
+    pub fn next_event(&mut self) -> Result<Option<SceneEvent<'a>>, BinaryFormatError> {
+        if self.done {
+            return Ok(None);
+        }
+        let event = match self.cursor.u8()? {
+            END if self.depth == 0 => {
+                self.done = true;
+                return Ok(None);
+            }
+            END => return Err(invalid("Scene graph ended inside a group")),
+            BEGIN_GROUP => {
+                let x = self.cursor.f32()?;
+                let y = self.cursor.f32()?;
+                let width = self.cursor.opt_f32()?;
+                let height = self.cursor.opt_f32()?;
+                let mut transform = [0.0; 6];
+                for v in transform.iter_mut() {
+                    *v = self.cursor.f32()?;
+                }
+                self.depth += 1;
+                SceneEvent::BeginGroup {
+                    bounds: GroupBounds {
+                        x,
+                        y,
+                        width,
+                        height,
+                    },
+                    transform: AffineTransform(transform),
+                }
+            }
+            END_GROUP if self.depth > 0 => {
+                self.depth -= 1;
+                SceneEvent::EndGroup
+            }
+            END_GROUP => return Err(invalid("Group ended without beginning")),
+            MARK if self.depth > 0 => SceneEvent::Mark(MarkRecord::read(&mut self.cursor)?),
+            MARK => return Err(invalid("Mark outside of a group")),
+            tag => return Err(invalid(format!("Unknown record type {tag}"))),
+        };
+        Ok(Some(event))
+    }
+}
+
+impl<'a> Iterator for SceneGraphReader<'a> {
+    type Item = Result<SceneEvent<'a>, BinaryFormatError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let result = self.next_event();
+        if result.is_err() {
+            self.done = true;
+        }
+        result.transpose()
+    }
+}
+
+/// Write a scene graph in the binary format, returning the writer
+pub fn write_scene_graph<W: Write>(
+    scene_graph: &SceneGraph,
+    writer: W,
+) -> Result<W, BinaryFormatError> {
+    let mut writer = SceneGraphWriter::with_background(
+        writer,
+        scene_graph.width,
+        scene_graph.height,
+        scene_graph.background,
+    )?;
+    scene_graph
+        .groups
+        .iter()
+        .try_for_each(|group| writer.write_group(group))?;
+    writer.finish()
+}
+
+/// Read a whole scene graph from the binary format
+pub fn read_scene_graph(bytes: &[u8]) -> Result<SceneGraph, BinaryFormatError> {
+    let mut reader = SceneGraphReader::new(bytes)?;
+    let mut groups = Vec::new();
+    let mut open_groups: Vec<SceneGroup> = Vec::new();
+    while let Some(event) = reader.next_event()? {
+        match event {
+            SceneEvent::BeginGroup { bounds, transform } => open_groups.push(SceneGroup {
+                bounds,
+                transform,
+                marks: Vec::new(),
+            }),
+            SceneEvent::Mark(record) => {
+                // The reader only reads marks inside groups
+                if let Some(group) = open_groups.last_mut() {
+                    group.marks.push(record.to_mark()?);
+                }
+            }
+            SceneEvent::EndGroup => {
+                let Some(group) = open_groups.pop() else {
+                    continue;
+                };
+                match open_groups.last_mut() {
+                    Some(parent) => parent.marks.push(SceneMark::Group(group)),
+                    None => groups.push(group),
+                }
+            }
+        }
+    }
+    Ok(SceneGraph {
+        groups,
+        width: reader.width(),
+        height: reader.height(),
+        background: reader.background(),
+    })
+}
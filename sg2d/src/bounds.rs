@@ -0,0 +1,84 @@
+use crate::transform::AffineTransform;
+use serde::{Deserialize, Serialize};
+
+/// Axis-aligned bounding box in scene coordinates, with y increasing downward
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Bounds {
+    pub x0: f32,
+    pub y0: f32,
+    pub x1: f32,
+    pub y1: f32,
+}
+
+impl Bounds {
+    /// Bounds spanning two corners, in any order
+    pub fn new(x0: f32, y0: f32, x1: f32, y1: f32) -> Self {
+        Self {
+            x0: x0.min(x1),
+            y0: y0.min(y1),
+            x1: x0.max(x1),
+            y1: y0.max(y1),
+        }
+    }
+
+    /// Smallest bounds containing every point, or None if there are no points
+    pub fn from_points(points: impl IntoIterator<Item = [f32; 2]>) -> Option<Self> {
+        points
+            .into_iter()
+            .map(|[x, y]| Self::new(x, y, x, y))
+            .reduce(|a, b| a.union(&b))
+    }
+
+    /// Smallest bounds containing all of the given bounds, or None if there are none
+    pub fn union_all(bounds: impl IntoIterator<Item = Bounds>) -> Option<Self> {
+        bounds.into_iter().reduce(|a, b| a.union(&b))
+    }
+
+    pub fn width(&self) -> f32 {
+        self.x1 - self.x0
+    }
+
+    pub fn height(&self) -> f32 {
+        self.y1 - self.y0
+    }
+
+    pub fn union(&self, other: &Bounds) -> Self {
+        Self {
+            x0: self.x0.min(other.x0),
+            y0: self.y0.min(other.y0),
+            x1: self.x1.max(other.x1),
+            y1: self.y1.max(other.y1),
+        }
+    }
+
+    /// Grow the bounds by the same amount on every side
+    pub fn expand(&self, amount: f32) -> Self {
+        Self {
+            x0: self.x0 - amount,
+            y0: self.y0 - amount,
+            x1: self.x1 + amount,
+            y1: self.y1 + amount,
+        }
+    }
+
+    /// Smallest bounds containing the transformed corners of these bounds
+    pub fn transformed(&self, transform: &AffineTransform) -> Self {
+        let corners = [
+            [self.x0, self.y0],
+            [self.x1, self.y0],
+            [self.x1, self.y1],
+            [self.x0, self.y1],
+        ];
+        Self::from_points(corners.map(|corner| transform.apply(corner))).unwrap_or(*self)
+    }
+
+    /// Whether a point lies inside the bounds, edges included
+    pub fn contains(&self, [x, y]: [f32; 2]) -> bool {
+        x >= self.x0 && x <= self.x1 && y >= self.y0 && y <= self.y1
+    }
+
+    /// Whether two bounds overlap, edges included
+    pub fn intersects(&self, other: &Bounds) -> bool {
+        self.x0 <= other.x1 && other.x0 <= self.x1 && self.y0 <= other.y1 && other.y0 <= self.y1
+    }
+}
@@ -0,0 +1,296 @@
+//! Builders for assembling scene graphs in code, without going through Vega.
+//!
+//! Each encoding channel of a mark builder has an array setter that takes one value per item,
+//! and a `_scalar` setter that applies a single value to every item. The number of items is taken
+//! from the first array channel, or is 1 if every channel is scalar, and `build` validates that
+//! the other array channels agree.
+use crate::error::SceneGraphError;
+use crate::marks::group::{GroupBounds, SceneGroup};
+use crate::marks::mark::SceneMark;
+use crate::marks::rect::RectMark;
+use crate::marks::rule::RuleMark;
+use crate::marks::symbol::{SymbolMark, SymbolShape};
+use crate::marks::text::{
+    FontStyleSpec, FontWeightSpec, TextAlignSpec, TextBaselineSpec, TextDirectionSpec, TextMark,
+};
+use crate::scene_graph::SceneGraph;
+use crate::transform::AffineTransform;
+use crate::value::{Color, EncodingValue, StrokeCap};
+
+fn array_len<T>(value: &EncodingValue<T>) -> Option<usize> {
+    match value {
+        EncodingValue::Scalar { .. } => None,
+        EncodingValue::Array { values } => Some(values.len()),
+    }
+}
+
+macro_rules! mark_builder {
+    ($builder:ident, $mark:ident { $($channel:ident, $scalar:ident: $t:ty;)* }) => {
+        #[derive(Debug, Clone)]
+        pub struct $builder {
+            mark: $mark,
+            len: Option<u32>,
+        }
+
+        impl $mark {
+            pub fn builder(name: impl Into<String>) -> $builder {
+                $builder {
+                    mark: $mark {
+                        name: name.into(),
+                        ..Default::default()
+                    },
+                    len: None,
+                }
+            }
+        }
+
+        impl $builder {
+            $(
+                pub fn $channel(mut self, values: Vec<$t>) -> Self {
+                    self.mark.$channel = EncodingValue::Array { values };
+                    self
+                }
+
+                pub fn $scalar(mut self, value: impl Into<$t>) -> Self {
+                    self.mark.$channel = EncodingValue::Scalar {
+                        value: value.into(),
+                    };
+                    self
+                }
+            )*
+
+            pub fn clip(mut self, clip: bool) -> Self {
+                self.mark.clip = clip;
+                self
+            }
+
+            /// Set the number of items explicitly, rather than inferring it from array channels
+            pub fn len(mut self, len: u32) -> Self {
+                self.len = Some(len);
+                self
+            }
+
+            /// Build and validate the mark
+            pub fn build(self) -> Result<$mark, SceneGraphError> {
+                let mark = self.into_mark();
+                mark.validate()?;
+                Ok(mark)
+            }
+
+            fn into_mark(mut self) -> $mark {
+                let array_len = [$(array_len(&self.mark.$channel)),*].into_iter().flatten().next();
+                self.mark.len = self
+                    .len
+                    .or(array_len.map(|len| len as u32))
+                    .unwrap_or(1);
+                self.mark
+            }
+        }
+
+        /// Marks added to groups are validated when the scene graph is built
+        impl From<$builder> for SceneMark {
+            fn from(builder: $builder) -> Self {
+                builder.into_mark().into()
+            }
+        }
+    };
+}
+
+mark_builder!(SymbolMarkBuilder, SymbolMark {
+    x, x_scalar: f32;
+    y, y_scalar: f32;
+    fill, fill_scalar: Color;
+    size, size_scalar: f32;
+    stroke, stroke_scalar: Color;
+    angle, angle_scalar: f32;
+});
+
+impl SymbolMarkBuilder {
+    pub fn shape(mut self, shape: SymbolShape) -> Self {
+        self.mark.shape = shape;
+        self
+    }
+
+    /// Stroke width shared by every symbol
+    pub fn stroke_width(mut self, stroke_width: f32) -> Self {
+        self.mark.stroke_width = Some(stroke_width);
+        self
+    }
+}
+
+mark_builder!(RectMarkBuilder, RectMark {
+    x, x_scalar: f32;
+    y, y_scalar: f32;
+    width, width_scalar: f32;
+    height, height_scalar: f32;
+    fill, fill_scalar: Color;
+});
+
+mark_builder!(RuleMarkBuilder, RuleMark {
+    x0, x0_scalar: f32;
+    y0, y0_scalar: f32;
+    x1, x1_scalar: f32;
+    y1, y1_scalar: f32;
+    stroke, stroke_scalar: Color;
+    stroke_width, stroke_width_scalar: f32;
+    stroke_cap, stroke_cap_scalar: StrokeCap;
+});
+
+mark_builder!(TextMarkBuilder, TextMark {
+    text, text_scalar: String;
+    x, x_scalar: f32;
+    y, y_scalar: f32;
+    align, align_scalar: TextAlignSpec;
+    baseline, baseline_scalar: TextBaselineSpec;
+    dir, dir_scalar: TextDirectionSpec;
+    opacity, opacity_scalar: f32;
+    angle, angle_scalar: f32;
+    color, color_scalar: Color;
+    stroke, stroke_scalar: Color;
+    stroke_width, stroke_width_scalar: f32;
+    stroke_opacity, stroke_opacity_scalar: f32;
+    dx, dx_scalar: f32;
+    dy, dy_scalar: f32;
+    font, font_scalar: String;
+    font_size, font_size_scalar: f32;
+    font_weight, font_weight_scalar: FontWeightSpec;
+    font_style, font_style_scalar: FontStyleSpec;
+    limit, limit_scalar: f32;
+});
+
+impl From<SymbolMark> for SceneMark {
+    fn from(mark: SymbolMark) -> Self {
+        SceneMark::Symbol(mark)
+    }
+}
+
+impl From<RectMark> for SceneMark {
+    fn from(mark: RectMark) -> Self {
+        SceneMark::Rect(mark)
+    }
+}
+
+impl From<RuleMark> for SceneMark {
+    fn from(mark: RuleMark) -> Self {
+        SceneMark::Rule(mark)
+    }
+}
+
+impl From<TextMark> for SceneMark {
+    fn from(mark: TextMark) -> Self {
+        SceneMark::Text(Box::new(mark))
+    }
+}
+
+impl From<SceneGroup> for SceneMark {
+    fn from(group: SceneGroup) -> Self {
+        SceneMark::Group(group)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct SceneGroupBuilder {
+    group: SceneGroup,
+}
+
+impl SceneGroup {
+    /// Builder for a group at the origin of its parent, with no width or height
+    pub fn builder() -> SceneGroupBuilder {
+        SceneGroupBuilder {
+            group: SceneGroup {
+                bounds: GroupBounds {
+                    x: 0.0,
+                    y: 0.0,
+                    width: None,
+                    height: None,
+                },
+                transform: AffineTransform::IDENTITY,
+                marks: Vec::new(),
+            },
+        }
+    }
+}
+
+impl SceneGroupBuilder {
+    /// Offset of the group from its parent group, or from the scene origin
+    pub fn origin(mut self, x: f32, y: f32) -> Self {
+        self.group.bounds.x = x;
+        self.group.bounds.y = y;
+        self
+    }
+
+    /// Size of the group, which bounds marks that are clipped
+    pub fn size(mut self, width: f32, height: f32) -> Self {
+        self.group.bounds.width = Some(width);
+        self.group.bounds.height = Some(height);
+        self
+    }
+
+    /// Transform the coordinates of the group's marks before offsetting them by the origin
+    pub fn transform(mut self, transform: AffineTransform) -> Self {
+        self.group.transform = transform;
+        self
+    }
+
+    /// Add a mark, mark builder or nested group, drawn on top of the marks added before it
+    pub fn mark(mut self, mark: impl Into<SceneMark>) -> Self {
+        self.group.marks.push(mark.into());
+        self
+    }
+
+    /// Build and validate the group
+    pub fn build(self) -> Result<SceneGroup, SceneGraphError> {
+        self.group.validate()?;
+        Ok(self.group)
+    }
+}
+
+impl From<SceneGroupBuilder> for SceneGroup {
+    fn from(builder: SceneGroupBuilder) -> Self {
+        builder.group
+    }
+}
+
+impl From<SceneGroupBuilder> for SceneMark {
+    fn from(builder: SceneGroupBuilder) -> Self {
+        SceneMark::Group(builder.group)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct SceneGraphBuilder {
+    scene_graph: SceneGraph,
+}
+
+impl SceneGraph {
+    pub fn builder(width: f32, height: f32) -> SceneGraphBuilder {
+        SceneGraphBuilder {
+            scene_graph: SceneGraph {
+                groups: Vec::new(),
+                width,
+                height,
+                background: None,
+            },
+        }
+    }
+}
+
+impl SceneGraphBuilder {
+    /// Add a top-level group, drawn on top of the groups added before it
+    pub fn group(mut self, group: impl Into<SceneGroup>) -> Self {
+        self.scene_graph.groups.push(group.into());
+        self
+    }
+
+    /// Set the color behind every group
+    pub fn background(mut self, background: impl Into<Color>) -> Self {
+        self.scene_graph.background = Some(background.into());
+        self
+    }
+
+    /// Build the scene graph, validating every group and mark in it
+    pub fn build(self) -> Result<SceneGraph, SceneGraphError> {
+        self.scene_graph.validate()?;
+        Ok(self.scene_graph)
+    }
+}
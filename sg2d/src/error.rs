@@ -1,7 +1,94 @@
 use thiserror::Error;
 
-#[derive(Error, Debug)]
+#[derive(Error, Debug, Clone, PartialEq)]
 pub enum SceneGraphError {
     #[error("Internal error: `{0}`")]
     InternalError(String),
+
+    #[error("Mark `{mark}` has {len} items, but its `{channel}` array has {values} values")]
+    ArrayLengthMismatch {
+        mark: String,
+        channel: &'static str,
+        len: u32,
+        values: usize,
+    },
+
+    #[error("Mark `{mark}` item {index} has non-finite `{channel}` value {value}")]
+    NonFiniteValue {
+        mark: String,
+        channel: &'static str,
+        index: usize,
+        value: f32,
+    },
+
+    #[error("Mark `{mark}` item {index} has negative `{channel}` value {value}")]
+    NegativeValue {
+        mark: String,
+        channel: &'static str,
+        index: usize,
+        value: f32,
+    },
+
+    #[error("Mark `{mark}` item {index} has invalid `{channel}` color {color:?}, components must be between 0 and 1")]
+    InvalidColor {
+        mark: String,
+        channel: &'static str,
+        index: usize,
+        color: Vec<f32>,
+    },
+
+    #[error("Group has invalid `{field}` value {value}, offsets must be finite and sizes finite and non-negative")]
+    InvalidGroupBounds { field: &'static str, value: f32 },
+
+    #[error("Group transform has non-finite component {component} with value {value}")]
+    InvalidTransform { component: usize, value: f32 },
+
+    #[error("Invalid CSS color `{0}`")]
+    InvalidCssColor(String),
+
+    #[error("Invalid background color {color:?}, components must be between 0 and 1")]
+    InvalidBackground { color: Vec<f32> },
+
+    #[error("Scene graph has invalid dimensions {width} x {height}")]
+    InvalidDimensions { width: f32, height: f32 },
+}
+
+#[derive(Error, Debug)]
+pub enum BinaryFormatError {
+    #[error("I/O error: {0}")]
+    Io(#[from] std::io::Error),
+
+    #[error("Not a binary scene graph")]
+    InvalidMagic,
+
+    #[error("Unsupported binary scene graph version {0}")]
+    UnsupportedVersion(u32),
+
+    #[error("Binary scene graph ended unexpectedly")]
+    UnexpectedEof,
+
+    #[error("Invalid binary scene graph: {0}")]
+    InvalidData(String),
+}
+
+#[cfg(feature = "arrow")]
+#[derive(Error, Debug)]
+pub enum ArrowConversionError {
+    #[error("Record batch has no column named `{0}`")]
+    MissingColumn(String),
+
+    #[error("Mark has no `{0}` channel that can be read from a column")]
+    UnknownChannel(String),
+
+    #[error(
+        "Column `{column}` has type {data_type}, which can't be used for the `{channel}` channel"
+    )]
+    UnsupportedType {
+        column: String,
+        channel: &'static str,
+        data_type: arrow_schema::DataType,
+    },
+
+    #[error(transparent)]
+    SceneGraph(#[from] SceneGraphError),
 }
@@ -1,4 +1,11 @@
+#[cfg(feature = "arrow")]
+pub mod arrow;
+pub mod binary;
+pub mod bounds;
+pub mod builder;
 pub mod error;
 pub mod marks;
 pub mod scene_graph;
+pub mod transform;
+mod validate;
 pub mod value;
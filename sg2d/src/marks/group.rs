@@ -1,6 +1,12 @@
+use crate::error::SceneGraphError;
 use crate::marks::mark::SceneMark;
+use crate::transform::AffineTransform;
 use serde::{Deserialize, Serialize};
 
+/// Position and size of a group.
+///
+/// `x` and `y` are the offset of the group from its parent group, or from the scene origin for
+/// top-level groups. Marks with `clip` set are clipped to `width` and `height` from this offset.
 #[derive(Debug, Clone, Copy, Serialize, Deserialize)]
 pub struct GroupBounds {
     pub x: f32,
@@ -9,8 +15,54 @@ pub struct GroupBounds {
     pub height: Option<f32>,
 }
 
+/// Group of marks whose coordinates are relative to the group, see `local_transform`
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SceneGroup {
     pub bounds: GroupBounds,
+    /// Transform applied to the coordinates of the group's marks before they are offset by the
+    /// group's `x` and `y`. It doesn't move the group's clip region, so it can pan and zoom
+    /// the contents of a clipped group.
+    #[serde(default)]
+    pub transform: AffineTransform,
     pub marks: Vec<SceneMark>,
 }
+
+impl SceneGroup {
+    /// Transform from the coordinates of the group's marks to the coordinates of its parent
+    pub fn local_transform(&self) -> AffineTransform {
+        self.transform.then_translate(self.bounds.x, self.bounds.y)
+    }
+
+    /// Validate the group bounds and transform, and every mark in the group, recursively
+    pub fn validate(&self) -> Result<(), SceneGraphError> {
+        let GroupBounds {
+            x,
+            y,
+            width,
+            height,
+        } = self.bounds;
+        let invalid_offset = [("x", x), ("y", y)]
+            .into_iter()
+            .find(|(_, value)| !value.is_finite());
+        let invalid_size = [("width", width), ("height", height)]
+            .into_iter()
+            .filter_map(|(field, size)| Some((field, size?)))
+            .find(|(_, size)| !(size.is_finite() && *size >= 0.0));
+        if let Some((field, value)) = invalid_offset.or(invalid_size) {
+            return Err(SceneGraphError::InvalidGroupBounds { field, value });
+        }
+        let non_finite = self
+            .transform
+            .0
+            .iter()
+            .enumerate()
+            .find(|(_, value)| !value.is_finite());
+        if let Some((component, value)) = non_finite {
+            return Err(SceneGraphError::InvalidTransform {
+                component,
+                value: *value,
+            });
+        }
+        self.marks.iter().try_for_each(|mark| mark.validate())
+    }
+}
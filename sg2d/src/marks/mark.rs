@@ -1,3 +1,4 @@
+use crate::error::SceneGraphError;
 use crate::marks::group::SceneGroup;
 use crate::marks::rect::RectMark;
 use crate::marks::rule::RuleMark;
@@ -13,3 +14,27 @@ pub enum SceneMark {
     Text(Box<TextMark>),
     Group(SceneGroup),
 }
+
+impl SceneMark {
+    pub fn validate(&self) -> Result<(), SceneGraphError> {
+        match self {
+            SceneMark::Symbol(mark) => mark.validate(),
+            SceneMark::Rect(mark) => mark.validate(),
+            SceneMark::Rule(mark) => mark.validate(),
+            SceneMark::Text(mark) => mark.validate(),
+            SceneMark::Group(group) => group.validate(),
+        }
+    }
+
+    /// Whether the mark is clipped to the width and height of its group. Nested groups aren't
+    /// clipped themselves, only the marks inside them can be.
+    pub fn clip(&self) -> bool {
+        match self {
+            SceneMark::Symbol(mark) => mark.clip,
+            SceneMark::Rect(mark) => mark.clip,
+            SceneMark::Rule(mark) => mark.clip,
+            SceneMark::Text(mark) => mark.clip,
+            SceneMark::Group(_) => false,
+        }
+    }
+}
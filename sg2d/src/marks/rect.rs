@@ -1,4 +1,6 @@
-use crate::value::EncodingValue;
+use crate::error::SceneGraphError;
+use crate::validate::MarkValidator;
+use crate::value::{Color, EncodingValue, EncodingValueIter};
 use serde::{Deserialize, Serialize};
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -11,31 +13,42 @@ pub struct RectMark {
     pub y: EncodingValue<f32>,
     pub width: EncodingValue<f32>,
     pub height: EncodingValue<f32>,
-    pub fill: EncodingValue<[f32; 3]>,
+    pub fill: EncodingValue<Color>,
 }
 
 impl RectMark {
-    pub fn x_iter(&self) -> Box<dyn Iterator<Item = &f32> + '_> {
+    pub fn x_iter(&self) -> EncodingValueIter<'_, f32> {
         self.x.as_iter(self.len as usize)
     }
 
-    pub fn y_iter(&self) -> Box<dyn Iterator<Item = &f32> + '_> {
+    pub fn y_iter(&self) -> EncodingValueIter<'_, f32> {
         self.y.as_iter(self.len as usize)
     }
 
-    pub fn width_iter(&self) -> Box<dyn Iterator<Item = &f32> + '_> {
+    pub fn width_iter(&self) -> EncodingValueIter<'_, f32> {
         self.width.as_iter(self.len as usize)
     }
 
-    pub fn height_iter(&self) -> Box<dyn Iterator<Item = &f32> + '_> {
+    pub fn height_iter(&self) -> EncodingValueIter<'_, f32> {
         self.height.as_iter(self.len as usize)
     }
 
-    pub fn fill_iter(&self) -> Box<dyn Iterator<Item = &[f32; 3]> + '_> {
+    pub fn fill_iter(&self) -> EncodingValueIter<'_, Color> {
         self.fill.as_iter(self.len as usize)
     }
 }
 
+impl RectMark {
+    pub fn validate(&self) -> Result<(), SceneGraphError> {
+        let validator = MarkValidator::new(&self.name, self.len);
+        validator.finite("x", &self.x)?;
+        validator.finite("y", &self.y)?;
+        validator.size("width", &self.width)?;
+        validator.size("height", &self.height)?;
+        validator.color("fill", &self.fill)
+    }
+}
+
 impl Default for RectMark {
     fn default() -> Self {
         Self {
@@ -47,7 +60,7 @@ impl Default for RectMark {
             width: EncodingValue::Scalar { value: 0.0 },
             height: EncodingValue::Scalar { value: 0.0 },
             fill: EncodingValue::Scalar {
-                value: [0.0, 0.0, 0.0],
+                value: Color::BLACK,
             },
         }
     }
@@ -1,4 +1,6 @@
-use crate::value::{EncodingValue, StrokeCap};
+use crate::error::SceneGraphError;
+use crate::validate::MarkValidator;
+use crate::value::{Color, EncodingValue, EncodingValueIter, StrokeCap};
 use serde::{Deserialize, Serialize};
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -11,35 +13,48 @@ pub struct RuleMark {
     pub y0: EncodingValue<f32>,
     pub x1: EncodingValue<f32>,
     pub y1: EncodingValue<f32>,
-    pub stroke: EncodingValue<[f32; 3]>,
+    pub stroke: EncodingValue<Color>,
     pub stroke_width: EncodingValue<f32>,
     pub stroke_cap: EncodingValue<StrokeCap>,
 }
 
 impl RuleMark {
-    pub fn x0_iter(&self) -> Box<dyn Iterator<Item = &f32> + '_> {
+    pub fn x0_iter(&self) -> EncodingValueIter<'_, f32> {
         self.x0.as_iter(self.len as usize)
     }
-    pub fn y0_iter(&self) -> Box<dyn Iterator<Item = &f32> + '_> {
+    pub fn y0_iter(&self) -> EncodingValueIter<'_, f32> {
         self.y0.as_iter(self.len as usize)
     }
-    pub fn x1_iter(&self) -> Box<dyn Iterator<Item = &f32> + '_> {
+    pub fn x1_iter(&self) -> EncodingValueIter<'_, f32> {
         self.x1.as_iter(self.len as usize)
     }
-    pub fn y1_iter(&self) -> Box<dyn Iterator<Item = &f32> + '_> {
+    pub fn y1_iter(&self) -> EncodingValueIter<'_, f32> {
         self.y1.as_iter(self.len as usize)
     }
-    pub fn stroke_iter(&self) -> Box<dyn Iterator<Item = &[f32; 3]> + '_> {
+    pub fn stroke_iter(&self) -> EncodingValueIter<'_, Color> {
         self.stroke.as_iter(self.len as usize)
     }
-    pub fn stroke_width_iter(&self) -> Box<dyn Iterator<Item = &f32> + '_> {
+    pub fn stroke_width_iter(&self) -> EncodingValueIter<'_, f32> {
         self.stroke_width.as_iter(self.len as usize)
     }
-    pub fn stroke_cap_iter(&self) -> Box<dyn Iterator<Item = &StrokeCap> + '_> {
+    pub fn stroke_cap_iter(&self) -> EncodingValueIter<'_, StrokeCap> {
         self.stroke_cap.as_iter(self.len as usize)
     }
 }
 
+impl RuleMark {
+    pub fn validate(&self) -> Result<(), SceneGraphError> {
+        let validator = MarkValidator::new(&self.name, self.len);
+        validator.finite("x0", &self.x0)?;
+        validator.finite("y0", &self.y0)?;
+        validator.finite("x1", &self.x1)?;
+        validator.finite("y1", &self.y1)?;
+        validator.color("stroke", &self.stroke)?;
+        validator.size("stroke_width", &self.stroke_width)?;
+        validator.len("stroke_cap", &self.stroke_cap)
+    }
+}
+
 impl Default for RuleMark {
     fn default() -> Self {
         Self {
@@ -51,7 +66,7 @@ impl Default for RuleMark {
             x1: EncodingValue::Scalar { value: 0.0 },
             y1: EncodingValue::Scalar { value: 0.0 },
             stroke: EncodingValue::Scalar {
-                value: [0.0, 0.0, 0.0],
+                value: Color::BLACK,
             },
             stroke_width: EncodingValue::Scalar { value: 1.0 },
             stroke_cap: EncodingValue::Scalar {
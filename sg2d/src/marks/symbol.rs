@@ -1,4 +1,6 @@
-use crate::value::EncodingValue;
+use crate::error::SceneGraphError;
+use crate::validate::MarkValidator;
+use crate::value::{Color, EncodingValue, EncodingValueIter};
 use serde::{Deserialize, Serialize};
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -11,36 +13,56 @@ pub struct SymbolMark {
     pub len: u32,
     pub x: EncodingValue<f32>,
     pub y: EncodingValue<f32>,
-    pub fill: EncodingValue<[f32; 4]>,
+    pub fill: EncodingValue<Color>,
     pub size: EncodingValue<f32>,
-    pub stroke: EncodingValue<[f32; 4]>,
+    pub stroke: EncodingValue<Color>,
     pub angle: EncodingValue<f32>,
 }
 
 impl SymbolMark {
-    pub fn x_iter(&self) -> Box<dyn Iterator<Item = &f32> + '_> {
+    pub fn x_iter(&self) -> EncodingValueIter<'_, f32> {
         self.x.as_iter(self.len as usize)
     }
 
-    pub fn y_iter(&self) -> Box<dyn Iterator<Item = &f32> + '_> {
+    pub fn y_iter(&self) -> EncodingValueIter<'_, f32> {
         self.y.as_iter(self.len as usize)
     }
 
-    pub fn fill_iter(&self) -> Box<dyn Iterator<Item = &[f32; 4]> + '_> {
+    pub fn fill_iter(&self) -> EncodingValueIter<'_, Color> {
         self.fill.as_iter(self.len as usize)
     }
 
-    pub fn size_iter(&self) -> Box<dyn Iterator<Item = &f32> + '_> {
+    pub fn size_iter(&self) -> EncodingValueIter<'_, f32> {
         self.size.as_iter(self.len as usize)
     }
-    pub fn stroke_iter(&self) -> Box<dyn Iterator<Item = &[f32; 4]> + '_> {
+    pub fn stroke_iter(&self) -> EncodingValueIter<'_, Color> {
         self.stroke.as_iter(self.len as usize)
     }
-    pub fn angle_iter(&self) -> Box<dyn Iterator<Item = &f32> + '_> {
+    pub fn angle_iter(&self) -> EncodingValueIter<'_, f32> {
         self.angle.as_iter(self.len as usize)
     }
 }
 
+impl SymbolMark {
+    pub fn validate(&self) -> Result<(), SceneGraphError> {
+        let validator = MarkValidator::new(&self.name, self.len);
+        if let Some(stroke_width) = self.stroke_width {
+            validator.size(
+                "stroke_width",
+                &EncodingValue::Scalar {
+                    value: stroke_width,
+                },
+            )?;
+        }
+        validator.finite("x", &self.x)?;
+        validator.finite("y", &self.y)?;
+        validator.color("fill", &self.fill)?;
+        validator.size("size", &self.size)?;
+        validator.color("stroke", &self.stroke)?;
+        validator.finite("angle", &self.angle)
+    }
+}
+
 impl Default for SymbolMark {
     fn default() -> Self {
         Self {
@@ -52,11 +74,11 @@ impl Default for SymbolMark {
             x: EncodingValue::Scalar { value: 0.0 },
             y: EncodingValue::Scalar { value: 0.0 },
             fill: EncodingValue::Scalar {
-                value: [0.0, 0.0, 0.0, 0.0],
+                value: Color::TRANSPARENT,
             },
             size: EncodingValue::Scalar { value: 20.0 },
             stroke: EncodingValue::Scalar {
-                value: [0.0, 0.0, 0.0, 0.0],
+                value: Color::TRANSPARENT,
             },
             angle: EncodingValue::Scalar { value: 0.0 },
         }
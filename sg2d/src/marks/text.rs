@@ -1,4 +1,6 @@
-use crate::value::EncodingValue;
+use crate::error::SceneGraphError;
+use crate::validate::MarkValidator;
+use crate::value::{Color, EncodingValue, EncodingValueIter};
 use serde::{Deserialize, Serialize};
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -12,9 +14,19 @@ pub struct TextMark {
     pub y: EncodingValue<f32>,
     pub align: EncodingValue<TextAlignSpec>,
     pub baseline: EncodingValue<TextBaselineSpec>,
+    /// Defaults to left-to-right, for scene graphs written before text had a direction
+    #[serde(default = "default_dir")]
+    pub dir: EncodingValue<TextDirectionSpec>,
     pub opacity: EncodingValue<f32>,
     pub angle: EncodingValue<f32>,
-    pub color: EncodingValue<[f32; 3]>,
+    pub color: EncodingValue<Color>,
+    /// Halo channels default to no halo, for scene graphs written before text had halos
+    #[serde(default = "default_stroke")]
+    pub stroke: EncodingValue<Color>,
+    #[serde(default = "default_stroke_width")]
+    pub stroke_width: EncodingValue<f32>,
+    #[serde(default = "default_stroke_opacity")]
+    pub stroke_opacity: EncodingValue<f32>,
     pub dx: EncodingValue<f32>,
     pub dy: EncodingValue<f32>,
     pub font: EncodingValue<String>,
@@ -24,54 +36,111 @@ pub struct TextMark {
     pub limit: EncodingValue<f32>,
 }
 
+fn default_dir() -> EncodingValue<TextDirectionSpec> {
+    EncodingValue::Scalar {
+        value: TextDirectionSpec::Ltr,
+    }
+}
+
+fn default_stroke() -> EncodingValue<Color> {
+    EncodingValue::Scalar {
+        value: Color::BLACK,
+    }
+}
+
+fn default_stroke_width() -> EncodingValue<f32> {
+    EncodingValue::Scalar { value: 0.0 }
+}
+
+fn default_stroke_opacity() -> EncodingValue<f32> {
+    EncodingValue::Scalar { value: 1.0 }
+}
+
 impl TextMark {
-    pub fn text_iter(&self) -> Box<dyn Iterator<Item = &String> + '_> {
+    pub fn text_iter(&self) -> EncodingValueIter<'_, String> {
         self.text.as_iter(self.len as usize)
     }
-    pub fn x_iter(&self) -> Box<dyn Iterator<Item = &f32> + '_> {
+    pub fn x_iter(&self) -> EncodingValueIter<'_, f32> {
         self.x.as_iter(self.len as usize)
     }
-    pub fn y_iter(&self) -> Box<dyn Iterator<Item = &f32> + '_> {
+    pub fn y_iter(&self) -> EncodingValueIter<'_, f32> {
         self.y.as_iter(self.len as usize)
     }
-    pub fn align_iter(&self) -> Box<dyn Iterator<Item = &TextAlignSpec> + '_> {
+    pub fn align_iter(&self) -> EncodingValueIter<'_, TextAlignSpec> {
         self.align.as_iter(self.len as usize)
     }
-    pub fn baseline_iter(&self) -> Box<dyn Iterator<Item = &TextBaselineSpec> + '_> {
+    pub fn baseline_iter(&self) -> EncodingValueIter<'_, TextBaselineSpec> {
         self.baseline.as_iter(self.len as usize)
     }
-    pub fn opacity_iter(&self) -> Box<dyn Iterator<Item = &f32> + '_> {
+    pub fn dir_iter(&self) -> EncodingValueIter<'_, TextDirectionSpec> {
+        self.dir.as_iter(self.len as usize)
+    }
+    pub fn opacity_iter(&self) -> EncodingValueIter<'_, f32> {
         self.opacity.as_iter(self.len as usize)
     }
-    pub fn angle_iter(&self) -> Box<dyn Iterator<Item = &f32> + '_> {
+    pub fn angle_iter(&self) -> EncodingValueIter<'_, f32> {
         self.angle.as_iter(self.len as usize)
     }
-    pub fn color_iter(&self) -> Box<dyn Iterator<Item = &[f32; 3]> + '_> {
+    pub fn color_iter(&self) -> EncodingValueIter<'_, Color> {
         self.color.as_iter(self.len as usize)
     }
-    pub fn dx_iter(&self) -> Box<dyn Iterator<Item = &f32> + '_> {
-        self.dx.as_iter(self.len as usize)
+    pub fn stroke_iter(&self) -> EncodingValueIter<'_, Color> {
+        self.stroke.as_iter(self.len as usize)
     }
-    pub fn dy_iter(&self) -> Box<dyn Iterator<Item = &f32> + '_> {
+    pub fn stroke_width_iter(&self) -> EncodingValueIter<'_, f32> {
+        self.stroke_width.as_iter(self.len as usize)
+    }
+    pub fn stroke_opacity_iter(&self) -> EncodingValueIter<'_, f32> {
+        self.stroke_opacity.as_iter(self.len as usize)
+    }
+    pub fn dx_iter(&self) -> EncodingValueIter<'_, f32> {
         self.dx.as_iter(self.len as usize)
     }
-    pub fn font_iter(&self) -> Box<dyn Iterator<Item = &String> + '_> {
+    pub fn dy_iter(&self) -> EncodingValueIter<'_, f32> {
+        self.dy.as_iter(self.len as usize)
+    }
+    pub fn font_iter(&self) -> EncodingValueIter<'_, String> {
         self.font.as_iter(self.len as usize)
     }
-    pub fn font_size_iter(&self) -> Box<dyn Iterator<Item = &f32> + '_> {
+    pub fn font_size_iter(&self) -> EncodingValueIter<'_, f32> {
         self.font_size.as_iter(self.len as usize)
     }
-    pub fn font_weight_iter(&self) -> Box<dyn Iterator<Item = &FontWeightSpec> + '_> {
+    pub fn font_weight_iter(&self) -> EncodingValueIter<'_, FontWeightSpec> {
         self.font_weight.as_iter(self.len as usize)
     }
-    pub fn font_style_iter(&self) -> Box<dyn Iterator<Item = &FontStyleSpec> + '_> {
+    pub fn font_style_iter(&self) -> EncodingValueIter<'_, FontStyleSpec> {
         self.font_style.as_iter(self.len as usize)
     }
-    pub fn limit_iter(&self) -> Box<dyn Iterator<Item = &f32> + '_> {
+    pub fn limit_iter(&self) -> EncodingValueIter<'_, f32> {
         self.limit.as_iter(self.len as usize)
     }
 }
 
+impl TextMark {
+    pub fn validate(&self) -> Result<(), SceneGraphError> {
+        let validator = MarkValidator::new(&self.name, self.len);
+        validator.len("text", &self.text)?;
+        validator.finite("x", &self.x)?;
+        validator.finite("y", &self.y)?;
+        validator.len("align", &self.align)?;
+        validator.len("baseline", &self.baseline)?;
+        validator.len("dir", &self.dir)?;
+        validator.finite("opacity", &self.opacity)?;
+        validator.finite("angle", &self.angle)?;
+        validator.color("color", &self.color)?;
+        validator.color("stroke", &self.stroke)?;
+        validator.size("stroke_width", &self.stroke_width)?;
+        validator.finite("stroke_opacity", &self.stroke_opacity)?;
+        validator.finite("dx", &self.dx)?;
+        validator.finite("dy", &self.dy)?;
+        validator.len("font", &self.font)?;
+        validator.size("font_size", &self.font_size)?;
+        validator.len("font_weight", &self.font_weight)?;
+        validator.len("font_style", &self.font_style)?;
+        validator.size("limit", &self.limit)
+    }
+}
+
 impl Default for TextMark {
     fn default() -> Self {
         Self {
@@ -89,11 +158,15 @@ impl Default for TextMark {
             baseline: EncodingValue::Scalar {
                 value: TextBaselineSpec::Bottom,
             },
+            dir: default_dir(),
             opacity: EncodingValue::Scalar { value: 1.0 },
             angle: EncodingValue::Scalar { value: 0.0 },
             color: EncodingValue::Scalar {
-                value: [0.0, 0.0, 0.0],
+                value: Color::BLACK,
             },
+            stroke: default_stroke(),
+            stroke_width: default_stroke_width(),
+            stroke_opacity: default_stroke_opacity(),
             dx: EncodingValue::Scalar { value: 0.0 },
             dy: EncodingValue::Scalar { value: 0.0 },
             font: EncodingValue::Scalar {
@@ -132,6 +205,16 @@ pub enum TextBaselineSpec {
     LineBottom,
 }
 
+/// Base direction of text, which determines the ordering of mixed-direction runs
+/// and the side that is truncated when text exceeds its limit
+#[derive(Default, Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TextDirectionSpec {
+    #[default]
+    Ltr,
+    Rtl,
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
 #[serde(untagged)]
 pub enum FontWeightSpec {
@@ -1,4 +1,6 @@
+use crate::error::SceneGraphError;
 use crate::marks::group::SceneGroup;
+use crate::value::Color;
 use serde::{Deserialize, Serialize};
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -6,4 +8,34 @@ pub struct SceneGraph {
     pub groups: Vec<SceneGroup>,
     pub width: f32,
     pub height: f32,
+    /// Color behind every group. When it's not set, canvases that render images clear to
+    /// white and vector formats leave the background empty.
+    #[serde(default)]
+    pub background: Option<Color>,
+}
+
+impl SceneGraph {
+    /// Check that the scene graph can be rendered.
+    ///
+    /// Array channels must have one value per mark item, coordinates must be finite, sizes must
+    /// be finite and non-negative, and color components, including those of the background, must
+    /// be between 0 and 1.
+    pub fn validate(&self) -> Result<(), SceneGraphError> {
+        let valid_dimension = |v: f32| v.is_finite() && v >= 0.0;
+        if !valid_dimension(self.width) || !valid_dimension(self.height) {
+            return Err(SceneGraphError::InvalidDimensions {
+                width: self.width,
+                height: self.height,
+            });
+        }
+        if let Some(background) = &self.background {
+            let color = background.to_array();
+            if !color.iter().all(|c| (0.0..=1.0).contains(c)) {
+                return Err(SceneGraphError::InvalidBackground {
+                    color: color.to_vec(),
+                });
+            }
+        }
+        self.groups.iter().try_for_each(|group| group.validate())
+    }
 }
@@ -0,0 +1,100 @@
+use serde::{Deserialize, Serialize};
+
+/// 2D affine transform `[a, b, c, d, e, f]`, in the order of SVG's `matrix()`.
+///
+/// A point `(x, y)` maps to `(a * x + c * y + e, b * x + d * y + f)`. Scene coordinates point
+/// down, so positive rotations are clockwise.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct AffineTransform(pub [f32; 6]);
+
+impl Default for AffineTransform {
+    fn default() -> Self {
+        Self::IDENTITY
+    }
+}
+
+impl AffineTransform {
+    pub const IDENTITY: Self = Self([1.0, 0.0, 0.0, 1.0, 0.0, 0.0]);
+
+    pub fn translate(tx: f32, ty: f32) -> Self {
+        Self([1.0, 0.0, 0.0, 1.0, tx, ty])
+    }
+
+    pub fn scale(sx: f32, sy: f32) -> Self {
+        Self([sx, 0.0, 0.0, sy, 0.0, 0.0])
+    }
+
+    /// Rotation about the origin by an angle in degrees
+    pub fn rotate(degrees: f32) -> Self {
+        let (sin, cos) = degrees.to_radians().sin_cos();
+        Self([cos, sin, -sin, cos, 0.0, 0.0])
+    }
+
+    /// Transform that applies this transform followed by `other`
+    pub fn then(&self, other: &Self) -> Self {
+        let [a0, b0, c0, d0, e0, f0] = self.0;
+        let [a1, b1, c1, d1, e1, f1] = other.0;
+        Self([
+            a1 * a0 + c1 * b0,
+            b1 * a0 + d1 * b0,
+            a1 * c0 + c1 * d0,
+            b1 * c0 + d1 * d0,
+            a1 * e0 + c1 * f0 + e1,
+            b1 * e0 + d1 * f0 + f1,
+        ])
+    }
+
+    pub fn then_translate(&self, tx: f32, ty: f32) -> Self {
+        self.then(&Self::translate(tx, ty))
+    }
+
+    pub fn apply(&self, [x, y]: [f32; 2]) -> [f32; 2] {
+        let [a, b, c, d, e, f] = self.0;
+        [a * x + c * y + e, b * x + d * y + f]
+    }
+
+    pub fn determinant(&self) -> f32 {
+        let [a, b, c, d, ..] = self.0;
+        a * d - b * c
+    }
+
+    /// Inverse transform, or None if the transform collapses the plane
+    pub fn inverse(&self) -> Option<Self> {
+        let det = self.determinant();
+        if det == 0.0 || !det.is_finite() {
+            return None;
+        }
+        let [a, b, c, d, e, f] = self.0;
+        Some(Self([
+            d / det,
+            -b / det,
+            -c / det,
+            a / det,
+            (c * f - d * e) / det,
+            (b * e - a * f) / det,
+        ]))
+    }
+
+    /// Factor by which the transform scales areas, as a length. This is exact for
+    /// transforms without skew or non-uniform scaling.
+    pub fn scale_factor(&self) -> f32 {
+        self.determinant().abs().sqrt()
+    }
+
+    /// Angle, in degrees, by which the transform rotates the x-axis
+    pub fn rotation(&self) -> f32 {
+        let [a, b, ..] = self.0;
+        b.atan2(a).to_degrees()
+    }
+
+    /// Whether the transform only scales and translates, without rotating, skewing or
+    /// reflecting
+    pub fn is_axis_aligned(&self) -> bool {
+        let [a, b, c, d, ..] = self.0;
+        b == 0.0 && c == 0.0 && a > 0.0 && d > 0.0
+    }
+
+    pub fn is_identity(&self) -> bool {
+        *self == Self::IDENTITY
+    }
+}
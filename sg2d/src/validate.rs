@@ -0,0 +1,95 @@
+use crate::error::SceneGraphError;
+use crate::value::{Color, EncodingValue};
+
+/// Checks the encoding channels of a single mark, reporting errors with the mark name
+/// and the index of the first invalid item
+pub(crate) struct MarkValidator<'a> {
+    mark: &'a str,
+    len: u32,
+}
+
+impl<'a> MarkValidator<'a> {
+    pub fn new(mark: &'a str, len: u32) -> Self {
+        Self { mark, len }
+    }
+
+    /// Check that an array channel has exactly one value per item
+    pub fn len<T>(
+        &self,
+        channel: &'static str,
+        value: &EncodingValue<T>,
+    ) -> Result<(), SceneGraphError> {
+        match value {
+            EncodingValue::Array { values } if values.len() != self.len as usize => {
+                Err(SceneGraphError::ArrayLengthMismatch {
+                    mark: self.mark.to_string(),
+                    channel,
+                    len: self.len,
+                    values: values.len(),
+                })
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// Check that every value of a numeric channel is finite
+    pub fn finite(
+        &self,
+        channel: &'static str,
+        value: &EncodingValue<f32>,
+    ) -> Result<(), SceneGraphError> {
+        self.len(channel, value)?;
+        for (index, value) in value.as_iter(self.len as usize).enumerate() {
+            if !value.is_finite() {
+                return Err(SceneGraphError::NonFiniteValue {
+                    mark: self.mark.to_string(),
+                    channel,
+                    index,
+                    value: *value,
+                });
+            }
+        }
+        Ok(())
+    }
+
+    /// Check that every value of a size channel is finite and not negative
+    pub fn size(
+        &self,
+        channel: &'static str,
+        value: &EncodingValue<f32>,
+    ) -> Result<(), SceneGraphError> {
+        self.finite(channel, value)?;
+        for (index, value) in value.as_iter(self.len as usize).enumerate() {
+            if *value < 0.0 {
+                return Err(SceneGraphError::NegativeValue {
+                    mark: self.mark.to_string(),
+                    channel,
+                    index,
+                    value: *value,
+                });
+            }
+        }
+        Ok(())
+    }
+
+    /// Check that every component of a color channel is between 0 and 1
+    pub fn color(
+        &self,
+        channel: &'static str,
+        value: &EncodingValue<Color>,
+    ) -> Result<(), SceneGraphError> {
+        self.len(channel, value)?;
+        for (index, color) in value.as_iter(self.len as usize).enumerate() {
+            let color = color.to_array();
+            if !color.iter().all(|c| (0.0..=1.0).contains(c)) {
+                return Err(SceneGraphError::InvalidColor {
+                    mark: self.mark.to_string(),
+                    channel,
+                    index,
+                    color: color.to_vec(),
+                });
+            }
+        }
+        Ok(())
+    }
+}
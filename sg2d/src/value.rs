@@ -1,4 +1,7 @@
+use crate::error::SceneGraphError;
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::str::FromStr;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(tag = "type", rename_all = "kebab-case")]
@@ -8,17 +11,247 @@ pub enum EncodingValue<T> {
 }
 
 impl<T> EncodingValue<T> {
-    pub fn as_iter(&self, scalar_len: usize) -> Box<dyn Iterator<Item = &T> + '_> {
+    /// Iterate over `scalar_len` items, repeating a scalar value
+    pub fn as_iter(&self, scalar_len: usize) -> EncodingValueIter<'_, T> {
         match self {
-            EncodingValue::Scalar { value } => Box::new(std::iter::repeat(value).take(scalar_len)),
-            EncodingValue::Array { values } => Box::new(values.iter()),
+            EncodingValue::Scalar { value } => {
+                EncodingValueIter::Scalar(std::iter::repeat_n(value, scalar_len))
+            }
+            EncodingValue::Array { values } => EncodingValueIter::Array(values.iter()),
         }
     }
+
+    /// Values of an array, or a single item slice of a scalar value
+    pub fn as_slice(&self) -> &[T] {
+        match self {
+            EncodingValue::Scalar { value } => std::slice::from_ref(value),
+            EncodingValue::Array { values } => values,
+        }
+    }
+
+    /// Value of item `i`, where a scalar value applies to every item
+    ///
+    /// Panics if `i` is out of bounds of an array.
+    #[inline]
+    pub fn item(&self, i: usize) -> &T {
+        match self {
+            EncodingValue::Scalar { value } => value,
+            EncodingValue::Array { values } => &values[i],
+        }
+    }
+
+    /// Map values, calling `f` once for a scalar
+    pub fn map<U>(&self, mut f: impl FnMut(&T) -> U) -> EncodingValue<U> {
+        match self {
+            EncodingValue::Scalar { value } => EncodingValue::Scalar { value: f(value) },
+            EncodingValue::Array { values } => EncodingValue::Array {
+                values: values.iter().map(f).collect(),
+            },
+        }
+    }
+}
+
+/// Iterator over the items of an [`EncodingValue`]
+#[derive(Debug, Clone)]
+pub enum EncodingValueIter<'a, T> {
+    Scalar(std::iter::RepeatN<&'a T>),
+    Array(std::slice::Iter<'a, T>),
 }
 
+impl<'a, T> Iterator for EncodingValueIter<'a, T> {
+    type Item = &'a T;
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        match self {
+            EncodingValueIter::Scalar(iter) => iter.next(),
+            EncodingValueIter::Array(iter) => iter.next(),
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        match self {
+            EncodingValueIter::Scalar(iter) => iter.size_hint(),
+            EncodingValueIter::Array(iter) => iter.size_hint(),
+        }
+    }
+}
+
+impl<T> ExactSizeIterator for EncodingValueIter<'_, T> {}
+
 #[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
 pub enum StrokeCap {
     Butt,
     Round,
     Square,
 }
+
+/// Encoding of color components
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum ColorSpace {
+    /// Gamma encoded sRGB, as in CSS
+    Srgb,
+    /// sRGB primaries with linear intensities, as shaders read and write sRGB textures
+    Linear,
+}
+
+/// RGBA color with straight (not premultiplied) alpha.
+///
+/// Components are stored gamma encoded, as in CSS. Use `to_space` and `from_space` to convert
+/// from or to linear components; alpha is never encoded. Colors serialize as `[r, g, b, a]`,
+/// and deserialize from `[r, g, b]`, `[r, g, b, a]` or a CSS color string.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(try_from = "ColorRepr", into = "[f32; 4]")]
+pub struct Color {
+    pub r: f32,
+    pub g: f32,
+    pub b: f32,
+    pub a: f32,
+}
+
+impl Color {
+    pub const BLACK: Self = Self::rgb(0.0, 0.0, 0.0);
+    pub const WHITE: Self = Self::rgb(1.0, 1.0, 1.0);
+    pub const TRANSPARENT: Self = Self::rgba(0.0, 0.0, 0.0, 0.0);
+
+    /// Opaque color from sRGB components
+    pub const fn rgb(r: f32, g: f32, b: f32) -> Self {
+        Self::rgba(r, g, b, 1.0)
+    }
+
+    /// Color from sRGB components and alpha
+    pub const fn rgba(r: f32, g: f32, b: f32, a: f32) -> Self {
+        Self { r, g, b, a }
+    }
+
+    /// Color from components in the given color space
+    pub fn from_space([r, g, b, a]: [f32; 4], space: ColorSpace) -> Self {
+        match space {
+            ColorSpace::Srgb => Self::rgba(r, g, b, a),
+            ColorSpace::Linear => {
+                Self::rgba(linear_to_srgb(r), linear_to_srgb(g), linear_to_srgb(b), a)
+            }
+        }
+    }
+
+    /// Components in the given color space
+    pub fn to_space(&self, space: ColorSpace) -> [f32; 4] {
+        match space {
+            ColorSpace::Srgb => self.to_array(),
+            ColorSpace::Linear => [
+                srgb_to_linear(self.r),
+                srgb_to_linear(self.g),
+                srgb_to_linear(self.b),
+                self.a,
+            ],
+        }
+    }
+
+    /// sRGB components and alpha
+    pub fn to_array(&self) -> [f32; 4] {
+        [self.r, self.g, self.b, self.a]
+    }
+
+    pub fn with_alpha(self, a: f32) -> Self {
+        Self { a, ..self }
+    }
+
+    /// Parse a CSS color, such as `"steelblue"`, `"#4682b4"` or `"rgba(70, 130, 180, 0.5)"`.
+    /// See `ColorCache` for parsing many repeated strings.
+    pub fn parse(css: &str) -> Result<Self, SceneGraphError> {
+        let c = csscolorparser::parse(css)
+            .map_err(|_| SceneGraphError::InvalidCssColor(css.to_string()))?;
+        Ok(Self::rgba(c.r as f32, c.g as f32, c.b as f32, c.a as f32))
+    }
+}
+
+fn srgb_to_linear(c: f32) -> f32 {
+    if c <= 0.04045 {
+        c / 12.92
+    } else {
+        ((c + 0.055) / 1.055).powf(2.4)
+    }
+}
+
+fn linear_to_srgb(c: f32) -> f32 {
+    if c <= 0.0031308 {
+        c * 12.92
+    } else {
+        1.055 * c.powf(1.0 / 2.4) - 0.055
+    }
+}
+
+impl Default for Color {
+    fn default() -> Self {
+        Self::BLACK
+    }
+}
+
+impl From<[f32; 3]> for Color {
+    fn from([r, g, b]: [f32; 3]) -> Self {
+        Self::rgb(r, g, b)
+    }
+}
+
+impl From<[f32; 4]> for Color {
+    fn from([r, g, b, a]: [f32; 4]) -> Self {
+        Self::rgba(r, g, b, a)
+    }
+}
+
+impl From<Color> for [f32; 4] {
+    fn from(color: Color) -> Self {
+        color.to_array()
+    }
+}
+
+impl FromStr for Color {
+    type Err = SceneGraphError;
+
+    fn from_str(css: &str) -> Result<Self, Self::Err> {
+        Self::parse(css)
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum ColorRepr {
+    Rgb([f32; 3]),
+    Rgba([f32; 4]),
+    Css(String),
+}
+
+impl TryFrom<ColorRepr> for Color {
+    type Error = SceneGraphError;
+
+    fn try_from(repr: ColorRepr) -> Result<Self, Self::Error> {
+        match repr {
+            ColorRepr::Rgb(rgb) => Ok(rgb.into()),
+            ColorRepr::Rgba(rgba) => Ok(rgba.into()),
+            ColorRepr::Css(css) => Self::parse(&css),
+        }
+    }
+}
+
+/// Parser of CSS colors that reuses the result for strings it has already parsed. Scene graphs
+/// typically repeat a handful of colors across many items.
+#[derive(Debug, Clone, Default)]
+pub struct ColorCache {
+    colors: HashMap<String, Color>,
+}
+
+impl ColorCache {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn parse(&mut self, css: &str) -> Result<Color, SceneGraphError> {
+        if let Some(color) = self.colors.get(css) {
+            return Ok(*color);
+        }
+        let color = Color::parse(css)?;
+        self.colors.insert(css.to_string(), color);
+        Ok(color)
+    }
+}
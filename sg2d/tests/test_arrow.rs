@@ -0,0 +1,165 @@
+#[cfg(all(test, feature = "arrow"))]
+mod tests {
+    use arrow_array::types::Int8Type;
+    use arrow_array::{
+        ArrayRef, DictionaryArray, Float32Array, Float64Array, Int32Array, RecordBatch, StringArray,
+    };
+    use sg2d::error::{ArrowConversionError, SceneGraphError};
+    use sg2d::marks::rect::RectMark;
+    use sg2d::marks::rule::RuleMark;
+    use sg2d::marks::symbol::SymbolMark;
+    use sg2d::marks::text::TextMark;
+    use sg2d::value::{Color, EncodingValue};
+    use std::sync::Arc;
+
+    fn batch(columns: Vec<(&str, ArrayRef)>) -> RecordBatch {
+        RecordBatch::try_from_iter(columns).unwrap()
+    }
+
+    fn array<T: Clone>(value: &EncodingValue<T>) -> Vec<T> {
+        match value {
+            EncodingValue::Array { values } => values.clone(),
+            EncodingValue::Scalar { .. } => panic!("Expected an array"),
+        }
+    }
+
+    #[test]
+    fn test_symbol_from_record_batch() {
+        let batch = batch(vec![
+            (
+                "a",
+                Arc::new(Float32Array::from(vec![1.0, 2.0, 3.0])) as ArrayRef,
+            ),
+            ("b", Arc::new(Float64Array::from(vec![4.0, 5.0, 6.0]))),
+            (
+                "category",
+                Arc::new(DictionaryArray::<Int8Type>::from_iter([
+                    Some("red"),
+                    Some("#0000ff"),
+                    None,
+                ])),
+            ),
+        ]);
+        let mark = SymbolMark::from_record_batch(
+            "points",
+            &batch,
+            &[("x", "a"), ("y", "b"), ("fill", "category")],
+        )
+        .unwrap();
+        assert_eq!(mark.name, "points");
+        assert_eq!(mark.len, 3);
+        assert_eq!(array(&mark.x), vec![1.0, 2.0, 3.0]);
+        assert_eq!(array(&mark.y), vec![4.0, 5.0, 6.0]);
+        assert_eq!(
+            array(&mark.fill),
+            vec![
+                Color::rgb(1.0, 0.0, 0.0),
+                Color::rgb(0.0, 0.0, 1.0),
+                Color::TRANSPARENT
+            ]
+        );
+        // Unmapped channels keep their defaults
+        assert!(matches!(mark.size, EncodingValue::Scalar { value } if value == 20.0));
+    }
+
+    #[test]
+    fn test_null_numbers_skip_rows() {
+        let batch = batch(vec![
+            (
+                "x0",
+                Arc::new(Float32Array::from(vec![
+                    Some(1.0),
+                    None,
+                    Some(3.0),
+                    Some(4.0),
+                ])) as ArrayRef,
+            ),
+            (
+                "x1",
+                Arc::new(Float64Array::from(vec![
+                    Some(10.0),
+                    Some(20.0),
+                    Some(30.0),
+                    None,
+                ])),
+            ),
+            (
+                "color",
+                Arc::new(StringArray::from(vec![
+                    Some("black"),
+                    Some("white"),
+                    None,
+                    Some("red"),
+                ])),
+            ),
+        ]);
+        let mark = RuleMark::from_record_batch(
+            "rules",
+            &batch,
+            &[("x0", "x0"), ("x1", "x1"), ("stroke", "color")],
+        )
+        .unwrap();
+        assert_eq!(mark.len, 2);
+        assert_eq!(array(&mark.x0), vec![1.0, 3.0]);
+        assert_eq!(array(&mark.x1), vec![10.0, 30.0]);
+        assert_eq!(array(&mark.stroke), vec![Color::BLACK, Color::TRANSPARENT]);
+    }
+
+    #[test]
+    fn test_text_from_record_batch() {
+        let batch = batch(vec![
+            (
+                "label",
+                Arc::new(StringArray::from(vec![Some("a"), None])) as ArrayRef,
+            ),
+            (
+                "font",
+                Arc::new(DictionaryArray::<Int8Type>::from_iter(["serif", "serif"])),
+            ),
+            ("x", Arc::new(Float32Array::from(vec![1.0, 2.0]))),
+        ]);
+        let mark = TextMark::from_record_batch(
+            "labels",
+            &batch,
+            &[("text", "label"), ("font", "font"), ("x", "x")],
+        )
+        .unwrap();
+        assert_eq!(mark.len, 2);
+        assert_eq!(array(&mark.text), vec!["a".to_string(), String::new()]);
+        assert_eq!(array(&mark.font), vec!["serif".to_string(); 2]);
+    }
+
+    #[test]
+    fn test_errors() {
+        let batch = batch(vec![
+            ("count", Arc::new(Int32Array::from(vec![1, 2])) as ArrayRef),
+            ("width", Arc::new(Float32Array::from(vec![1.0, -2.0]))),
+            ("color", Arc::new(StringArray::from(vec!["red", "bogus"]))),
+        ]);
+        assert!(matches!(
+            RectMark::from_record_batch("bars", &batch, &[("x", "missing")]),
+            Err(ArrowConversionError::MissingColumn(column)) if column == "missing"
+        ));
+        assert!(matches!(
+            RectMark::from_record_batch("bars", &batch, &[("stroke", "color")]),
+            Err(ArrowConversionError::UnknownChannel(channel)) if channel == "stroke"
+        ));
+        assert!(matches!(
+            RectMark::from_record_batch("bars", &batch, &[("x", "count")]),
+            Err(ArrowConversionError::UnsupportedType { channel: "x", .. })
+        ));
+        assert!(matches!(
+            RectMark::from_record_batch("bars", &batch, &[("fill", "color")]),
+            Err(ArrowConversionError::SceneGraph(
+                SceneGraphError::InvalidCssColor(_)
+            ))
+        ));
+        // Marks are validated
+        assert!(matches!(
+            RectMark::from_record_batch("bars", &batch, &[("width", "width")]),
+            Err(ArrowConversionError::SceneGraph(
+                SceneGraphError::NegativeValue { .. }
+            ))
+        ));
+    }
+}
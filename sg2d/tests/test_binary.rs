@@ -0,0 +1,262 @@
+#[cfg(test)]
+mod tests {
+    use lyon_path::geom::point;
+    use lyon_path::Path;
+    use sg2d::binary::{
+        read_scene_graph, write_scene_graph, MarkKind, SceneEvent, SceneGraphReader,
+        SceneGraphWriter, MAGIC, VERSION,
+    };
+    use sg2d::error::BinaryFormatError;
+    use sg2d::marks::group::{GroupBounds, SceneGroup};
+    use sg2d::marks::mark::SceneMark;
+    use sg2d::marks::rect::RectMark;
+    use sg2d::marks::rule::RuleMark;
+    use sg2d::marks::symbol::{SymbolMark, SymbolShape};
+    use sg2d::marks::text::{
+        FontStyleSpec, FontWeightSpec, TextAlignSpec, TextBaselineSpec, TextDirectionSpec, TextMark,
+    };
+    use sg2d::scene_graph::SceneGraph;
+    use sg2d::transform::AffineTransform;
+    use sg2d::value::{Color, StrokeCap};
+    use std::borrow::Cow;
+
+    fn triangle() -> Path {
+        let mut builder = Path::builder();
+        builder.begin(point(0.0, -1.0));
+        builder.line_to(point(1.0, 1.0));
+        builder.quadratic_bezier_to(point(0.0, 1.5), point(-1.0, 1.0));
+        builder.end(true);
+        builder.begin(point(0.0, 0.0));
+        builder.cubic_bezier_to(point(0.2, 0.2), point(0.4, 0.2), point(0.6, 0.0));
+        builder.end(false);
+        builder.build()
+    }
+
+    fn scene_graph() -> SceneGraph {
+        SceneGraph::builder(200.0, 100.0)
+            .background([0.0, 0.0, 0.0, 0.5])
+            .group(
+                SceneGroup::builder()
+                    .origin(10.0, 20.0)
+                    .size(180.0, 80.0)
+                    .mark(
+                        SymbolMark::builder("points")
+                            .x(vec![10.0, 20.0, 30.0])
+                            .y(vec![15.0, 25.0, 35.0])
+                            .fill(vec![
+                                Color::rgb(1.0, 0.0, 0.0),
+                                Color::rgba(0.0, 1.0, 0.0, 0.5),
+                                Color::TRANSPARENT,
+                            ])
+                            .shape(SymbolShape::Path(triangle()))
+                            .stroke_width(2.0),
+                    )
+                    .mark(
+                        SceneGroup::builder()
+                            .transform(AffineTransform::rotate(30.0))
+                            .mark(
+                                RuleMark::builder("rules")
+                                    .x0_scalar(0.0)
+                                    .x1_scalar(100.0)
+                                    .y0(vec![10.0, 20.0])
+                                    .y1(vec![10.0, 20.0])
+                                    .stroke_cap(vec![StrokeCap::Round, StrokeCap::Square]),
+                            )
+                            .mark(
+                                RectMark::builder("bars")
+                                    .x(vec![0.0, 10.0])
+                                    .y_scalar(5.0)
+                                    .width_scalar(8.0)
+                                    .height(vec![20.0, 30.0])
+                                    .clip(true),
+                            ),
+                    ),
+            )
+            .group(
+                SceneGroup::builder().mark(
+                    TextMark::builder("labels")
+                        .text(vec!["Title".to_string(), "Ünïcode".to_string()])
+                        .align_scalar(TextAlignSpec::Center)
+                        .baseline(vec![TextBaselineSpec::Top, TextBaselineSpec::LineBottom])
+                        .dir_scalar(TextDirectionSpec::Rtl)
+                        .font_style_scalar(FontStyleSpec::Italic)
+                        .font_weight(vec![
+                            FontWeightSpec::Number(300.0),
+                            FontWeightSpec::default(),
+                        ])
+                        .x(vec![100.0, 50.0])
+                        .y_scalar(10.0),
+                ),
+            )
+            .build()
+            .unwrap()
+    }
+
+    fn to_bytes(scene_graph: &SceneGraph) -> Vec<u8> {
+        write_scene_graph(scene_graph, Vec::new()).unwrap()
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let scene_graph = scene_graph();
+        let round_tripped = read_scene_graph(&to_bytes(&scene_graph)).unwrap();
+        assert_eq!(
+            serde_json::to_value(&round_tripped).unwrap(),
+            serde_json::to_value(&scene_graph).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_smaller_than_json() {
+        let scene_graph = SceneGraph::builder(100.0, 100.0)
+            .group(
+                SceneGroup::builder().mark(
+                    SymbolMark::builder("points")
+                        .x((0..1000).map(|i| i as f32 / 7.0).collect())
+                        .y((0..1000).map(|i| i as f32 / 3.0).collect()),
+                ),
+            )
+            .build()
+            .unwrap();
+        let json = serde_json::to_vec(&scene_graph).unwrap();
+        assert!(to_bytes(&scene_graph).len() < json.len() / 2);
+    }
+
+    fn bounds() -> GroupBounds {
+        GroupBounds {
+            x: 0.0,
+            y: 0.0,
+            width: None,
+            height: None,
+        }
+    }
+
+    #[test]
+    fn test_streaming() {
+        let mark = SceneMark::Rect(
+            RectMark::builder("bars")
+                .x(vec![1.0, 2.0, 3.0])
+                .fill_scalar(Color::WHITE)
+                .build()
+                .unwrap(),
+        );
+        let mut writer = SceneGraphWriter::new(Vec::new(), 50.0, 60.0).unwrap();
+        writer
+            .begin_group(&bounds(), &AffineTransform::scale(2.0, 2.0))
+            .unwrap();
+        writer.write_mark(&mark).unwrap();
+        writer.end_group().unwrap();
+        let bytes = writer.finish().unwrap();
+
+        let mut reader = SceneGraphReader::new(&bytes).unwrap();
+        assert_eq!((reader.width(), reader.height()), (50.0, 60.0));
+        let Some(SceneEvent::BeginGroup { transform, .. }) = reader.next_event().unwrap() else {
+            panic!("Expected a group");
+        };
+        assert_eq!(transform, AffineTransform::scale(2.0, 2.0));
+        let Some(SceneEvent::Mark(record)) = reader.next_event().unwrap() else {
+            panic!("Expected a mark");
+        };
+        assert_eq!(
+            (record.kind, record.name, record.len),
+            (MarkKind::Rect, "bars", 3)
+        );
+        assert!(record.channel_names().any(|name| name == "height"));
+        assert_eq!(
+            record.f32_values("x").as_deref(),
+            Some([1.0, 2.0, 3.0].as_slice())
+        );
+        assert_eq!(
+            record.f32_values("fill").as_deref(),
+            Some([1.0, 1.0, 1.0, 1.0].as_slice())
+        );
+        assert!(record.f32_values("missing").is_none());
+        assert!(matches!(
+            reader.next_event().unwrap(),
+            Some(SceneEvent::EndGroup)
+        ));
+        assert!(reader.next_event().unwrap().is_none());
+        assert!(reader.next().is_none());
+    }
+
+    #[test]
+    fn test_columns_are_borrowed() {
+        // Vec<u8> allocations are aligned enough for f32 columns to be read in place
+        let bytes = to_bytes(&scene_graph());
+        let mut marks =
+            SceneGraphReader::new(&bytes)
+                .unwrap()
+                .filter_map(|event| match event.unwrap() {
+                    SceneEvent::Mark(record) => Some(record),
+                    _ => None,
+                });
+        let points = marks.next().unwrap();
+        assert!(matches!(points.f32_values("x"), Some(Cow::Borrowed(_))));
+        assert_eq!(points.f32_values("fill").unwrap().len(), 12);
+        // Text isn't numeric
+        let labels = marks.nth(2).unwrap();
+        assert_eq!(labels.kind, MarkKind::Text);
+        assert!(labels.f32_values("text").is_none());
+    }
+
+    #[test]
+    fn test_version_1() {
+        // Version 1 headers end after the dimensions, and have no background
+        let mut bytes = MAGIC.to_vec();
+        bytes.extend(1u32.to_le_bytes());
+        bytes.extend(50.0f32.to_le_bytes());
+        bytes.extend(60.0f32.to_le_bytes());
+        bytes.push(0);
+        let scene_graph = read_scene_graph(&bytes).unwrap();
+        assert_eq!((scene_graph.width, scene_graph.height), (50.0, 60.0));
+        assert_eq!(scene_graph.background, None);
+        assert!(scene_graph.groups.is_empty());
+    }
+
+    #[test]
+    fn test_writer_errors() {
+        let mark = SceneMark::Rect(RectMark::default());
+        let mut writer = SceneGraphWriter::new(Vec::new(), 10.0, 10.0).unwrap();
+        assert!(matches!(
+            writer.write_mark(&mark),
+            Err(BinaryFormatError::InvalidData(_))
+        ));
+        assert!(matches!(
+            writer.end_group(),
+            Err(BinaryFormatError::InvalidData(_))
+        ));
+        writer
+            .begin_group(&bounds(), &AffineTransform::IDENTITY)
+            .unwrap();
+        assert!(matches!(
+            writer.finish(),
+            Err(BinaryFormatError::InvalidData(_))
+        ));
+    }
+
+    #[test]
+    fn test_reader_errors() {
+        let bytes = to_bytes(&scene_graph());
+        assert!(matches!(
+            read_scene_graph(b"{\"groups\": []}"),
+            Err(BinaryFormatError::InvalidMagic)
+        ));
+
+        let mut future = bytes.clone();
+        future[MAGIC.len()..MAGIC.len() + 4].copy_from_slice(&(VERSION + 1).to_le_bytes());
+        assert!(matches!(
+            read_scene_graph(&future),
+            Err(BinaryFormatError::UnsupportedVersion(version)) if version == VERSION + 1
+        ));
+
+        for len in [6, 20, bytes.len() / 2, bytes.len() - 1] {
+            assert!(
+                matches!(
+                    read_scene_graph(&bytes[..len]),
+                    Err(BinaryFormatError::UnexpectedEof)
+                ),
+                "{len}"
+            );
+        }
+    }
+}
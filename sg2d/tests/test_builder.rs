@@ -0,0 +1,201 @@
+#[cfg(test)]
+mod tests {
+    use sg2d::error::SceneGraphError;
+    use sg2d::marks::group::{GroupBounds, SceneGroup};
+    use sg2d::marks::mark::SceneMark;
+    use sg2d::marks::rect::RectMark;
+    use sg2d::marks::rule::RuleMark;
+    use sg2d::marks::symbol::SymbolMark;
+    use sg2d::marks::text::{TextAlignSpec, TextMark};
+    use sg2d::scene_graph::SceneGraph;
+    use sg2d::value::{Color, EncodingValue, StrokeCap};
+
+    fn built_scene_graph() -> SceneGraph {
+        SceneGraph::builder(200.0, 100.0)
+            .group(
+                SceneGroup::builder()
+                    .origin(10.0, 20.0)
+                    .size(180.0, 80.0)
+                    .mark(
+                        SymbolMark::builder("points")
+                            .x(vec![10.0, 20.0, 30.0])
+                            .y(vec![15.0, 25.0, 35.0])
+                            .fill_scalar([1.0, 0.0, 0.0, 1.0])
+                            .stroke_width(2.0),
+                    )
+                    .mark(
+                        SceneGroup::builder().mark(
+                            RuleMark::builder("rules")
+                                .x0_scalar(0.0)
+                                .x1_scalar(100.0)
+                                .y0(vec![10.0, 20.0])
+                                .y1(vec![10.0, 20.0])
+                                .stroke_cap_scalar(StrokeCap::Round),
+                        ),
+                    ),
+            )
+            .group(
+                SceneGroup::builder().mark(
+                    TextMark::builder("title")
+                        .text_scalar("Title")
+                        .align_scalar(TextAlignSpec::Center)
+                        .x_scalar(100.0)
+                        .y_scalar(10.0),
+                ),
+            )
+            .build()
+            .unwrap()
+    }
+
+    fn to_json(scene_graph: &SceneGraph) -> serde_json::Value {
+        serde_json::to_value(scene_graph).unwrap()
+    }
+
+    #[test]
+    fn test_builder_matches_structs() {
+        let expected = SceneGraph {
+            groups: vec![
+                SceneGroup {
+                    bounds: GroupBounds {
+                        x: 10.0,
+                        y: 20.0,
+                        width: Some(180.0),
+                        height: Some(80.0),
+                    },
+                    transform: Default::default(),
+                    marks: vec![
+                        SceneMark::Symbol(SymbolMark {
+                            name: "points".to_string(),
+                            len: 3,
+                            x: EncodingValue::Array {
+                                values: vec![10.0, 20.0, 30.0],
+                            },
+                            y: EncodingValue::Array {
+                                values: vec![15.0, 25.0, 35.0],
+                            },
+                            fill: EncodingValue::Scalar {
+                                value: Color::rgb(1.0, 0.0, 0.0),
+                            },
+                            stroke_width: Some(2.0),
+                            ..Default::default()
+                        }),
+                        SceneMark::Group(SceneGroup {
+                            bounds: GroupBounds {
+                                x: 0.0,
+                                y: 0.0,
+                                width: None,
+                                height: None,
+                            },
+                            transform: Default::default(),
+                            marks: vec![SceneMark::Rule(RuleMark {
+                                name: "rules".to_string(),
+                                len: 2,
+                                x1: EncodingValue::Scalar { value: 100.0 },
+                                y0: EncodingValue::Array {
+                                    values: vec![10.0, 20.0],
+                                },
+                                y1: EncodingValue::Array {
+                                    values: vec![10.0, 20.0],
+                                },
+                                stroke_cap: EncodingValue::Scalar {
+                                    value: StrokeCap::Round,
+                                },
+                                ..Default::default()
+                            })],
+                        }),
+                    ],
+                },
+                SceneGroup {
+                    bounds: GroupBounds {
+                        x: 0.0,
+                        y: 0.0,
+                        width: None,
+                        height: None,
+                    },
+                    transform: Default::default(),
+                    marks: vec![SceneMark::Text(Box::new(TextMark {
+                        name: "title".to_string(),
+                        text: EncodingValue::Scalar {
+                            value: "Title".to_string(),
+                        },
+                        align: EncodingValue::Scalar {
+                            value: TextAlignSpec::Center,
+                        },
+                        x: EncodingValue::Scalar { value: 100.0 },
+                        y: EncodingValue::Scalar { value: 10.0 },
+                        ..Default::default()
+                    }))],
+                },
+            ],
+            width: 200.0,
+            height: 100.0,
+            background: None,
+        };
+        assert_eq!(to_json(&built_scene_graph()), to_json(&expected));
+    }
+
+    #[test]
+    fn test_serde_round_trip() {
+        let scene_graph = built_scene_graph();
+        let json = serde_json::to_string(&scene_graph).unwrap();
+        let round_tripped: SceneGraph = serde_json::from_str(&json).unwrap();
+        assert_eq!(to_json(&round_tripped), to_json(&scene_graph));
+        assert_eq!(round_tripped.validate(), Ok(()));
+    }
+
+    #[test]
+    fn test_len() {
+        // Scalar-only marks have a single item, unless the length is given
+        let mark = RectMark::builder("bar").width_scalar(10.0).build().unwrap();
+        assert_eq!(mark.len, 1);
+        let mark = RectMark::builder("bars").len(4).build().unwrap();
+        assert_eq!(mark.len, 4);
+
+        // Otherwise the length comes from the first array channel
+        let mark = RectMark::builder("bars")
+            .height(vec![1.0, 2.0])
+            .x(vec![0.0, 10.0])
+            .build()
+            .unwrap();
+        assert_eq!(mark.len, 2);
+        let mark = SymbolMark::builder("empty").x(vec![]).build().unwrap();
+        assert_eq!(mark.len, 0);
+    }
+
+    #[test]
+    fn test_length_mismatch() {
+        assert_eq!(
+            RectMark::builder("bars")
+                .x(vec![0.0, 10.0, 20.0])
+                .y(vec![0.0, 10.0])
+                .build()
+                .unwrap_err(),
+            SceneGraphError::ArrayLengthMismatch {
+                mark: "bars".to_string(),
+                channel: "y",
+                len: 3,
+                values: 2,
+            }
+        );
+
+        // Marks added to groups are validated when the scene graph is built
+        let result = SceneGraph::builder(100.0, 100.0)
+            .group(
+                SceneGroup::builder().mark(
+                    TextMark::builder("labels")
+                        .text(vec!["a".to_string(), "b".to_string()])
+                        .x(vec![1.0]),
+                ),
+            )
+            .build();
+        assert!(matches!(
+            result,
+            Err(SceneGraphError::ArrayLengthMismatch {
+                channel: "x",
+                len: 2,
+                values: 1,
+                ..
+            })
+        ));
+    }
+}
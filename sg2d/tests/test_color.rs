@@ -0,0 +1,92 @@
+#[cfg(test)]
+mod tests {
+    use sg2d::error::SceneGraphError;
+    use sg2d::marks::rect::RectMark;
+    use sg2d::value::{Color, ColorCache, ColorSpace, EncodingValue};
+
+    fn assert_color_eq(actual: [f32; 4], expected: [f32; 4]) {
+        assert!(
+            actual
+                .iter()
+                .zip(expected)
+                .all(|(a, b)| (a - b).abs() < 1e-4),
+            "{actual:?} != {expected:?}"
+        );
+    }
+
+    #[test]
+    fn test_parse() {
+        assert_eq!(Color::parse("red"), Ok(Color::rgb(1.0, 0.0, 0.0)));
+        assert_eq!("#0000ff".parse(), Ok(Color::rgb(0.0, 0.0, 1.0)));
+        assert_eq!(
+            Color::parse("rgba(255, 255, 255, 0.5)"),
+            Ok(Color::WHITE.with_alpha(0.5))
+        );
+        assert_eq!(
+            Color::parse("not a color"),
+            Err(SceneGraphError::InvalidCssColor("not a color".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_cache() {
+        let mut cache = ColorCache::new();
+        assert_eq!(cache.parse("steelblue"), Color::parse("steelblue"));
+        assert_eq!(cache.parse("steelblue"), Color::parse("steelblue"));
+        assert!(cache.parse("steelblu").is_err());
+    }
+
+    #[test]
+    fn test_color_space() {
+        // Mid gray in sRGB is about a fifth of the intensity of white
+        let gray = Color::rgba(0.5, 0.5, 0.5, 0.5);
+        assert_color_eq(gray.to_space(ColorSpace::Srgb), [0.5, 0.5, 0.5, 0.5]);
+        assert_color_eq(
+            gray.to_space(ColorSpace::Linear),
+            [0.21404, 0.21404, 0.21404, 0.5],
+        );
+
+        for c in [0.0, 0.002, 0.04, 0.3, 1.0] {
+            let color = Color::rgb(c, c, c);
+            let round_tripped =
+                Color::from_space(color.to_space(ColorSpace::Linear), ColorSpace::Linear);
+            assert_color_eq(round_tripped.to_array(), color.to_array());
+        }
+    }
+
+    #[test]
+    fn test_serde() {
+        let color: Color = serde_json::from_str("[1.0, 0.5, 0.0]").unwrap();
+        assert_eq!(color, Color::rgb(1.0, 0.5, 0.0));
+        let color: Color = serde_json::from_str("[1.0, 0.5, 0.0, 0.25]").unwrap();
+        assert_eq!(color, Color::rgba(1.0, 0.5, 0.0, 0.25));
+        let color: Color = serde_json::from_str("\"#ff8000\"").unwrap();
+        assert_color_eq(color.to_array(), [1.0, 128.0 / 255.0, 0.0, 1.0]);
+        assert!(serde_json::from_str::<Color>("\"bogus\"").is_err());
+
+        assert_eq!(
+            serde_json::to_value(Color::rgba(1.0, 0.5, 0.0, 0.25)).unwrap(),
+            serde_json::json!([1.0, 0.5, 0.0, 0.25])
+        );
+    }
+
+    #[test]
+    fn test_validate_alpha() {
+        let mark = RectMark {
+            name: "bars".to_string(),
+            fill: EncodingValue::Scalar {
+                value: Color::rgba(0.0, 0.0, 0.0, 1.5),
+            },
+            ..Default::default()
+        };
+        assert_eq!(
+            mark.validate(),
+            Err(SceneGraphError::InvalidColor {
+                mark: "bars".to_string(),
+                channel: "fill",
+                index: 0,
+                color: vec![0.0, 0.0, 0.0, 1.5],
+            })
+        );
+    }
+}
@@ -0,0 +1,52 @@
+#[cfg(test)]
+mod tests {
+    use sg2d::value::EncodingValue;
+
+    #[test]
+    fn test_scalar_broadcast() {
+        let value = EncodingValue::Scalar { value: 2.0f32 };
+        assert_eq!(value.as_slice(), &[2.0]);
+        assert_eq!(*value.item(0), 2.0);
+        assert_eq!(*value.item(1000), 2.0);
+
+        let iter = value.as_iter(3);
+        assert_eq!(iter.len(), 3);
+        assert_eq!(iter.copied().collect::<Vec<_>>(), vec![2.0; 3]);
+    }
+
+    #[test]
+    fn test_array_slice() {
+        let values = vec![1.0f32, 2.0, 3.0];
+        let value = EncodingValue::Array {
+            values: values.clone(),
+        };
+        assert_eq!(value.as_slice(), values.as_slice());
+        assert_eq!(*value.item(2), 3.0);
+        // Arrays ignore the scalar length
+        assert_eq!(value.as_iter(10).len(), 3);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_array_item_out_of_bounds() {
+        let value = EncodingValue::Array { values: vec![1.0] };
+        value.item(1);
+    }
+
+    #[test]
+    fn test_map() {
+        let mut calls = 0;
+        let value = EncodingValue::Scalar { value: 2.0f32 }.map(|v| {
+            calls += 1;
+            v * 2.0
+        });
+        assert!(matches!(value, EncodingValue::Scalar { value } if value == 4.0));
+        assert_eq!(calls, 1);
+
+        let value = EncodingValue::Array {
+            values: vec![1.0f32, 2.0],
+        }
+        .map(|v| *v as u32);
+        assert_eq!(value.as_slice(), &[1, 2]);
+    }
+}
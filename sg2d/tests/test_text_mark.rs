@@ -0,0 +1,53 @@
+#[cfg(test)]
+mod test_text_mark {
+    use sg2d::marks::text::{TextDirectionSpec, TextMark};
+    use sg2d::value::{Color, EncodingValue};
+
+    /// JSON of a default text mark without the given channels, as written before they existed
+    fn json_without(channels: &[&str]) -> serde_json::Value {
+        let mut json = serde_json::to_value(TextMark::default()).unwrap();
+        for channel in channels {
+            json.as_object_mut().unwrap().remove(*channel).unwrap();
+        }
+        json
+    }
+
+    #[test]
+    fn test_missing_halo_channels() {
+        let json = json_without(&["stroke", "stroke-width", "stroke-opacity"]);
+        let mark: TextMark = serde_json::from_value(json).unwrap();
+        assert!(matches!(
+            mark.stroke,
+            EncodingValue::Scalar {
+                value: Color::BLACK
+            }
+        ));
+        assert!(matches!(
+            mark.stroke_width,
+            EncodingValue::Scalar { value } if value == 0.0
+        ));
+        assert!(matches!(
+            mark.stroke_opacity,
+            EncodingValue::Scalar { value } if value == 1.0
+        ));
+        assert_eq!(mark.validate(), Ok(()));
+    }
+
+    #[test]
+    fn test_missing_dir() {
+        let mark: TextMark = serde_json::from_value(json_without(&["dir"])).unwrap();
+        assert!(matches!(
+            mark.dir,
+            EncodingValue::Scalar {
+                value: TextDirectionSpec::Ltr
+            }
+        ));
+    }
+
+    #[test]
+    fn test_text_mark_before_halos_and_direction() {
+        let json = json_without(&["dir", "stroke", "stroke-width", "stroke-opacity"]);
+        let mark: TextMark = serde_json::from_value(json).unwrap();
+        assert_eq!(mark.validate(), Ok(()));
+    }
+}
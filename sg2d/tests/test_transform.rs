@@ -0,0 +1,94 @@
+#[cfg(test)]
+mod tests {
+    use sg2d::error::SceneGraphError;
+    use sg2d::marks::group::SceneGroup;
+    use sg2d::transform::AffineTransform;
+
+    fn assert_point_eq(actual: [f32; 2], expected: [f32; 2]) {
+        let close = |a: f32, b: f32| (a - b).abs() < 1e-4;
+        assert!(
+            close(actual[0], expected[0]) && close(actual[1], expected[1]),
+            "{actual:?} != {expected:?}"
+        );
+    }
+
+    #[test]
+    fn test_apply() {
+        assert_eq!(AffineTransform::IDENTITY.apply([3.0, 4.0]), [3.0, 4.0]);
+        assert_eq!(
+            AffineTransform::translate(1.0, 2.0).apply([3.0, 4.0]),
+            [4.0, 6.0]
+        );
+        assert_eq!(
+            AffineTransform::scale(2.0, -1.0).apply([3.0, 4.0]),
+            [6.0, -4.0]
+        );
+
+        // y points down, so a positive rotation turns the x-axis towards the y-axis
+        assert_point_eq(AffineTransform::rotate(90.0).apply([1.0, 0.0]), [0.0, 1.0]);
+        assert!((AffineTransform::rotate(30.0).rotation() - 30.0).abs() < 1e-4);
+    }
+
+    #[test]
+    fn test_then() {
+        // Scaling then translating moves the scaled point, translating then scaling scales
+        // the translation too
+        let scale = AffineTransform::scale(2.0, 3.0);
+        let translate = AffineTransform::translate(10.0, 20.0);
+        assert_eq!(scale.then(&translate).apply([1.0, 1.0]), [12.0, 23.0]);
+        assert_eq!(translate.then(&scale).apply([1.0, 1.0]), [22.0, 63.0]);
+        assert_eq!(scale.then_translate(10.0, 20.0), scale.then(&translate));
+    }
+
+    #[test]
+    fn test_inverse() {
+        let transform = AffineTransform::rotate(30.0)
+            .then(&AffineTransform::scale(2.0, 0.5))
+            .then_translate(10.0, -5.0);
+        let inverse = transform.inverse().unwrap();
+        assert_point_eq(inverse.apply(transform.apply([3.0, 4.0])), [3.0, 4.0]);
+        assert!((transform.scale_factor() - 1.0).abs() < 1e-4);
+        assert!(!transform.is_axis_aligned());
+        assert!(AffineTransform::scale(2.0, 0.5)
+            .then_translate(10.0, -5.0)
+            .is_axis_aligned());
+        assert!(!AffineTransform::scale(-1.0, 1.0).is_axis_aligned());
+        assert!(!AffineTransform::rotate(180.0).is_axis_aligned());
+
+        assert_eq!(AffineTransform::scale(0.0, 1.0).inverse(), None);
+        assert_eq!(AffineTransform::scale(f32::INFINITY, 1.0).inverse(), None);
+    }
+
+    #[test]
+    fn test_group_transform() {
+        // Omitted transforms are the identity, and the group origin is applied after the
+        // transform
+        let group: SceneGroup = serde_json::from_value(serde_json::json!({
+            "bounds": {"x": 100.0, "y": 50.0},
+            "marks": [],
+        }))
+        .unwrap();
+        assert!(group.transform.is_identity());
+
+        let group: SceneGroup = serde_json::from_value(serde_json::json!({
+            "bounds": {"x": 100.0, "y": 50.0},
+            "transform": [2.0, 0.0, 0.0, 2.0, 0.0, 0.0],
+            "marks": [],
+        }))
+        .unwrap();
+        assert_eq!(group.local_transform().apply([1.0, 1.0]), [102.0, 52.0]);
+        assert_eq!(group.validate(), Ok(()));
+
+        let group = SceneGroup {
+            transform: AffineTransform([1.0, 0.0, 0.0, 1.0, f32::INFINITY, 0.0]),
+            ..group
+        };
+        assert_eq!(
+            group.validate(),
+            Err(SceneGraphError::InvalidTransform {
+                component: 4,
+                value: f32::INFINITY,
+            })
+        );
+    }
+}
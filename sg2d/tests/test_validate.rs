@@ -0,0 +1,263 @@
+#[cfg(test)]
+mod tests {
+    use sg2d::error::SceneGraphError;
+    use sg2d::marks::group::{GroupBounds, SceneGroup};
+    use sg2d::marks::mark::SceneMark;
+    use sg2d::marks::rect::RectMark;
+    use sg2d::marks::rule::RuleMark;
+    use sg2d::marks::symbol::SymbolMark;
+    use sg2d::marks::text::TextMark;
+    use sg2d::scene_graph::SceneGraph;
+    use sg2d::value::{Color, EncodingValue};
+
+    fn scene_graph(marks: Vec<SceneMark>) -> SceneGraph {
+        SceneGraph {
+            groups: vec![SceneGroup {
+                bounds: GroupBounds {
+                    x: 0.0,
+                    y: 0.0,
+                    width: None,
+                    height: None,
+                },
+                transform: Default::default(),
+                marks,
+            }],
+            width: 100.0,
+            height: 100.0,
+            background: None,
+        }
+    }
+
+    fn rect_mark() -> RectMark {
+        RectMark {
+            name: "bars".to_string(),
+            len: 3,
+            x: EncodingValue::Array {
+                values: vec![0.0, 10.0, 20.0],
+            },
+            width: EncodingValue::Scalar { value: 5.0 },
+            height: EncodingValue::Array {
+                values: vec![1.0, 2.0, 3.0],
+            },
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_valid_scene_graph() {
+        let sg = scene_graph(vec![
+            SceneMark::Rect(rect_mark()),
+            SceneMark::Rule(RuleMark::default()),
+            SceneMark::Symbol(SymbolMark::default()),
+            SceneMark::Text(Box::default()),
+        ]);
+        assert_eq!(sg.validate(), Ok(()));
+    }
+
+    #[test]
+    fn test_array_length_mismatch() {
+        let mark = RectMark {
+            y: EncodingValue::Array {
+                values: vec![0.0, 1.0],
+            },
+            ..rect_mark()
+        };
+        assert_eq!(
+            scene_graph(vec![SceneMark::Rect(mark)]).validate(),
+            Err(SceneGraphError::ArrayLengthMismatch {
+                mark: "bars".to_string(),
+                channel: "y",
+                len: 3,
+                values: 2,
+            })
+        );
+    }
+
+    #[test]
+    fn test_non_finite_coordinate() {
+        let mark = RectMark {
+            x: EncodingValue::Array {
+                values: vec![0.0, 10.0, f32::NAN],
+            },
+            ..rect_mark()
+        };
+        let Err(SceneGraphError::NonFiniteValue {
+            mark,
+            channel,
+            index,
+            ..
+        }) = mark.validate()
+        else {
+            panic!("Expected non-finite value error")
+        };
+        assert_eq!((mark.as_str(), channel, index), ("bars", "x", 2));
+
+        let mark = RuleMark {
+            name: "rules".to_string(),
+            y1: EncodingValue::Scalar {
+                value: f32::INFINITY,
+            },
+            ..Default::default()
+        };
+        assert_eq!(
+            mark.validate(),
+            Err(SceneGraphError::NonFiniteValue {
+                mark: "rules".to_string(),
+                channel: "y1",
+                index: 0,
+                value: f32::INFINITY,
+            })
+        );
+    }
+
+    #[test]
+    fn test_negative_size() {
+        let mark = RectMark {
+            height: EncodingValue::Array {
+                values: vec![1.0, -2.0, 3.0],
+            },
+            ..rect_mark()
+        };
+        assert_eq!(
+            mark.validate(),
+            Err(SceneGraphError::NegativeValue {
+                mark: "bars".to_string(),
+                channel: "height",
+                index: 1,
+                value: -2.0,
+            })
+        );
+
+        let mark = TextMark {
+            name: "labels".to_string(),
+            font_size: EncodingValue::Scalar { value: -1.0 },
+            ..Default::default()
+        };
+        assert!(matches!(
+            mark.validate(),
+            Err(SceneGraphError::NegativeValue {
+                channel: "font_size",
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn test_invalid_color() {
+        let mark = SymbolMark {
+            name: "points".to_string(),
+            len: 2,
+            fill: EncodingValue::Array {
+                values: vec![Color::rgb(0.0, 0.5, 1.0), Color::rgb(0.0, 255.0, 0.0)],
+            },
+            ..Default::default()
+        };
+        assert_eq!(
+            mark.validate(),
+            Err(SceneGraphError::InvalidColor {
+                mark: "points".to_string(),
+                channel: "fill",
+                index: 1,
+                color: vec![0.0, 255.0, 0.0, 1.0],
+            })
+        );
+    }
+
+    #[test]
+    fn test_nested_group_and_dimensions() {
+        let nested = SceneGroup {
+            bounds: GroupBounds {
+                x: 0.0,
+                y: 0.0,
+                width: Some(10.0),
+                height: Some(10.0),
+            },
+            transform: Default::default(),
+            marks: vec![SceneMark::Text(Box::new(TextMark {
+                name: "nested_text".to_string(),
+                len: 2,
+                ..Default::default()
+            }))],
+        };
+        assert_eq!(
+            scene_graph(vec![SceneMark::Group(nested.clone())]).validate(),
+            Ok(())
+        );
+
+        let invalid = SceneGroup {
+            marks: vec![SceneMark::Text(Box::new(TextMark {
+                name: "nested_text".to_string(),
+                len: 2,
+                text: EncodingValue::Array {
+                    values: vec!["a".to_string()],
+                },
+                ..Default::default()
+            }))],
+            ..nested
+        };
+        assert!(matches!(
+            scene_graph(vec![SceneMark::Group(invalid)]).validate(),
+            Err(SceneGraphError::ArrayLengthMismatch { mark, channel: "text", .. }) if mark == "nested_text"
+        ));
+
+        // Group offsets must be finite, and sizes finite and non-negative
+        let bounds = nested.bounds;
+        let cases = [
+            (
+                "x",
+                GroupBounds {
+                    x: f32::NAN,
+                    ..bounds
+                },
+                f32::NAN,
+            ),
+            (
+                "width",
+                GroupBounds {
+                    width: Some(-1.0),
+                    ..bounds
+                },
+                -1.0,
+            ),
+            (
+                "height",
+                GroupBounds {
+                    height: Some(f32::INFINITY),
+                    ..bounds
+                },
+                f32::INFINITY,
+            ),
+        ];
+        for (expected_field, bounds, expected_value) in cases {
+            let group = SceneGroup {
+                bounds,
+                ..nested.clone()
+            };
+            let Err(SceneGraphError::InvalidGroupBounds { field, value }) =
+                scene_graph(vec![SceneMark::Group(group)]).validate()
+            else {
+                panic!("Expected invalid group bounds error for {expected_field}")
+            };
+            assert_eq!(field, expected_field);
+            assert!(value == expected_value || value.is_nan() && expected_value.is_nan());
+        }
+
+        let sg = SceneGraph {
+            width: f32::NAN,
+            ..scene_graph(vec![])
+        };
+        assert!(matches!(
+            sg.validate(),
+            Err(SceneGraphError::InvalidDimensions { .. })
+        ));
+
+        let sg = SceneGraph {
+            background: Some(Color::rgba(1.0, 1.0, 2.0, 1.0)),
+            ..scene_graph(vec![])
+        };
+        assert!(matches!(
+            sg.validate(),
+            Err(SceneGraphError::InvalidBackground { .. })
+        ));
+    }
+}